@@ -161,6 +161,28 @@ async function fetchApi<T>(
   }
 }
 
+// Largest page the node serves on list endpoints
+const MAX_PAGE_LIMIT = 500;
+
+/**
+ * Fetch every page of a cursor-paginated list endpoint
+ * The node returns one page plus `next_cursor`; follow it until there are no
+ * more pages.
+ */
+async function fetchAllPages<T>(endpoint: string, key: string): Promise<T[]> {
+  const items: T[] = [];
+  let cursor: string | null | undefined;
+  do {
+    const query = cursor
+      ? `?limit=${MAX_PAGE_LIMIT}&cursor=${encodeURIComponent(cursor)}`
+      : `?limit=${MAX_PAGE_LIMIT}`;
+    const page = await fetchApi<Record<string, unknown> & { next_cursor?: string | null }>(`${endpoint}${query}`);
+    items.push(...((page[key] as T[] | undefined) || []));
+    cursor = page.next_cursor;
+  } while (cursor);
+  return items;
+}
+
 // ============================================================================
 // Token Factory API
 // ============================================================================
@@ -214,8 +236,7 @@ export async function createToken(params: CreateTokenParams, signature: string):
 
 export async function getPools(): Promise<Pool[]> {
   try {
-    const result = await fetchApi<{ pools: Pool[] }>('/dex/pools');
-    return result.pools || [];
+    return await fetchAllPages<Pool>('/dex/pools', 'pools');
   } catch {
     return [];
   }
@@ -0,0 +1,398 @@
+//! Typed async client for the node's REST routes
+//!
+//! One method per public route, in the order the node registers them. Bodies and
+//! queries are the `sultan_core::api` types; see `Signer` for building the signed
+//! ones. Replies are the node's reply structs, the same ones its OpenAPI document
+//! is generated from. The WebSocket route (`/ws`) and the admin listener are not covered.
+
+use crate::error::ApiError;
+use anyhow::{Context, Result};
+use reqwest::RequestBuilder;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use sultan_core::api::{
+    AddLiquidityRequest, BalanceResponse, BeaconResponse, BlocksListQuery, BlocksPage, BridgeTxRequest, BridgeTxSubmitted,
+    BridgesResponse, BurnTokenRequest, CreatePairRequest, CreateTokenRequest, CreateValidatorRequest, Delegated,
+    EconomicsResponse, EventsPage, EventsQuery, FaucetChallenge, FaucetClaimRequest, FaucetClaimed, FaucetStatus,
+    FeaturesResponse, FeeQuery, KeyRotationQueued, LatestBlockResponse, LiquidityAdded, LiquidityRemoved,
+    MintTokenRequest, NodeStatus, PairCreated, PoolResponse, PoolsPage, PoolsQuery, PriceResponse, ProposalExecuted,
+    ProposalSubmitted, ProposalsPage, ProposalsQuery, RemoveLiquidityRequest, RewardWalletResponse, RewardsWithdrawn,
+    RotateKeyRequest, SetRewardWalletRequest, StakeRequest, StatsResponse, SubmitProposalRequest, SuccessResponse,
+    SwapRequest, SwapResponse, TokenBalanceResponse, TokenCreated, TokenMetadataResponse, TokensPage, TokensQuery,
+    TotalSupplyResponse, TransferTokenRequest, TreasuryResponse, TxHistoryQuery, TxHistoryResponse, TxRequest,
+    TxSubmitted, Undelegated, ValidatorCreated, ValidatorsPage, ValidatorsQuery, VoteRecorded, VoteRequest,
+    WalletTxRequest, WithdrawRewardsRequest,
+};
+use sultan_core::blockchain::Block;
+use sultan_core::bridge_fees::{FeeBreakdown, FeeStatistics};
+use sultan_core::bridge_integration::BridgeStatus;
+use sultan_core::governance::{GovernanceStatistics, Proposal, TallyResult};
+use sultan_core::health::HealthReport;
+use sultan_core::sharded_blockchain_production::ConfirmedTransaction;
+use sultan_core::simulate::{SimulateRequest, SimulationResult};
+use sultan_core::staking::{Delegation, StakingStatistics};
+
+#[derive(Debug, Clone)]
+pub struct SultanClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl SultanClient {
+    /// Client for the node RPC at `base_url`, e.g. `http://localhost:26657`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_http(reqwest::Client::new(), base_url)
+    }
+
+    /// Client using a preconfigured `reqwest::Client` (timeouts, TLS roots, proxies)
+    pub fn with_http(http: reqwest::Client, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        Self { http, base_url }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    // ========= CHAIN =========
+
+    pub async fn status(&self) -> Result<NodeStatus> {
+        self.get("/status").await
+    }
+
+    pub async fn submit_tx(&self, request: &TxRequest) -> Result<TxSubmitted> {
+        self.post("/tx", request).await
+    }
+
+    pub async fn block(&self, height: u64) -> Result<Block> {
+        self.get(&format!("/block/{}", height)).await
+    }
+
+    pub async fn latest_block(&self) -> Result<LatestBlockResponse> {
+        self.get("/block/latest").await
+    }
+
+    pub async fn blocks(&self, query: &BlocksListQuery) -> Result<BlocksPage> {
+        self.get_query("/blocks", query).await
+    }
+
+    pub async fn events(&self, query: &EventsQuery) -> Result<EventsPage> {
+        self.get_query("/events", query).await
+    }
+
+    /// Dry-run an operation; a failing operation is an `Ok` result with `success: false`
+    pub async fn simulate(&self, request: &SimulateRequest) -> Result<SimulationResult> {
+        self.send(self.http.post(self.url("/simulate")).json(request), false).await
+    }
+
+    pub async fn stats(&self) -> Result<StatsResponse> {
+        self.get("/stats").await
+    }
+
+    /// Prometheus text exposition
+    pub async fn metrics(&self) -> Result<String> {
+        let response = self.http.get(self.url("/metrics")).send().await
+            .with_context(|| format!("Request to {} failed", self.base_url))?;
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(ApiError { status: status.as_u16(), message: text }.into());
+        }
+        Ok(text)
+    }
+
+    /// Raw JSON-RPC 2.0 call or batch to `/rpc`
+    pub async fn jsonrpc(&self, body: &Value) -> Result<Value> {
+        self.send(self.http.post(self.url("/rpc")).json(body), false).await
+    }
+
+    pub async fn beacon(&self) -> Result<BeaconResponse> {
+        self.get("/beacon").await
+    }
+
+    pub async fn balance(&self, address: &str) -> Result<BalanceResponse> {
+        self.get(&format!("/balance/{}", segment(address))).await
+    }
+
+    pub async fn transactions(&self, address: &str, query: &TxHistoryQuery) -> Result<TxHistoryResponse> {
+        self.get_query(&format!("/transactions/{}", segment(address)), query).await
+    }
+
+    pub async fn tx(&self, hash: &str) -> Result<ConfirmedTransaction> {
+        self.get(&format!("/tx/{}", segment(hash))).await
+    }
+
+    pub async fn economics(&self) -> Result<EconomicsResponse> {
+        self.get("/economics").await
+    }
+
+    pub async fn total_supply(&self) -> Result<TotalSupplyResponse> {
+        self.get("/supply/total").await
+    }
+
+    /// Readiness report; `ready` is false when any check failed
+    pub async fn health(&self) -> Result<HealthReport> {
+        self.get("/health").await
+    }
+
+    /// The node's OpenAPI 3 document
+    pub async fn openapi(&self) -> Result<Value> {
+        self.get("/openapi.json").await
+    }
+
+    // ========= BRIDGES =========
+
+    pub async fn bridges(&self) -> Result<BridgesResponse> {
+        self.get("/bridges").await
+    }
+
+    pub async fn bridge(&self, chain: &str) -> Result<BridgeStatus> {
+        self.get(&format!("/bridge/{}", segment(chain))).await
+    }
+
+    pub async fn submit_bridge_tx(&self, request: &BridgeTxRequest) -> Result<BridgeTxSubmitted> {
+        self.post("/bridge/submit", request).await
+    }
+
+    pub async fn bridge_fee(&self, chain: &str, query: &FeeQuery) -> Result<FeeBreakdown> {
+        self.get_query(&format!("/bridge/{}/fee", segment(chain)), query).await
+    }
+
+    pub async fn bridge_treasury(&self) -> Result<TreasuryResponse> {
+        self.get("/bridge/fees/treasury").await
+    }
+
+    pub async fn bridge_fee_statistics(&self) -> Result<FeeStatistics> {
+        self.get("/bridge/fees/statistics").await
+    }
+
+    // ========= STAKING =========
+
+    pub async fn create_validator(&self, request: &CreateValidatorRequest) -> Result<ValidatorCreated> {
+        self.post("/staking/create_validator", request).await
+    }
+
+    /// Signed wallet-format delegation (see `Signer::delegate`)
+    pub async fn delegate(&self, request: &WalletTxRequest) -> Result<Delegated> {
+        self.post("/staking/delegate", request).await
+    }
+
+    pub async fn validators(&self, query: &ValidatorsQuery) -> Result<ValidatorsPage> {
+        self.get_query("/staking/validators", query).await
+    }
+
+    pub async fn delegations(&self, address: &str) -> Result<Vec<Delegation>> {
+        self.get(&format!("/staking/delegations/{}", segment(address))).await
+    }
+
+    pub async fn withdraw_rewards(&self, request: &WithdrawRewardsRequest) -> Result<RewardsWithdrawn> {
+        self.post("/staking/withdraw_rewards", request).await
+    }
+
+    pub async fn set_reward_wallet(&self, request: &SetRewardWalletRequest) -> Result<RewardWalletResponse> {
+        self.post("/staking/set_reward_wallet", request).await
+    }
+
+    pub async fn rotate_key(&self, request: &RotateKeyRequest) -> Result<KeyRotationQueued> {
+        self.post("/staking/rotate_key", request).await
+    }
+
+    pub async fn reward_wallet(&self, validator_address: &str) -> Result<RewardWalletResponse> {
+        self.get(&format!("/staking/reward_wallet/{}", segment(validator_address))).await
+    }
+
+    pub async fn undelegate(&self, request: &StakeRequest) -> Result<Undelegated> {
+        self.post("/staking/undelegate", request).await
+    }
+
+    pub async fn staking_statistics(&self) -> Result<StakingStatistics> {
+        self.get("/staking/statistics").await
+    }
+
+    // ========= GOVERNANCE =========
+
+    pub async fn submit_proposal(&self, request: &SubmitProposalRequest) -> Result<ProposalSubmitted> {
+        self.post("/governance/propose", request).await
+    }
+
+    pub async fn vote(&self, request: &VoteRequest) -> Result<VoteRecorded> {
+        self.post("/governance/vote", request).await
+    }
+
+    pub async fn proposals(&self, query: &ProposalsQuery) -> Result<ProposalsPage> {
+        self.get_query("/governance/proposals", query).await
+    }
+
+    pub async fn proposal(&self, id: u64) -> Result<Proposal> {
+        self.get(&format!("/governance/proposal/{}", id)).await
+    }
+
+    pub async fn tally(&self, id: u64) -> Result<TallyResult> {
+        self.post_empty(&format!("/governance/tally/{}", id)).await
+    }
+
+    pub async fn governance_statistics(&self) -> Result<GovernanceStatistics> {
+        self.get("/governance/statistics").await
+    }
+
+    pub async fn execute_proposal(&self, id: u64) -> Result<ProposalExecuted> {
+        self.post_empty(&format!("/governance/execute/{}", id)).await
+    }
+
+    pub async fn features(&self) -> Result<FeaturesResponse> {
+        self.get("/governance/features").await
+    }
+
+    // ========= TOKENS AND FAUCET =========
+
+    pub async fn create_token(&self, request: &CreateTokenRequest) -> Result<TokenCreated> {
+        self.post("/tokens/create", request).await
+    }
+
+    pub async fn mint_token(&self, request: &MintTokenRequest) -> Result<SuccessResponse> {
+        self.post("/tokens/mint", request).await
+    }
+
+    pub async fn transfer_token(&self, request: &TransferTokenRequest) -> Result<SuccessResponse> {
+        self.post("/tokens/transfer", request).await
+    }
+
+    pub async fn burn_token(&self, request: &BurnTokenRequest) -> Result<SuccessResponse> {
+        self.post("/tokens/burn", request).await
+    }
+
+    pub async fn token_metadata(&self, denom: &str) -> Result<TokenMetadataResponse> {
+        self.get(&format!("/tokens/{}/metadata", segment(denom))).await
+    }
+
+    pub async fn token_balance(&self, denom: &str, address: &str) -> Result<TokenBalanceResponse> {
+        self.get(&format!("/tokens/{}/balance/{}", segment(denom), segment(address))).await
+    }
+
+    pub async fn tokens(&self, query: &TokensQuery) -> Result<TokensPage> {
+        self.get_query("/tokens/list", query).await
+    }
+
+    /// Challenge nonce to sign with `Signer::faucet_claim`
+    pub async fn faucet_challenge(&self, address: &str) -> Result<String> {
+        let reply: FaucetChallenge = self.get(&format!("/faucet/challenge/{}", segment(address))).await?;
+        Ok(reply.nonce)
+    }
+
+    pub async fn faucet_claim(&self, request: &FaucetClaimRequest) -> Result<FaucetClaimed> {
+        self.post("/faucet/claim", request).await
+    }
+
+    pub async fn faucet_status(&self) -> Result<FaucetStatus> {
+        self.get("/faucet/status").await
+    }
+
+    // ========= DEX =========
+
+    pub async fn create_pair(&self, request: &CreatePairRequest) -> Result<PairCreated> {
+        self.post("/dex/create_pair", request).await
+    }
+
+    pub async fn swap(&self, request: &SwapRequest) -> Result<SwapResponse> {
+        self.post("/dex/swap", request).await
+    }
+
+    pub async fn add_liquidity(&self, request: &AddLiquidityRequest) -> Result<LiquidityAdded> {
+        self.post("/dex/add_liquidity", request).await
+    }
+
+    pub async fn remove_liquidity(&self, request: &RemoveLiquidityRequest) -> Result<LiquidityRemoved> {
+        self.post("/dex/remove_liquidity", request).await
+    }
+
+    pub async fn pool(&self, pair_id: &str) -> Result<PoolResponse> {
+        self.get(&format!("/dex/pool/{}", segment(pair_id))).await
+    }
+
+    pub async fn pools(&self, query: &PoolsQuery) -> Result<PoolsPage> {
+        self.get_query("/dex/pools", query).await
+    }
+
+    pub async fn price(&self, pair_id: &str) -> Result<PriceResponse> {
+        self.get(&format!("/dex/price/{}", segment(pair_id))).await
+    }
+
+    // ========= TRANSPORT =========
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        self.send(self.http.get(self.url(path)), true).await
+    }
+
+    async fn get_query<Q: Serialize, T: DeserializeOwned>(&self, path: &str, query: &Q) -> Result<T> {
+        self.send(self.http.get(self.url(path)).query(query), true).await
+    }
+
+    async fn post<B: Serialize, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T> {
+        self.send(self.http.post(self.url(path)).json(body), true).await
+    }
+
+    async fn post_empty<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        self.send(self.http.post(self.url(path)), true).await
+    }
+
+    /// Send a request and decode its JSON reply
+    ///
+    /// With `check`, a reply carrying an `error` field is an `ApiError` even
+    /// when its status is 200, as several routes report errors that way.
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder, check: bool) -> Result<T> {
+        let response = request.send().await
+            .with_context(|| format!("Request to {} failed", self.base_url))?;
+        let status = response.status();
+        let text = response.text().await?;
+        let body: Value = match serde_json::from_str(&text) {
+            Ok(body) => body,
+            Err(_) => {
+                let message = if text.is_empty() {
+                    status.canonical_reason().unwrap_or("Request failed").to_string()
+                } else {
+                    text
+                };
+                return Err(ApiError { status: status.as_u16(), message }.into());
+            }
+        };
+        if check {
+            if let Some(error) = ApiError::from_body(status.as_u16(), &body) {
+                return Err(error.into());
+            }
+        }
+        if !status.is_success() {
+            let message = status.canonical_reason().unwrap_or("Request failed").to_string();
+            return Err(ApiError { status: status.as_u16(), message }.into());
+        }
+        serde_json::from_value(body).context("Unexpected reply from node")
+    }
+}
+
+/// Percent-encode one path segment (denoms like `factory/<creator>/<symbol>` contain `/`)
+fn segment(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_segments_are_encoded() {
+        assert_eq!(segment("sultan1abc"), "sultan1abc");
+        assert_eq!(segment("factory/sultan1abc/tok"), "factory%2Fsultan1abc%2Ftok");
+        assert_eq!(SultanClient::new("http://localhost:26657/").base_url(), "http://localhost:26657");
+    }
+}
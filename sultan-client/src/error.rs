@@ -0,0 +1,57 @@
+//! Errors reported by the node
+
+use serde_json::Value;
+
+/// A request the node rejected
+///
+/// The node reports errors either with an HTTP error status or with a 200 reply
+/// carrying an `error` field; both end up here.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    /// HTTP status of the reply
+    pub status: u16,
+    pub message: String,
+}
+
+impl ApiError {
+    /// The error carried by a reply body, if it has one
+    ///
+    /// `{"success": true, ...}` replies are never errors.
+    pub fn from_body(status: u16, body: &Value) -> Option<Self> {
+        if body.get("success").and_then(Value::as_bool) == Some(true) {
+            return None;
+        }
+        let message = body.get("error")?;
+        let message = message.as_str().map(str::to_string).unwrap_or_else(|| message.to_string());
+        Some(Self { status, message })
+    }
+
+    /// Whether the node rejected a transaction for its nonce
+    pub fn is_nonce_error(&self) -> bool {
+        self.message.to_lowercase().contains("nonce")
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "node returned {}: {}", self.status, self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_error_shapes() {
+        let error = ApiError::from_body(200, &json!({"error": "Invalid nonce: expected 2, got 1", "status": 400})).unwrap();
+        assert_eq!(error.status, 200);
+        assert!(error.is_nonce_error());
+        assert!(ApiError::from_body(400, &json!({"success": false, "error": "Token not found"})).is_some());
+        assert!(ApiError::from_body(200, &json!({"success": true, "error": null})).is_none());
+        assert!(ApiError::from_body(200, &json!({"hash": "abc"})).is_none());
+    }
+}
@@ -0,0 +1,42 @@
+//! Sultan Client - Rust SDK for the Sultan node RPC
+//!
+//! Typed async access to the node's REST routes, request signing for transfers,
+//! staking, tokens and the DEX, and nonce tracking. Request bodies, query
+//! strings and signing messages are the node's own types from
+//! [`sultan_core::api`], so the client can't drift from what the node accepts.
+//!
+//! # Quick Start
+//!
+//! ```rust,no_run
+//! use sultan_client::{Signer, SultanClient, Wallet};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let client = SultanClient::new("http://localhost:26657");
+//! println!("height: {}", client.status().await?.height);
+//!
+//! let signer = Signer::from_secret_hex("sultan1...", "<64 hex chars>")?;
+//! let wallet = Wallet::new(client, signer);
+//! let hash = wallet.transfer("sultan1...", 1_000_000_000).await?;
+//! println!("submitted {}", hash);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only the raw `/rpc` call and `/openapi.json` return `serde_json::Value`.
+//! Errors the node reports come back as an [`ApiError`] inside the `anyhow::Error`.
+
+pub mod client;
+pub mod error;
+pub mod nonce;
+pub mod signer;
+pub mod wallet;
+
+pub use client::SultanClient;
+pub use error::ApiError;
+pub use nonce::NonceManager;
+pub use signer::Signer;
+pub use wallet::Wallet;
+
+pub use sultan_core::api;
+pub use sultan_core::pagination::Order;
+pub use sultan_core::simulate::{SimulateRequest, SimulatedTx, SimulationResult};
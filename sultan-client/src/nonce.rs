@@ -0,0 +1,44 @@
+//! Nonce tracking
+//!
+//! The node takes an account's transactions in nonce order, starting from the
+//! nonce `/balance` reports. A `NonceManager` reads it once and then hands out
+//! consecutive nonces locally, so several transactions can be submitted without
+//! waiting for each to land in a block. After a nonce rejection, `reset` makes
+//! the next call read it from the node again.
+
+use crate::client::SultanClient;
+use anyhow::Result;
+use tokio::sync::Mutex;
+
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    /// Next nonce to hand out; `None` until read from the node
+    next: Mutex<Option<u64>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserve the next nonce for `address`
+    pub async fn next(&self, client: &SultanClient, address: &str) -> Result<u64> {
+        let mut next = self.next.lock().await;
+        let nonce = match *next {
+            Some(nonce) => nonce,
+            None => client.balance(address).await?.nonce,
+        };
+        *next = Some(nonce + 1);
+        Ok(nonce)
+    }
+
+    /// Read the nonce from the node on the next call
+    pub async fn reset(&self) {
+        *self.next.lock().await = None;
+    }
+
+    /// Next nonce to hand out, if it has been read
+    pub async fn peek(&self) -> Option<u64> {
+        *self.next.lock().await
+    }
+}
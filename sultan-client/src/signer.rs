@@ -0,0 +1,347 @@
+//! Request signing
+//!
+//! A `Signer` holds an account's Ed25519 key and builds the signed body of each
+//! route, using the node's signing messages from `sultan_core::api`.
+
+use anyhow::{Context, Result};
+use ed25519_dalek::{Signer as _, SigningKey};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use sultan_core::api::{
+    self, AddLiquidityRequest, BurnTokenRequest, CreatePairRequest, CreateTokenRequest, CreateValidatorRequest,
+    FaucetClaimRequest, MintTokenRequest, RemoveLiquidityRequest, RotateKeyRequest, SetRewardWalletRequest,
+    SubmitProposalRequest, SwapRequest, TransferTokenRequest, TxRequest, VoteRequest, WalletTxInner,
+    WalletTxRequest,
+};
+use sultan_core::key_rotation::KeyRotation;
+
+/// Memo the wallet puts on delegations
+pub const DELEGATE_MEMO: &str = "delegate";
+
+/// An account's address and signing key
+#[derive(Clone)]
+pub struct Signer {
+    address: String,
+    key: SigningKey,
+}
+
+impl std::fmt::Debug for Signer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Signer")
+            .field("address", &self.address)
+            .field("public_key", &self.public_key_hex())
+            .finish()
+    }
+}
+
+impl Signer {
+    pub fn new(address: impl Into<String>, key: SigningKey) -> Self {
+        Self { address: address.into(), key }
+    }
+
+    /// Signer from a 32-byte secret key in hex
+    pub fn from_secret_hex(address: impl Into<String>, secret_hex: &str) -> Result<Self> {
+        let secret: [u8; 32] = hex::decode(secret_hex.trim()).ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .context("Secret key must be 64 hex characters")?;
+        Ok(Self::new(address, SigningKey::from_bytes(&secret)))
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.key.verifying_key().to_bytes())
+    }
+
+    /// Hex signature over the SHA-256 of `message` (transfers, staking)
+    pub fn sign_hashed(&self, message: &str) -> String {
+        hex::encode(self.key.sign(&Sha256::digest(message.as_bytes())).to_bytes())
+    }
+
+    /// Hex signature over `message` itself (tokens, DEX, faucet)
+    pub fn sign_raw(&self, message: &str) -> String {
+        hex::encode(self.key.sign(message.as_bytes()).to_bytes())
+    }
+
+    // ========= NATIVE TRANSFERS AND STAKING =========
+
+    /// Signed native transfer for `/tx`
+    pub fn transfer(&self, to: &str, amount: u64, nonce: u64, timestamp: u64) -> TxRequest {
+        let message = api::transfer_message(&self.address, to, amount, nonce, timestamp);
+        TxRequest::Wallet(WalletTxRequest {
+            tx: WalletTxInner {
+                from: self.address.clone(),
+                to: to.to_string(),
+                amount,
+                memo: None,
+                nonce,
+                timestamp,
+            },
+            signature: self.sign_hashed(&message),
+            public_key: self.public_key_hex(),
+        })
+    }
+
+    /// Signed wallet-format delegation for `/staking/delegate`
+    pub fn delegate(&self, validator_address: &str, amount: u64) -> WalletTxRequest {
+        let message = api::delegate_message(&self.address, validator_address, amount, DELEGATE_MEMO);
+        WalletTxRequest {
+            tx: WalletTxInner {
+                from: self.address.clone(),
+                to: validator_address.to_string(),
+                amount,
+                memo: Some(DELEGATE_MEMO.to_string()),
+                nonce: 0,
+                timestamp: 0,
+            },
+            signature: self.sign_hashed(&message),
+            public_key: self.public_key_hex(),
+        }
+    }
+
+    /// Registration of this account as a validator, with its key as the consensus key
+    pub fn create_validator(&self, initial_stake: u64, commission_rate: f64, reward_wallet: Option<String>) -> CreateValidatorRequest {
+        CreateValidatorRequest {
+            validator_address: self.address.clone(),
+            moniker: None,
+            initial_stake,
+            commission_rate,
+            signature: None,
+            public_key: Some(self.public_key_hex()),
+            reward_wallet,
+        }
+    }
+
+    /// Signed reward wallet change; `timestamp` must be within 5 minutes of the node's clock
+    pub fn set_reward_wallet(&self, reward_wallet: &str, timestamp: u64) -> SetRewardWalletRequest {
+        let message = api::set_reward_wallet_message(&self.address, reward_wallet, timestamp);
+        SetRewardWalletRequest {
+            validator_address: self.address.clone(),
+            reward_wallet: reward_wallet.to_string(),
+            signature: self.sign_hashed(&message),
+            public_key: self.public_key_hex(),
+            timestamp,
+        }
+    }
+
+    /// Rotation of this validator's consensus key to `new_key` at `activation_height`
+    /// on chain `chain_id` (as in the node's `config.json`)
+    pub fn rotate_key(&self, chain_id: &str, new_key: &SigningKey, activation_height: u64) -> RotateKeyRequest {
+        KeyRotation::sign(chain_id, &self.address, &self.key, new_key, activation_height).into()
+    }
+
+    // ========= GOVERNANCE =========
+
+    /// Proposal from this account (governance requests are not signed)
+    pub fn propose(
+        &self,
+        title: &str,
+        description: &str,
+        proposal_type: &str,
+        initial_deposit: u64,
+        parameters: Option<HashMap<String, String>>,
+    ) -> SubmitProposalRequest {
+        SubmitProposalRequest {
+            proposer: self.address.clone(),
+            title: title.to_string(),
+            description: description.to_string(),
+            proposal_type: proposal_type.to_string(),
+            initial_deposit,
+            parameters,
+            telegram_discussion_url: None,
+            discord_discussion_url: None,
+        }
+    }
+
+    /// Vote from this account: yes, no, abstain or no_with_veto
+    pub fn vote(&self, proposal_id: u64, option: &str, voting_power: u64) -> VoteRequest {
+        VoteRequest {
+            proposal_id,
+            voter: self.address.clone(),
+            option: option.to_string(),
+            voting_power,
+        }
+    }
+
+    // ========= TOKENS AND FAUCET =========
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_token(
+        &self,
+        name: &str,
+        symbol: &str,
+        decimals: u8,
+        initial_supply: u128,
+        max_supply: Option<u128>,
+        logo_url: Option<String>,
+        description: Option<String>,
+    ) -> CreateTokenRequest {
+        let message = api::create_token_message(&self.address, name, symbol, decimals, initial_supply, max_supply);
+        CreateTokenRequest {
+            creator: self.address.clone(),
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            decimals,
+            initial_supply,
+            max_supply,
+            logo_url,
+            description,
+            signature: self.sign_raw(&message),
+            pubkey: self.public_key_hex(),
+        }
+    }
+
+    /// Mint of a token this account created
+    pub fn mint_token(&self, denom: &str, to_address: &str, amount: u128) -> MintTokenRequest {
+        let message = api::mint_message(denom, to_address, amount);
+        MintTokenRequest {
+            denom: denom.to_string(),
+            to_address: to_address.to_string(),
+            amount,
+            minter: self.address.clone(),
+            signature: self.sign_raw(&message),
+            pubkey: self.public_key_hex(),
+        }
+    }
+
+    pub fn transfer_token(&self, denom: &str, to_address: &str, amount: u128) -> TransferTokenRequest {
+        let message = api::token_transfer_message(denom, &self.address, to_address, amount);
+        TransferTokenRequest {
+            denom: denom.to_string(),
+            from_address: self.address.clone(),
+            to_address: to_address.to_string(),
+            amount,
+            signature: self.sign_raw(&message),
+            pubkey: self.public_key_hex(),
+        }
+    }
+
+    pub fn burn_token(&self, denom: &str, amount: u128) -> BurnTokenRequest {
+        let message = api::burn_message(denom, &self.address, amount);
+        BurnTokenRequest {
+            denom: denom.to_string(),
+            from_address: self.address.clone(),
+            amount,
+            signature: self.sign_raw(&message),
+            pubkey: self.public_key_hex(),
+        }
+    }
+
+    /// Claim answering a `/faucet/challenge` nonce
+    pub fn faucet_claim(&self, nonce: &str) -> FaucetClaimRequest {
+        FaucetClaimRequest {
+            address: self.address.clone(),
+            nonce: nonce.to_string(),
+            signature: self.sign_raw(nonce),
+            pubkey: self.public_key_hex(),
+        }
+    }
+
+    // ========= DEX =========
+
+    pub fn create_pair(&self, token_a: &str, token_b: &str, amount_a: u128, amount_b: u128) -> CreatePairRequest {
+        let message = api::create_pair_message(&self.address, token_a, token_b, amount_a, amount_b);
+        CreatePairRequest {
+            creator: self.address.clone(),
+            token_a: token_a.to_string(),
+            token_b: token_b.to_string(),
+            amount_a,
+            amount_b,
+            signature: self.sign_raw(&message),
+            pubkey: self.public_key_hex(),
+        }
+    }
+
+    pub fn swap(&self, pair_id: &str, token_in: &str, amount_in: u128, min_amount_out: u128) -> SwapRequest {
+        let message = api::swap_message(pair_id, &self.address, token_in, amount_in, min_amount_out);
+        SwapRequest {
+            from_address: self.address.clone(),
+            pair_id: pair_id.to_string(),
+            token_in: token_in.to_string(),
+            amount_in,
+            min_amount_out,
+            signature: self.sign_raw(&message),
+            pubkey: self.public_key_hex(),
+        }
+    }
+
+    pub fn add_liquidity(&self, pair_id: &str, amount_a: u128, amount_b: u128) -> AddLiquidityRequest {
+        // The route signs with zero minimums
+        let message = api::add_liquidity_message(pair_id, &self.address, amount_a, amount_b, 0, 0);
+        AddLiquidityRequest {
+            provider: self.address.clone(),
+            pair_id: pair_id.to_string(),
+            amount_a,
+            amount_b,
+            min_lp_tokens: None,
+            signature: self.sign_raw(&message),
+            pubkey: self.public_key_hex(),
+        }
+    }
+
+    pub fn remove_liquidity(&self, pair_id: &str, liquidity: u128, min_amount_a: u128, min_amount_b: u128) -> RemoveLiquidityRequest {
+        let message = api::remove_liquidity_message(pair_id, &self.address, liquidity, min_amount_a, min_amount_b);
+        RemoveLiquidityRequest {
+            provider: self.address.clone(),
+            pair_id: pair_id.to_string(),
+            liquidity,
+            min_amount_a: Some(min_amount_a),
+            min_amount_b: Some(min_amount_b),
+            signature: self.sign_raw(&message),
+            pubkey: self.public_key_hex(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sultan_core::blockchain::Transaction;
+    use sultan_core::token_factory::TokenFactory;
+
+    fn signer() -> Signer {
+        Signer::from_secret_hex("sultan1alice", &"11".repeat(32)).unwrap()
+    }
+
+    #[test]
+    fn test_transfer_passes_node_verification() {
+        let TxRequest::Wallet(request) = signer().transfer("sultan1bob", 500, 2, 1_700_000_000) else {
+            panic!("expected wallet format");
+        };
+        let tx = Transaction {
+            from: request.tx.from,
+            to: request.tx.to,
+            amount: request.tx.amount,
+            gas_fee: 0,
+            timestamp: request.tx.timestamp,
+            nonce: request.tx.nonce,
+            signature: Some(request.signature),
+            public_key: Some(request.public_key),
+            memo: None,
+        };
+        assert!(api::verify_transfer_signature(&tx).is_ok());
+    }
+
+    #[test]
+    fn test_signatures_match_node_messages() {
+        let signer = signer();
+        let pubkey: [u8; 32] = hex::decode(signer.public_key_hex()).unwrap().try_into().unwrap();
+
+        let delegation = signer.delegate("sultan1validator", 1_000);
+        let message = api::delegate_message("sultan1alice", "sultan1validator", 1_000, DELEGATE_MEMO);
+        assert!(api::verify_request_signature(&delegation.public_key, &delegation.signature, &message).is_ok());
+
+        let mint = signer.mint_token("factory/sultan1alice/abc", "sultan1bob", 10);
+        let message = api::mint_message("factory/sultan1alice/abc", "sultan1bob", 10);
+        let signature = hex::decode(&mint.signature).unwrap();
+        assert!(TokenFactory::verify_ed25519_signature(&pubkey, message.as_bytes(), &signature));
+
+        let claim = signer.faucet_claim("sultan_faucet:sultan1alice:1");
+        let signature = hex::decode(&claim.signature).unwrap();
+        assert!(TokenFactory::verify_ed25519_signature(&pubkey, claim.nonce.as_bytes(), &signature));
+
+        assert!(Signer::from_secret_hex("sultan1alice", "abcd").is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Account-level helpers
+//!
+//! A `Wallet` pairs a client with a signer and a nonce manager for the common
+//! flows. Anything else is a `Signer` builder passed to the matching
+//! `SultanClient` method.
+
+use crate::client::SultanClient;
+use crate::error::ApiError;
+use crate::nonce::NonceManager;
+use crate::signer::Signer;
+use anyhow::Result;
+use sultan_core::api::{Delegated, FaucetClaimed, RewardWalletResponse, StakeRequest, Undelegated};
+
+#[derive(Debug)]
+pub struct Wallet {
+    client: SultanClient,
+    signer: Signer,
+    nonces: NonceManager,
+}
+
+impl Wallet {
+    pub fn new(client: SultanClient, signer: Signer) -> Self {
+        Self { client, signer, nonces: NonceManager::new() }
+    }
+
+    pub fn client(&self) -> &SultanClient {
+        &self.client
+    }
+
+    pub fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    pub fn address(&self) -> &str {
+        self.signer.address()
+    }
+
+    pub async fn balance(&self) -> Result<u64> {
+        Ok(self.client.balance(self.address()).await?.balance)
+    }
+
+    /// Sign and submit a native transfer with the next nonce, returning its hash
+    ///
+    /// A nonce rejection (another client used the account) is retried once with
+    /// the nonce read from the node again.
+    pub async fn transfer(&self, to: &str, amount: u64) -> Result<String> {
+        let mut retried = false;
+        loop {
+            let nonce = self.nonces.next(&self.client, self.address()).await?;
+            let request = self.signer.transfer(to, amount, nonce, now());
+            match self.client.submit_tx(&request).await {
+                Ok(submitted) => return Ok(submitted.hash),
+                Err(e) => {
+                    // The nonce wasn't used; read it again before the next transaction
+                    self.nonces.reset().await;
+                    let nonce_error = e.downcast_ref::<ApiError>().is_some_and(ApiError::is_nonce_error);
+                    if nonce_error && !retried {
+                        retried = true;
+                        continue;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    pub async fn delegate(&self, validator_address: &str, amount: u64) -> Result<Delegated> {
+        self.client.delegate(&self.signer.delegate(validator_address, amount)).await
+    }
+
+    /// Start unbonding; with no validator the node picks the first delegation
+    pub async fn undelegate(&self, validator_address: Option<&str>, amount: u64) -> Result<Undelegated> {
+        self.client.undelegate(&StakeRequest {
+            delegator_address: self.address().to_string(),
+            validator_address: validator_address.unwrap_or_default().to_string(),
+            amount,
+        }).await
+    }
+
+    pub async fn set_reward_wallet(&self, reward_wallet: &str) -> Result<RewardWalletResponse> {
+        self.client.set_reward_wallet(&self.signer.set_reward_wallet(reward_wallet, now())).await
+    }
+
+    /// Request a faucet challenge, sign it and claim
+    pub async fn claim_faucet(&self) -> Result<FaucetClaimed> {
+        let nonce = self.client.faucet_challenge(self.address()).await?;
+        self.client.faucet_claim(&self.signer.faucet_claim(&nonce)).await
+    }
+
+    /// Forget the cached nonce, e.g. after submitting from elsewhere
+    pub async fn resync_nonce(&self) {
+        self.nonces.reset().await;
+    }
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
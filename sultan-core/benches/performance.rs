@@ -2,14 +2,18 @@
 //!
 //! Run with: cargo bench
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId, Throughput};
+use ed25519_dalek::{Signer, SigningKey};
+use sha2::{Digest, Sha256};
 use sultan_core::*;
+use sultan_core::sharding_production::Shard;
+use sultan_core::transaction_validator::SignatureRules;
 
 fn benchmark_block_creation(c: &mut Criterion) {
     let mut group = c.benchmark_group("block_creation");
     
     for tx_count in [10, 50, 100, 500].iter() {
-        group.benchmark_with_input(
+        group.bench_with_input(
             BenchmarkId::from_parameter(tx_count),
             tx_count,
             |b, &tx_count| {
@@ -41,7 +45,7 @@ fn benchmark_block_creation(c: &mut Criterion) {
 
 fn benchmark_transaction_validation(c: &mut Criterion) {
     c.bench_function("transaction_validation", |b| {
-        let mut validator = TransactionValidator::new();
+        let validator = TransactionValidator::new();
         let tx = Transaction::new(
             "alice".to_string(),
             "bob".to_string(),
@@ -59,7 +63,7 @@ fn benchmark_state_updates(c: &mut Criterion) {
     let mut group = c.benchmark_group("state_updates");
     
     for account_count in [100, 1000, 10000].iter() {
-        group.benchmark_with_input(
+        group.bench_with_input(
             BenchmarkId::from_parameter(account_count),
             account_count,
             |b, &account_count| {
@@ -99,7 +103,8 @@ fn benchmark_consensus_selection(c: &mut Criterion) {
         for i in 0..100 {
             consensus.add_validator(
                 format!("validator{}", i),
-                10000 + (i * 100) as u64,
+                consensus.min_stake + (i * 100) as u64,
+                [i as u8; 32],
             ).ok();
         }
         
@@ -125,10 +130,17 @@ fn benchmark_block_validation(c: &mut Criterion) {
             blockchain.add_transaction(tx).ok();
         }
         
+        // Validate against the chain as it was before the block was appended;
+        // the block lands in the same second as genesis, so backdate genesis
+        blockchain.chain[0].timestamp -= 1;
+        let mut validator_view = Blockchain::new();
+        validator_view.chain = blockchain.chain.clone();
+        validator_view.state = blockchain.state.clone();
+
         let block = blockchain.create_block("validator1".to_string()).unwrap();
         
         b.iter(|| {
-            black_box(blockchain.validate_block(&block).unwrap());
+            black_box(validator_view.validate_block(&block).unwrap());
         });
     });
 }
@@ -140,7 +152,8 @@ fn benchmark_throughput(c: &mut Criterion) {
             let mut consensus = ConsensusEngine::new();
             
             blockchain.init_account("alice".to_string(), 100_000_000);
-            consensus.add_validator("validator1".to_string(), 100_000).ok();
+            let min_stake = consensus.min_stake;
+            consensus.add_validator("validator1".to_string(), min_stake, [1u8; 32]).ok();
             
             // Produce 1000 blocks
             for i in 0..1000 {
@@ -159,6 +172,51 @@ fn benchmark_throughput(c: &mut Criterion) {
     });
 }
 
+/// Transfers signed the way the wallet signs them
+fn signed_transfers(count: usize) -> Vec<Transaction> {
+    (0..count)
+        .map(|i| {
+            let signing_key = SigningKey::from_bytes(&Sha256::digest(i.to_le_bytes()).into());
+            let mut tx = Transaction::new(format!("sender{}", i), "bob".to_string(), 100, 0);
+            let message = format!(
+                r#"{{"from":"{}","to":"{}","amount":"{}","memo":"","nonce":{},"timestamp":{}}}"#,
+                tx.from, tx.to, tx.amount, tx.nonce, tx.timestamp
+            );
+            let signature = signing_key.sign(&Sha256::digest(message.as_bytes()));
+            tx.signature = Some(hex::encode(signature.to_bytes()));
+            tx.public_key = Some(hex::encode(signing_key.verifying_key().to_bytes()));
+            tx
+        })
+        .collect()
+}
+
+fn benchmark_shard_signature_verification(c: &mut Criterion) {
+    let mut group = c.benchmark_group("shard_signature_verification");
+    let shard = Shard::new(0);
+
+    // Transactions one shard verifies per block
+    for tx_count in [16, 64, 256, 1024].iter() {
+        let txs = signed_transfers(*tx_count);
+        let refs: Vec<&Transaction> = txs.iter().collect();
+        group.throughput(Throughput::Elements(*tx_count as u64));
+
+        group.bench_with_input(BenchmarkId::new("individual", tx_count), &refs, |b, refs| {
+            b.iter(|| {
+                for tx in refs {
+                    shard.verify_signature(black_box(tx)).unwrap();
+                }
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("batch", tx_count), &refs, |b, refs| {
+            b.iter(|| {
+                black_box(shard.verify_signatures(refs, SignatureRules::Strict));
+            });
+        });
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     benchmark_block_creation,
@@ -167,6 +225,7 @@ criterion_group!(
     benchmark_consensus_selection,
     benchmark_block_validation,
     benchmark_throughput,
+    benchmark_shard_signature_verification,
 );
 
 criterion_main!(benches);
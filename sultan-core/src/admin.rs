@@ -0,0 +1,222 @@
+//! Admin RPC listener settings and authentication
+//!
+//! Privileged operations (faucet control, peer bans, shard expansion, log level,
+//! pruning, compaction, snapshots, halting the node) are served on their own
+//! listener, never on the public RPC port. The listener is a unix socket or a TCP
+//! address. Requests carry `Authorization: Bearer <token>`; a TCP listener off
+//! loopback must additionally require client certificates (mTLS).
+
+use anyhow::{bail, Context, Result};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::token_factory::TokenFactory;
+
+/// Shortest admin token accepted
+pub const MIN_TOKEN_LEN: usize = 32;
+
+/// Where the admin listener binds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdminListen {
+    Tcp(SocketAddr),
+    /// `unix:<path>`
+    Unix(PathBuf),
+}
+
+impl FromStr for AdminListen {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.strip_prefix("unix:") {
+            Some("") => bail!("Admin socket path is empty"),
+            Some(path) => Ok(Self::Unix(PathBuf::from(path))),
+            None => Ok(Self::Tcp(s.parse().with_context(|| format!("Invalid admin address: {}", s))?)),
+        }
+    }
+}
+
+impl std::fmt::Display for AdminListen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{}", addr),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// Client certificate authentication for a TCP admin listener
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdminTls {
+    pub cert_path: String,
+    pub key_path: String,
+    /// CA that client certificates must chain to
+    pub client_ca_path: String,
+}
+
+/// Admin listener configuration, checked by `AdminConfig::new`
+#[derive(Debug, Clone)]
+pub struct AdminConfig {
+    pub listen: AdminListen,
+    pub auth: AdminAuth,
+    pub tls: Option<AdminTls>,
+}
+
+impl AdminConfig {
+    /// Refuses configurations that would leave the admin API open
+    ///
+    /// A token is required unless mTLS is on, and a TCP listener that isn't on
+    /// loopback must use mTLS. TLS is not available on unix sockets.
+    pub fn new(listen: AdminListen, token: Option<String>, tls: Option<AdminTls>) -> Result<Self> {
+        let token = token.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+        if let Some(token) = &token {
+            if token.len() < MIN_TOKEN_LEN {
+                bail!("Admin token must be at least {} characters", MIN_TOKEN_LEN);
+            }
+        }
+        match (&listen, &tls) {
+            (AdminListen::Unix(_), Some(_)) => bail!("Admin mTLS is only available on a TCP listener"),
+            (AdminListen::Tcp(addr), None) if !addr.ip().is_loopback() => {
+                bail!("Admin listener {} is not on loopback; configure mTLS or bind to 127.0.0.1", addr)
+            }
+            _ => {}
+        }
+        if token.is_none() && tls.is_none() {
+            bail!("Admin listener needs a token (SULTAN_ADMIN_TOKEN) or mTLS");
+        }
+        Ok(Self { listen, auth: AdminAuth { token }, tls })
+    }
+}
+
+/// Bearer-token check for admin requests
+#[derive(Debug, Clone)]
+pub struct AdminAuth {
+    /// `None` only when client certificates authenticate instead
+    token: Option<String>,
+}
+
+impl AdminAuth {
+    /// Whether an `Authorization` header value grants access (constant-time)
+    pub fn authorize(&self, authorization: Option<&str>) -> bool {
+        let Some(expected) = &self.token else {
+            return true;
+        };
+        authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|token| TokenFactory::constant_time_compare(token.trim(), expected))
+    }
+}
+
+/// Bind a unix socket only the node's user can connect to
+///
+/// A socket left behind by an earlier run is replaced. The socket is bound
+/// inside a fresh 0700 directory and only moved to `path` once it is 0600, so
+/// it is never reachable with looser permissions.
+#[cfg(unix)]
+pub fn bind_unix_socket(path: &Path) -> Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            bail!("{:?} exists and is not a socket", path);
+        }
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale admin socket {:?}", path))?;
+    }
+
+    let file_name = path.file_name().with_context(|| format!("{:?} is not a socket path", path))?;
+    let staging = path.with_file_name(format!(".{}.{}", file_name.to_string_lossy(), std::process::id()));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&staging)
+        .with_context(|| format!("Failed to create {:?}", staging))?;
+
+    let staged = staging.join("admin.sock");
+    let bound = tokio::net::UnixListener::bind(&staged)
+        .with_context(|| format!("Failed to bind admin socket {:?}", path))
+        .and_then(|listener| {
+            std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+            std::fs::rename(&staged, path).with_context(|| format!("Failed to move admin socket to {:?}", path))?;
+            Ok(listener)
+        });
+    let _ = std::fs::remove_file(&staged);
+    let _ = std::fs::remove_dir(&staging);
+    bound
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "0123456789abcdef0123456789abcdef";
+
+    fn tls() -> AdminTls {
+        AdminTls {
+            cert_path: "admin.crt".to_string(),
+            key_path: "admin.key".to_string(),
+            client_ca_path: "ca.crt".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_listen() {
+        assert_eq!("127.0.0.1:26660".parse::<AdminListen>().unwrap(), AdminListen::Tcp("127.0.0.1:26660".parse().unwrap()));
+        assert_eq!("unix:/run/sultan/admin.sock".parse::<AdminListen>().unwrap(), AdminListen::Unix("/run/sultan/admin.sock".into()));
+        assert!("unix:".parse::<AdminListen>().is_err());
+        assert!("localhost".parse::<AdminListen>().is_err());
+    }
+
+    #[test]
+    fn test_config_refuses_open_listeners() {
+        let local: AdminListen = "127.0.0.1:26660".parse().unwrap();
+        let public: AdminListen = "0.0.0.0:26660".parse().unwrap();
+        let socket: AdminListen = "unix:/tmp/admin.sock".parse().unwrap();
+
+        assert!(AdminConfig::new(local.clone(), Some(TOKEN.to_string()), None).is_ok());
+        assert!(AdminConfig::new(local.clone(), None, None).is_err());
+        assert!(AdminConfig::new(local, Some("short".to_string()), None).is_err());
+        assert!(AdminConfig::new(public.clone(), Some(TOKEN.to_string()), None).is_err());
+        assert!(AdminConfig::new(public, None, Some(tls())).is_ok());
+        assert!(AdminConfig::new(socket.clone(), Some(TOKEN.to_string()), Some(tls())).is_err());
+        assert!(AdminConfig::new(socket, Some(TOKEN.to_string()), None).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("admin.sock");
+        std::fs::write(&path, b"").unwrap();
+        assert!(bind_unix_socket(&path).is_err(), "replaced a regular file");
+        std::fs::remove_file(&path).unwrap();
+
+        let listener = bind_unix_socket(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        // Only the socket is left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let (accepted, connected) = tokio::join!(listener.accept(), tokio::net::UnixStream::connect(&path));
+        accepted.unwrap();
+        connected.unwrap();
+
+        // A stale socket from an earlier run is replaced
+        drop(listener);
+        bind_unix_socket(&path).unwrap();
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        let config = AdminConfig::new("127.0.0.1:26660".parse().unwrap(), Some(TOKEN.to_string()), None).unwrap();
+        assert!(config.auth.authorize(Some(&format!("Bearer {}", TOKEN))));
+        assert!(!config.auth.authorize(Some(TOKEN)));
+        assert!(!config.auth.authorize(Some("Bearer 0123456789abcdef0123456789abcdee")));
+        assert!(!config.auth.authorize(None));
+
+        // With mTLS and no token the certificate is the credential
+        let config = AdminConfig::new("0.0.0.0:26660".parse().unwrap(), None, Some(tls())).unwrap();
+        assert!(config.auth.authorize(None));
+    }
+}
@@ -0,0 +1,1198 @@
+//! RPC request types and signing messages
+//!
+//! Bodies and query strings of the REST routes, shared by the node and by
+//! `sultan-client` so neither side redeclares them. Each signed route has a
+//! `*_message` function building the exact string its signature covers.
+//!
+//! Two signing schemes are in use, kept for wallet compatibility:
+//! - native transfers, delegations and reward wallet changes sign the SHA-256
+//!   hash of the message;
+//! - token, DEX and faucet operations sign the message bytes as they are.
+
+use crate::blockchain::{Block, Transaction};
+use crate::bridge_integration::{BridgeStatistics, BridgeStatus};
+use crate::events::StoredEvent;
+use crate::governance::{Proposal, ProposalStatus};
+use crate::key_rotation::KeyRotation;
+use crate::native_dex::LiquidityPool;
+use crate::pagination::Order;
+use crate::sharded_blockchain_production::ConfirmedTransaction;
+use crate::storage::{BlockHeader, PrunedRange};
+use crate::token_factory::TokenMetadata;
+use anyhow::{bail, Context, Result};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey, SIGNATURE_LENGTH};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Events per `/events` page when the request gives no `limit`
+pub const DEFAULT_EVENTS_LIMIT: usize = 100;
+
+// ========= SIGNING MESSAGES =========
+
+/// Native transfer (`/tx`), signed as SHA-256
+///
+/// The wallet signs `JSON.stringify({from, to, amount, memo, nonce, timestamp})`
+/// with the amount as a string and an empty memo.
+pub fn transfer_message(from: &str, to: &str, amount: u64, nonce: u64, timestamp: u64) -> String {
+    format!(
+        r#"{{"from":"{}","to":"{}","amount":"{}","memo":"","nonce":{},"timestamp":{}}}"#,
+        from, to, amount, nonce, timestamp
+    )
+}
+
+/// Wallet-format delegation (`/staking/delegate`), signed as SHA-256
+pub fn delegate_message(from: &str, to: &str, amount: u64, memo: &str) -> String {
+    format!("{}:{}:{}:delegate:{}", from, to, amount, memo)
+}
+
+/// Reward wallet change (`/staking/set_reward_wallet`), signed as SHA-256
+pub fn set_reward_wallet_message(validator_address: &str, reward_wallet: &str, timestamp: u64) -> String {
+    format!("set_reward_wallet:{}:{}:{}", validator_address, reward_wallet, timestamp)
+}
+
+/// Token creation (`/tokens/create`), signed as is
+pub fn create_token_message(
+    creator: &str,
+    name: &str,
+    symbol: &str,
+    decimals: u8,
+    total_supply: u128,
+    max_supply: Option<u128>,
+) -> String {
+    format!(
+        "create_token:{}:{}:{}:{}:{}:{:?}",
+        creator, name, symbol, decimals, total_supply, max_supply
+    )
+}
+
+/// Token mint (`/tokens/mint`), signed as is
+pub fn mint_message(denom: &str, recipient: &str, amount: u128) -> String {
+    format!("mint:{}:{}:{}", denom, recipient, amount)
+}
+
+/// Token transfer (`/tokens/transfer`), signed as is
+pub fn token_transfer_message(denom: &str, from: &str, to: &str, amount: u128) -> String {
+    format!("transfer:{}:{}:{}:{}", denom, from, to, amount)
+}
+
+/// Token burn (`/tokens/burn`), signed as is
+pub fn burn_message(denom: &str, from: &str, amount: u128) -> String {
+    format!("burn:{}:{}:{}", denom, from, amount)
+}
+
+/// Pair creation (`/dex/create_pair`), signed as is
+pub fn create_pair_message(creator: &str, token_a: &str, token_b: &str, amount_a: u128, amount_b: u128) -> String {
+    format!("create_pair:{}:{}:{}:{}:{}", creator, token_a, token_b, amount_a, amount_b)
+}
+
+/// Swap (`/dex/swap`), signed as is
+pub fn swap_message(pair_id: &str, user: &str, token_in: &str, amount_in: u128, min_amount_out: u128) -> String {
+    format!("swap:{}:{}:{}:{}:{}", pair_id, user, token_in, amount_in, min_amount_out)
+}
+
+/// Liquidity deposit (`/dex/add_liquidity`), signed as is
+///
+/// The route currently passes 0 for both minimums.
+pub fn add_liquidity_message(
+    pair_id: &str,
+    user: &str,
+    amount_a_desired: u128,
+    amount_b_desired: u128,
+    amount_a_min: u128,
+    amount_b_min: u128,
+) -> String {
+    format!(
+        "add_liquidity:{}:{}:{}:{}:{}:{}",
+        pair_id, user, amount_a_desired, amount_b_desired, amount_a_min, amount_b_min
+    )
+}
+
+/// Liquidity withdrawal (`/dex/remove_liquidity`), signed as is
+pub fn remove_liquidity_message(
+    pair_id: &str,
+    user: &str,
+    lp_tokens: u128,
+    amount_a_min: u128,
+    amount_b_min: u128,
+) -> String {
+    format!("remove_liquidity:{}:{}:{}:{}:{}", pair_id, user, lp_tokens, amount_a_min, amount_b_min)
+}
+
+/// Protocol fee collection, signed as is
+pub fn collect_fees_message(pair_id: &str, caller: &str) -> String {
+    format!("collect_fees:{}:{}", pair_id, caller)
+}
+
+/// Verify a hex Ed25519 signature over the SHA-256 of `message`
+pub fn verify_request_signature(public_key_hex: &str, signature_hex: &str, message: &str) -> Result<(), String> {
+    if public_key_hex.len() != 64 {
+        return Err(format!("Invalid public key length: {} (expected 64 hex chars)", public_key_hex.len()));
+    }
+    let pubkey_bytes = hex::decode(public_key_hex)
+        .map_err(|e| format!("Invalid public key hex: {}", e))?;
+    let mut pubkey_arr = [0u8; 32];
+    pubkey_arr.copy_from_slice(&pubkey_bytes);
+    let verifying_key = VerifyingKey::from_bytes(&pubkey_arr)
+        .map_err(|e| format!("Invalid Ed25519 public key: {}", e))?;
+
+    if signature_hex.len() != 128 {
+        return Err(format!("Invalid signature length: {} (expected 128 hex chars)", signature_hex.len()));
+    }
+    let sig_bytes = hex::decode(signature_hex)
+        .map_err(|e| format!("Invalid signature hex: {}", e))?;
+    let signature = Signature::from_slice(&sig_bytes)
+        .map_err(|e| format!("Invalid Ed25519 signature: {}", e))?;
+
+    let message_hash = Sha256::digest(message.as_bytes());
+    verifying_key.verify(&message_hash, &signature)
+        .map_err(|_| "Signature verification failed".to_string())
+}
+
+/// Verify the wallet signature on a native transfer (see `transfer_message`)
+pub fn verify_transfer_signature(tx: &Transaction) -> Result<()> {
+    let sig_str = match tx.signature.as_ref() {
+        Some(s) if !s.is_empty() => s,
+        _ => bail!("Transaction signature is required"),
+    };
+    let pubkey_str = match tx.public_key.as_ref() {
+        Some(pk) if !pk.is_empty() => pk,
+        _ => bail!("Transaction public key is required for signature verification"),
+    };
+
+    let sig_bytes = hex::decode(sig_str)
+        .context("Invalid signature: not valid hex")?;
+    if sig_bytes.len() != SIGNATURE_LENGTH {
+        bail!("Invalid signature length: expected {}, got {}", SIGNATURE_LENGTH, sig_bytes.len());
+    }
+    let sig_array: [u8; SIGNATURE_LENGTH] = sig_bytes.try_into()
+        .map_err(|_| anyhow::anyhow!("Failed to convert signature bytes to array"))?;
+    let signature = Signature::from_bytes(&sig_array);
+
+    let pubkey_bytes = hex::decode(pubkey_str)
+        .context("Invalid public key: not valid hex")?;
+    if pubkey_bytes.len() != 32 {
+        bail!("Invalid public key length: expected 32, got {}", pubkey_bytes.len());
+    }
+    let pubkey_array: [u8; 32] = pubkey_bytes.try_into()
+        .map_err(|_| anyhow::anyhow!("Failed to convert public key bytes to array"))?;
+    let verifying_key = VerifyingKey::from_bytes(&pubkey_array)
+        .context("Invalid Ed25519 public key")?;
+
+    let message = transfer_message(&tx.from, &tx.to, tx.amount, tx.nonce, tx.timestamp);
+    let message_hash = Sha256::digest(message.as_bytes());
+
+    // STRICT: reject invalid signatures
+    verifying_key.verify(&message_hash, &signature)
+        .context("Signature verification failed: invalid signature")?;
+    Ok(())
+}
+
+/// Accept an amount as a JSON number or a decimal string
+pub fn deserialize_amount<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+    let value: serde_json::Value = serde::Deserialize::deserialize(deserializer)?;
+    match value {
+        serde_json::Value::Number(n) => {
+            n.as_u64().ok_or_else(|| D::Error::custom("Invalid amount"))
+        }
+        serde_json::Value::String(s) => {
+            s.parse::<u64>().map_err(|_| D::Error::custom("Invalid amount string"))
+        }
+        _ => Err(D::Error::custom("Amount must be number or string")),
+    }
+}
+
+/// Schema of a `deserialize_amount` field
+fn amount_schema(_: &mut SchemaGenerator) -> Schema {
+    serde_json::from_value(serde_json::json!({
+        "description": "Amount as a JSON number or a decimal string",
+        "oneOf": [
+            { "type": "integer", "format": "uint64", "minimum": 0 },
+            { "type": "string", "pattern": "^[0-9]+$" }
+        ]
+    }))
+    .expect("amount schema is valid")
+}
+
+// ========= TRANSACTIONS =========
+
+/// Body of `/tx`: the wallet format or the simple format
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum TxRequest {
+    /// Wallet format: { tx: {...}, signature: "...", public_key: "..." }
+    Wallet(WalletTxRequest),
+    /// Simple format: { from: "...", to: "...", amount: 100, ... }
+    Simple(SimpleTxRequest),
+}
+
+/// Signed wallet request (`/tx`, and `/staking/delegate` with a memo)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WalletTxRequest {
+    pub tx: WalletTxInner,
+    pub signature: String,
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SimpleTxRequest {
+    pub from: String,
+    pub to: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    #[schemars(schema_with = "amount_schema")]
+    pub amount: u64,
+    #[serde(default)]
+    pub gas_fee: u64,
+    #[serde(default)]
+    pub nonce: u64,
+    #[serde(default)]
+    pub timestamp: u64,
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WalletTxInner {
+    pub from: String,
+    pub to: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    #[schemars(schema_with = "amount_schema")]
+    pub amount: u64,
+    #[serde(default)]
+    pub memo: Option<String>,
+    #[serde(default)]
+    pub nonce: u64,
+    #[serde(default)]
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BridgeTxRequest {
+    pub source_chain: String,
+    pub dest_chain: String,
+    pub source_tx: String,
+    pub amount: u64,
+    pub recipient: String,
+    pub signature: String,  // hex-encoded Ed25519 signature
+    pub pubkey: String,     // hex-encoded 32-byte public key
+}
+
+// ========= STAKING =========
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreateValidatorRequest {
+    pub validator_address: String,
+    #[serde(default)]
+    pub moniker: Option<String>,
+    #[serde(deserialize_with = "deserialize_amount")]
+    #[schemars(schema_with = "amount_schema")]
+    pub initial_stake: u64,
+    #[serde(default = "default_commission")]
+    pub commission_rate: f64,
+    #[serde(default)]
+    pub signature: Option<String>,
+    #[serde(default)]
+    pub public_key: Option<String>,
+    /// Optional: wallet address for receiving APY rewards
+    /// If not provided, defaults to validator_address
+    #[serde(default)]
+    pub reward_wallet: Option<String>,
+}
+
+fn default_commission() -> f64 {
+    0.05 // 5% default commission
+}
+
+/// Body of `/staking/delegate` and `/staking/undelegate`
+///
+/// The wallet format's signature covers `delegate_message` and is checked on
+/// delegation when given; undelegation does not check it.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum DelegateRequest {
+    /// Wallet format: { tx: { from, to, amount, memo }, signature, public_key }
+    Wallet(WalletTxRequest),
+    /// Simple format: { delegator_address, validator_address, amount }
+    Simple(StakeRequest),
+}
+
+impl DelegateRequest {
+    /// Delegator, validator and amount, whichever the format
+    pub fn stake(&self) -> (&str, &str, u64) {
+        match self {
+            DelegateRequest::Wallet(request) => (&request.tx.from, &request.tx.to, request.tx.amount),
+            DelegateRequest::Simple(request) => (&request.delegator_address, &request.validator_address, request.amount),
+        }
+    }
+}
+
+/// Simple-format body of `/staking/delegate` and `/staking/undelegate`
+///
+/// Undelegation finds the validator from the delegator's delegations when
+/// `validator_address` is empty.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StakeRequest {
+    #[serde(alias = "delegator")]
+    pub delegator_address: String,
+    #[serde(default, alias = "validator")]
+    pub validator_address: String,
+    #[serde(deserialize_with = "deserialize_amount")]
+    #[schemars(schema_with = "amount_schema")]
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WithdrawRewardsRequest {
+    pub address: String,
+    pub validator_address: Option<String>,
+    pub is_validator: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SetRewardWalletRequest {
+    pub validator_address: String,
+    pub reward_wallet: String,
+    /// Signature proving ownership of validator (required)
+    /// Signs: "set_reward_wallet:{validator_address}:{reward_wallet}:{timestamp}"
+    pub signature: String,
+    /// Public key of the validator (hex encoded, 64 chars)
+    pub public_key: String,
+    /// Timestamp to prevent replay attacks (must be within 5 minutes)
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RotateKeyRequest {
+    pub validator_address: String,
+    /// New consensus public key (hex encoded, 64 chars)
+    pub new_pubkey: String,
+    /// First height signed with the new key
+    pub activation_height: u64,
+    /// Signatures over "sultan_key_rotation_v1:{validator_address}:{new_pubkey}:{activation_height}"
+    /// by the current key and by the new key (hex encoded, 128 chars each)
+    pub signature: String,
+    pub new_key_signature: String,
+}
+
+impl From<KeyRotation> for RotateKeyRequest {
+    fn from(rotation: KeyRotation) -> Self {
+        Self {
+            validator_address: rotation.validator,
+            new_pubkey: hex::encode(rotation.new_pubkey),
+            activation_height: rotation.activation_height,
+            signature: hex::encode(rotation.signature),
+            new_key_signature: hex::encode(rotation.new_key_signature),
+        }
+    }
+}
+
+// ========= GOVERNANCE =========
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SubmitProposalRequest {
+    pub proposer: String,
+    pub title: String,
+    pub description: String,
+    /// parameter_change, software_upgrade, community_pool or text
+    pub proposal_type: String,
+    pub initial_deposit: u64,
+    pub parameters: Option<HashMap<String, String>>,
+    pub telegram_discussion_url: Option<String>,
+    pub discord_discussion_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VoteRequest {
+    pub proposal_id: u64,
+    pub voter: String,
+    /// yes, no, abstain or no_with_veto
+    pub option: String,
+    pub voting_power: u64,
+}
+
+// ========= TOKENS, FAUCET AND DEX =========
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreateTokenRequest {
+    pub creator: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub initial_supply: u128,
+    pub max_supply: Option<u128>,
+    pub logo_url: Option<String>,
+    pub description: Option<String>,
+    pub signature: String,  // hex-encoded Ed25519 signature
+    pub pubkey: String,     // hex-encoded 32-byte public key
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MintTokenRequest {
+    pub denom: String,
+    pub to_address: String,
+    pub amount: u128,
+    pub minter: String,     // address of minter (must be token creator)
+    pub signature: String,
+    pub pubkey: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TransferTokenRequest {
+    pub denom: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub amount: u128,
+    pub signature: String,
+    pub pubkey: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BurnTokenRequest {
+    pub denom: String,
+    pub from_address: String,
+    pub amount: u128,
+    pub signature: String,
+    pub pubkey: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FaucetClaimRequest {
+    pub address: String,
+    pub nonce: String,
+    pub signature: String,  // hex-encoded Ed25519 signature over the nonce
+    pub pubkey: String,     // hex-encoded 32-byte Ed25519 public key
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreatePairRequest {
+    pub creator: String,
+    pub token_a: String,
+    pub token_b: String,
+    pub amount_a: u128,
+    pub amount_b: u128,
+    pub signature: String,
+    pub pubkey: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SwapRequest {
+    pub from_address: String,
+    pub pair_id: String,
+    pub token_in: String,
+    pub amount_in: u128,
+    pub min_amount_out: u128,
+    pub signature: String,
+    pub pubkey: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AddLiquidityRequest {
+    pub provider: String,
+    pub pair_id: String,
+    pub amount_a: u128,
+    pub amount_b: u128,
+    /// Reserved for slippage protection; not yet enforced
+    pub min_lp_tokens: Option<u128>,
+    pub signature: String,
+    pub pubkey: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RemoveLiquidityRequest {
+    pub provider: String,
+    pub pair_id: String,
+    pub liquidity: u128,
+    pub min_amount_a: Option<u128>,
+    pub min_amount_b: Option<u128>,
+    pub signature: String,
+    pub pubkey: String,
+}
+
+// ========= PATH PARAMETERS =========
+
+/// A percent-decoded path parameter
+///
+/// warp passes path segments through as sent, so a denom like
+/// `factory/<creator>/<symbol>` arrives as `factory%2F...` unless decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathSegment(pub String);
+
+impl std::str::FromStr for PathSegment {
+    type Err = std::str::Utf8Error;
+
+    fn from_str(segment: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(Self(percent_encoding::percent_decode_str(segment).decode_utf8()?.into_owned()))
+    }
+}
+
+// ========= REPLIES =========
+
+/// Error body
+///
+/// Every route reports errors with an `error` message. Depending on the route
+/// the body also has the HTTP `status`, `"status": "error"`, `success: false`
+/// or context such as the heights of pruned blocks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
+/// Reply of `/status`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NodeStatus {
+    pub height: u64,
+    pub latest_hash: String,
+    pub validator_count: usize,
+    pub pending_txs: usize,
+    pub total_accounts: usize,
+    pub sharding_enabled: bool,
+    pub shard_count: usize,
+    pub inflation_rate: f64,
+    pub validator_apy: f64,
+    pub total_burned: u64,
+    pub is_deflationary: bool,
+}
+
+/// Reply of `/balance/:address`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct BalanceResponse {
+    pub address: String,
+    pub balance: u64,
+    /// Nonce the account's next transaction must carry
+    pub nonce: u64,
+}
+
+/// Reply of `/tx` for an accepted transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TxSubmitted {
+    pub hash: String,
+}
+
+/// Reply of `/block/:height` (status 410) for a block whose body was pruned
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BlockPruned {
+    pub error: String,
+    /// Always `pruned`
+    pub code: String,
+    pub height: u64,
+    pub header: Option<BlockHeader>,
+    pub pruned_ranges: Vec<PrunedRange>,
+}
+
+/// Reply of `/block/latest`; before the first block only the height and a message
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LatestBlockResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block: Option<Block>,
+    pub height: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// A block in `/blocks`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BlockSummary {
+    pub height: u64,
+    pub hash: String,
+    pub timestamp: u64,
+    pub tx_count: u64,
+    pub validator: String,
+    /// Set when only the header is left
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pruned: bool,
+}
+
+/// Reply of `/blocks`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BlocksPage {
+    pub blocks: Vec<BlockSummary>,
+    pub total_height: u64,
+    pub count: usize,
+    pub limit: usize,
+    pub next_cursor: Option<String>,
+}
+
+/// Reply of `/events`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EventsPage {
+    pub events: Vec<StoredEvent>,
+    pub count: usize,
+    pub from_height: u64,
+    /// Height to continue from, if the query stopped before the chain tip
+    pub next_height: Option<u64>,
+}
+
+/// Reply of `/stats`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StatsResponse {
+    pub height: u64,
+    pub total_transactions: u64,
+    pub total_processed: u64,
+    pub estimated_tps: u64,
+    /// Percentage, e.g. `12.5%`
+    pub current_load: String,
+    pub validator_count: usize,
+    pub shard_count: usize,
+    pub healthy_shards: usize,
+    pub max_shards: usize,
+    pub pending_cross_shard: usize,
+    pub total_accounts: usize,
+    pub should_expand: bool,
+    /// Pruning mode of the node's storage
+    pub pruning: String,
+    pub pruned_ranges: Vec<PrunedRange>,
+    pub sharding_enabled: bool,
+    pub block_time_seconds: u64,
+    pub gas_fees: String,
+    pub network: String,
+}
+
+/// Reply of `/beacon`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BeaconResponse {
+    /// Height the seed is for (the next block)
+    pub height: u64,
+    pub epoch: u64,
+    /// Hex-encoded seed
+    pub seed: String,
+    /// Whether the seed comes from validator reveals rather than the fallback
+    pub from_reveals: bool,
+}
+
+/// Reply of `/transactions/:address`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TxHistoryResponse {
+    pub address: String,
+    pub transactions: Vec<ConfirmedTransaction>,
+    pub count: usize,
+    pub next_cursor: Option<String>,
+}
+
+/// Reply of `/economics`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EconomicsResponse {
+    pub current_inflation_rate: f64,
+    pub inflation_percentage: String,
+    pub current_burn_rate: f64,
+    pub burn_percentage: String,
+    pub validator_apy: f64,
+    pub apy_percentage: String,
+    pub total_burned: u64,
+    pub years_since_genesis: u32,
+    pub is_deflationary: bool,
+    pub inflation_rate: String,
+    pub inflation_policy: String,
+    pub total_supply: u64,
+    pub total_supply_formatted: String,
+    pub circulating_supply: u64,
+    pub genesis_supply: u64,
+}
+
+/// Reply of `/supply/total`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TotalSupplyResponse {
+    pub total_supply: u64,
+    pub total_supply_sltn: f64,
+    pub circulating_supply: u64,
+    pub circulating_supply_sltn: f64,
+    pub genesis_supply: u64,
+    pub genesis_supply_sltn: f64,
+    pub total_burned: u64,
+    pub decimals: u8,
+    pub denom: String,
+}
+
+/// Reply of `/bridges`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BridgesResponse {
+    pub bridges: Vec<BridgeStatus>,
+    pub statistics: BridgeStatistics,
+}
+
+/// Reply of `/bridge/submit`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BridgeTxSubmitted {
+    pub tx_id: String,
+    pub status: String,
+}
+
+/// Reply of `/bridge/fees/treasury`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TreasuryResponse {
+    pub treasury_address: String,
+    pub description: String,
+    pub usage: String,
+}
+
+/// Reply of `/staking/create_validator`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ValidatorCreated {
+    pub validator_address: String,
+    /// Same as `validator_address`, for older wallets
+    #[serde(rename = "validatorAddress")]
+    pub validator_address_camel: String,
+    /// Decimal string
+    pub stake: String,
+    pub commission: f64,
+    pub status: String,
+    /// Whether the validator also joined the consensus set
+    pub consensus: bool,
+}
+
+/// Reply of `/staking/delegate`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Delegated {
+    pub delegator: String,
+    pub validator: String,
+    pub amount: u64,
+    pub status: String,
+    pub hash: String,
+}
+
+/// Reply of `/staking/undelegate`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Undelegated {
+    pub delegator: String,
+    pub validator: String,
+    pub amount: u64,
+    pub status: String,
+    /// Height the unbonded amount returns to the delegator's balance
+    pub completion_height: u64,
+    pub completion_time: u64,
+    pub hash: String,
+    pub message: String,
+}
+
+/// Validator in `/staking/validators`, with computed fields for explorers
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ValidatorInfo {
+    pub validator_address: String,
+    pub reward_wallet: Option<String>,
+    pub self_stake: u64,
+    pub delegated_stake: u64,
+    pub total_stake: u64,
+    pub commission_rate: f64,
+    pub rewards_accumulated: u64,
+    pub blocks_signed: u64,
+    pub blocks_missed: u64,
+    pub jailed: bool,
+    pub jailed_until: u64,
+    pub created_at: u64,
+    pub last_reward_height: u64,
+    // Computed fields for explorer
+    pub uptime_percent: f64,
+    /// Signed and missed heights in the signed-blocks window (judged for downtime jailing)
+    pub signed_in_window: u64,
+    pub missed_in_window: u64,
+    pub window_uptime_percent: f64,
+    pub voting_power_percent: f64,
+}
+
+/// Reply of `/staking/validators`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ValidatorsPage {
+    pub validators: Vec<ValidatorInfo>,
+    pub count: usize,
+    /// Stake of the whole set, filtered or not
+    pub total_network_stake: u64,
+    pub next_cursor: Option<String>,
+}
+
+/// Reply of `/staking/withdraw_rewards`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum RewardsWithdrawn {
+    /// Validator commission, credited to the reward wallet
+    Validator {
+        validator_address: String,
+        reward_wallet: String,
+        rewards_withdrawn: u64,
+        status: String,
+    },
+    /// Delegator rewards, credited to the delegator
+    Delegator {
+        address: String,
+        rewards_withdrawn: u64,
+        status: String,
+    },
+}
+
+/// Reply of `/staking/set_reward_wallet` and `/staking/reward_wallet/:validator_address`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RewardWalletResponse {
+    pub validator_address: String,
+    /// None until the validator sets one
+    pub reward_wallet: Option<String>,
+    pub status: String,
+}
+
+/// Reply of `/staking/rotate_key`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct KeyRotationQueued {
+    pub validator_address: String,
+    pub activation_height: u64,
+    /// Hash of the system transaction carrying the rotation
+    pub tx_hash: String,
+    pub status: String,
+}
+
+/// Reply of `/governance/propose`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProposalSubmitted {
+    pub proposal_id: u64,
+    pub status: String,
+}
+
+/// Reply of `/governance/vote`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VoteRecorded {
+    pub proposal_id: u64,
+    pub voter: String,
+    pub status: String,
+}
+
+/// Reply of `/governance/proposals`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProposalsPage {
+    pub proposals: Vec<Proposal>,
+    pub count: usize,
+    pub next_cursor: Option<String>,
+}
+
+/// Feature flag switched by an executed proposal
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FeatureActivation {
+    pub feature: String,
+    pub enabled: bool,
+}
+
+/// Reply of `/governance/execute/:id`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProposalExecuted {
+    pub success: bool,
+    pub proposal_id: u64,
+    pub title: String,
+    pub activated_features: Vec<FeatureActivation>,
+}
+
+/// Reply of `/governance/features`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FeaturesResponse {
+    pub features: EnabledFeatures,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EnabledFeatures {
+    pub sharding_enabled: bool,
+    pub governance_enabled: bool,
+    pub bridges_enabled: bool,
+    pub smart_contracts_enabled: bool,
+    pub evm_contracts_enabled: bool,
+    pub quantum_signatures_enabled: bool,
+    pub ibc_enabled: bool,
+}
+
+/// Reply of the token and DEX operations that return nothing else
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SuccessResponse {
+    pub success: bool,
+}
+
+/// Reply of `/tokens/create`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TokenCreated {
+    pub success: bool,
+    pub denom: String,
+}
+
+/// Reply of `/tokens/:denom/metadata`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TokenMetadataResponse {
+    pub success: bool,
+    pub metadata: TokenMetadata,
+}
+
+/// Reply of `/tokens/:denom/balance/:address`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TokenBalanceResponse {
+    pub success: bool,
+    /// Decimal string
+    pub balance: String,
+}
+
+/// A token in `/tokens/list`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TokenEntry {
+    pub denom: String,
+    pub metadata: TokenMetadata,
+}
+
+/// Reply of `/tokens/list`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TokensPage {
+    pub success: bool,
+    pub tokens: Vec<TokenEntry>,
+    pub count: usize,
+    pub next_cursor: Option<String>,
+}
+
+/// Reply of `/faucet/challenge/:address`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FaucetChallenge {
+    pub success: bool,
+    pub address: String,
+    /// Sign this (as is) for `/faucet/claim`
+    pub nonce: String,
+    pub message: String,
+    pub expires_in_seconds: u64,
+}
+
+/// Reply of `/faucet/claim`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FaucetClaimed {
+    pub success: bool,
+    /// Decimal string
+    pub amount: String,
+    pub amount_sltn: u128,
+    pub message: String,
+}
+
+/// Reply of `/faucet/status`
+///
+/// Amounts are decimal strings, with whole-SLTN `*_sltn` counterparts.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FaucetStatus {
+    pub success: bool,
+    pub enabled: bool,
+    pub amount_per_claim: String,
+    pub amount_per_claim_sltn: u128,
+    pub max_cap: String,
+    pub max_cap_sltn: u128,
+    pub total_claims: usize,
+    pub total_distributed: String,
+    pub total_distributed_sltn: u128,
+    pub remaining: String,
+    pub remaining_sltn: u128,
+}
+
+/// Reply of `/dex/create_pair`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PairCreated {
+    pub success: bool,
+    pub pair_id: String,
+}
+
+/// Reply of `/dex/swap`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SwapResponse {
+    pub success: bool,
+    pub amount_out: u128,
+}
+
+/// Reply of `/dex/add_liquidity`; amounts are decimal strings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LiquidityAdded {
+    pub success: bool,
+    /// LP tokens minted
+    pub liquidity: String,
+    pub amount_a: String,
+    pub amount_b: String,
+}
+
+/// Reply of `/dex/remove_liquidity`; amounts are decimal strings
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LiquidityRemoved {
+    pub success: bool,
+    pub amount_a: String,
+    pub amount_b: String,
+}
+
+/// Reply of `/dex/pool/:pair_id`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PoolResponse {
+    pub success: bool,
+    pub pool: LiquidityPool,
+}
+
+/// Reply of `/dex/pools`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PoolsPage {
+    pub success: bool,
+    pub pools: Vec<LiquidityPool>,
+    pub count: usize,
+    pub next_cursor: Option<String>,
+}
+
+/// Reply of `/dex/price/:pair_id`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PriceResponse {
+    pub success: bool,
+    pub price_a_to_b: f64,
+    pub price_b_to_a: f64,
+}
+
+// ========= QUERIES =========
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FeeQuery {
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct TxHistoryQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    pub order: Option<Order>,
+    /// Only transactions sent or only those received; both when absent
+    pub direction: Option<TxDirection>,
+    pub from_height: Option<u64>,
+    pub to_height: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TxDirection {
+    Sent,
+    Received,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct BlocksListQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    pub order: Option<Order>,
+    /// Legacy offset from the tip, used when no cursor is given
+    #[serde(default)]
+    pub offset: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ValidatorsQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    pub order: Option<Order>,
+    pub status: Option<ValidatorStatusFilter>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidatorStatusFilter {
+    Active,
+    Jailed,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ProposalsQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    pub order: Option<Order>,
+    pub status: Option<ProposalStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EventsQuery {
+    #[serde(rename = "type")]
+    pub event_type: Option<String>,
+    pub module: Option<String>,
+    /// `key:value` an event attribute must equal
+    pub attr: Option<String>,
+    pub tx: Option<String>,
+    #[serde(default)]
+    pub from_height: u64,
+    #[serde(default = "default_events_limit")]
+    pub limit: usize,
+}
+
+fn default_events_limit() -> usize {
+    DEFAULT_EVENTS_LIMIT
+}
+
+impl Default for EventsQuery {
+    fn default() -> Self {
+        Self {
+            event_type: None,
+            module: None,
+            attr: None,
+            tx: None,
+            from_height: 0,
+            limit: DEFAULT_EVENTS_LIMIT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct TokensQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    pub order: Option<Order>,
+    pub creator: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PoolsQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    pub order: Option<Order>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    #[test]
+    fn test_path_segment_decodes_denoms() {
+        let denom: PathSegment = "factory%2Fsultan1abc%2Ftok".parse().unwrap();
+        assert_eq!(denom.0, "factory/sultan1abc/tok");
+        assert_eq!("sultan1abc".parse::<PathSegment>().unwrap().0, "sultan1abc");
+        assert!("%FF".parse::<PathSegment>().is_err());
+    }
+
+    #[test]
+    fn test_transfer_signature_roundtrip() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let mut tx = Transaction {
+            from: "sultan1alice".to_string(),
+            to: "sultan1bob".to_string(),
+            amount: 1_500,
+            gas_fee: 0,
+            timestamp: 1_700_000_000,
+            nonce: 3,
+            signature: None,
+            public_key: Some(hex::encode(key.verifying_key().to_bytes())),
+            memo: None,
+        };
+        let message = transfer_message(&tx.from, &tx.to, tx.amount, tx.nonce, tx.timestamp);
+        assert_eq!(
+            message,
+            r#"{"from":"sultan1alice","to":"sultan1bob","amount":"1500","memo":"","nonce":3,"timestamp":1700000000}"#
+        );
+        tx.signature = Some(hex::encode(key.sign(&Sha256::digest(message.as_bytes())).to_bytes()));
+        assert!(verify_transfer_signature(&tx).is_ok());
+
+        tx.nonce = 4;
+        assert!(verify_transfer_signature(&tx).is_err());
+    }
+
+    #[test]
+    fn test_request_formats() {
+        // Amounts may come as strings from wallets
+        let request: TxRequest = serde_json::from_str(
+            r#"{"tx":{"from":"a","to":"b","amount":"42","nonce":1,"timestamp":2},"signature":"s","public_key":"p"}"#
+        ).unwrap();
+        assert!(matches!(request, TxRequest::Wallet(ref w) if w.tx.amount == 42));
+        let request: TxRequest = serde_json::from_str(r#"{"from":"a","to":"b","amount":7}"#).unwrap();
+        assert!(matches!(request, TxRequest::Simple(ref s) if s.amount == 7 && s.signature.is_none()));
+
+        let query: EventsQuery = serde_json::from_str(r#"{"type":"transfer"}"#).unwrap();
+        assert_eq!(query.limit, DEFAULT_EVENTS_LIMIT);
+        assert_eq!(create_token_message("c", "Name", "SYM", 6, 100, None), "create_token:c:Name:SYM:6:100:None");
+    }
+}
@@ -0,0 +1,414 @@
+//! Validator commit-reveal randomness beacon
+//!
+//! Every proposer puts a beacon entry in its block: the secret it committed to
+//! in its previous block, plus a commitment to the next one. Secrets are derived
+//! from the validator key and the commitment height, so a proposer has exactly one
+//! valid reveal and can only bias the beacon by withholding its block.
+//!
+//! The proposer signs its entry with its consensus key, so nobody relaying the
+//! block can swap the reveal or commitment (the block hash doesn't cover them).
+//!
+//! The valid reveals of epoch `e - 1` seed epoch `e`. Commitments lapse after
+//! `BEACON_COMMIT_WINDOW` blocks, so the whole state can be rebuilt from the last
+//! `BEACON_REPLAY_BLOCKS` blocks, which pruned nodes always keep.
+
+use crate::blockchain::{Block, Transaction};
+use anyhow::{bail, Context, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use tracing::{debug, warn};
+
+/// System recipient of beacon entries (never a real account)
+pub const BEACON_ADDRESS: &str = "sultan1beacon";
+
+/// Blocks per beacon epoch
+pub const BEACON_EPOCH_BLOCKS: u64 = 100;
+
+/// Blocks a commitment stays revealable
+pub const BEACON_COMMIT_WINDOW: u64 = 800;
+
+/// Blocks replayed at startup to rebuild the beacon
+pub const BEACON_REPLAY_BLOCKS: u64 = BEACON_COMMIT_WINDOW + 2 * BEACON_EPOCH_BLOCKS;
+
+const MEMO_PREFIX: &str = "beacon:v1:";
+
+/// Domain separator for entry signatures
+const SIGNING_DOMAIN: &[u8] = b"sultan_beacon_entry_v1";
+
+/// Epoch seeds kept (current and next)
+const MAX_SEEDS: usize = 2;
+
+/// A proposer's beacon contribution for one block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BeaconEntry {
+    /// Secret behind the proposer's previous commitment, if it has one
+    pub reveal: Option<[u8; 32]>,
+    pub commitment: [u8; 32],
+}
+
+impl BeaconEntry {
+    /// Bytes the proposer signs: the entry bound to its address and height
+    fn signing_message(&self, proposer: &str, height: u64) -> Vec<u8> {
+        let mut message = SIGNING_DOMAIN.to_vec();
+        message.extend_from_slice(&(proposer.len() as u64).to_le_bytes());
+        message.extend_from_slice(proposer.as_bytes());
+        message.extend_from_slice(&height.to_le_bytes());
+        message.extend_from_slice(&self.reveal.unwrap_or_default());
+        message.push(self.reveal.is_some() as u8);
+        message.extend_from_slice(&self.commitment);
+        message
+    }
+
+    /// Wrap the entry in the zero-value system transaction carried by the block,
+    /// signed with the proposer's consensus key
+    pub fn into_transaction(self, proposer: &str, key: &SigningKey, height: u64, timestamp: u64) -> Transaction {
+        let signature = key.sign(&self.signing_message(proposer, height));
+        let reveal = self.reveal.map(hex::encode).unwrap_or_default();
+        Transaction {
+            from: proposer.to_string(),
+            to: BEACON_ADDRESS.to_string(),
+            amount: 0,
+            gas_fee: 0,
+            timestamp,
+            nonce: height,
+            signature: Some(hex::encode(signature.to_bytes())),
+            public_key: None,
+            memo: Some(format!("{}{}:{}", MEMO_PREFIX, reveal, hex::encode(self.commitment))),
+        }
+    }
+
+    /// Parse the entry from a beacon transaction and check that `proposer_key`
+    /// signed it for `height`
+    pub fn verify_transaction(tx: &Transaction, height: u64, proposer_key: &[u8; 32]) -> Result<Self> {
+        let entry = Self::from_transaction(tx)
+            .with_context(|| format!("Malformed beacon entry from {}", tx.from))?;
+        if tx.nonce != height {
+            bail!("Beacon entry from {} is for height {}, not {}", tx.from, tx.nonce, height);
+        }
+        let signature: [u8; 64] = tx.signature.as_deref()
+            .and_then(|s| hex::decode(s).ok())
+            .and_then(|bytes| bytes.try_into().ok())
+            .with_context(|| format!("Beacon entry from {} is not signed", tx.from))?;
+        VerifyingKey::from_bytes(proposer_key)
+            .context("Invalid proposer key")?
+            .verify(&entry.signing_message(&tx.from, height), &Signature::from_bytes(&signature))
+            .with_context(|| format!("Bad beacon entry signature from {} at {}", tx.from, height))?;
+        Ok(entry)
+    }
+
+    /// Parse the entry from a beacon transaction
+    pub fn from_transaction(tx: &Transaction) -> Option<Self> {
+        if !is_beacon_tx(tx) {
+            return None;
+        }
+        let body = tx.memo.as_deref()?.strip_prefix(MEMO_PREFIX)?;
+        let (reveal, commitment) = body.split_once(':')?;
+        let reveal = if reveal.is_empty() { None } else { Some(decode_hash(reveal)?) };
+        Some(Self { reveal, commitment: decode_hash(commitment)? })
+    }
+}
+
+/// Whether `tx` is a beacon entry rather than a transfer
+pub fn is_beacon_tx(tx: &Transaction) -> bool {
+    tx.to == BEACON_ADDRESS
+}
+
+/// Whether blocks at `height` carry beacon entries on a network activating the
+/// beacon at `activation`. Entries start one commit window early so the first
+/// beacon-seeded epoch has reveals to draw on; without an activation height
+/// blocks never carry them.
+pub fn entries_enabled(activation: Option<u64>, height: u64) -> bool {
+    activation.is_some_and(|activation| height.saturating_add(BEACON_COMMIT_WINDOW) >= activation)
+}
+
+fn decode_hash(s: &str) -> Option<[u8; 32]> {
+    hex::decode(s).ok()?.try_into().ok()
+}
+
+/// Secret a validator commits to at `height`
+fn secret(key: &SigningKey, height: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"sultan_beacon_secret_v1");
+    hasher.update(key.to_bytes());
+    hasher.update(height.to_le_bytes());
+    hasher.finalize().into()
+}
+
+fn commit(secret: &[u8; 32]) -> [u8; 32] {
+    Sha256::digest(secret).into()
+}
+
+/// Seed of `epoch` from the valid reveals of the epoch before it
+pub fn epoch_seed(epoch: u64, reveals: &[[u8; 32]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"sultan_beacon_epoch_v1");
+    hasher.update(epoch.to_le_bytes());
+    for reveal in reveals {
+        hasher.update(reveal);
+    }
+    hasher.finalize().into()
+}
+
+/// Beacon state rebuilt from the chain
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RandomnessBeacon {
+    /// Latest commitment per validator: (height, commitment)
+    commitments: HashMap<String, (u64, [u8; 32])>,
+    /// Valid reveals so far in the current epoch, in block order
+    epoch_reveals: Vec<[u8; 32]>,
+    /// Seeds by epoch
+    seeds: BTreeMap<u64, [u8; 32]>,
+    /// First height observed since the last gap
+    observed_from: Option<u64>,
+    /// Last height observed
+    height: Option<u64>,
+}
+
+impl RandomnessBeacon {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The entry `proposer` should put in its block at `height`
+    pub fn entry_for(&self, proposer: &str, key: &SigningKey, height: u64) -> BeaconEntry {
+        let reveal = self.live_commitment(proposer, height)
+            .map(|(committed_at, _)| secret(key, committed_at));
+        BeaconEntry { reveal, commitment: commit(&secret(key, height)) }
+    }
+
+    fn live_commitment(&self, validator: &str, height: u64) -> Option<(u64, [u8; 32])> {
+        self.commitments.get(validator)
+            .filter(|(committed_at, _)| *committed_at < height && height - committed_at <= BEACON_COMMIT_WINDOW)
+            .copied()
+    }
+
+    /// Take in the beacon entry of the next block, signed by `proposer_key` (the
+    /// proposer's consensus key at that height; entries are ignored without one).
+    /// Blocks must arrive in order; after a gap the beacon starts over and waits
+    /// for enough history again.
+    pub fn observe_block(&mut self, block: &Block, proposer_key: Option<&[u8; 32]>) {
+        let height = block.index;
+        match self.height {
+            Some(last) if height <= last => return,
+            Some(last) if height == last + 1 => {}
+            _ => {
+                self.commitments.clear();
+                self.epoch_reveals.clear();
+                self.observed_from = Some(height);
+            }
+        }
+        self.height = Some(height);
+
+        let entry = block.transactions.iter()
+            .find(|tx| is_beacon_tx(tx) && tx.from == block.validator)
+            .zip(proposer_key)
+            .and_then(|(tx, key)| match BeaconEntry::verify_transaction(tx, height, key) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    warn!("🎲 Ignoring beacon entry in block {}: {:#}", height, e);
+                    None
+                }
+            });
+        if let Some(entry) = entry {
+            match (self.live_commitment(&block.validator, height), entry.reveal) {
+                (Some((_, commitment)), Some(reveal)) if commit(&reveal) == commitment => {
+                    self.epoch_reveals.push(reveal);
+                }
+                (Some(_), _) => debug!("🎲 {} did not reveal its beacon secret at {}", block.validator, height),
+                (None, _) => {}
+            }
+            self.commitments.insert(block.validator.clone(), (height, entry.commitment));
+        }
+
+        if (height + 1).is_multiple_of(BEACON_EPOCH_BLOCKS) {
+            let next_epoch = (height + 1) / BEACON_EPOCH_BLOCKS;
+            let epoch_start = height + 1 - BEACON_EPOCH_BLOCKS;
+            // Reveals can only be checked against commitments we've seen
+            let complete = match self.observed_from {
+                Some(from) => from <= 1 || epoch_start >= from + BEACON_COMMIT_WINDOW,
+                None => false,
+            };
+            if complete {
+                let seed = epoch_seed(next_epoch, &self.epoch_reveals);
+                debug!("🎲 Beacon seed for epoch {}: {} ({} reveals)", next_epoch, hex::encode(seed), self.epoch_reveals.len());
+                self.seeds.insert(next_epoch, seed);
+                while self.seeds.len() > MAX_SEEDS {
+                    self.seeds.pop_first();
+                }
+            }
+            self.epoch_reveals.clear();
+            self.commitments.retain(|_, (committed_at, _)| height - *committed_at < BEACON_COMMIT_WINDOW);
+        }
+    }
+
+    /// Randomness for `height`; epochs without reveals get a fixed per-epoch seed
+    pub fn seed_for_height(&self, height: u64) -> [u8; 32] {
+        let epoch = height / BEACON_EPOCH_BLOCKS;
+        self.seeds.get(&epoch).copied().unwrap_or_else(|| epoch_seed(epoch, &[]))
+    }
+
+    /// Whether the seed for `height` came from observed reveals
+    pub fn has_seed_for_height(&self, height: u64) -> bool {
+        self.seeds.contains_key(&(height / BEACON_EPOCH_BLOCKS))
+    }
+
+    /// Last height taken in
+    pub fn height(&self) -> Option<u64> {
+        self.height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(index: u64, validator: &str, txs: Vec<Transaction>) -> Block {
+        Block {
+            index,
+            timestamp: 1_700_000_000 + index,
+            transactions: txs,
+            prev_hash: String::new(),
+            hash: String::new(),
+            nonce: 0,
+            validator: validator.to_string(),
+            state_root: String::new(),
+        }
+    }
+
+    /// Produce blocks 1..=to, alternating between two honest proposers
+    fn run_chain(beacon: &mut RandomnessBeacon, keys: &[(&str, SigningKey)], to: u64) {
+        for height in 1..=to {
+            let (name, key) = &keys[height as usize % keys.len()];
+            let entry = beacon.entry_for(name, key, height);
+            let tx = entry.into_transaction(name, key, height, 0);
+            beacon.observe_block(&block(height, name, vec![tx]), Some(&key.verifying_key().to_bytes()));
+        }
+    }
+
+    #[test]
+    fn test_entry_round_trips_through_transaction() {
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let beacon = RandomnessBeacon::new();
+        let entry = beacon.entry_for("sultan1a", &key, 5);
+        assert!(entry.reveal.is_none());
+
+        let tx = entry.clone().into_transaction("sultan1a", &key, 5, 0);
+        assert!(is_beacon_tx(&tx));
+        assert_eq!(BeaconEntry::from_transaction(&tx), Some(entry.clone()));
+        assert_eq!(BeaconEntry::verify_transaction(&tx, 5, &key.verifying_key().to_bytes()).unwrap(), entry);
+    }
+
+    #[test]
+    fn test_relayed_entry_must_carry_proposer_signature() {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let pubkey = key.verifying_key().to_bytes();
+        let mut beacon = RandomnessBeacon::new();
+        let first = beacon.entry_for("sultan1a", &key, 1).into_transaction("sultan1a", &key, 1, 0);
+        beacon.observe_block(&block(1, "sultan1a", vec![first]), Some(&pubkey));
+
+        // A relay swaps the reveal in an honestly signed entry
+        let honest = beacon.entry_for("sultan1a", &key, 2);
+        let mut tampered = honest.clone().into_transaction("sultan1a", &key, 2, 0);
+        let mut swapped = honest.clone();
+        swapped.reveal = Some([9u8; 32]);
+        tampered.memo = swapped.into_transaction("sultan1a", &key, 2, 0).memo;
+        assert!(BeaconEntry::verify_transaction(&tampered, 2, &pubkey).is_err());
+
+        // ...or the commitment, or re-signs it with another key
+        let mut recommitted = honest.clone();
+        recommitted.commitment = [7u8; 32];
+        let mut tampered_commitment = honest.clone().into_transaction("sultan1a", &key, 2, 0);
+        tampered_commitment.memo = recommitted.into_transaction("sultan1a", &key, 2, 0).memo;
+        assert!(BeaconEntry::verify_transaction(&tampered_commitment, 2, &pubkey).is_err());
+        let resigned = honest.clone().into_transaction("sultan1a", &SigningKey::from_bytes(&[2u8; 32]), 2, 0);
+        assert!(BeaconEntry::verify_transaction(&resigned, 2, &pubkey).is_err());
+
+        // The beacon keeps the commitment from block 1 and mixes nothing in
+        beacon.observe_block(&block(2, "sultan1a", vec![tampered]), Some(&pubkey));
+        assert!(beacon.epoch_reveals.is_empty());
+        assert_eq!(beacon.commitments["sultan1a"].0, 1);
+
+        // Unsigned entries (the pre-fix format) are ignored too
+        let mut unsigned = beacon.entry_for("sultan1a", &key, 3).into_transaction("sultan1a", &key, 3, 0);
+        unsigned.signature = None;
+        beacon.observe_block(&block(3, "sultan1a", vec![unsigned]), Some(&pubkey));
+        assert!(beacon.epoch_reveals.is_empty());
+
+        // The honest entry still verifies and reveals the block 1 secret
+        let honest = beacon.entry_for("sultan1a", &key, 4).into_transaction("sultan1a", &key, 4, 0);
+        beacon.observe_block(&block(4, "sultan1a", vec![honest]), Some(&pubkey));
+        assert_eq!(beacon.epoch_reveals.len(), 1);
+    }
+
+    #[test]
+    fn test_entries_start_one_commit_window_before_activation() {
+        assert!(!entries_enabled(None, 0));
+        assert!(!entries_enabled(None, u64::MAX));
+        assert!(!entries_enabled(Some(10_000), 10_000 - BEACON_COMMIT_WINDOW - 1));
+        assert!(entries_enabled(Some(10_000), 10_000 - BEACON_COMMIT_WINDOW));
+        assert!(entries_enabled(Some(10_000), 10_000));
+        assert!(entries_enabled(Some(0), 1));
+    }
+
+    #[test]
+    fn test_honest_reveals_seed_next_epoch() {
+        let keys = [("sultan1a", SigningKey::from_bytes(&[1u8; 32])), ("sultan1b", SigningKey::from_bytes(&[2u8; 32]))];
+        let mut beacon = RandomnessBeacon::new();
+        run_chain(&mut beacon, &keys, 2 * BEACON_EPOCH_BLOCKS - 1);
+
+        assert!(beacon.has_seed_for_height(BEACON_EPOCH_BLOCKS));
+        assert!(beacon.has_seed_for_height(2 * BEACON_EPOCH_BLOCKS));
+        assert_ne!(beacon.seed_for_height(2 * BEACON_EPOCH_BLOCKS), epoch_seed(2, &[]));
+
+        // Same chain, same seeds
+        let mut other = RandomnessBeacon::new();
+        run_chain(&mut other, &keys, 2 * BEACON_EPOCH_BLOCKS - 1);
+        assert_eq!(other.seed_for_height(2 * BEACON_EPOCH_BLOCKS), beacon.seed_for_height(2 * BEACON_EPOCH_BLOCKS));
+    }
+
+    #[test]
+    fn test_wrong_reveal_is_not_mixed() {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let pubkey = key.verifying_key().to_bytes();
+        let mut beacon = RandomnessBeacon::new();
+        let first = beacon.entry_for("sultan1a", &key, 1).into_transaction("sultan1a", &key, 1, 0);
+        beacon.observe_block(&block(1, "sultan1a", vec![first]), Some(&pubkey));
+
+        // A forged reveal doesn't match the commitment
+        let mut forged = beacon.entry_for("sultan1a", &key, 2);
+        forged.reveal = Some([9u8; 32]);
+        beacon.observe_block(&block(2, "sultan1a", vec![forged.into_transaction("sultan1a", &key, 2, 0)]), Some(&pubkey));
+        assert!(beacon.epoch_reveals.is_empty());
+
+        // Entries from anyone but the proposer are ignored
+        let other = SigningKey::from_bytes(&[2u8; 32]);
+        let stray = beacon.entry_for("sultan1b", &other, 3).into_transaction("sultan1b", &other, 3, 0);
+        beacon.observe_block(&block(3, "sultan1a", vec![stray]), Some(&pubkey));
+        assert!(!beacon.commitments.contains_key("sultan1b"));
+    }
+
+    #[test]
+    fn test_replay_window_rebuilds_the_same_seed() {
+        let keys = [("sultan1a", SigningKey::from_bytes(&[1u8; 32])), ("sultan1b", SigningKey::from_bytes(&[2u8; 32]))];
+        let tip = 20 * BEACON_EPOCH_BLOCKS - 1;
+        let mut blocks = Vec::new();
+        let mut full = RandomnessBeacon::new();
+        for height in 1..=tip {
+            let (name, key) = &keys[height as usize % keys.len()];
+            let tx = full.entry_for(name, key, height).into_transaction(name, key, height, 0);
+            let b = block(height, name, vec![tx]);
+            full.observe_block(&b, Some(&key.verifying_key().to_bytes()));
+            blocks.push((b, key.verifying_key().to_bytes()));
+        }
+
+        // A restarted node only replays the tail of the chain
+        let mut restarted = RandomnessBeacon::new();
+        for (b, pubkey) in &blocks[(tip - BEACON_REPLAY_BLOCKS) as usize..] {
+            restarted.observe_block(b, Some(pubkey));
+        }
+        assert!(restarted.has_seed_for_height(tip + 1));
+        assert_eq!(restarted.seed_for_height(tip + 1), full.seed_for_height(tip + 1));
+    }
+}
@@ -19,6 +19,7 @@ use tracing::{info, warn, debug};
 
 use crate::blockchain::Block;
 use crate::consensus::ConsensusEngine;
+use crate::metrics::Histogram;
 
 /// Block synchronization configuration
 #[derive(Debug, Clone)]
@@ -124,6 +125,8 @@ pub struct BlockSyncManager {
     votes_recorded: Arc<RwLock<u64>>,
     /// Statistics: rejected votes
     votes_rejected: Arc<RwLock<u64>>,
+    /// Time from a block arriving for votes to its finalization
+    vote_collection: Arc<Histogram>,
 }
 
 impl BlockSyncManager {
@@ -146,6 +149,7 @@ impl BlockSyncManager {
             blocks_synced: Arc::new(RwLock::new(0)),
             votes_recorded: Arc::new(RwLock::new(0)),
             votes_rejected: Arc::new(RwLock::new(0)),
+            vote_collection: Arc::new(Histogram::default()),
         }
     }
 
@@ -243,11 +247,15 @@ impl BlockSyncManager {
         self.peer_heights.read().await.clone()
     }
 
+    /// Blocks the highest peer is ahead of us (0 when no peer is)
+    pub async fn blocks_behind(&self) -> u64 {
+        let our_height = *self.current_height.read().await;
+        self.max_peer_height().await.saturating_sub(our_height)
+    }
+
     /// Check if we need to sync (peers are ahead)
     pub async fn needs_sync(&self) -> bool {
-        let our_height = *self.current_height.read().await;
-        let max_peer = self.max_peer_height().await;
-        max_peer > our_height + 1
+        self.blocks_behind().await > 1
     }
 
     /// Add a pending block awaiting votes
@@ -394,7 +402,8 @@ impl BlockSyncManager {
         }
 
         let block = pending.remove(&height);
-        if block.is_some() {
+        if let Some(pb) = &block {
+            self.vote_collection.observe(pb.created_at.elapsed());
             *self.blocks_synced.write().await += 1;
             info!("✅ Finalized block at height {}", height);
         }
@@ -498,6 +507,11 @@ impl BlockSyncManager {
         matches!(*self.sync_state.read().await, SyncState::Syncing { .. })
     }
 
+    /// Vote collection times of finalized blocks
+    pub fn vote_collection_times(&self) -> &Histogram {
+        &self.vote_collection
+    }
+
     /// Get sync statistics
     pub async fn get_statistics(&self) -> SyncStatistics {
         SyncStatistics {
@@ -979,6 +993,7 @@ mod tests {
         assert_eq!(stats.votes_recorded, 1);
         assert_eq!(stats.peer_count, 1);
         assert_eq!(stats.max_peer_height, 105);
+        assert_eq!(sync.vote_collection_times().count(), 1);
     }
 
     #[tokio::test]
@@ -1000,6 +1015,11 @@ mod tests {
         // Peer 2+ ahead (needs sync)
         sync.update_peer_height("peer1".to_string(), 103).await;
         assert!(sync.needs_sync().await);
+        assert_eq!(sync.blocks_behind().await, 3);
+
+        // Ahead of every peer
+        sync.set_height(110).await;
+        assert_eq!(sync.blocks_behind().await, 0);
     }
 
     #[tokio::test]
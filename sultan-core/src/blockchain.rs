@@ -4,6 +4,7 @@
 //! and transaction processing.
 
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use sha2::{Sha256, Digest};
 use sha3::Keccak256;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -12,7 +13,7 @@ use anyhow::{Result, bail};
 use tracing::{info, warn};
 
 /// Block represents a single block in the Sultan blockchain
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub struct Block {
     pub index: u64,
     pub timestamp: u64,
@@ -25,7 +26,7 @@ pub struct Block {
 }
 
 /// Transaction represents a value transfer on Sultan Chain
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, JsonSchema)]
 pub struct Transaction {
     pub from: String,
     pub to: String,
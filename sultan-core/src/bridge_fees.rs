@@ -10,6 +10,7 @@
 
 use anyhow::{Result, Context, bail};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use tracing::{info, warn};
@@ -433,7 +434,7 @@ pub struct FeeBreakdownWithOracle {
     pub oracle_estimate: DynamicFeeEstimate,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FeeBreakdown {
     /// Total Sultan-side fee in SLTN (currently 0)
     pub sultan_fee: u64,
@@ -451,7 +452,7 @@ pub struct FeeBreakdown {
     pub external_fee: ExternalChainFee,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExternalChainFee {
     pub chain: String,
     pub estimated_cost: String,
@@ -642,7 +643,7 @@ pub struct DynamicFeeEstimate {
     pub source: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FeeStatistics {
     pub treasury_address: String,
     pub total_bridges: usize,
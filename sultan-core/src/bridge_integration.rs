@@ -16,6 +16,7 @@
 
 use anyhow::{Result, Context, bail};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -23,6 +24,7 @@ use tracing::{info, warn, error, debug};
 
 use crate::bridge_fees::{BridgeFees, FeeBreakdown, RateLimiter};
 use crate::token_factory::TokenFactory;
+use crate::events::{EventBuffer, ModuleEvent};
 
 /// Legacy callback for minting wrapped tokens (kept for tests)
 /// In production: Use `with_token_factory()` for async TokenFactory integration
@@ -112,7 +114,7 @@ pub enum VerificationResult {
     Failed(String),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BridgeStatus {
     pub name: String,
     pub active: bool,
@@ -123,7 +125,7 @@ pub struct BridgeStatus {
     pub last_sync: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub enum BridgeType {
     Bitcoin,      // HTLC + SPV verification (3+ confirmations)
     Ethereum,     // Light client + zero-knowledge proofs
@@ -431,6 +433,7 @@ pub struct BridgeManager {
     rate_limiter: Arc<RwLock<RateLimiter>>,
     /// Multi-sig config for large transactions
     multi_sig_config: Arc<RwLock<MultiSigConfig>>,
+    events: EventBuffer,
 }
 
 impl BridgeManager {
@@ -510,9 +513,37 @@ impl BridgeManager {
             token_factory: None,
             rate_limiter: Arc::new(RwLock::new(RateLimiter::new(50, 60))), // 50 tx/min per pubkey
             multi_sig_config: Arc::new(RwLock::new(MultiSigConfig::default())),
+            events: EventBuffer::default(),
         }
     }
 
+    /// Events emitted since the last block commit
+    pub fn events(&self) -> &EventBuffer {
+        &self.events
+    }
+
+    /// Emit the event for a transaction that just completed or failed
+    fn emit_outcome(&self, tx: &CrossChainTransaction) {
+        let event = match &tx.status {
+            TxStatus::Completed => ModuleEvent::BridgeCompleted {
+                id: tx.id.clone(),
+                source_chain: tx.source_chain.clone(),
+                dest_chain: tx.dest_chain.clone(),
+                recipient: tx.recipient.clone(),
+                amount: tx.amount,
+                wrapped_token: tx.wrapped_token.clone(),
+            },
+            TxStatus::Failed(reason) => ModuleEvent::BridgeFailed {
+                id: tx.id.clone(),
+                source_chain: tx.source_chain.clone(),
+                recipient: tx.recipient.clone(),
+                reason: reason.clone(),
+            },
+            TxStatus::Pending | TxStatus::Confirmed => return,
+        };
+        self.events.emit(event);
+    }
+
     /// Create bridge manager with custom multi-sig configuration
     pub fn with_multi_sig(treasury_address: String, multi_sig: MultiSigConfig) -> Self {
         let mut manager = Self::with_treasury(treasury_address);
@@ -801,6 +832,7 @@ impl BridgeManager {
             match self.verify_and_complete(&mut tx).await {
                 Ok(VerificationResult::Verified) => {
                     tx.status = TxStatus::Completed;
+                    self.emit_outcome(&tx);
                     completed.push(tx);
                     processed += 1;
                 }
@@ -811,11 +843,13 @@ impl BridgeManager {
                 }
                 Ok(VerificationResult::Failed(reason)) => {
                     tx.status = TxStatus::Failed(reason.clone());
+                    self.emit_outcome(&tx);
                     completed.push(tx);
                     warn!("❌ Bridge tx failed: {}", reason);
                 }
                 Err(e) => {
                     tx.status = TxStatus::Failed(e.to_string());
+                    self.emit_outcome(&tx);
                     completed.push(tx);
                     error!("Bridge transaction failed: {}", e);
                 }
@@ -860,6 +894,7 @@ impl BridgeManager {
                 Ok((mut tx, Ok(VerificationResult::Verified))) => {
                     tx.status = TxStatus::Completed;
                     let mut completed = self.completed_txs.write().await;
+                    self.emit_outcome(&tx);
                     completed.push(tx);
                     processed += 1;
                 }
@@ -871,11 +906,13 @@ impl BridgeManager {
                 Ok((mut tx, Ok(VerificationResult::Failed(reason)))) => {
                     tx.status = TxStatus::Failed(reason);
                     let mut completed = self.completed_txs.write().await;
+                    self.emit_outcome(&tx);
                     completed.push(tx);
                 }
                 Ok((mut tx, Err(e))) => {
                     tx.status = TxStatus::Failed(e.to_string());
                     let mut completed = self.completed_txs.write().await;
+                    self.emit_outcome(&tx);
                     completed.push(tx);
                 }
                 Err(e) => {
@@ -1082,7 +1119,7 @@ impl BridgeManager {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BridgeStatistics {
     pub total_bridges: usize,
     pub active_bridges: usize,
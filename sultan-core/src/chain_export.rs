@@ -0,0 +1,626 @@
+//! Sultan Chain Export/Import
+//!
+//! Portable, streaming block archive used by `sultan-node export` / `import`:
+//! - Seed new nodes offline
+//! - Archive history outside RocksDB
+//! - Reproduce bugs from production data
+//!
+//! File format (all integers little-endian):
+//! - Header: magic `SLTNCHN1` | format version u32 | flags u32 | from u64 | to u64
+//! - Records: kind u8 | payload length u32 | bincode payload | SHA-256(kind || payload)
+//! - Trailer: an `END` record whose payload is the (blocks, finality proofs) count
+//!
+//! A missing trailer means the file was truncated; a checksum mismatch means
+//! it was corrupted. Import stops at the first bad record.
+
+use anyhow::{Result, Context, bail};
+use sha2::{Sha256, Digest};
+use std::io::{Read, Write};
+use tracing::{info, warn};
+
+use crate::blockchain::Block;
+use crate::consensus::ConsensusEngine;
+use crate::sharded_blockchain_production::SultanBlockchain;
+use crate::storage::{AccountStateSnapshot, FinalityProof, PersistentStorage};
+
+/// File magic
+const MAGIC: &[u8; 8] = b"SLTNCHN1";
+
+/// Current export format version
+pub const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// Header flag: finality proofs are included after their blocks
+const FLAG_FINALITY_PROOFS: u32 = 1;
+
+/// Record kinds
+const RECORD_END: u8 = 0;
+const RECORD_BLOCK: u8 = 1;
+const RECORD_FINALITY_PROOF: u8 = 2;
+
+/// Reject records larger than this (guards against corrupt length prefixes)
+const MAX_RECORD_LEN: u32 = 64 * 1024 * 1024;
+
+/// Export file header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportHeader {
+    pub version: u32,
+    pub from: u64,
+    pub to: u64,
+    pub with_finality: bool,
+}
+
+/// One record read from an export file
+#[derive(Debug, Clone)]
+pub enum ExportRecord {
+    Block(Block),
+    FinalityProof(FinalityProof),
+}
+
+/// Counts reported after an export or import
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExportSummary {
+    pub blocks: u64,
+    pub finality_proofs: u64,
+    /// Import only: blocks already present locally (same hash)
+    pub skipped: u64,
+    /// Import only: finality proofs dropped because they don't match the
+    /// local block or don't verify
+    pub rejected_proofs: u64,
+}
+
+fn record_checksum(kind: u8, payload: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([kind]);
+    hasher.update(payload);
+    hasher.finalize().into()
+}
+
+/// Streaming writer for the export format
+pub struct ExportWriter<W: Write> {
+    out: W,
+    summary: ExportSummary,
+}
+
+impl<W: Write> ExportWriter<W> {
+    /// Write the file header
+    pub fn new(mut out: W, from: u64, to: u64, with_finality: bool) -> Result<Self> {
+        out.write_all(MAGIC)?;
+        out.write_all(&EXPORT_FORMAT_VERSION.to_le_bytes())?;
+        let flags = if with_finality { FLAG_FINALITY_PROOFS } else { 0 };
+        out.write_all(&flags.to_le_bytes())?;
+        out.write_all(&from.to_le_bytes())?;
+        out.write_all(&to.to_le_bytes())?;
+        Ok(Self { out, summary: ExportSummary::default() })
+    }
+
+    fn write_record(&mut self, kind: u8, payload: &[u8]) -> Result<()> {
+        let len = u32::try_from(payload.len())
+            .ok()
+            .filter(|len| *len <= MAX_RECORD_LEN)
+            .with_context(|| format!("Record too large: {} bytes", payload.len()))?;
+        self.out.write_all(&[kind])?;
+        self.out.write_all(&len.to_le_bytes())?;
+        self.out.write_all(payload)?;
+        self.out.write_all(&record_checksum(kind, payload))?;
+        Ok(())
+    }
+
+    pub fn write_block(&mut self, block: &Block) -> Result<()> {
+        self.write_record(RECORD_BLOCK, &bincode::serialize(block)?)?;
+        self.summary.blocks += 1;
+        Ok(())
+    }
+
+    pub fn write_finality_proof(&mut self, proof: &FinalityProof) -> Result<()> {
+        self.write_record(RECORD_FINALITY_PROOF, &bincode::serialize(proof)?)?;
+        self.summary.finality_proofs += 1;
+        Ok(())
+    }
+
+    /// Write the trailer and flush
+    pub fn finish(mut self) -> Result<ExportSummary> {
+        let counts = (self.summary.blocks, self.summary.finality_proofs);
+        self.write_record(RECORD_END, &bincode::serialize(&counts)?)?;
+        self.out.flush()?;
+        Ok(self.summary)
+    }
+}
+
+/// Streaming reader for the export format (verifies every checksum)
+pub struct ExportReader<R: Read> {
+    input: R,
+    header: ExportHeader,
+    seen: ExportSummary,
+    finished: bool,
+}
+
+impl<R: Read> ExportReader<R> {
+    /// Read and validate the file header
+    pub fn new(mut input: R) -> Result<Self> {
+        let mut magic = [0u8; 8];
+        input.read_exact(&mut magic).context("Export file too short")?;
+        if &magic != MAGIC {
+            bail!("Not a Sultan chain export file");
+        }
+        let version = read_u32(&mut input)?;
+        if version != EXPORT_FORMAT_VERSION {
+            bail!("Unsupported export format version {} (expected {})", version, EXPORT_FORMAT_VERSION);
+        }
+        let flags = read_u32(&mut input)?;
+        let from = read_u64(&mut input)?;
+        let to = read_u64(&mut input)?;
+        Ok(Self {
+            input,
+            header: ExportHeader {
+                version,
+                from,
+                to,
+                with_finality: flags & FLAG_FINALITY_PROOFS != 0,
+            },
+            seen: ExportSummary::default(),
+            finished: false,
+        })
+    }
+
+    pub fn header(&self) -> ExportHeader {
+        self.header
+    }
+
+    /// Next record, or `None` after a valid trailer
+    pub fn next_record(&mut self) -> Result<Option<ExportRecord>> {
+        if self.finished {
+            return Ok(None);
+        }
+        let mut kind = [0u8; 1];
+        self.input.read_exact(&mut kind)
+            .context("Export file truncated (missing trailer)")?;
+        let kind = kind[0];
+        let len = read_u32(&mut self.input)?;
+        if len > MAX_RECORD_LEN {
+            bail!("Record length {} exceeds limit (corrupt file?)", len);
+        }
+        let mut payload = vec![0u8; len as usize];
+        self.input.read_exact(&mut payload).context("Export file truncated mid-record")?;
+        let mut checksum = [0u8; 32];
+        self.input.read_exact(&mut checksum).context("Export file truncated mid-record")?;
+        if checksum != record_checksum(kind, &payload) {
+            bail!(
+                "Checksum mismatch in record {} (corrupt export file)",
+                self.seen.blocks + self.seen.finality_proofs
+            );
+        }
+
+        match kind {
+            RECORD_BLOCK => {
+                let block: Block = bincode::deserialize(&payload)
+                    .context("Failed to deserialize exported block")?;
+                self.seen.blocks += 1;
+                Ok(Some(ExportRecord::Block(block)))
+            }
+            RECORD_FINALITY_PROOF => {
+                let proof: FinalityProof = bincode::deserialize(&payload)
+                    .context("Failed to deserialize exported finality proof")?;
+                self.seen.finality_proofs += 1;
+                Ok(Some(ExportRecord::FinalityProof(proof)))
+            }
+            RECORD_END => {
+                let (blocks, proofs): (u64, u64) = bincode::deserialize(&payload)
+                    .context("Failed to deserialize export trailer")?;
+                if (blocks, proofs) != (self.seen.blocks, self.seen.finality_proofs) {
+                    bail!(
+                        "Export trailer expects {} blocks / {} proofs, read {} / {}",
+                        blocks, proofs, self.seen.blocks, self.seen.finality_proofs
+                    );
+                }
+                self.finished = true;
+                Ok(None)
+            }
+            other => bail!("Unknown record kind {}", other),
+        }
+    }
+}
+
+fn read_u32<R: Read>(input: &mut R) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    input.read_exact(&mut buf).context("Export file truncated")?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64<R: Read>(input: &mut R) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    input.read_exact(&mut buf).context("Export file truncated")?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Export blocks `from..=to` from storage
+///
+/// Fails on pruned or missing heights rather than writing a gapped file.
+pub fn export_blocks<W: Write>(
+    storage: &PersistentStorage,
+    from: u64,
+    to: u64,
+    with_finality: bool,
+    out: W,
+) -> Result<ExportSummary> {
+    if from > to {
+        bail!("Invalid range: --from {} is above --to {}", from, to);
+    }
+    let mut writer = ExportWriter::new(out, from, to, with_finality)?;
+    for height in from..=to {
+        if storage.is_pruned(height) {
+            return Err(storage.pruned_error(height));
+        }
+        let block = storage.get_block_by_height(height)?
+            .with_context(|| format!("Block {} not found in storage", height))?;
+        writer.write_block(&block)?;
+        if with_finality {
+            if let Some(proof) = storage.get_finality_proof(height)? {
+                writer.write_finality_proof(&proof)?;
+            }
+        }
+        if height.is_multiple_of(10_000) {
+            info!("📤 Exported {} / {} blocks", height - from + 1, to - from + 1);
+        }
+    }
+    writer.finish()
+}
+
+/// Import an export file, re-validating every block through `apply_block`
+///
+/// The blocks must be exactly the header's `from..=to` range, in order. Blocks
+/// the local chain already has are skipped if their hash matches and rejected
+/// otherwise. Applied blocks are persisted like synced ones (with an
+/// account snapshot when due) and fed to `consensus` so later key rotations
+/// are known. Finality proofs are kept only if they match the local block at
+/// their height and verify against the validator set; others are counted as
+/// rejected.
+pub async fn import_blocks<R: Read>(
+    blockchain: &SultanBlockchain,
+    consensus: &mut ConsensusEngine,
+    storage: &PersistentStorage,
+    input: R,
+) -> Result<ExportSummary> {
+    let mut reader = ExportReader::new(input)?;
+    let header = reader.header();
+    info!("📥 Importing blocks {}..={} (finality proofs: {})", header.from, header.to, header.with_finality);
+
+    let mut summary = ExportSummary::default();
+    let mut next_index = header.from;
+    while let Some(record) = reader.next_record()? {
+        match record {
+            ExportRecord::Block(block) => {
+                // Blocks must cover the header's range in order, with no gaps or splices
+                if block.index != next_index || block.index > header.to {
+                    bail!(
+                        "Block {} out of place: file covers {}..={}, expected block {}",
+                        block.index, header.from, header.to, next_index
+                    );
+                }
+                next_index += 1;
+                if block.index <= blockchain.get_height().await {
+                    let existing = blockchain.get_block(block.index).await
+                        .with_context(|| format!("Block {} missing from local chain", block.index))?;
+                    if existing.hash != block.hash {
+                        bail!(
+                            "Block {} conflicts with local chain: local {}, imported {}",
+                            block.index, existing.hash, block.hash
+                        );
+                    }
+                    summary.skipped += 1;
+                    continue;
+                }
+                consensus.verify_beacon_entries(&block)
+                    .with_context(|| format!("Imported block {} rejected", block.index))?;
+                blockchain.apply_block(block.clone()).await
+                    .with_context(|| format!("Imported block {} rejected", block.index))?;
+                storage.save_block(&block)?;
+                if storage.account_snapshot_due(block.index) {
+                    let snapshot = AccountStateSnapshot::new(&block, blockchain.get_all_accounts().await);
+                    storage.save_account_snapshot(&snapshot)?;
+                }
+                consensus.observe_block(&block);
+                summary.blocks += 1;
+            }
+            ExportRecord::FinalityProof(proof) => {
+                match check_finality_proof(blockchain, consensus, &proof).await {
+                    Ok(()) => {
+                        storage.save_finality_proof(&proof)?;
+                        summary.finality_proofs += 1;
+                    }
+                    Err(e) => {
+                        warn!("⚠️ Rejected finality proof for block {}: {}", proof.height, e);
+                        summary.rejected_proofs += 1;
+                    }
+                }
+            }
+        }
+    }
+    if next_index != header.to + 1 {
+        bail!(
+            "File ends after block {} but its header covers {}..={}",
+            next_index.saturating_sub(1), header.from, header.to
+        );
+    }
+    Ok(summary)
+}
+
+/// A finality proof must sign the local block at its height
+async fn check_finality_proof(
+    blockchain: &SultanBlockchain,
+    consensus: &ConsensusEngine,
+    proof: &FinalityProof,
+) -> Result<()> {
+    let block = blockchain.get_block(proof.height).await
+        .with_context(|| format!("Block {} missing from local chain", proof.height))?;
+    if hex::encode(proof.block_hash) != block.hash {
+        bail!("Proof is for block {}, local block is {}", hex::encode(proof.block_hash), block.hash);
+    }
+    consensus.verify_finality_proof(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sharding_production::ShardConfig;
+    use crate::storage::{PruningMode, ACCOUNT_SNAPSHOT_INTERVAL_BLOCKS};
+    use ed25519_dalek::{Signer, SigningKey};
+    use tempfile::tempdir;
+
+    /// Valid empty blocks 1..=count on top of the standard genesis block
+    fn build_chain(count: u64) -> Vec<Block> {
+        let mut prev_hash = "genesis".to_string();
+        (1..=count)
+            .map(|i| {
+                let mut block = Block {
+                    index: i,
+                    timestamp: 1768867200 + i,
+                    transactions: vec![],
+                    prev_hash: prev_hash.clone(),
+                    hash: String::new(),
+                    nonce: 0,
+                    validator: "validator1".to_string(),
+                    state_root: "root".to_string(),
+                };
+                block.hash = SultanBlockchain::calculate_block_hash(&block);
+                prev_hash = block.hash.clone();
+                block
+            })
+            .collect()
+    }
+
+    fn storage_with_chain(count: u64) -> (tempfile::TempDir, PersistentStorage) {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        for block in build_chain(count) {
+            storage.save_block(&block).unwrap();
+        }
+        (dir, storage)
+    }
+
+    /// Consensus engine with `validator1` holding all the voting power
+    fn single_validator() -> (ConsensusEngine, SigningKey) {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let mut consensus = ConsensusEngine::new();
+        let stake = consensus.min_stake;
+        consensus.add_validator("validator1".to_string(), stake, key.verifying_key().to_bytes()).unwrap();
+        (consensus, key)
+    }
+
+    fn signed_proof(block: &Block, key: &SigningKey) -> FinalityProof {
+        let block_hash: [u8; 32] = hex::decode(&block.hash).unwrap().try_into().unwrap();
+        let mut signatures = std::collections::BTreeMap::new();
+        signatures.insert("validator1".to_string(), key.sign(&block_hash).to_bytes().to_vec());
+        FinalityProof { height: block.index, block_hash, signatures, voting_power: 100 }
+    }
+
+    fn proof(height: u64) -> FinalityProof {
+        let mut signatures = std::collections::BTreeMap::new();
+        signatures.insert("validator1".to_string(), vec![1u8; 64]);
+        FinalityProof { height, block_hash: [height as u8; 32], signatures, voting_power: 100 }
+    }
+
+    #[test]
+    fn test_export_roundtrip() {
+        let (_dir, storage) = storage_with_chain(5);
+        storage.save_finality_proof(&proof(3)).unwrap();
+
+        let mut file = Vec::new();
+        let summary = export_blocks(&storage, 2, 4, true, &mut file).unwrap();
+        assert_eq!(summary.blocks, 3);
+        assert_eq!(summary.finality_proofs, 1);
+
+        let mut reader = ExportReader::new(file.as_slice()).unwrap();
+        assert_eq!(reader.header(), ExportHeader { version: 1, from: 2, to: 4, with_finality: true });
+        let mut heights = Vec::new();
+        let mut proofs = Vec::new();
+        while let Some(record) = reader.next_record().unwrap() {
+            match record {
+                ExportRecord::Block(block) => heights.push(block.index),
+                ExportRecord::FinalityProof(p) => proofs.push(p),
+            }
+        }
+        assert_eq!(heights, vec![2, 3, 4]);
+        assert_eq!(proofs, vec![proof(3)]);
+    }
+
+    #[test]
+    fn test_corrupt_and_truncated_files_rejected() {
+        let (_dir, storage) = storage_with_chain(3);
+        let mut file = Vec::new();
+        export_blocks(&storage, 1, 3, false, &mut file).unwrap();
+
+        let read_all = |bytes: &[u8]| -> Result<u64> {
+            let mut reader = ExportReader::new(bytes)?;
+            let mut count = 0;
+            while reader.next_record()?.is_some() {
+                count += 1;
+            }
+            Ok(count)
+        };
+        assert_eq!(read_all(&file).unwrap(), 3);
+
+        // Flip a byte inside the first block payload
+        let mut corrupt = file.clone();
+        corrupt[40] ^= 0xff;
+        assert!(read_all(&corrupt).unwrap_err().to_string().contains("Checksum mismatch"));
+
+        // Drop the trailer
+        let truncated = &file[..file.len() - 10];
+        assert!(read_all(truncated).is_err());
+
+        assert!(ExportReader::new(&b"NOTSULTANFILE......."[..]).is_err());
+    }
+
+    #[test]
+    fn test_export_refuses_pruned_or_missing_blocks() {
+        let (_dir, storage) = storage_with_chain(3);
+        assert!(export_blocks(&storage, 2, 5, false, Vec::new()).is_err());
+        assert!(export_blocks(&storage, 3, 2, false, Vec::new()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_applies_and_persists_blocks() {
+        let (_src_dir, source) = storage_with_chain(6);
+        let (mut consensus, key) = single_validator();
+        let chain = build_chain(6);
+        source.save_finality_proof(&signed_proof(&chain[5], &key)).unwrap();
+        let mut file = Vec::new();
+        export_blocks(&source, 1, 6, true, &mut file).unwrap();
+
+        let dest_dir = tempdir().unwrap();
+        let dest = PersistentStorage::new(dest_dir.path().to_str().unwrap()).unwrap();
+        let blockchain = SultanBlockchain::new(ShardConfig::default());
+
+        let summary = import_blocks(&blockchain, &mut consensus, &dest, file.as_slice()).await.unwrap();
+        assert_eq!(summary, ExportSummary { blocks: 6, finality_proofs: 1, skipped: 0, rejected_proofs: 0 });
+        assert_eq!(blockchain.get_height().await, 6);
+        assert_eq!(dest.get_latest_block().unwrap().unwrap().index, 6);
+        assert_eq!(dest.get_finality_proof(6).unwrap().unwrap(), signed_proof(&chain[5], &key));
+
+        // Re-importing the same file is a no-op
+        let summary = import_blocks(&blockchain, &mut consensus, &dest, file.as_slice()).await.unwrap();
+        assert_eq!(summary.skipped, 6);
+        assert_eq!(summary.blocks, 0);
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_unverified_finality_proofs() {
+        let chain = build_chain(4);
+        let (mut consensus, key) = single_validator();
+        let mut bad_signature = signed_proof(&chain[2], &key);
+        bad_signature.signatures.insert("validator1".to_string(), vec![1u8; 64]);
+        let mut unknown_signer = signed_proof(&chain[3], &key);
+        unknown_signer.signatures = [("mallory".to_string(), vec![1u8; 64])].into_iter().collect();
+
+        let mut file = Vec::new();
+        let mut writer = ExportWriter::new(&mut file, 1, 4, true).unwrap();
+        for block in &chain {
+            writer.write_block(block).unwrap();
+        }
+        writer.write_finality_proof(&signed_proof(&chain[0], &key)).unwrap();
+        writer.write_finality_proof(&proof(2)).unwrap(); // not the local block's hash
+        writer.write_finality_proof(&bad_signature).unwrap();
+        writer.write_finality_proof(&unknown_signer).unwrap();
+        writer.write_finality_proof(&signed_proof(&build_chain(9)[8], &key)).unwrap(); // no such block
+        writer.finish().unwrap();
+
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        let blockchain = SultanBlockchain::new(ShardConfig::default());
+
+        let summary = import_blocks(&blockchain, &mut consensus, &storage, file.as_slice()).await.unwrap();
+        assert_eq!(summary.blocks, 4);
+        assert_eq!(summary.finality_proofs, 1);
+        assert_eq!(summary.rejected_proofs, 4);
+        assert!(storage.get_finality_proof(1).unwrap().is_some());
+        for height in 2..=9 {
+            assert!(storage.get_finality_proof(height).unwrap().is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restart_after_import_restores_state() {
+        let count = ACCOUNT_SNAPSHOT_INTERVAL_BLOCKS + 5;
+        let (_src_dir, source) = storage_with_chain(count);
+        let mut file = Vec::new();
+        export_blocks(&source, 1, count, false, &mut file).unwrap();
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        let mode = PruningMode::Custom { keep_recent: 10, keep_every: 0 };
+        {
+            let storage = PersistentStorage::with_pruning(path, mode).unwrap();
+            let blockchain = SultanBlockchain::new(ShardConfig::default());
+            blockchain.init_account("alice".to_string(), 1_000_000).await.unwrap();
+            let (mut consensus, _) = single_validator();
+            import_blocks(&blockchain, &mut consensus, &storage, file.as_slice()).await.unwrap();
+
+            // The import left a snapshot behind, so old bodies can be pruned
+            assert_eq!(storage.load_account_snapshot().unwrap().unwrap().height, ACCOUNT_SNAPSHOT_INTERVAL_BLOCKS);
+            assert!(storage.prune_to(count).unwrap() > 0);
+        }
+
+        // Restart: state comes back from storage alone
+        let storage = PersistentStorage::with_pruning(path, mode).unwrap();
+        let blockchain = SultanBlockchain::new(ShardConfig::default());
+        blockchain.restore_from_storage(&storage).await.unwrap();
+        assert_eq!(blockchain.get_height().await, count);
+        assert_eq!(blockchain.get_block(count).await.unwrap().hash, source.get_block_by_height(count).unwrap().unwrap().hash);
+        assert_eq!(blockchain.get_balance("alice").await, 1_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_invalid_block() {
+        let mut chain = build_chain(3);
+        chain[1].state_root = "tampered".to_string(); // hash no longer matches
+
+        let mut file = Vec::new();
+        let mut writer = ExportWriter::new(&mut file, 1, 3, false).unwrap();
+        for block in &chain {
+            writer.write_block(block).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        let blockchain = SultanBlockchain::new(ShardConfig::default());
+
+        let (mut consensus, _) = single_validator();
+        let err = import_blocks(&blockchain, &mut consensus, &storage, file.as_slice()).await.unwrap_err();
+        assert!(err.to_string().contains("Imported block 2 rejected"));
+        assert_eq!(blockchain.get_height().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_blocks_outside_header_range() {
+        let chain = build_chain(5);
+        let write = |from: u64, to: u64, blocks: &[&Block]| {
+            let mut file = Vec::new();
+            let mut writer = ExportWriter::new(&mut file, from, to, false).unwrap();
+            for block in blocks {
+                writer.write_block(block).unwrap();
+            }
+            writer.finish().unwrap();
+            file
+        };
+        let import = |file: Vec<u8>| async move {
+            let dir = tempdir().unwrap();
+            let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+            let blockchain = SultanBlockchain::new(ShardConfig::default());
+            let (mut consensus, _) = single_validator();
+            import_blocks(&blockchain, &mut consensus, &storage, file.as_slice()).await.unwrap_err().to_string()
+        };
+
+        // Truncated before the header's end, with a valid trailer
+        let err = import(write(1, 5, &[&chain[0], &chain[1], &chain[2]])).await;
+        assert!(err.contains("File ends after block 3"), "{}", err);
+
+        // A block spliced out of the middle
+        let err = import(write(1, 4, &[&chain[0], &chain[1], &chain[3]])).await;
+        assert!(err.contains("Block 4 out of place"), "{}", err);
+
+        // Blocks starting before the header's range
+        let err = import(write(2, 3, &[&chain[0], &chain[1]])).await;
+        assert!(err.contains("Block 1 out of place"), "{}", err);
+    }
+}
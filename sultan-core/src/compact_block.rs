@@ -0,0 +1,230 @@
+//! Compact block relay
+//!
+//! A proposal is gossiped as the block header plus one short ID per transaction.
+//! Receivers rebuild the block from bodies they already hold (see `tx_fetch`) and
+//! fetch only the missing ones. Short IDs are keyed by the header, so they
+//! differ per block. The rebuilt block must hash to the hash the proposer signed;
+//! if a short ID collision swapped a transaction, the receiver fetches the
+//! block's transactions in full from the peer that relayed it.
+//!
+//! Protocol 1.0 peers only take full proposals on `BLOCK_TOPIC`. Nodes with such
+//! a peer pass rebuilt blocks on to it in full and re-encode full proposals from
+//! it as compact ones, so both halves of a mixed network see every block.
+
+use crate::blockchain::{Block, Transaction};
+use crate::tx_fetch::{short_id, tx_id, FetchedTx, ShortIdKey, ShortTxId, TxCache, TxId};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+/// Block header plus the short IDs of its transactions, in block order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactBlock {
+    /// The block with `transactions` left empty
+    pub header: Block,
+    pub short_ids: Vec<ShortTxId>,
+}
+
+impl CompactBlock {
+    /// Compact form of `block`, plus the encoded bodies so peers can fetch them from us
+    pub fn from_block(block: &Block) -> Result<(Self, Vec<(TxId, FetchedTx)>)> {
+        let header = Block { transactions: Vec::new(), ..block.clone() };
+        let key = short_id_key(&header);
+        let mut short_ids = Vec::with_capacity(block.transactions.len());
+        let mut bodies = Vec::with_capacity(block.transactions.len());
+        for tx in &block.transactions {
+            let body = encode_tx(tx)?;
+            let id = tx_id(&body.tx_data);
+            short_ids.push(short_id(&key, &id));
+            bodies.push((id, body));
+        }
+        Ok((Self { header, short_ids }, bodies))
+    }
+}
+
+/// SipHash key for a block's short IDs: the first 16 bytes of its header hash
+pub fn short_id_key(header: &Block) -> ShortIdKey {
+    let digest = Sha256::digest(bincode::serialize(header).unwrap_or_default());
+    let mut key = [0u8; 16];
+    key.copy_from_slice(&digest[..16]);
+    key
+}
+
+/// Encode a transaction the way it's relayed between peers
+pub fn encode_tx(tx: &Transaction) -> Result<FetchedTx> {
+    Ok(FetchedTx {
+        tx_hash: format!("{}:{}:{}", tx.from, tx.to, tx.nonce),
+        tx_data: bincode::serialize(tx)?,
+    })
+}
+
+/// Hex SHA-256 of an encoded block, as signed by its proposer
+pub fn block_hash(block_data: &[u8]) -> String {
+    hex::encode(Sha256::digest(block_data))
+}
+
+/// A compact block being rebuilt from local and fetched transactions
+#[derive(Debug, Clone)]
+pub struct PartialBlock {
+    header: Block,
+    key: ShortIdKey,
+    short_ids: Vec<ShortTxId>,
+    slots: Vec<Option<Transaction>>,
+}
+
+impl PartialBlock {
+    /// Fill in every transaction the cache already holds
+    pub fn new(compact: CompactBlock, cache: &TxCache) -> Self {
+        let key = short_id_key(&compact.header);
+        let slots = cache.get_by_short(&key, &compact.short_ids).into_iter()
+            .map(|tx| tx.and_then(|tx| bincode::deserialize(&tx.tx_data).ok()))
+            .collect();
+        Self { header: compact.header, key, short_ids: compact.short_ids, slots }
+    }
+
+    /// Key for this block's short IDs, sent along with fetches
+    pub fn key(&self) -> ShortIdKey {
+        self.key
+    }
+
+    /// Short IDs still to fetch, without duplicates
+    pub fn missing(&self) -> Vec<ShortTxId> {
+        let mut seen = HashSet::new();
+        self.short_ids.iter()
+            .zip(&self.slots)
+            .filter(|(short, slot)| slot.is_none() && seen.insert(**short))
+            .map(|(short, _)| *short)
+            .collect()
+    }
+
+    /// Fill the slots `txs` belong to; returns how many were filled
+    pub fn fill(&mut self, txs: &[FetchedTx]) -> usize {
+        let mut filled = 0;
+        for body in txs {
+            let short = short_id(&self.key, &tx_id(&body.tx_data));
+            let Ok(tx) = bincode::deserialize::<Transaction>(&body.tx_data) else { continue };
+            for (slot_id, slot) in self.short_ids.iter().zip(self.slots.iter_mut()) {
+                if slot.is_none() && *slot_id == short {
+                    *slot = Some(tx.clone());
+                    filled += 1;
+                }
+            }
+        }
+        filled
+    }
+
+    /// Replace every slot with the block's transactions fetched in full, in
+    /// order; returns false if they don't line up with the short IDs' count
+    pub fn fill_in_order(&mut self, txs: &[FetchedTx]) -> bool {
+        if txs.len() != self.slots.len() {
+            return false;
+        }
+        self.slots = txs.iter()
+            .map(|body| bincode::deserialize(&body.tx_data).ok())
+            .collect();
+        true
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.slots.iter().all(Option::is_some)
+    }
+
+    /// The full block, once every transaction is present
+    pub fn into_block(self) -> Option<Block> {
+        let transactions = self.slots.into_iter().collect::<Option<Vec<_>>>()?;
+        Some(Block { transactions, ..self.header })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn tx(nonce: u64) -> Transaction {
+        Transaction {
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            amount: 100 + nonce,
+            gas_fee: 0,
+            timestamp: 1_700_000_000,
+            nonce,
+            signature: None,
+            public_key: None,
+            memo: None,
+        }
+    }
+
+    fn block(txs: Vec<Transaction>) -> Block {
+        Block {
+            index: 7,
+            timestamp: 1_700_000_000,
+            transactions: txs,
+            prev_hash: "prev".to_string(),
+            hash: "hash".to_string(),
+            nonce: 0,
+            validator: "sultan1validator".to_string(),
+            state_root: "root".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_rebuild_with_missing_transactions() {
+        let full = block(vec![tx(1), tx(2), tx(3)]);
+        let full_data = bincode::serialize(&full).unwrap();
+        let (compact, bodies) = CompactBlock::from_block(&full).unwrap();
+        assert_eq!(compact.short_ids.len(), 3);
+        assert!(compact.header.transactions.is_empty());
+        assert!(bincode::serialize(&compact).unwrap().len() < full_data.len());
+
+        // Receiver has the first two transactions in its cache
+        let mut cache = TxCache::new(100, Duration::from_secs(60));
+        for (id, body) in bodies.iter().take(2) {
+            cache.insert(*id, body.clone());
+        }
+        let key = short_id_key(&compact.header);
+        let mut partial = PartialBlock::new(compact, &cache);
+        assert!(!partial.is_complete());
+        assert_eq!(partial.missing(), vec![short_id(&key, &bodies[2].0)]);
+
+        assert_eq!(partial.fill(&[bodies[2].1.clone()]), 1);
+        assert!(partial.is_complete());
+        let rebuilt = partial.into_block().unwrap();
+        assert_eq!(rebuilt, full);
+        assert_eq!(block_hash(&bincode::serialize(&rebuilt).unwrap()), block_hash(&full_data));
+    }
+
+    #[test]
+    fn test_incomplete_block_does_not_build() {
+        let (compact, _) = CompactBlock::from_block(&block(vec![tx(1)])).unwrap();
+        let partial = PartialBlock::new(compact, &TxCache::default());
+        assert_eq!(partial.missing().len(), 1);
+        assert!(partial.into_block().is_none());
+
+        // An empty block is complete straight away
+        let (compact, _) = CompactBlock::from_block(&block(Vec::new())).unwrap();
+        assert!(PartialBlock::new(compact, &TxCache::default()).is_complete());
+    }
+
+    #[test]
+    fn test_short_ids_differ_per_block() {
+        let mut other = block(vec![tx(1)]);
+        other.index = 8;
+        let (first, _) = CompactBlock::from_block(&block(vec![tx(1)])).unwrap();
+        let (second, _) = CompactBlock::from_block(&other).unwrap();
+        assert_ne!(first.short_ids, second.short_ids);
+    }
+
+    #[test]
+    fn test_rebuild_from_full_fetch() {
+        let full = block(vec![tx(1), tx(2)]);
+        let (compact, bodies) = CompactBlock::from_block(&full).unwrap();
+        let mut partial = PartialBlock::new(compact, &TxCache::default());
+
+        let ordered: Vec<FetchedTx> = bodies.into_iter().map(|(_, body)| body).collect();
+        assert!(!partial.fill_in_order(&ordered[..1]));
+        assert!(partial.fill_in_order(&ordered));
+        assert_eq!(partial.into_block().unwrap(), full);
+    }
+}
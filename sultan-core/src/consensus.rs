@@ -2,25 +2,33 @@
 //!
 //! Production-grade consensus implementation with:
 //! - Validator management (add/remove/update with min stake)
-//! - Stake-weighted proposer selection (deterministic SHA256)
+//! - Stake-weighted proposer selection (SHA256 over the randomness beacon from its activation height)
 //! - Byzantine fault tolerance (2/3 + 1 quorum)
-//! - Slashing for downtime (0.1%) and double-signing (5%)
+//! - Slashing for downtime (0.1%, judged over a signed-blocks window of commit votes) and double-signing (5%)
 //! - Ed25519 signature verification for Byzantine consensus
 //! - Slashing evidence collection for on-chain proofs
 //! - Signature collection and threshold validation
+//! - Consensus key rotation with per-height key history
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use anyhow::{Result, bail};
 use tracing::{info, warn, debug, error};
 use sha2::{Sha256, Digest};
 use ed25519_dalek::{Verifier, VerifyingKey, Signature};
+use crate::beacon::{is_beacon_tx, BeaconEntry, RandomnessBeacon};
+use crate::blockchain::Block;
+use crate::config::Config;
+use crate::key_rotation::{KeyRotation, PendingKey, RetiredKey, MIN_KEY_ROTATION_DELAY};
+use crate::liveness::{SignedBlocksWindow, MIN_WINDOW_DUTIES};
+use crate::storage::FinalityProof;
 
 /// Slashing parameters matching Sultan's PoS design
 pub const DOWNTIME_SLASH_PERCENT: u64 = 1;    // 0.1% = 1/1000
 pub const DOUBLE_SIGN_SLASH_PERCENT: u64 = 50; // 5% = 50/1000
 pub const SLASH_DENOMINATOR: u64 = 1000;
-pub const MAX_MISSED_BLOCKS_BEFORE_SLASH: u64 = 100;
+/// Missed heights that jail a validator with nothing signed in its window
+pub const MAX_MISSED_BLOCKS_BEFORE_SLASH: u64 = MIN_WINDOW_DUTIES;
 pub const JAIL_DURATION_BLOCKS: u64 = 10_000; // ~5.5 hours at 2s blocks
 
 /// Fallback proposer parameters
@@ -33,13 +41,22 @@ pub struct Validator {
     pub address: String,
     /// Ed25519 public key (32 bytes) for signature verification
     pub pubkey: [u8; 32],
+    /// Rotation to a new key, waiting for its activation height
+    #[serde(default)]
+    pub pending_key: Option<PendingKey>,
+    /// Earlier keys, oldest first, for verifying signatures from before a rotation
+    #[serde(default)]
+    pub retired_keys: Vec<RetiredKey>,
     pub stake: u64,
     pub voting_power: u64,
     pub is_active: bool,
     pub blocks_proposed: u64,
     pub blocks_signed: u64,
-    /// Consecutive missed blocks (reset on sign)
+    /// Consecutive missed blocks (reset on sign, drives fallback proposers)
     pub missed_blocks: u64,
+    /// Heights whose commit did or didn't carry this validator's vote (drives downtime slashing)
+    #[serde(default)]
+    pub signed_window: SignedBlocksWindow,
     /// Whether validator is jailed (cannot participate)
     pub is_jailed: bool,
     /// Block height when jail ends (can unjail after this)
@@ -48,6 +65,19 @@ pub struct Validator {
     pub total_slashed: u64,
 }
 
+impl Validator {
+    /// The consensus key that signs for `height`
+    pub fn pubkey_at(&self, height: u64) -> [u8; 32] {
+        if let Some(pending) = self.pending_key.filter(|p| height >= p.activation_height) {
+            return pending.pubkey;
+        }
+        self.retired_keys.iter()
+            .find(|retired| height < retired.until_height)
+            .map(|retired| retired.pubkey)
+            .unwrap_or(self.pubkey)
+    }
+}
+
 /// Evidence of slashing offense (stored for transparency/appeals)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlashingEvidence {
@@ -58,7 +88,7 @@ pub struct SlashingEvidence {
     pub slash_amount: u64,
     /// For double-sign: the two conflicting block hashes
     pub evidence_hashes: Option<([u8; 32], [u8; 32])>,
-    /// For downtime: number of consecutive missed blocks
+    /// For downtime: missed heights in the signed-blocks window
     pub missed_blocks: Option<u64>,
 }
 
@@ -95,6 +125,8 @@ pub struct ConsensusSnapshot {
     pub total_stake: u64,
     pub prev_block_hash: [u8; 32],
     pub slashing_evidence: Vec<SlashingEvidence>,
+    #[serde(default)]
+    pub beacon: RandomnessBeacon,
 }
 
 pub struct ConsensusEngine {
@@ -114,8 +146,15 @@ pub struct ConsensusEngine {
     /// Whether to verify Ed25519 signatures (disable for testing)
     pub verify_signatures: bool,
     /// Track which heights we've already recorded missed blocks for (prevents double-counting)
-    /// Maps height -> validator_address that missed
-    pub recorded_misses: HashMap<u64, String>,
+    /// Maps height -> validators that missed
+    pub recorded_misses: HashMap<u64, HashSet<String>>,
+    /// Commit-reveal randomness beacon seeding proposer selection
+    pub beacon: RandomnessBeacon,
+    /// First height whose proposer is seeded from the beacon; earlier heights
+    /// keep the v2 seed, and `None` keeps it everywhere (network upgrade height)
+    pub beacon_activation_height: Option<u64>,
+    /// Chain key rotations must be signed for
+    pub chain_id: String,
 }
 
 impl Default for ConsensusEngine {
@@ -139,6 +178,9 @@ impl ConsensusEngine {
             slashing_evidence: Vec::new(),
             verify_signatures: true, // Enable signature verification by default
             recorded_misses: HashMap::new(),
+            beacon: RandomnessBeacon::new(),
+            beacon_activation_height: None,
+            chain_id: Config::default().chain_id,
         }
     }
 
@@ -180,12 +222,15 @@ impl ConsensusEngine {
         let validator = Validator {
             address: address.clone(),
             pubkey,
+            pending_key: None,
+            retired_keys: Vec::new(),
             stake,
             voting_power,
             is_active: true,
             blocks_proposed: 0,
             blocks_signed: 0,
             missed_blocks: 0,
+            signed_window: SignedBlocksWindow::default(),
             is_jailed: false,
             jail_until: 0,
             total_slashed: 0,
@@ -285,18 +330,21 @@ impl ConsensusEngine {
         }
     }
 
-    /// Record block signature by validator (resets missed block counter)
-    pub fn record_signature(&mut self, validator_address: &str) -> Result<()> {
+    /// Record that a validator's vote is in the commit for `height` (resets missed block counter)
+    pub fn record_signature(&mut self, validator_address: &str, height: u64) -> Result<()> {
         if let Some(validator) = self.validators.get_mut(validator_address) {
             validator.blocks_signed += 1;
             validator.missed_blocks = 0; // Reset on successful sign
+            validator.signed_window.record(height, true);
             Ok(())
         } else {
             bail!("Validator {} not found", validator_address);
         }
     }
 
-    /// Record missed block for validator (triggers slashing if threshold exceeded)
+    /// Record that a validator's vote is missing from the commit for `height`
+    /// (triggers slashing once the validator signs too few heights in the
+    /// signed-blocks window)
     /// Returns the slash amount if slashing occurred
     /// 
     /// SAFETY: Uses height-based deduplication to prevent double-counting
@@ -304,40 +352,37 @@ impl ConsensusEngine {
     pub fn record_missed_block(&mut self, validator_address: &str, current_height: u64) -> Result<Option<u64>> {
         // CRITICAL: Check if we already recorded a miss for this height
         // This prevents double-counting when multiple nodes report the same miss
-        if let Some(existing) = self.recorded_misses.get(&current_height) {
-            if existing == validator_address {
-                debug!("Already recorded missed block at height {} for {}", current_height, validator_address);
-                return Ok(None);
-            }
+        if self.recorded_misses.get(&current_height).is_some_and(|missed| missed.contains(validator_address)) {
+            debug!("Already recorded missed block at height {} for {}", current_height, validator_address);
+            return Ok(None);
         }
         
-        // First check if validator exists and get missed_blocks
-        let (is_jailed, missed_blocks) = {
-            let validator = self.validators.get(validator_address)
-                .ok_or_else(|| anyhow::anyhow!("Validator {} not found", validator_address))?;
-            (validator.is_jailed, validator.missed_blocks)
-        };
-        
-        if is_jailed {
+        let validator = self.validators.get(validator_address)
+            .ok_or_else(|| anyhow::anyhow!("Validator {} not found", validator_address))?;
+        if validator.is_jailed {
             return Ok(None); // Already jailed, don't accumulate
         }
         
         // Record that we've processed this height (before incrementing to prevent races)
-        self.recorded_misses.insert(current_height, validator_address.to_string());
+        self.recorded_misses.entry(current_height).or_default().insert(validator_address.to_string());
         
         // Periodic cleanup to prevent memory growth
-        if current_height % 100 == 0 {
+        if current_height.is_multiple_of(100) {
             self.cleanup_recorded_misses(current_height);
         }
         
         // Increment missed blocks
-        if let Some(validator) = self.validators.get_mut(validator_address) {
-            validator.missed_blocks += 1;
+        let Some(validator) = self.validators.get_mut(validator_address) else {
+            return Ok(None);
+        };
+        if !validator.signed_window.record(current_height, false) {
+            return Ok(None); // Height already judged (e.g. signed) or out of the window
         }
+        validator.missed_blocks += 1;
         
-        let new_missed = missed_blocks + 1;
-        
-        if new_missed >= MAX_MISSED_BLOCKS_BEFORE_SLASH {
+        if validator.signed_window.is_below_minimum() {
+            let window_missed = validator.signed_window.missed_count();
+            let window_slots = validator.signed_window.duties();
             // Apply downtime slashing (0.1%)
             let slash_amount = self.apply_slash_with_evidence(
                 validator_address, 
@@ -345,16 +390,48 @@ impl ConsensusEngine {
                 current_height,
                 SlashingOffense::Downtime,
                 None,
-                Some(new_missed),
+                Some(window_missed),
             )?;
-            info!("Validator {} slashed {} for downtime ({} missed blocks)", 
-                  validator_address, slash_amount, MAX_MISSED_BLOCKS_BEFORE_SLASH);
+            info!("Validator {} slashed {} for downtime (missed {} of {} heights in window)", 
+                  validator_address, slash_amount, window_missed, window_slots);
             return Ok(Some(slash_amount));
         }
         
         Ok(None)
     }
 
+    /// Validators whose vote for `height` has been collected
+    pub fn commit_signers(&self, height: u64) -> HashSet<String> {
+        self.pending_signatures.get(&height)
+            .map(|set| set.signatures.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Record liveness for `height` from its commit
+    ///
+    /// Every active validator gets one bit in its signed-blocks window: signed
+    /// if its vote is among `signers`, missed otherwise. The collected votes for
+    /// the height are dropped afterwards. Returns the validators slashed for
+    /// downtime with the amounts.
+    pub fn record_commit(&mut self, height: u64, signers: &HashSet<String>) -> Vec<(String, u64)> {
+        let mut active: Vec<String> = self.validators.values()
+            .filter(|v| v.is_active && !v.is_jailed)
+            .map(|v| v.address.clone())
+            .collect();
+        active.sort();
+
+        let mut slashed = Vec::new();
+        for address in active {
+            if signers.contains(&address) {
+                let _ = self.record_signature(&address, height);
+            } else if let Ok(Some(amount)) = self.record_missed_block(&address, height) {
+                slashed.push((address, amount));
+            }
+        }
+        self.pending_signatures.retain(|h, _| *h > height);
+        slashed
+    }
+
     /// Record a block vote and check for double-signing
     /// Returns slash amount if double-sign detected
     pub fn record_block_vote(&mut self, vote: &BlockVote, current_height: u64) -> Result<Option<u64>> {
@@ -417,6 +494,7 @@ impl ConsensusEngine {
             validator.is_active = false;
             validator.jail_until = current_height + JAIL_DURATION_BLOCKS;
             validator.missed_blocks = 0; // Reset counter
+            validator.signed_window.reset();
             
             warn!("Validator {} jailed until block {} (slashed {} = {}%)", 
                   validator_address, validator.jail_until, slash_amount, 
@@ -478,7 +556,7 @@ impl ConsensusEngine {
                              validator_address: &str, signature: Vec<u8>) -> Result<bool> {
         // Verify validator exists and is active, get voting power and pubkey
         let (voting_power, pubkey) = match self.validators.get(validator_address) {
-            Some(v) if v.is_active && !v.is_jailed => (v.voting_power, v.pubkey),
+            Some(v) if v.is_active && !v.is_jailed => (v.voting_power, v.pubkey_at(height)),
             Some(_) => bail!("Validator {} is not active or is jailed", validator_address),
             None => bail!("Validator {} not found", validator_address),
         };
@@ -488,6 +566,36 @@ impl ConsensusEngine {
             self.verify_ed25519_signature(&pubkey, &block_hash, &signature)?;
         }
         
+        Ok(self.add_signature(height, block_hash, validator_address, signature, voting_power))
+    }
+    
+    /// Collect a vote gossiped as a `BlockVote` for the block with hex hash
+    /// `block_hash`
+    ///
+    /// Network votes sign the hex string, like block proposals (see
+    /// `P2PNetwork::verify_vote_signature`); otherwise this is `collect_signature`.
+    pub fn collect_vote(&mut self, height: u64, block_hash: &str,
+                        validator_address: &str, signature: Vec<u8>) -> Result<bool> {
+        let hash: [u8; 32] = hex::decode(block_hash).ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| anyhow::anyhow!("Malformed block hash {}", block_hash))?;
+        let (voting_power, pubkey) = match self.validators.get(validator_address) {
+            Some(v) if v.is_active && !v.is_jailed => (v.voting_power, v.pubkey_at(height)),
+            Some(_) => bail!("Validator {} is not active or is jailed", validator_address),
+            None => bail!("Validator {} not found", validator_address),
+        };
+        
+        if self.verify_signatures {
+            self.verify_ed25519_signature(&pubkey, block_hash.as_bytes(), &signature)?;
+        }
+        
+        Ok(self.add_signature(height, hash, validator_address, signature, voting_power))
+    }
+    
+    /// Add a verified signature to the set for `height`; returns whether the
+    /// height now has consensus
+    fn add_signature(&mut self, height: u64, block_hash: [u8; 32], validator_address: &str,
+                     signature: Vec<u8>, voting_power: u64) -> bool {
         let sig_set = self.pending_signatures.entry(height).or_insert_with(|| {
             SignatureSet {
                 block_hash,
@@ -501,7 +609,7 @@ impl ConsensusEngine {
         if sig_set.block_hash != block_hash {
             warn!("Block hash mismatch at height {}: validator {} signed different block", 
                   height, validator_address);
-            return Ok(false);
+            return false;
         }
         
         // Add signature if not already present
@@ -511,12 +619,11 @@ impl ConsensusEngine {
         }
         
         // Check if we have consensus
-        let has_consensus = self.check_consensus(height);
-        Ok(has_consensus)
+        self.check_consensus(height)
     }
     
     /// Verify Ed25519 signature
-    fn verify_ed25519_signature(&self, pubkey: &[u8; 32], message: &[u8; 32], signature: &[u8]) -> Result<()> {
+    fn verify_ed25519_signature(&self, pubkey: &[u8; 32], message: &[u8], signature: &[u8]) -> Result<()> {
         if signature.len() != 64 {
             bail!("Invalid signature length: expected 64 bytes, got {}", signature.len());
         }
@@ -535,6 +642,32 @@ impl ConsensusEngine {
         Ok(())
     }
 
+    /// Check a finality proof from outside the node (e.g. an imported chain file)
+    ///
+    /// Every signer must be a known validator whose key at `proof.height`
+    /// signed `proof.block_hash`, and together they must hold the required
+    /// voting power. The claimed `voting_power` is not trusted.
+    pub fn verify_finality_proof(&self, proof: &FinalityProof) -> Result<()> {
+        let mut voting_power = 0u64;
+        for (address, signature) in &proof.signatures {
+            let validator = match self.validators.get(address) {
+                Some(v) => v,
+                None => bail!("Validator {} not found", address),
+            };
+            if self.verify_signatures {
+                self.verify_ed25519_signature(&validator.pubkey_at(proof.height), &proof.block_hash, signature)
+                    .map_err(|e| anyhow::anyhow!("Signature from {}: {}", address, e))?;
+            }
+            voting_power += validator.voting_power;
+        }
+        
+        let required = self.required_voting_power();
+        if voting_power < required {
+            bail!("Insufficient voting power: {} signed, {} required", voting_power, required);
+        }
+        Ok(())
+    }
+
     /// Check if we have enough signatures for consensus at given height
     pub fn check_consensus(&self, height: u64) -> bool {
         if let Some(sig_set) = self.pending_signatures.get(&height) {
@@ -585,6 +718,7 @@ impl ConsensusEngine {
             total_stake: self.total_stake,
             prev_block_hash: self.prev_block_hash,
             slashing_evidence: self.slashing_evidence.clone(),
+            beacon: self.beacon.clone(),
         }
     }
 
@@ -595,6 +729,7 @@ impl ConsensusEngine {
         self.total_stake = snapshot.total_stake;
         self.prev_block_hash = snapshot.prev_block_hash;
         self.slashing_evidence = snapshot.slashing_evidence;
+        self.beacon = snapshot.beacon;
         // Clear transient state
         self.pending_signatures.clear();
         self.height_signatures.clear();
@@ -633,15 +768,22 @@ impl ConsensusEngine {
 
     /// Calculate deterministic seed based on block height (for synchronized proposer selection)
     /// CRITICAL: Must be deterministic across ALL nodes at the same height
-    /// Does NOT include prev_block_hash because that varies across nodes during sync
+    /// Does NOT include prev_block_hash because that varies across nodes during sync,
+    /// and the previous proposer could grind it. From `beacon_activation_height` on
+    /// (v3) the epoch's beacon seed is mixed in: it is fixed by reveals committed
+    /// before the epoch and is the same on every node with the chain.
     fn calculate_height_seed(&self, height: u64) -> u64 {
+        let beacon_active = self.beacon_activation_height.is_some_and(|activation| height >= activation);
         let mut hasher = Sha256::new();
-        hasher.update(b"sultan_proposer_v2_");
+        hasher.update(if beacon_active { b"sultan_proposer_v3_" } else { b"sultan_proposer_v2_" });
         hasher.update(&height.to_le_bytes());
         // Use genesis_total_stake constant for determinism (all nodes have same genesis)
         // total_stake can vary if validators register/unregister at different times
         let genesis_stake: u64 = 60_000_000_000_000; // 6 validators × 10T each
         hasher.update(&genesis_stake.to_le_bytes());
+        if beacon_active {
+            hasher.update(self.beacon.seed_for_height(height));
+        }
         let result = hasher.finalize();
         
         u64::from_le_bytes([
@@ -747,6 +889,106 @@ impl ConsensusEngine {
         self.validators.get(address).map(|v| v.pubkey)
     }
 
+    /// Get the public key a validator signed with at `height`
+    pub fn get_validator_pubkey_at(&self, address: &str, height: u64) -> Option<[u8; 32]> {
+        self.validators.get(address).map(|v| v.pubkey_at(height))
+    }
+
+    /// Schedule a key rotation carried by the block at `height`
+    pub fn schedule_key_rotation(&mut self, rotation: &KeyRotation, height: u64) -> Result<()> {
+        let Some(validator) = self.validators.get_mut(&rotation.validator) else {
+            bail!("Validator {} not found", rotation.validator);
+        };
+        if rotation.activation_height < height + MIN_KEY_ROTATION_DELAY {
+            bail!("Key rotation must activate at least {} blocks after inclusion (height {}, activation {})",
+                  MIN_KEY_ROTATION_DELAY, height, rotation.activation_height);
+        }
+        if let Some(pending) = validator.pending_key {
+            bail!("Validator {} already has a key rotation pending for height {}",
+                  rotation.validator, pending.activation_height);
+        }
+        rotation.verify(&self.chain_id, &validator.pubkey_at(height))?;
+
+        validator.pending_key = Some(PendingKey {
+            pubkey: rotation.new_pubkey,
+            activation_height: rotation.activation_height,
+        });
+        info!("🔑 Validator {} rotates consensus key at height {}", rotation.validator, rotation.activation_height);
+        Ok(())
+    }
+
+    /// Switch to every pending key due by `height`
+    /// Returns the validators whose key changed, with their new key
+    pub fn activate_key_rotations(&mut self, height: u64) -> Vec<(String, [u8; 32])> {
+        let mut activated = Vec::new();
+        for validator in self.validators.values_mut() {
+            let Some(pending) = validator.pending_key.filter(|p| height >= p.activation_height) else {
+                continue;
+            };
+            validator.retired_keys.push(RetiredKey {
+                pubkey: validator.pubkey,
+                until_height: pending.activation_height,
+            });
+            validator.pubkey = pending.pubkey;
+            validator.pending_key = None;
+            info!("🔑 Validator {} now signs with key {}...", validator.address, hex::encode(&pending.pubkey[..8]));
+            activated.push((validator.address.clone(), pending.pubkey));
+        }
+        activated
+    }
+
+    /// Rebuild key history from every rotation the chain carried, as
+    /// `(inclusion height, rotation)` in height order, up to the block at `tip`
+    ///
+    /// Same outcome as observing each block from genesis, without needing the
+    /// block bodies (see `PersistentStorage::get_key_rotations`).
+    pub fn replay_key_rotations(&mut self, rotations: &[(u64, KeyRotation)], tip: u64) {
+        for (height, rotation) in rotations {
+            self.activate_key_rotations(*height);
+            if let Err(e) = self.schedule_key_rotation(rotation, *height) {
+                warn!("Ignoring key rotation for {} in block {}: {}", rotation.validator, height, e);
+            }
+        }
+        self.activate_key_rotations(tip + 1);
+    }
+
+    /// Take in the next block: beacon entries and key rotations
+    /// Returns the validators whose key changed for the following height
+    pub fn observe_block(&mut self, block: &Block) -> Vec<(String, [u8; 32])> {
+        self.observe_beacon_entry(block);
+        for rotation in block.transactions.iter().filter_map(KeyRotation::from_transaction) {
+            if let Err(e) = self.schedule_key_rotation(&rotation, block.index) {
+                warn!("Ignoring key rotation for {} in block {}: {}", rotation.validator, block.index, e);
+            }
+        }
+        self.activate_key_rotations(block.index + 1)
+    }
+
+    /// Take in the beacon entry of the next block, checked against the
+    /// proposer's key for that height
+    pub fn observe_beacon_entry(&mut self, block: &Block) {
+        let proposer_key = self.get_validator_pubkey_at(&block.validator, block.index);
+        self.beacon.observe_block(block, proposer_key.as_ref());
+    }
+
+    /// Check the beacon entries of a block received from the network
+    ///
+    /// The block hash doesn't cover transaction contents, so a relay could
+    /// otherwise swap the reveal or commitment; each entry must be signed by
+    /// the proposer's consensus key for the block's height.
+    pub fn verify_beacon_entries(&self, block: &Block) -> Result<()> {
+        for tx in block.transactions.iter().filter(|tx| is_beacon_tx(tx)) {
+            if tx.from != block.validator {
+                bail!("Beacon entry from {} in block proposed by {}", tx.from, block.validator);
+            }
+            let Some(pubkey) = self.get_validator_pubkey_at(&block.validator, block.index) else {
+                bail!("Validator {} not found", block.validator);
+            };
+            BeaconEntry::verify_transaction(tx, block.index, &pubkey)?;
+        }
+        Ok(())
+    }
+
     /// Get all active validators
     pub fn get_active_validators(&self) -> Vec<&Validator> {
         self.validators
@@ -850,6 +1092,56 @@ mod tests {
         assert_eq!(proposer1, proposer2, "Proposer selection should be deterministic");
     }
 
+    #[test]
+    fn test_proposer_selection_follows_beacon() {
+        use crate::beacon::BEACON_EPOCH_BLOCKS;
+        use ed25519_dalek::SigningKey;
+
+        let start = BEACON_EPOCH_BLOCKS * 2;
+        let mut a = test_consensus();
+        for i in 0..4 {
+            a.add_validator(format!("validator{}", i), MIN_STAKE, TEST_PUBKEY).unwrap();
+        }
+        a.beacon_activation_height = Some(start);
+        let mut b = test_consensus();
+        b.validators = a.validators.clone();
+        b.beacon_activation_height = Some(start);
+
+        // Only `b` sees a chain with beacon reveals
+        let key = SigningKey::from_bytes(&[5u8; 32]);
+        b.validators.get_mut("validator0").unwrap().pubkey = key.verifying_key().to_bytes();
+        for height in 1..BEACON_EPOCH_BLOCKS * 2 {
+            let entry = b.beacon.entry_for("validator0", &key, height);
+            let block = crate::blockchain::Block {
+                index: height,
+                timestamp: height,
+                transactions: vec![entry.into_transaction("validator0", &key, height, height)],
+                prev_hash: String::new(),
+                hash: String::new(),
+                nonce: 0,
+                validator: "validator0".to_string(),
+                state_root: String::new(),
+            };
+            b.observe_beacon_entry(&block);
+        }
+
+        let order_a: Vec<_> = (start..start + 50).map(|h| a.select_proposer_for_height(h)).collect();
+        let order_b: Vec<_> = (start..start + 50).map(|h| b.select_proposer_for_height(h)).collect();
+        assert_ne!(order_a, order_b);
+
+        // Below the activation height the v2 seed ignores the beacon
+        let order_a: Vec<_> = (start - 50..start).map(|h| a.select_proposer_for_height(h)).collect();
+        let order_b: Vec<_> = (start - 50..start).map(|h| b.select_proposer_for_height(h)).collect();
+        assert_eq!(order_a, order_b);
+
+        // So does every height until the network sets one
+        a.beacon_activation_height = None;
+        b.beacon_activation_height = None;
+        let order_a: Vec<_> = (start..start + 50).map(|h| a.select_proposer_for_height(h)).collect();
+        let order_b: Vec<_> = (start..start + 50).map(|h| b.select_proposer_for_height(h)).collect();
+        assert_eq!(order_a, order_b);
+    }
+
     #[test]
     fn test_proposer_weighted_by_stake() {
         let mut consensus = test_consensus();
@@ -1038,10 +1330,107 @@ mod tests {
         assert_eq!(validator.missed_blocks, 50);
         
         // Sign a block - should reset counter
-        consensus.record_signature("validator1").unwrap();
+        consensus.record_signature("validator1", 150).unwrap();
         
         let validator = consensus.get_validator("validator1").unwrap();
         assert_eq!(validator.missed_blocks, 0, "Signing should reset missed blocks");
+        // ...but the window still remembers the misses
+        assert_eq!(validator.signed_window.missed_count(), 50);
+        assert_eq!(validator.signed_window.signed_count(), 1);
+    }
+
+    #[test]
+    fn test_intermittent_downtime_slashing() {
+        let mut consensus = test_consensus();
+        consensus.add_validator("validator1".to_string(), MIN_STAKE * 10, TEST_PUBKEY).unwrap();
+        
+        // Up half the time: the consecutive counter never gets past 1
+        let mut slashed = None;
+        for i in 0..MIN_WINDOW_DUTIES {
+            let height = 1000 + i;
+            if i % 2 == 0 {
+                consensus.record_signature("validator1", height).unwrap();
+            } else {
+                slashed = consensus.record_missed_block("validator1", height).unwrap();
+            }
+            assert!(consensus.get_validator("validator1").unwrap().missed_blocks <= 1);
+        }
+        
+        assert!(slashed.is_some(), "Signing half of the window's heights should slash");
+        let validator = consensus.get_validator("validator1").unwrap();
+        assert!(validator.is_jailed);
+        assert_eq!(validator.signed_window.duties(), 0, "Window resets on jailing");
+    }
+
+    #[test]
+    fn test_commit_records_every_active_validator() {
+        let mut consensus = test_consensus();
+        for name in ["a", "b", "c", "d"] {
+            consensus.add_validator(name.to_string(), MIN_STAKE * 10, TEST_PUBKEY).unwrap();
+        }
+        
+        // "c" votes on every other height, "d" never votes; neither ever proposes
+        let mut slashed = Vec::new();
+        for i in 0..MIN_WINDOW_DUTIES {
+            let height = 1000 + i;
+            let mut signers: HashSet<String> = ["a", "b"].iter().map(|s| s.to_string()).collect();
+            if i % 2 == 0 {
+                signers.insert("c".to_string());
+            }
+            slashed.extend(consensus.record_commit(height, &signers));
+            let a = &consensus.get_validator("a").unwrap().signed_window;
+            assert_eq!(a.duties(), i + 1, "one bit per height, not per proposer slot");
+        }
+        
+        let jailed: Vec<_> = slashed.iter().map(|(address, _)| address.as_str()).collect();
+        assert_eq!(jailed, vec!["c", "d"]);
+        assert!(consensus.get_validator("c").unwrap().is_jailed);
+        assert!(!consensus.get_validator("a").unwrap().is_jailed);
+        assert_eq!(consensus.get_validator("b").unwrap().signed_window.uptime_percent(), 100.0);
+        
+        // Jailed validators aren't judged, and the same height isn't recorded twice
+        let signers: HashSet<String> = ["a".to_string()].into();
+        consensus.record_commit(1000 + MIN_WINDOW_DUTIES, &signers);
+        consensus.record_commit(1000 + MIN_WINDOW_DUTIES, &HashSet::new());
+        let b = &consensus.get_validator("b").unwrap().signed_window;
+        assert_eq!((b.duties(), b.missed_count()), (MIN_WINDOW_DUTIES + 1, 1));
+        assert_eq!(consensus.get_validator("c").unwrap().signed_window.duties(), 0);
+    }
+    
+    #[test]
+    fn test_commit_signers_come_from_collected_votes() {
+        let mut consensus = test_consensus();
+        consensus.add_validator("a".to_string(), MIN_STAKE, TEST_PUBKEY).unwrap();
+        consensus.add_validator("b".to_string(), MIN_STAKE, TEST_PUBKEY).unwrap();
+        
+        consensus.collect_signature(7, [1u8; 32], "a", vec![1]).unwrap();
+        // A vote for another block at the same height isn't part of the commit
+        consensus.collect_signature(7, [2u8; 32], "b", vec![2]).unwrap();
+        let signers = consensus.commit_signers(7);
+        assert_eq!(signers, HashSet::from(["a".to_string()]));
+        
+        consensus.record_commit(7, &signers);
+        assert!(consensus.pending_signatures.is_empty(), "votes are dropped once recorded");
+        assert_eq!(consensus.get_validator("b").unwrap().signed_window.missed_count(), 1);
+    }
+    
+    #[test]
+    fn test_collect_vote_checks_network_signature() {
+        use ed25519_dalek::{SigningKey, Signer};
+        
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let mut consensus = ConsensusEngine::new(); // Verification enabled
+        consensus.add_validator("v1".to_string(), MIN_STAKE, key.verifying_key().to_bytes()).unwrap();
+        let block_hash = hex::encode([4u8; 32]);
+        
+        // Signed over the raw hash rather than its hex form: rejected
+        let raw = key.sign(&[4u8; 32]).to_bytes().to_vec();
+        assert!(consensus.collect_vote(9, &block_hash, "v1", raw).is_err());
+        assert!(consensus.collect_vote(9, "not-hex", "v1", vec![0; 64]).is_err());
+        
+        let vote = key.sign(block_hash.as_bytes()).to_bytes().to_vec();
+        consensus.collect_vote(9, &block_hash, "v1", vote).unwrap();
+        assert_eq!(consensus.commit_signers(9), HashSet::from(["v1".to_string()]));
     }
 
     #[test]
@@ -1142,6 +1531,146 @@ mod tests {
         assert!(new_consensus.get_validator("v2").is_some());
     }
 
+    #[test]
+    fn test_key_rotation_keeps_old_key_for_earlier_heights() {
+        use ed25519_dalek::{SigningKey, Signer};
+        use crate::key_rotation::KeyRotation;
+        
+        let old_key = SigningKey::from_bytes(&[1u8; 32]);
+        let new_key = SigningKey::from_bytes(&[2u8; 32]);
+        let old_pubkey = old_key.verifying_key().to_bytes();
+        let new_pubkey = new_key.verifying_key().to_bytes();
+        
+        let mut consensus = ConsensusEngine::new(); // Verification enabled
+        consensus.add_validator("v1".to_string(), MIN_STAKE, old_pubkey).unwrap();
+        
+        let block = |index: u64, transactions| crate::blockchain::Block {
+            index,
+            timestamp: index,
+            transactions,
+            prev_hash: String::new(),
+            hash: String::new(),
+            nonce: 0,
+            validator: "v1".to_string(),
+            state_root: String::new(),
+        };
+        
+        // Too soon: ignored
+        let early = KeyRotation::sign(&consensus.chain_id, "v1", &old_key, &new_key, 10 + MIN_KEY_ROTATION_DELAY - 1);
+        consensus.observe_block(&block(10, vec![early.into_transaction(0)]));
+        assert!(consensus.get_validator("v1").unwrap().pending_key.is_none());
+        
+        // Not signed by the current key: ignored
+        let forged = KeyRotation::sign(&consensus.chain_id, "v1", &new_key, &new_key, 500);
+        consensus.observe_block(&block(11, vec![forged.into_transaction(0)]));
+        assert!(consensus.get_validator("v1").unwrap().pending_key.is_none());
+        
+        let rotation = KeyRotation::sign(&consensus.chain_id, "v1", &old_key, &new_key, 500);
+        assert!(consensus.observe_block(&block(12, vec![rotation.into_transaction(0)])).is_empty());
+        assert_eq!(consensus.get_validator_pubkey("v1"), Some(old_pubkey));
+        assert_eq!(consensus.get_validator_pubkey_at("v1", 499), Some(old_pubkey));
+        assert_eq!(consensus.get_validator_pubkey_at("v1", 500), Some(new_pubkey));
+        
+        // The block before activation switches the current key
+        let activated = consensus.observe_block(&block(499, Vec::new()));
+        assert_eq!(activated, vec![("v1".to_string(), new_pubkey)]);
+        assert_eq!(consensus.get_validator_pubkey("v1"), Some(new_pubkey));
+        
+        // Signatures verify against the key for their height
+        let block_hash = [42u8; 32];
+        let old_sig = old_key.sign(&block_hash).to_bytes().to_vec();
+        let new_sig = new_key.sign(&block_hash).to_bytes().to_vec();
+        assert!(consensus.collect_signature(499, block_hash, "v1", old_sig.clone()).is_ok());
+        assert!(consensus.collect_signature(500, block_hash, "v1", old_sig).is_err());
+        assert!(consensus.collect_signature(500, block_hash, "v1", new_sig).is_ok());
+    }
+
+    #[test]
+    fn test_key_rotation_replay_matches_observed_chain() {
+        use ed25519_dalek::SigningKey;
+        use crate::key_rotation::KeyRotation;
+        
+        let keys: Vec<SigningKey> = (1..=3u8).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+        let chain_id = ConsensusEngine::new().chain_id;
+        let first = KeyRotation::sign(&chain_id, "v1", &keys[0], &keys[1], 200);
+        let second = KeyRotation::sign(&chain_id, "v1", &keys[1], &keys[2], 2_000);
+        let rotations = vec![(50, first), (1_500, second)];
+        
+        let mut replayed = ConsensusEngine::new();
+        replayed.add_validator("v1".to_string(), MIN_STAKE, keys[0].verifying_key().to_bytes()).unwrap();
+        let mut observed = ConsensusEngine::new();
+        observed.add_validator("v1".to_string(), MIN_STAKE, keys[0].verifying_key().to_bytes()).unwrap();
+        
+        replayed.replay_key_rotations(&rotations, 2_500);
+        for index in 1..=2_500 {
+            let transactions = rotations.iter()
+                .filter(|(height, _)| *height == index)
+                .map(|(_, rotation)| rotation.clone().into_transaction(0))
+                .collect();
+            observed.observe_block(&crate::blockchain::Block {
+                index,
+                timestamp: index,
+                transactions,
+                prev_hash: String::new(),
+                hash: String::new(),
+                nonce: 0,
+                validator: "v1".to_string(),
+                state_root: String::new(),
+            });
+        }
+        
+        assert_eq!(replayed.get_validator("v1"), observed.get_validator("v1"));
+        assert_eq!(replayed.get_validator_pubkey_at("v1", 199), Some(keys[0].verifying_key().to_bytes()));
+        assert_eq!(replayed.get_validator_pubkey_at("v1", 1_999), Some(keys[1].verifying_key().to_bytes()));
+        assert_eq!(replayed.get_validator_pubkey("v1"), Some(keys[2].verifying_key().to_bytes()));
+    }
+
+    #[test]
+    fn test_tampered_beacon_reveal_is_rejected() {
+        use ed25519_dalek::SigningKey;
+        
+        let key = SigningKey::from_bytes(&[5u8; 32]);
+        let mut consensus = ConsensusEngine::new();
+        consensus.add_validator("v1".to_string(), MIN_STAKE, key.verifying_key().to_bytes()).unwrap();
+        let block = |index: u64, transactions| crate::blockchain::Block {
+            index,
+            timestamp: index,
+            transactions,
+            prev_hash: String::new(),
+            hash: String::new(),
+            nonce: 0,
+            validator: "v1".to_string(),
+            state_root: String::new(),
+        };
+        
+        let first = block(1, vec![consensus.beacon.entry_for("v1", &key, 1).into_transaction("v1", &key, 1, 1)]);
+        consensus.verify_beacon_entries(&first).unwrap();
+        consensus.observe_block(&first);
+        
+        // A relay replaces the reveal in the proposer's signed entry
+        let honest = block(2, vec![consensus.beacon.entry_for("v1", &key, 2).into_transaction("v1", &key, 2, 2)]);
+        consensus.verify_beacon_entries(&honest).unwrap();
+        let mut relayed = honest.clone();
+        let mut swapped = consensus.beacon.entry_for("v1", &key, 2);
+        swapped.reveal = Some([9u8; 32]);
+        relayed.transactions[0].memo = swapped.into_transaction("v1", &key, 2, 2).memo;
+        assert!(consensus.verify_beacon_entries(&relayed).is_err());
+        
+        // A re-signed entry doesn't verify against the registered key either
+        let mallory = SigningKey::from_bytes(&[6u8; 32]);
+        let resigned = block(2, vec![consensus.beacon.entry_for("v1", &mallory, 2).into_transaction("v1", &mallory, 2, 2)]);
+        assert!(consensus.verify_beacon_entries(&resigned).is_err());
+        
+        // Observed anyway (e.g. from storage), the tampered entry leaves the beacon alone
+        let mut observed = ConsensusEngine::new();
+        observed.validators = consensus.validators.clone();
+        observed.observe_block(&first);
+        observed.observe_block(&relayed);
+        consensus.observe_block(&honest);
+        let next = |c: &ConsensusEngine| c.beacon.entry_for("v1", &key, 3).reveal;
+        assert_ne!(next(&observed), next(&consensus));
+    }
+    
     #[test]
     fn test_ed25519_verification_rejects_invalid() {
         use ed25519_dalek::{SigningKey, Signer};
@@ -0,0 +1,384 @@
+//! Typed module events
+//!
+//! Staking, tokens, the DEX, governance and the bridge each record a
+//! `ModuleEvent` in their own `EventBuffer` as they execute. At block commit the
+//! node drains every buffer with `collect_block_events` and stores the events
+//! under that height, tagged with the transaction hash they ran under when there
+//! is one (see `in_tx`). Indexers read them back through `/events` instead of
+//! scraping logs.
+
+use crate::governance::{ProposalStatus, VoteOption};
+use crate::staking::SlashReason;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Events a buffer holds before the oldest are dropped (nothing is committing)
+const MAX_PENDING: usize = 100_000;
+
+/// Emission order across all buffers
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(0);
+
+tokio::task_local! {
+    static CURRENT_TX: String;
+}
+
+/// Run `f` with every event it emits tagged with `tx_hash`
+pub async fn in_tx<F: Future>(tx_hash: String, f: F) -> F::Output {
+    CURRENT_TX.scope(tx_hash, f).await
+}
+
+/// An event emitted by a module
+///
+/// Token and DEX amounts are u128 and sent as decimal strings, like the REST routes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ModuleEvent {
+    // Staking
+    ValidatorCreated {
+        validator: String,
+        stake: u64,
+        commission_rate: f64,
+    },
+    Delegated {
+        delegator: String,
+        validator: String,
+        amount: u64,
+    },
+    Undelegated {
+        delegator: String,
+        validator: String,
+        amount: u64,
+        completion_height: u64,
+    },
+    /// Validator commission (`validator` == `address`) or delegator rewards
+    RewardsWithdrawn {
+        address: String,
+        validator: String,
+        amount: u64,
+        recipient: String,
+    },
+    ValidatorSlashed {
+        validator: String,
+        reason: SlashReason,
+        amount: u64,
+        jailed_until: u64,
+    },
+    ValidatorUnjailed {
+        validator: String,
+    },
+
+    // Tokens
+    TokenCreated {
+        denom: String,
+        creator: String,
+        initial_supply: String,
+    },
+    TokenMinted {
+        denom: String,
+        recipient: String,
+        amount: String,
+    },
+    TokenTransferred {
+        denom: String,
+        from: String,
+        to: String,
+        amount: String,
+    },
+    TokenBurned {
+        denom: String,
+        from: String,
+        amount: String,
+    },
+
+    // DEX
+    PairCreated {
+        pair_id: String,
+        creator: String,
+        token_a: String,
+        token_b: String,
+        amount_a: String,
+        amount_b: String,
+    },
+    Swapped {
+        pair_id: String,
+        trader: String,
+        token_in: String,
+        amount_in: String,
+        token_out: String,
+        amount_out: String,
+    },
+    LiquidityAdded {
+        pair_id: String,
+        provider: String,
+        amount_a: String,
+        amount_b: String,
+        lp_tokens: String,
+    },
+    LiquidityRemoved {
+        pair_id: String,
+        provider: String,
+        amount_a: String,
+        amount_b: String,
+        lp_tokens: String,
+    },
+
+    // Governance
+    ProposalSubmitted {
+        proposal_id: u64,
+        proposer: String,
+        title: String,
+    },
+    VoteCast {
+        proposal_id: u64,
+        voter: String,
+        option: VoteOption,
+        voting_power: u64,
+    },
+    ProposalStatusChanged {
+        proposal_id: u64,
+        status: ProposalStatus,
+    },
+
+    // Bridge
+    BridgeCompleted {
+        id: String,
+        source_chain: String,
+        dest_chain: String,
+        recipient: String,
+        amount: u64,
+        wrapped_token: String,
+    },
+    BridgeFailed {
+        id: String,
+        source_chain: String,
+        recipient: String,
+        reason: String,
+    },
+}
+
+impl ModuleEvent {
+    /// Module that emitted the event
+    pub fn module(&self) -> &'static str {
+        use ModuleEvent::*;
+        match self {
+            ValidatorCreated { .. } | Delegated { .. } | Undelegated { .. }
+            | RewardsWithdrawn { .. } | ValidatorSlashed { .. } | ValidatorUnjailed { .. } => "staking",
+            TokenCreated { .. } | TokenMinted { .. } | TokenTransferred { .. } | TokenBurned { .. } => "tokens",
+            PairCreated { .. } | Swapped { .. } | LiquidityAdded { .. } | LiquidityRemoved { .. } => "dex",
+            ProposalSubmitted { .. } | VoteCast { .. } | ProposalStatusChanged { .. } => "governance",
+            BridgeCompleted { .. } | BridgeFailed { .. } => "bridge",
+        }
+    }
+
+    /// Name of the event, the `type` field on the wire
+    pub fn event_type(&self) -> String {
+        self.to_value()["type"].as_str().unwrap_or_default().to_string()
+    }
+
+    /// Value of attribute `key` as a string, if the event has it
+    pub fn attribute(&self, key: &str) -> Option<String> {
+        match self.to_value().get(key)? {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Null => None,
+            other => Some(other.to_string()),
+        }
+    }
+
+    fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+/// An event as stored for a block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct StoredEvent {
+    pub height: u64,
+    /// Position within the block
+    pub index: u32,
+    /// Transaction the event ran under, if any
+    pub tx_hash: Option<String>,
+    pub module: String,
+    #[serde(flatten)]
+    pub event: ModuleEvent,
+}
+
+struct Pending {
+    seq: u64,
+    tx_hash: Option<String>,
+    event: ModuleEvent,
+}
+
+/// Events a module emitted since the last block commit
+#[derive(Default)]
+pub struct EventBuffer {
+    pending: Mutex<VecDeque<Pending>>,
+}
+
+impl EventBuffer {
+    /// Record an event, tagged with the current transaction if there is one
+    pub fn emit(&self, event: ModuleEvent) {
+        let pending = Pending {
+            seq: NEXT_SEQ.fetch_add(1, Ordering::Relaxed),
+            tx_hash: CURRENT_TX.try_with(|hash| hash.clone()).ok(),
+            event,
+        };
+        let mut buffer = self.pending.lock();
+        if buffer.len() >= MAX_PENDING {
+            buffer.pop_front();
+        }
+        buffer.push_back(pending);
+    }
+
+    /// Number of events waiting for the next commit
+    pub fn len(&self) -> usize {
+        self.pending.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn drain(&self) -> VecDeque<Pending> {
+        std::mem::take(&mut *self.pending.lock())
+    }
+}
+
+/// Drain `buffers` into the events of block `height`, in emission order
+pub fn collect_block_events(height: u64, buffers: &[&EventBuffer]) -> Vec<StoredEvent> {
+    let mut pending: Vec<Pending> = buffers.iter().flat_map(|b| b.drain()).collect();
+    pending.sort_by_key(|p| p.seq);
+    pending.into_iter()
+        .enumerate()
+        .map(|(index, p)| StoredEvent {
+            height,
+            index: index as u32,
+            tx_hash: p.tx_hash,
+            module: p.event.module().to_string(),
+            event: p.event,
+        })
+        .collect()
+}
+
+/// Which events an `/events` query returns
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    pub event_type: Option<String>,
+    pub module: Option<String>,
+    /// `(key, value)` an event attribute must equal
+    pub attribute: Option<(String, String)>,
+    pub tx_hash: Option<String>,
+}
+
+impl EventFilter {
+    /// Parse an `attr=key:value` query parameter
+    pub fn parse_attribute(attr: &str) -> Option<(String, String)> {
+        let (key, value) = attr.split_once(':')?;
+        if key.is_empty() {
+            return None;
+        }
+        Some((key.to_string(), value.to_string()))
+    }
+
+    pub fn matches(&self, stored: &StoredEvent) -> bool {
+        if self.module.as_ref().is_some_and(|m| *m != stored.module) {
+            return false;
+        }
+        if self.tx_hash.is_some() && self.tx_hash != stored.tx_hash {
+            return false;
+        }
+        if self.event_type.as_ref().is_some_and(|t| *t != stored.event.event_type()) {
+            return false;
+        }
+        match &self.attribute {
+            Some((key, value)) => stored.event.attribute(key).as_ref() == Some(value),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delegated(delegator: &str) -> ModuleEvent {
+        ModuleEvent::Delegated {
+            delegator: delegator.to_string(),
+            validator: "validator1".to_string(),
+            amount: 5,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_collect_orders_and_tags_events() {
+        let staking = EventBuffer::default();
+        let tokens = EventBuffer::default();
+
+        staking.emit(delegated("alice"));
+        in_tx("tx1".to_string(), async {
+            tokens.emit(ModuleEvent::TokenBurned {
+                denom: "factory/alice/xyz".to_string(),
+                from: "alice".to_string(),
+                amount: "7".to_string(),
+            });
+        }).await;
+        staking.emit(delegated("bob"));
+
+        let events = collect_block_events(9, &[&staking, &tokens]);
+        assert_eq!(events.iter().map(|e| e.index).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(events[0].event, delegated("alice"));
+        assert_eq!(events[1].module, "tokens");
+        assert_eq!(events[1].tx_hash.as_deref(), Some("tx1"));
+        assert_eq!(events[2].tx_hash, None);
+        assert!(events.iter().all(|e| e.height == 9));
+
+        // Buffers are empty after a commit
+        assert!(staking.is_empty() && tokens.is_empty());
+        assert!(collect_block_events(10, &[&staking, &tokens]).is_empty());
+    }
+
+    #[test]
+    fn test_filter_by_type_module_and_attribute() {
+        let stored = StoredEvent {
+            height: 1,
+            index: 0,
+            tx_hash: Some("tx1".to_string()),
+            module: "governance".to_string(),
+            event: ModuleEvent::ProposalStatusChanged { proposal_id: 3, status: ProposalStatus::Passed },
+        };
+
+        assert!(EventFilter::default().matches(&stored));
+        let by_type = EventFilter { event_type: Some("proposal_status_changed".to_string()), ..Default::default() };
+        assert!(by_type.matches(&stored));
+        let by_module = EventFilter { module: Some("staking".to_string()), ..Default::default() };
+        assert!(!by_module.matches(&stored));
+
+        let attr = |a: &str| EventFilter { attribute: EventFilter::parse_attribute(a), ..Default::default() };
+        assert!(attr("proposal_id:3").matches(&stored));
+        assert!(attr("status:Passed").matches(&stored));
+        assert!(!attr("status:Rejected").matches(&stored));
+        assert!(!attr("voter:alice").matches(&stored));
+        assert_eq!(EventFilter::parse_attribute("no_separator"), None);
+        assert_eq!(EventFilter::parse_attribute(":x"), None);
+    }
+
+    #[test]
+    fn test_stored_event_wire_format() {
+        let stored = StoredEvent {
+            height: 4,
+            index: 2,
+            tx_hash: None,
+            module: "staking".to_string(),
+            event: delegated("alice"),
+        };
+        let json = serde_json::to_value(&stored).unwrap();
+        assert_eq!(json["type"], "delegated");
+        assert_eq!(json["delegator"], "alice");
+        assert_eq!(json["module"], "staking");
+        let back: StoredEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(back, stored);
+    }
+}
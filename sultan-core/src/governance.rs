@@ -17,10 +17,12 @@
 
 use anyhow::{Result, Context, bail};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{info, warn};
+use crate::events::{EventBuffer, ModuleEvent};
 
 const PROPOSAL_DEPOSIT: u64 = 1_000_000_000_000; // 1,000 SLTN
 const VOTING_PERIOD_BLOCKS: u64 = 302_400; // ~7 days with 2s blocks (7*24*60*60/2)
@@ -85,7 +87,7 @@ fn validate_address(address: &str) -> Result<()> {
     Ok(())
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub enum ProposalType {
     ParameterChange,
     SoftwareUpgrade,
@@ -98,7 +100,7 @@ pub enum ProposalType {
     SlashingProposal,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub enum ProposalStatus {
     /// Proposal submitted, awaiting discussion period
     DiscussionPeriod,
@@ -112,7 +114,7 @@ pub enum ProposalStatus {
     EmergencyPaused,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub enum VoteOption {
     Yes,
     No,
@@ -120,7 +122,7 @@ pub enum VoteOption {
     NoWithVeto, // Veto if >33.4% of votes
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Proposal {
     pub id: u64,
     pub proposer: String,
@@ -152,14 +154,14 @@ pub struct Proposal {
 }
 
 /// Validator signature for critical proposals (upgrades)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ValidatorSignature {
     pub validator_address: String,
     pub signature: String,
     pub signed_at: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Deposit {
     pub depositor: String,
     pub amount: u64,
@@ -175,7 +177,7 @@ pub struct Vote {
     pub time: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TallyResult {
     pub yes: u64,
     pub no: u64,
@@ -198,6 +200,7 @@ pub struct GovernanceManager {
     last_proposal_by_address: Arc<RwLock<HashMap<String, u64>>>,
     /// Track staking snapshots for voting power verification
     staking_snapshots: Arc<RwLock<HashMap<u64, HashMap<String, u64>>>>,
+    events: EventBuffer,
 }
 
 impl GovernanceManager {
@@ -210,9 +213,22 @@ impl GovernanceManager {
             total_bonded_tokens: Arc::new(RwLock::new(0)),
             last_proposal_by_address: Arc::new(RwLock::new(HashMap::new())),
             staking_snapshots: Arc::new(RwLock::new(HashMap::new())),
+            events: EventBuffer::default(),
         }
     }
 
+    /// Events emitted since the last block commit
+    pub fn events(&self) -> &EventBuffer {
+        &self.events
+    }
+
+    fn emit_status(&self, proposal: &Proposal) {
+        self.events.emit(ModuleEvent::ProposalStatusChanged {
+            proposal_id: proposal.id,
+            status: proposal.status.clone(),
+        });
+    }
+
     /// Validate parameter bounds for ParameterChange proposals
     /// Prevents extreme/malicious parameter changes
     fn validate_parameter_bounds(params: &HashMap<String, String>) -> Result<()> {
@@ -399,12 +415,14 @@ impl GovernanceManager {
             "Proposal #{} submitted by {}: '{}' (Discussion until block {}, Voting until block {})",
             proposal_id, proposer, title, discussion_end, voting_end
         );
+        self.events.emit(ModuleEvent::ProposalSubmitted { proposal_id, proposer, title });
 
         Ok(proposal_id)
     }
     
     /// Submit a proposal with auto-persist to storage
     /// Use this when storage is available for automatic durability
+    #[cfg(feature = "node")]
     pub async fn submit_proposal_with_storage(
         &self,
         proposer: String,
@@ -521,11 +539,18 @@ impl GovernanceManager {
             "Vote cast on proposal #{}: {} voted {:?} with {} power (verified)",
             proposal_id, voter, option, verified_voting_power
         );
+        self.events.emit(ModuleEvent::VoteCast {
+            proposal_id,
+            voter,
+            option,
+            voting_power: verified_voting_power,
+        });
 
         Ok(())
     }
     
     /// Cast a vote with auto-persist to storage
+    #[cfg(feature = "node")]
     pub async fn vote_with_storage(
         &self,
         proposal_id: u64,
@@ -598,6 +623,7 @@ impl GovernanceManager {
         // Auto-execute if threshold reached
         if sig_count >= UPGRADE_MULTISIG_THRESHOLD {
             proposal.status = ProposalStatus::Executed;
+            self.emit_status(proposal);
             info!("Upgrade proposal #{} has enough signatures - marked for execution", proposal_id);
         }
 
@@ -605,6 +631,7 @@ impl GovernanceManager {
     }
     
     /// Sign upgrade proposal with auto-persist to storage
+    #[cfg(feature = "node")]
     pub async fn sign_upgrade_proposal_with_storage(
         &self,
         proposal_id: u64,
@@ -658,6 +685,7 @@ impl GovernanceManager {
         // Check if threshold reached
         if pause_votes >= threshold {
             proposal.status = ProposalStatus::EmergencyPaused;
+            self.emit_status(proposal);
             warn!(
                 "🚨 EMERGENCY PAUSE: Proposal #{} has been paused by validator consensus",
                 proposal_id
@@ -669,6 +697,7 @@ impl GovernanceManager {
     }
     
     /// Emergency pause vote with auto-persist to storage
+    #[cfg(feature = "node")]
     pub async fn emergency_pause_vote_with_storage(
         &self,
         proposal_id: u64,
@@ -695,6 +724,7 @@ impl GovernanceManager {
             if proposal.status == ProposalStatus::DiscussionPeriod 
                && current_height >= proposal.discussion_end_height {
                 proposal.status = ProposalStatus::VotingPeriod;
+                self.emit_status(proposal);
                 info!(
                     "Proposal #{} entering voting period (discussion complete)",
                     proposal.id
@@ -780,6 +810,7 @@ impl GovernanceManager {
         } else {
             ProposalStatus::Rejected
         };
+        self.emit_status(proposal);
 
         info!(
             "Proposal #{} tallied: {} (Yes: {}, No: {}, Abstain: {}, Veto: {})",
@@ -800,6 +831,7 @@ impl GovernanceManager {
     }
     
     /// Tally votes with auto-persist to storage
+    #[cfg(feature = "node")]
     pub async fn tally_proposal_with_storage(
         &self, 
         proposal_id: u64,
@@ -957,6 +989,7 @@ impl GovernanceManager {
         }
 
         proposal.status = ProposalStatus::Executed;
+        self.emit_status(proposal);
 
         info!("✅ Proposal #{} executed successfully", proposal_id);
 
@@ -999,6 +1032,7 @@ impl GovernanceManager {
     
     /// Save governance state to persistent storage
     /// Call this after every governance operation
+    #[cfg(feature = "node")]
     pub async fn persist_to_storage(&self, storage: &crate::storage::PersistentStorage) -> Result<()> {
         // Save all proposals
         let proposals = self.proposals.read().await;
@@ -1042,6 +1076,7 @@ impl GovernanceManager {
     
     /// Restore governance state from persistent storage
     /// Called during node startup if state exists
+    #[cfg(feature = "node")]
     pub async fn restore_from_storage(&self, storage: &crate::storage::PersistentStorage) -> Result<()> {
         // Load governance state
         if let Some(state) = storage.load_governance_state()? {
@@ -1123,7 +1158,7 @@ impl GovernanceManager {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GovernanceStatistics {
     pub total_proposals: usize,
     pub active_proposals: usize,
@@ -1710,6 +1745,7 @@ mod tests {
         assert_eq!(proposal.status, ProposalStatus::Executed);
     }
     
+    #[cfg(feature = "node")]
     #[tokio::test]
     async fn test_governance_persistence_roundtrip() {
         use tempfile::tempdir;
@@ -1760,6 +1796,7 @@ mod tests {
         assert_eq!(stats.total_proposals, 1);
     }
     
+    #[cfg(feature = "node")]
     #[tokio::test]
     async fn test_e2e_full_restart_recovery() {
         // E2E test: Staking + Governance persist and recover after simulated restart
@@ -1870,6 +1907,7 @@ mod tests {
         assert_eq!(final_proposal.status, ProposalStatus::Executed);
     }
     
+    #[cfg(feature = "node")]
     #[tokio::test]
     async fn test_auto_persist_methods() {
         use tempfile::tempdir;
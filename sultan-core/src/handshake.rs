@@ -0,0 +1,472 @@
+//! Chain handshake for P2P connections
+//!
+//! Both ends of every connection open a `/sultan/handshake/1.0.0` stream and send
+//! their chain ID, genesis hash, protocol version and height. Peers on another
+//! chain, running an incompatible protocol version or not speaking the protocol
+//! at all are reported so the P2P loop can drop them before processing their gossip.
+
+use crate::blockchain::Block;
+use anyhow::{bail, Context as _, Result};
+use futures::future::BoxFuture;
+use futures::{AsyncReadExt, AsyncWriteExt, FutureExt};
+use libp2p::core::upgrade::ReadyUpgrade;
+use libp2p::core::Endpoint;
+use libp2p::swarm::handler::{
+    ConnectionEvent, DialUpgradeError, FullyNegotiatedInbound, FullyNegotiatedOutbound,
+};
+use libp2p::swarm::{
+    ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, SubstreamProtocol, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p::{Multiaddr, PeerId, Stream, StreamProtocol};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// Stream protocol used for the handshake
+pub const HANDSHAKE_PROTOCOL: StreamProtocol = StreamProtocol::new("/sultan/handshake/1.0.0");
+
+/// Wire protocol version; peers must agree on the major version
+///
+/// Minor versions only add features, which are negotiated per peer from the
+/// version it sent. 1.0 nodes check nothing but the major version, so they
+/// still accept us and get the 1.0 wire format.
+pub const PROTOCOL_VERSION: &str = "1.1.0";
+
+/// Upper bound on an encoded handshake
+const MAX_HANDSHAKE_SIZE: usize = 4096;
+
+/// Time a peer has to complete the handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What each side of a connection tells the other
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandshakeInfo {
+    pub chain_id: String,
+    pub genesis_hash: String,
+    pub protocol_version: String,
+    pub height: u64,
+}
+
+impl HandshakeInfo {
+    /// Check a remote handshake against ours
+    pub fn check_compatible(&self, remote: &HandshakeInfo) -> Result<()> {
+        if remote.chain_id != self.chain_id {
+            bail!("chain ID mismatch (ours {}, theirs {})", self.chain_id, remote.chain_id);
+        }
+        if remote.genesis_hash != self.genesis_hash {
+            bail!("genesis hash mismatch (ours {}, theirs {})", self.genesis_hash, remote.genesis_hash);
+        }
+        if major_version(&remote.protocol_version) != major_version(&self.protocol_version) {
+            bail!(
+                "incompatible protocol version (ours {}, theirs {})",
+                self.protocol_version,
+                remote.protocol_version
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether the peer fetches announced transactions over `tx_fetch` (1.1 and up)
+    pub fn supports_tx_fetch(&self) -> bool {
+        self.version_at_least(1, 1)
+    }
+
+    /// Whether the peer rebuilds compact block proposals (1.1 and up)
+    pub fn supports_compact_blocks(&self) -> bool {
+        self.version_at_least(1, 1)
+    }
+
+    /// Whether the version this peer sent is at least `major.minor`
+    pub fn version_at_least(&self, major: u64, minor: u64) -> bool {
+        let mut parts = self.protocol_version.split('.').map(|p| p.parse::<u64>().unwrap_or(0));
+        let remote = (parts.next().unwrap_or(0), parts.next().unwrap_or(0));
+        remote >= (major, minor)
+    }
+}
+
+/// Fingerprint of the genesis block, compared during the handshake
+pub fn genesis_hash(genesis: &Block) -> String {
+    let data = bincode::serialize(genesis).unwrap_or_default();
+    hex::encode(Sha256::digest(&data))
+}
+
+fn major_version(version: &str) -> &str {
+    version.split('.').next().unwrap_or(version)
+}
+
+/// Outcome of a handshake with a peer
+#[derive(Debug)]
+pub enum Event {
+    /// The peer is on our chain and speaks a compatible protocol
+    Verified { peer_id: PeerId, info: HandshakeInfo },
+    /// The peer is on another chain or protocol version
+    Mismatch { peer_id: PeerId, info: HandshakeInfo, reason: String },
+    /// The peer didn't complete the handshake (unsupported protocol, bad message or timeout);
+    /// unlike a mismatch this says nothing about which chain it's on
+    Failed { peer_id: PeerId, error: String },
+}
+
+/// Handshake behaviour; the advertised height is read from a shared counter
+pub struct Behaviour {
+    chain_id: String,
+    genesis_hash: String,
+    height: Arc<AtomicU64>,
+    events: VecDeque<Event>,
+}
+
+impl Behaviour {
+    pub fn new(chain_id: String, genesis_hash: String, height: Arc<AtomicU64>) -> Self {
+        Self { chain_id, genesis_hash, height, events: VecDeque::new() }
+    }
+
+    /// Our handshake as of now
+    pub fn local_info(&self) -> HandshakeInfo {
+        HandshakeInfo {
+            chain_id: self.chain_id.clone(),
+            genesis_hash: self.genesis_hash.clone(),
+            protocol_version: PROTOCOL_VERSION.to_string(),
+            height: self.height.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = Handler;
+    type ToSwarm = Event;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        _peer: PeerId,
+        _local_addr: &Multiaddr,
+        _remote_addr: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(self.local_info()))
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        _peer: PeerId,
+        _addr: &Multiaddr,
+        _role_override: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(self.local_info()))
+    }
+
+    fn on_swarm_event(&mut self, _event: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer_id: PeerId,
+        _connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        let event = match event {
+            HandlerEvent::Received(info) => match self.local_info().check_compatible(&info) {
+                Ok(()) => Event::Verified { peer_id, info },
+                Err(e) => Event::Mismatch { peer_id, info, reason: e.to_string() },
+            },
+            HandlerEvent::Failed(error) => Event::Failed { peer_id, error },
+        };
+        self.events.push_back(event);
+    }
+
+    fn poll(&mut self, _cx: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        match self.events.pop_front() {
+            Some(event) => Poll::Ready(ToSwarm::GenerateEvent(event)),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// What a connection handler reports to the behaviour
+#[derive(Debug)]
+pub enum HandlerEvent {
+    Received(HandshakeInfo),
+    Failed(String),
+}
+
+/// Per-connection handler: sends our handshake on an outbound stream and reads theirs from an inbound one
+pub struct Handler {
+    local: HandshakeInfo,
+    outbound_requested: bool,
+    sending: Option<BoxFuture<'static, Result<()>>>,
+    receiving: Option<BoxFuture<'static, Result<HandshakeInfo>>>,
+    failure: Option<String>,
+    deadline: Pin<Box<tokio::time::Sleep>>,
+    /// Our handshake has been written (or the stream failed)
+    sent: bool,
+    /// Theirs has been received (or the handshake failed)
+    done: bool,
+}
+
+impl Handler {
+    fn new(local: HandshakeInfo) -> Self {
+        Self {
+            local,
+            outbound_requested: false,
+            sending: None,
+            receiving: None,
+            failure: None,
+            deadline: Box::pin(tokio::time::sleep(HANDSHAKE_TIMEOUT)),
+            sent: false,
+            done: false,
+        }
+    }
+
+    fn substream_protocol() -> SubstreamProtocol<ReadyUpgrade<StreamProtocol>, ()> {
+        SubstreamProtocol::new(ReadyUpgrade::new(HANDSHAKE_PROTOCOL), ()).with_timeout(HANDSHAKE_TIMEOUT)
+    }
+}
+
+impl ConnectionHandler for Handler {
+    type FromBehaviour = Infallible;
+    type ToBehaviour = HandlerEvent;
+    type InboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type OutboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = ();
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        Self::substream_protocol()
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        !self.done || !self.sent
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>> {
+        if !self.outbound_requested {
+            self.outbound_requested = true;
+            return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                protocol: Self::substream_protocol(),
+            });
+        }
+
+        if let Some(sending) = self.sending.as_mut() {
+            if let Poll::Ready(result) = sending.poll_unpin(cx) {
+                self.sending = None;
+                self.sent = true;
+                if let Err(e) = result {
+                    tracing::debug!("Failed to send handshake: {}", e);
+                }
+            }
+        }
+
+        if self.done {
+            return Poll::Pending;
+        }
+
+        if let Some(error) = self.failure.take() {
+            self.done = true;
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(HandlerEvent::Failed(error)));
+        }
+
+        if let Some(receiving) = self.receiving.as_mut() {
+            if let Poll::Ready(result) = receiving.poll_unpin(cx) {
+                self.receiving = None;
+                self.done = true;
+                let event = match result {
+                    Ok(info) => HandlerEvent::Received(info),
+                    Err(e) => HandlerEvent::Failed(format!("{:#}", e)),
+                };
+                return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(event));
+            }
+        }
+
+        if self.deadline.poll_unpin(cx).is_ready() {
+            self.done = true;
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(HandlerEvent::Failed(
+                "handshake timed out".to_string(),
+            )));
+        }
+
+        Poll::Pending
+    }
+
+    fn on_behaviour_event(&mut self, event: Self::FromBehaviour) {
+        match event {}
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        match event {
+            // Only the first handshake on a connection counts
+            ConnectionEvent::FullyNegotiatedInbound(FullyNegotiatedInbound { protocol: stream, .. })
+                if self.receiving.is_none() && !self.done =>
+            {
+                self.receiving = Some(read_handshake(stream).boxed());
+            }
+            ConnectionEvent::FullyNegotiatedOutbound(FullyNegotiatedOutbound { protocol: stream, .. }) => {
+                self.sending = Some(write_handshake(stream, self.local.clone()).boxed());
+            }
+            ConnectionEvent::DialUpgradeError(DialUpgradeError { error, .. }) => {
+                self.sent = true;
+                if !self.done {
+                    self.failure = Some(format!("handshake stream failed: {}", error));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Write a length-prefixed handshake and close the stream
+async fn write_handshake(mut stream: Stream, info: HandshakeInfo) -> Result<()> {
+    tokio::time::timeout(HANDSHAKE_TIMEOUT, async {
+        write_frame(&mut stream, &info).await?;
+        stream.close().await?;
+        Ok::<_, anyhow::Error>(())
+    })
+    .await
+    .context("timed out sending handshake")?
+}
+
+/// Read a length-prefixed handshake
+async fn read_handshake(mut stream: Stream) -> Result<HandshakeInfo> {
+    tokio::time::timeout(HANDSHAKE_TIMEOUT, read_frame(&mut stream, MAX_HANDSHAKE_SIZE))
+        .await
+        .context("timed out waiting for handshake")?
+        .context("bad handshake")
+}
+
+/// Write a bincode value with a u32 big-endian length prefix
+pub(crate) async fn write_frame<T: Serialize>(stream: &mut Stream, value: &T) -> Result<()> {
+    let data = bincode::serialize(value)?;
+    stream.write_all(&(data.len() as u32).to_be_bytes()).await?;
+    stream.write_all(&data).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+/// Read a length-prefixed bincode value of at most `max_size` bytes
+pub(crate) async fn read_frame<T: DeserializeOwned>(stream: &mut Stream, max_size: usize) -> Result<T> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len).await.context("failed to read frame length")?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > max_size {
+        bail!("frame of {} bytes exceeds maximum {}", len, max_size);
+    }
+    let mut data = vec![0u8; len];
+    stream.read_exact(&mut data).await.context("failed to read frame")?;
+    bincode::deserialize(&data).context("undecodable frame")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use libp2p::swarm::SwarmEvent;
+    use libp2p::{noise, tcp, yamux, Swarm};
+
+    fn info(chain_id: &str, version: &str) -> HandshakeInfo {
+        HandshakeInfo {
+            chain_id: chain_id.to_string(),
+            genesis_hash: "abc".to_string(),
+            protocol_version: version.to_string(),
+            height: 10,
+        }
+    }
+
+    #[test]
+    fn test_check_compatible() {
+        let ours = info("sultan-mainnet-1", "1.0.0");
+        assert!(ours.check_compatible(&info("sultan-mainnet-1", "1.2.0")).is_ok());
+        assert!(ours.check_compatible(&info("sultan-testnet-1", "1.0.0")).is_err());
+        assert!(ours.check_compatible(&info("sultan-mainnet-1", "2.0.0")).is_err());
+
+        let mut other_genesis = info("sultan-mainnet-1", "1.0.0");
+        other_genesis.genesis_hash = "def".to_string();
+        assert!(ours.check_compatible(&other_genesis).is_err());
+    }
+
+    #[test]
+    fn test_accepts_1_0_peers() {
+        let ours = info("sultan-mainnet-1", PROTOCOL_VERSION);
+        let old = info("sultan-mainnet-1", "1.0.0");
+        assert!(ours.check_compatible(&old).is_ok());
+        // ...and they accept us, since they only compare the major version
+        assert!(old.check_compatible(&ours).is_ok());
+    }
+
+    #[test]
+    fn test_version_at_least() {
+        assert!(info("sultan-mainnet-1", "1.1.0").version_at_least(1, 1));
+        assert!(info("sultan-mainnet-1", "1.2.3").version_at_least(1, 1));
+        assert!(info("sultan-mainnet-1", "2.0.0").version_at_least(1, 1));
+        assert!(!info("sultan-mainnet-1", "1.0.0").version_at_least(1, 1));
+        assert!(!info("sultan-mainnet-1", "garbage").version_at_least(1, 1));
+        assert!(!info("sultan-mainnet-1", "1.0.0").supports_tx_fetch());
+        assert!(info("sultan-mainnet-1", PROTOCOL_VERSION).supports_tx_fetch());
+        assert!(!info("sultan-mainnet-1", "1.0.0").supports_compact_blocks());
+        assert!(info("sultan-mainnet-1", PROTOCOL_VERSION).supports_compact_blocks());
+    }
+
+    fn swarm(chain_id: &str, height: u64) -> Swarm<Behaviour> {
+        let behaviour = Behaviour::new(chain_id.to_string(), "abc".to_string(), Arc::new(AtomicU64::new(height)));
+        libp2p::SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(tcp::Config::default(), noise::Config::new, yamux::Config::default)
+            .unwrap()
+            .with_behaviour(|_| Ok(behaviour))
+            .unwrap()
+            .with_swarm_config(|cfg| cfg.with_idle_connection_timeout(Duration::from_secs(60)))
+            .build()
+    }
+
+    /// Connect two swarms and return what each side concluded about the other
+    async fn handshake(a_chain: &str, b_chain: &str) -> (Event, Event) {
+        let mut a = swarm(a_chain, 5);
+        let mut b = swarm(b_chain, 7);
+        a.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap()).unwrap();
+        let addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = a.select_next_some().await {
+                break address;
+            }
+        };
+        b.dial(addr).unwrap();
+
+        let (mut a_event, mut b_event) = (None, None);
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while a_event.is_none() || b_event.is_none() {
+                tokio::select! {
+                    event = a.select_next_some() => if let SwarmEvent::Behaviour(e) = event { a_event = Some(e) },
+                    event = b.select_next_some() => if let SwarmEvent::Behaviour(e) = event { b_event = Some(e) },
+                }
+            }
+        })
+        .await
+        .expect("handshake did not complete");
+        (a_event.unwrap(), b_event.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_handshake_between_peers() {
+        let (a, b) = handshake("sultan-mainnet-1", "sultan-mainnet-1").await;
+        assert!(matches!(a, Event::Verified { ref info, .. } if info.height == 7));
+        assert!(matches!(b, Event::Verified { ref info, .. } if info.height == 5));
+
+        let (a, b) = handshake("sultan-mainnet-1", "sultan-testnet-1").await;
+        assert!(matches!(a, Event::Mismatch { ref info, .. } if info.chain_id == "sultan-testnet-1"));
+        assert!(matches!(b, Event::Mismatch { .. }));
+    }
+}
@@ -0,0 +1,127 @@
+//! Liveness and readiness checks
+//!
+//! The node answers `/health/live` as long as its RPC server runs. It is ready
+//! to take traffic once every check here passes: it is within `max_sync_lag`
+//! blocks of its peers, storage accepts writes, all shards are healthy and P2P
+//! has its minimum peers. `/health` reports each check so an operator can see
+//! which one failed. The RPC server reruns the checks once a second and the
+//! probes serve that report, so polling them stays cheap.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Blocks a node may trail its best peer by and still be ready
+pub const DEFAULT_MAX_SYNC_LAG: u64 = 2;
+
+/// Outcome of one readiness check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Check {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &str, ok: bool, detail: String) -> Self {
+        Self { name: name.to_string(), ok, detail }
+    }
+
+    /// Within `max_lag` blocks of the highest height a peer reported
+    /// (`behind` is `BlockSyncManager::blocks_behind`)
+    pub fn sync(height: u64, behind: u64, max_lag: u64) -> Self {
+        let detail = if behind == 0 {
+            format!("at height {}, no peer is ahead", height)
+        } else {
+            format!("at height {}, {} blocks behind peers (allowed {})", height, behind, max_lag)
+        };
+        Self::new("sync", behind <= max_lag, detail)
+    }
+
+    /// A write to storage succeeded (`error` is the failure otherwise)
+    pub fn storage(error: Option<String>) -> Self {
+        match error {
+            None => Self::new("storage", true, "writable".to_string()),
+            Some(error) => Self::new("storage", false, format!("write failed: {}", error)),
+        }
+    }
+
+    pub fn shards(healthy: usize, total: usize) -> Self {
+        Self::new("shards", total > 0 && healthy == total, format!("{} of {} shards healthy", healthy, total))
+    }
+
+    /// `healthy` is `P2PNetwork::is_healthy` (at least the minimum peers connected)
+    pub fn peers(connected: usize, healthy: bool) -> Self {
+        let detail = if healthy {
+            format!("{} peers connected", connected)
+        } else {
+            format!("{} peers connected, below the minimum", connected)
+        };
+        Self::new("peers", healthy, detail)
+    }
+
+    /// A check that doesn't apply to this node, e.g. peers with P2P disabled
+    pub fn skipped(name: &str, reason: &str) -> Self {
+        Self::new(name, true, reason.to_string())
+    }
+}
+
+/// Body of `/health/live`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Liveness {
+    /// Always "alive"; a node that isn't doesn't answer
+    pub status: String,
+}
+
+/// Body of `/health` and `/health/ready`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct HealthReport {
+    /// "ready" or "not_ready"
+    pub status: String,
+    pub ready: bool,
+    pub height: u64,
+    pub checks: Vec<Check>,
+}
+
+impl HealthReport {
+    pub fn new(height: u64, checks: Vec<Check>) -> Self {
+        let ready = checks.iter().all(|check| check.ok);
+        let status = if ready { "ready" } else { "not_ready" }.to_string();
+        Self { status, ready, height, checks }
+    }
+
+    /// Names of the checks that failed
+    pub fn failed(&self) -> Vec<&str> {
+        self.checks.iter().filter(|check| !check.ok).map(|check| check.name.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_allows_configured_lag() {
+        assert!(Check::sync(100, 0, 0).ok);
+        assert!(Check::sync(100, 2, 2).ok);
+        assert!(!Check::sync(100, 3, 2).ok);
+        assert_eq!(Check::sync(100, 0, 2).detail, "at height 100, no peer is ahead");
+        assert_eq!(Check::sync(100, 10, 2).detail, "at height 100, 10 blocks behind peers (allowed 2)");
+    }
+
+    #[test]
+    fn test_report_lists_failed_checks() {
+        let report = HealthReport::new(7, vec![
+            Check::sync(7, 0, DEFAULT_MAX_SYNC_LAG),
+            Check::storage(Some("read-only filesystem".to_string())),
+            Check::shards(3, 4),
+            Check::skipped("peers", "P2P disabled"),
+        ]);
+        assert!(!report.ready);
+        assert_eq!(report.status, "not_ready");
+        assert_eq!(report.failed(), vec!["storage", "shards"]);
+
+        let report = HealthReport::new(7, vec![Check::storage(None), Check::peers(3, true)]);
+        assert!(report.ready);
+        assert!(report.failed().is_empty());
+    }
+}
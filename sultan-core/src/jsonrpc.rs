@@ -0,0 +1,336 @@
+//! JSON-RPC 2.0 wire format
+//!
+//! The node serves JSON-RPC on `/rpc` alongside the REST routes. This module holds
+//! the envelope (requests, responses, batches, parameters and error codes); the
+//! methods themselves are namespaced after the REST routes they call
+//! (`chain_getBlock`, `staking_delegate`, `dex_swap` ...).
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// Protocol version every request and response carries
+pub const VERSION: &str = "2.0";
+
+/// Most calls accepted in one batch
+pub const MAX_BATCH_SIZE: usize = 20;
+
+// Standard error codes
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+// Server error codes (-32000 to -32099)
+/// The node refused the operation (bad signature, insufficient balance, disabled feature ...)
+pub const REJECTED: i64 = -32000;
+pub const NOT_FOUND: i64 = -32001;
+/// The block was pruned from this node
+pub const PRUNED: i64 = -32002;
+pub const UNAUTHORIZED: i64 = -32003;
+pub const RATE_LIMITED: i64 = -32005;
+
+/// Deserialize a present field as `Some`, even when it is `null`
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// A method call; without an `id` it is a notification and gets no response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+    #[serde(default, deserialize_with = "present", skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+}
+
+impl Request {
+    pub fn new(method: impl Into<String>, params: Value, id: u64) -> Self {
+        Self {
+            jsonrpc: VERSION.to_string(),
+            method: method.into(),
+            params: Some(params),
+            id: Some(Value::from(id)),
+        }
+    }
+
+    pub fn is_notification(&self) -> bool {
+        self.id.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into(), data: None }
+    }
+
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(METHOD_NOT_FOUND, format!("Method not found: {}", method))
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+/// Wire shape of a response, which carries exactly one of `result` and `error`
+#[derive(Clone, Serialize, Deserialize)]
+struct RawResponse {
+    jsonrpc: String,
+    #[serde(default, deserialize_with = "present", skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+    id: Value,
+}
+
+/// The reply to one request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawResponse", into = "RawResponse")]
+pub struct Response {
+    /// Id of the request, or null if it couldn't be read
+    pub id: Value,
+    pub outcome: Result<Value, RpcError>,
+}
+
+impl Response {
+    pub fn success(id: Value, result: Value) -> Self {
+        Self { id, outcome: Ok(result) }
+    }
+
+    pub fn failure(id: Value, error: RpcError) -> Self {
+        Self { id, outcome: Err(error) }
+    }
+}
+
+impl From<Response> for RawResponse {
+    fn from(response: Response) -> Self {
+        let (result, error) = match response.outcome {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self { jsonrpc: VERSION.to_string(), result, error, id: response.id }
+    }
+}
+
+impl TryFrom<RawResponse> for Response {
+    type Error = String;
+
+    fn try_from(raw: RawResponse) -> Result<Self, String> {
+        if raw.jsonrpc != VERSION {
+            return Err(format!("Unsupported JSON-RPC version {}", raw.jsonrpc));
+        }
+        let outcome = match (raw.result, raw.error) {
+            (Some(result), None) => Ok(result),
+            (None, Some(error)) => Err(error),
+            _ => return Err("Response must have exactly one of result and error".to_string()),
+        };
+        Ok(Self { id: raw.id, outcome })
+    }
+}
+
+/// A request body: one call or a batch of them
+#[derive(Debug)]
+pub enum Body {
+    Single(Value),
+    Batch(Vec<Value>),
+}
+
+/// Split a request body into calls
+///
+/// Fails with the response to send when the body isn't JSON or the batch is empty or too large.
+pub fn parse_body(bytes: &[u8]) -> Result<Body, Response> {
+    let value: Value = serde_json::from_slice(bytes).map_err(|e| {
+        Response::failure(Value::Null, RpcError::new(PARSE_ERROR, format!("Parse error: {}", e)))
+    })?;
+    match value {
+        Value::Array(calls) if calls.is_empty() => Err(Response::failure(
+            Value::Null,
+            RpcError::new(INVALID_REQUEST, "Empty batch"),
+        )),
+        Value::Array(calls) if calls.len() > MAX_BATCH_SIZE => Err(Response::failure(
+            Value::Null,
+            RpcError::new(INVALID_REQUEST, format!("Batch exceeds {} calls", MAX_BATCH_SIZE)),
+        )),
+        Value::Array(calls) => Ok(Body::Batch(calls)),
+        call => Ok(Body::Single(call)),
+    }
+}
+
+/// Check one call of a body
+///
+/// Fails with the response to send, addressed to the call's id if it has a usable one.
+pub fn parse_request(call: Value) -> Result<Request, Response> {
+    let id = match call.get("id") {
+        Some(id @ (Value::Null | Value::Number(_) | Value::String(_))) => id.clone(),
+        _ => Value::Null,
+    };
+    let invalid = |message: String| Response::failure(id.clone(), RpcError::new(INVALID_REQUEST, message));
+
+    let request: Request = serde_json::from_value(call).map_err(|e| invalid(format!("Invalid request: {}", e)))?;
+    if request.jsonrpc != VERSION {
+        return Err(invalid(format!("jsonrpc must be \"{}\"", VERSION)));
+    }
+    if !matches!(request.id, None | Some(Value::Null | Value::Number(_) | Value::String(_))) {
+        return Err(invalid("id must be a string, number or null".to_string()));
+    }
+    if !matches!(request.params, None | Some(Value::Array(_) | Value::Object(_))) {
+        return Err(invalid("params must be an array or object".to_string()));
+    }
+    Ok(request)
+}
+
+/// Method parameters, by name (object) or by position (array)
+#[derive(Debug, Clone, Default)]
+pub struct Params(Option<Value>);
+
+impl Params {
+    pub fn new(params: Option<Value>) -> Self {
+        Self(params)
+    }
+
+    fn lookup(&self, index: usize, name: &str) -> Option<&Value> {
+        match &self.0 {
+            Some(Value::Object(map)) => map.get(name),
+            Some(Value::Array(items)) => items.get(index),
+            _ => None,
+        }
+    }
+
+    /// Required parameter `name`, or the one at `index` when passed by position
+    pub fn get<T: DeserializeOwned>(&self, index: usize, name: &str) -> Result<T, RpcError> {
+        let value = self.lookup(index, name)
+            .filter(|v| !v.is_null())
+            .ok_or_else(|| RpcError::invalid_params(format!("Missing parameter '{}'", name)))?;
+        serde_json::from_value(value.clone())
+            .map_err(|e| RpcError::invalid_params(format!("Invalid parameter '{}': {}", name, e)))
+    }
+
+    /// Struct of named options, e.g. a REST query string
+    ///
+    /// By name the options sit alongside the other parameters; by position they are
+    /// an object at `index`. Missing options take their defaults.
+    pub fn options<T: DeserializeOwned>(&self, index: usize) -> Result<T, RpcError> {
+        let value = match &self.0 {
+            Some(Value::Object(_)) => self.0.clone(),
+            Some(Value::Array(items)) => items.get(index).cloned(),
+            _ => None,
+        };
+        serde_json::from_value(value.unwrap_or_else(|| Value::Object(Default::default())))
+            .map_err(|e| RpcError::invalid_params(format!("Invalid parameters: {}", e)))
+    }
+
+    /// All parameters as one value, e.g. a REST request body
+    ///
+    /// By position the body is the only element.
+    pub fn body(&self) -> Result<Value, RpcError> {
+        match &self.0 {
+            Some(value @ Value::Object(_)) => Ok(value.clone()),
+            Some(Value::Array(items)) if items.len() == 1 => Ok(items[0].clone()),
+            _ => Err(RpcError::invalid_params("Expected the request body as params")),
+        }
+    }
+
+    /// `body` deserialized as `T`
+    pub fn body_as<T: DeserializeOwned>(&self) -> Result<T, RpcError> {
+        serde_json::from_value(self.body()?)
+            .map_err(|e| RpcError::invalid_params(format!("Invalid parameters: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_single_and_batch() {
+        let call = parse_request(json!({"jsonrpc": "2.0", "method": "chain_getBlock", "params": [5], "id": 1})).unwrap();
+        assert_eq!(call.method, "chain_getBlock");
+        assert!(!call.is_notification());
+        assert_eq!(Params::new(call.params).get::<u64>(0, "height").unwrap(), 5);
+
+        // A null id is a call, a missing id a notification
+        assert!(!parse_request(json!({"jsonrpc": "2.0", "method": "m", "id": null})).unwrap().is_notification());
+        assert!(parse_request(json!({"jsonrpc": "2.0", "method": "m"})).unwrap().is_notification());
+
+        assert!(matches!(parse_body(br#"[{"jsonrpc":"2.0","method":"m","id":1}, 2]"#), Ok(Body::Batch(calls)) if calls.len() == 2));
+        assert_eq!(parse_body(b"[]").unwrap_err().outcome.unwrap_err().code, INVALID_REQUEST);
+        assert_eq!(parse_body(b"{oops").unwrap_err().outcome.unwrap_err().code, PARSE_ERROR);
+        let too_many = serde_json::to_vec(&vec![json!({}); MAX_BATCH_SIZE + 1]).unwrap();
+        assert_eq!(parse_body(&too_many).unwrap_err().outcome.unwrap_err().code, INVALID_REQUEST);
+    }
+
+    #[test]
+    fn test_invalid_requests_keep_their_id() {
+        let wrong_version = parse_request(json!({"jsonrpc": "1.0", "method": "m", "id": "a"})).unwrap_err();
+        assert_eq!(wrong_version.id, json!("a"));
+        assert_eq!(wrong_version.outcome.unwrap_err().code, INVALID_REQUEST);
+
+        let bad_params = parse_request(json!({"jsonrpc": "2.0", "method": "m", "params": 5, "id": 2})).unwrap_err();
+        assert_eq!(bad_params.id, json!(2));
+
+        // Not an object at all: no id to answer to
+        assert_eq!(parse_request(json!(2)).unwrap_err().id, Value::Null);
+        assert_eq!(parse_request(json!({"jsonrpc": "2.0", "method": "m", "id": [1]})).unwrap_err().id, Value::Null);
+    }
+
+    #[test]
+    fn test_params_by_name_and_position() {
+        #[derive(Deserialize)]
+        struct Page {
+            #[serde(default)]
+            limit: usize,
+        }
+
+        let named = Params::new(Some(json!({"address": "alice", "limit": 5})));
+        assert_eq!(named.get::<String>(0, "address").unwrap(), "alice");
+        assert_eq!(named.options::<Page>(1).unwrap().limit, 5);
+        assert_eq!(named.body().unwrap()["limit"], 5);
+
+        let positional = Params::new(Some(json!(["alice"])));
+        assert_eq!(positional.get::<String>(0, "address").unwrap(), "alice");
+        assert_eq!(positional.options::<Page>(1).unwrap().limit, 0);
+        assert_eq!(positional.body().unwrap(), json!("alice"));
+
+        assert_eq!(Params::default().get::<u64>(0, "height").unwrap_err().code, INVALID_PARAMS);
+        assert_eq!(named.get::<u64>(0, "address").unwrap_err().code, INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_response_wire_format() {
+        let ok = serde_json::to_value(Response::success(json!(1), Value::Null)).unwrap();
+        assert_eq!(ok, json!({"jsonrpc": "2.0", "result": null, "id": 1}));
+
+        let err = Response::failure(json!("x"), RpcError::new(NOT_FOUND, "Not found"));
+        let wire = serde_json::to_string(&err).unwrap();
+        assert_eq!(serde_json::from_str::<Response>(&wire).unwrap(), err);
+
+        assert!(serde_json::from_value::<Response>(json!({"jsonrpc": "2.0", "id": 1})).is_err());
+    }
+}
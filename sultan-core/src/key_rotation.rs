@@ -0,0 +1,269 @@
+//! Validator consensus key rotation
+//!
+//! A validator switches consensus keys with a zero-value system transaction to
+//! `KEY_ROTATION_ADDRESS`. The rotation names the new key and the height it takes
+//! over at, and is signed by both keys: the current key authorizes it, the new key
+//! proves the validator holds it. Both sign over the chain ID, so a rotation
+//! can't be replayed on another network. It must be included at least
+//! `MIN_KEY_ROTATION_DELAY` blocks before activation so every node has it by then.
+//!
+//! Retired keys are kept with the height they stopped at, so signatures and
+//! evidence from earlier heights still verify against the right key. Storage
+//! indexes every rotation, so the history is rebuilt from genesis on restart even
+//! after the blocks carrying it are pruned.
+
+use crate::blockchain::Transaction;
+use anyhow::{bail, Context, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+/// System recipient of key rotations (never a real account)
+pub const KEY_ROTATION_ADDRESS: &str = "sultan1keyrotation";
+
+/// Minimum blocks between a rotation's inclusion and its activation
+pub const MIN_KEY_ROTATION_DELAY: u64 = 100;
+
+const MEMO_PREFIX: &str = "keyrot:v1:";
+
+/// Domain tag of the signed message (nothing else signs with this prefix)
+const SIGNING_DOMAIN: &str = "sultan_key_rotation_v1";
+
+/// A signed request to switch a validator's consensus key at `activation_height`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyRotation {
+    pub validator: String,
+    pub new_pubkey: [u8; 32],
+    pub activation_height: u64,
+    /// Signature by the current key over `signing_message`
+    pub signature: [u8; 64],
+    /// Signature by the new key over `signing_message`
+    pub new_key_signature: [u8; 64],
+}
+
+/// A key waiting for its activation height
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingKey {
+    pub pubkey: [u8; 32],
+    pub activation_height: u64,
+}
+
+/// A key that signed for heights below `until_height`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetiredKey {
+    pub pubkey: [u8; 32],
+    pub until_height: u64,
+}
+
+/// A validator's consensus keys over time, for checking signatures by height
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyHistory {
+    current: [u8; 32],
+    /// Oldest first, as in `Validator::retired_keys`
+    retired: Vec<RetiredKey>,
+}
+
+impl KeyHistory {
+    pub fn new(pubkey: [u8; 32]) -> Self {
+        Self { current: pubkey, retired: Vec::new() }
+    }
+
+    /// Start from a validator's known history
+    pub fn with_retired(pubkey: [u8; 32], retired: Vec<RetiredKey>) -> Self {
+        Self { current: pubkey, retired }
+    }
+
+    pub fn current(&self) -> [u8; 32] {
+        self.current
+    }
+
+    /// Replace the current key without a rotation (e.g. a re-announcement),
+    /// keeping the retired ones
+    pub fn set_current(&mut self, pubkey: [u8; 32]) {
+        self.current = pubkey;
+    }
+
+    /// Retire the current key: `pubkey` signs from `activation_height` on
+    pub fn rotate(&mut self, pubkey: [u8; 32], activation_height: u64) {
+        if pubkey == self.current {
+            return;
+        }
+        self.retired.push(RetiredKey { pubkey: self.current, until_height: activation_height });
+        self.current = pubkey;
+    }
+
+    /// The key that signs for `height`
+    pub fn at(&self, height: u64) -> [u8; 32] {
+        self.retired.iter()
+            .find(|retired| height < retired.until_height)
+            .map(|retired| retired.pubkey)
+            .unwrap_or(self.current)
+    }
+}
+
+/// Message both keys sign, bound to the chain it is meant for
+pub fn signing_message(chain_id: &str, validator: &str, new_pubkey: &[u8; 32], activation_height: u64) -> Vec<u8> {
+    format!(
+        "{}:{}:{}:{}:{}",
+        SIGNING_DOMAIN, chain_id, validator, hex::encode(new_pubkey), activation_height
+    ).into_bytes()
+}
+
+impl KeyRotation {
+    /// Sign a rotation from `current` to `new` on chain `chain_id`
+    pub fn sign(chain_id: &str, validator: &str, current: &SigningKey, new: &SigningKey, activation_height: u64) -> Self {
+        let new_pubkey = new.verifying_key().to_bytes();
+        let message = signing_message(chain_id, validator, &new_pubkey, activation_height);
+        Self {
+            validator: validator.to_string(),
+            new_pubkey,
+            activation_height,
+            signature: current.sign(&message).to_bytes(),
+            new_key_signature: new.sign(&message).to_bytes(),
+        }
+    }
+
+    fn message(&self, chain_id: &str) -> Vec<u8> {
+        signing_message(chain_id, &self.validator, &self.new_pubkey, self.activation_height)
+    }
+
+    /// Check the new key's signature for chain `chain_id` (needs no chain state)
+    pub fn verify_new_key(&self, chain_id: &str) -> Result<()> {
+        let new_key = VerifyingKey::from_bytes(&self.new_pubkey)
+            .context("New consensus key is not a valid Ed25519 public key")?;
+        new_key.verify(&self.message(chain_id), &Signature::from_bytes(&self.new_key_signature))
+            .context("New consensus key signature is invalid")
+    }
+
+    /// Check both signatures for chain `chain_id`, the current one against `current_pubkey`
+    pub fn verify(&self, chain_id: &str, current_pubkey: &[u8; 32]) -> Result<()> {
+        if self.new_pubkey == *current_pubkey {
+            bail!("New consensus key is the current key");
+        }
+        self.verify_new_key(chain_id)?;
+        let current = VerifyingKey::from_bytes(current_pubkey)
+            .context("Current consensus key is not a valid Ed25519 public key")?;
+        current.verify(&self.message(chain_id), &Signature::from_bytes(&self.signature))
+            .context("Rotation is not signed by the current consensus key")
+    }
+
+    /// Wrap the rotation in the zero-value system transaction carried by a block
+    pub fn into_transaction(self, timestamp: u64) -> Transaction {
+        Transaction {
+            from: self.validator,
+            to: KEY_ROTATION_ADDRESS.to_string(),
+            amount: 0,
+            gas_fee: 0,
+            timestamp,
+            nonce: self.activation_height,
+            signature: None,
+            public_key: None,
+            memo: Some(format!(
+                "{}{}:{}:{}:{}",
+                MEMO_PREFIX,
+                hex::encode(self.new_pubkey),
+                self.activation_height,
+                hex::encode(self.signature),
+                hex::encode(self.new_key_signature),
+            )),
+        }
+    }
+
+    /// Parse the rotation from a key rotation transaction
+    pub fn from_transaction(tx: &Transaction) -> Option<Self> {
+        if !is_key_rotation_tx(tx) {
+            return None;
+        }
+        let mut parts = tx.memo.as_deref()?.strip_prefix(MEMO_PREFIX)?.split(':');
+        let rotation = Self {
+            validator: tx.from.clone(),
+            new_pubkey: hex::decode(parts.next()?).ok()?.try_into().ok()?,
+            activation_height: parts.next()?.parse().ok()?,
+            signature: hex::decode(parts.next()?).ok()?.try_into().ok()?,
+            new_key_signature: hex::decode(parts.next()?).ok()?.try_into().ok()?,
+        };
+        if parts.next().is_some() || tx.nonce != rotation.activation_height {
+            return None;
+        }
+        Some(rotation)
+    }
+}
+
+/// Whether `tx` is a key rotation rather than a transfer
+pub fn is_key_rotation_tx(tx: &Transaction) -> bool {
+    tx.to == KEY_ROTATION_ADDRESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAIN: &str = "sultan-mainnet-1";
+
+    #[test]
+    fn test_rotation_round_trips_through_transaction() {
+        let old = SigningKey::from_bytes(&[1u8; 32]);
+        let new = SigningKey::from_bytes(&[2u8; 32]);
+        let rotation = KeyRotation::sign(CHAIN, "validator1", &old, &new, 500);
+
+        let tx = rotation.clone().into_transaction(1_700_000_000);
+        assert!(is_key_rotation_tx(&tx));
+        assert_eq!(tx.amount, 0);
+        let parsed = KeyRotation::from_transaction(&tx).unwrap();
+        assert_eq!(parsed, rotation);
+        parsed.verify(CHAIN, &old.verifying_key().to_bytes()).unwrap();
+
+        // Tampering with the activation height breaks the memo/nonce link
+        let mut tampered = tx.clone();
+        tampered.nonce = 501;
+        assert!(KeyRotation::from_transaction(&tampered).is_none());
+    }
+
+    #[test]
+    fn test_key_history_picks_key_by_height() {
+        let mut history = KeyHistory::new([1u8; 32]);
+        history.rotate([2u8; 32], 500);
+        history.rotate([3u8; 32], 900);
+        assert_eq!(history.at(0), [1u8; 32]);
+        assert_eq!(history.at(499), [1u8; 32]);
+        assert_eq!(history.at(500), [2u8; 32]);
+        assert_eq!(history.at(899), [2u8; 32]);
+        assert_eq!(history.at(900), [3u8; 32]);
+
+        // Re-announcing the current key keeps the history
+        history.set_current([4u8; 32]);
+        assert_eq!(history.at(600), [2u8; 32]);
+        assert_eq!(history.current(), [4u8; 32]);
+    }
+
+    #[test]
+    fn test_rotation_needs_both_keys() {
+        let old = SigningKey::from_bytes(&[1u8; 32]);
+        let new = SigningKey::from_bytes(&[2u8; 32]);
+        let mallory = SigningKey::from_bytes(&[3u8; 32]);
+
+        // Signed by someone other than the current key
+        let forged = KeyRotation::sign(CHAIN, "validator1", &mallory, &new, 500);
+        assert!(forged.verify_new_key(CHAIN).is_ok());
+        assert!(forged.verify(CHAIN, &old.verifying_key().to_bytes()).is_err());
+
+        // New key claimed without holding it
+        let mut unproven = KeyRotation::sign(CHAIN, "validator1", &old, &new, 500);
+        unproven.new_pubkey = mallory.verifying_key().to_bytes();
+        assert!(unproven.verify_new_key(CHAIN).is_err());
+
+        // Rotating to the same key is rejected
+        let same = KeyRotation::sign(CHAIN, "validator1", &old, &old, 500);
+        assert!(same.verify(CHAIN, &old.verifying_key().to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_rotation_does_not_replay_across_chains() {
+        let old = SigningKey::from_bytes(&[1u8; 32]);
+        let new = SigningKey::from_bytes(&[2u8; 32]);
+        let rotation = KeyRotation::sign("sultan-testnet-1", "validator1", &old, &new, 500);
+
+        assert!(rotation.verify("sultan-testnet-1", &old.verifying_key().to_bytes()).is_ok());
+        assert!(rotation.verify_new_key(CHAIN).is_err());
+        assert!(rotation.verify(CHAIN, &old.verifying_key().to_bytes()).is_err());
+    }
+}
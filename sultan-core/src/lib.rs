@@ -29,14 +29,45 @@
 //! - `bridges_enabled`: Cross-chain bridges (BTC, ETH, SOL, TON)
 //!
 //! See [`Config`] and [`FeatureFlags`] for details.
+//!
+//! # Cargo Features
+//!
+//! - `node` (default): the node runtime, RocksDB storage and libp2p networking.
+//!   Without it the crate only carries the chain types, signing and RPC
+//!   payloads, which is what `sultan-client` builds against.
 
 pub mod blockchain;
+#[cfg(feature = "node")]
 pub mod block_sync;
 pub mod consensus;
+pub mod beacon;
+pub mod liveness;
+pub mod key_rotation;
+pub mod subscriptions;
+pub mod events;
+pub mod pagination;
+pub mod simulate;
+pub mod jsonrpc;
+pub mod metrics;
+pub mod health;
+pub mod admin;
+pub mod api;
+pub mod openapi;
+#[cfg(feature = "node")]
 pub mod p2p;
+#[cfg(feature = "node")]
+pub mod handshake;
+#[cfg(feature = "node")]
+pub mod tx_fetch;
+#[cfg(feature = "node")]
+pub mod compact_block;
 pub mod quantum;
 pub mod database;
 pub mod storage;
+#[cfg(feature = "node")]
+pub mod chain_export;
+#[cfg(feature = "node")]
+pub mod backup;
 pub mod types;
 pub mod config;
 pub mod economics;
@@ -51,12 +82,16 @@ pub mod staking;
 pub mod governance;
 pub mod token_factory;
 pub mod native_dex;
+#[cfg(feature = "node")]
+pub mod node;
 
 // Re-export main types for convenience
 pub use blockchain::{Blockchain, Block, Transaction, Account};
 pub use consensus::{ConsensusEngine, Validator};
+#[cfg(feature = "node")]
 pub use p2p::{P2PNetwork, NetworkMessage};
 pub use quantum::QuantumCrypto;
+#[cfg(feature = "node")]
 pub use storage::PersistentStorage;
 pub use types::{Address, AddressError};
 pub use config::{Config, FeatureFlags};
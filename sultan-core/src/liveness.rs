@@ -0,0 +1,147 @@
+//! Validator liveness tracking
+//!
+//! Each validator keeps a bitmap over the last `SIGNED_BLOCKS_WINDOW` heights:
+//! one bit per height it was active for, set when its vote is in that height's
+//! commit. A validator that signs less than `MIN_SIGNED_PER_WINDOW` of the
+//! heights in the window is jailed for downtime, whether or not the misses were
+//! consecutive.
+
+use serde::{Deserialize, Serialize};
+
+/// Heights covered by the signed-blocks window
+pub const SIGNED_BLOCKS_WINDOW: u64 = 1_000;
+
+/// Minimum fraction of heights in the window a validator must sign
+pub const MIN_SIGNED_PER_WINDOW: f64 = 0.75;
+
+/// Heights recorded in the window before a validator's ratio is judged
+pub const MIN_WINDOW_DUTIES: u64 = 100;
+
+const WINDOW_WORDS: usize = SIGNED_BLOCKS_WINDOW.div_ceil(64) as usize;
+
+/// Signed and missed heights over the last `SIGNED_BLOCKS_WINDOW` heights
+///
+/// Bit `height % SIGNED_BLOCKS_WINDOW` is reused once the window moves past
+/// `height`, so the whole window fits in two fixed-size bitmaps.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedBlocksWindow {
+    signed: Vec<u64>,
+    missed: Vec<u64>,
+    /// Highest height recorded
+    latest: u64,
+}
+
+impl SignedBlocksWindow {
+    /// Record whether the validator signed the commit at `height`
+    ///
+    /// Returns false if the height is already recorded or has left the window.
+    pub fn record(&mut self, height: u64, signed: bool) -> bool {
+        if self.signed.is_empty() {
+            self.signed = vec![0; WINDOW_WORDS];
+            self.missed = vec![0; WINDOW_WORDS];
+            self.latest = height;
+        }
+        if height + SIGNED_BLOCKS_WINDOW <= self.latest {
+            return false;
+        }
+        if height > self.latest {
+            // Clear the slots of heights that just left the window
+            let first = (self.latest + 1).max(height.saturating_sub(SIGNED_BLOCKS_WINDOW - 1));
+            for h in first..=height {
+                let (word, mask) = Self::slot(h);
+                self.signed[word] &= !mask;
+                self.missed[word] &= !mask;
+            }
+            self.latest = height;
+        }
+
+        let (word, mask) = Self::slot(height);
+        if (self.signed[word] | self.missed[word]) & mask != 0 {
+            return false;
+        }
+        if signed {
+            self.signed[word] |= mask;
+        } else {
+            self.missed[word] |= mask;
+        }
+        true
+    }
+
+    fn slot(height: u64) -> (usize, u64) {
+        let bit = height % SIGNED_BLOCKS_WINDOW;
+        ((bit / 64) as usize, 1 << (bit % 64))
+    }
+
+    pub fn signed_count(&self) -> u64 {
+        self.signed.iter().map(|w| w.count_ones() as u64).sum()
+    }
+
+    pub fn missed_count(&self) -> u64 {
+        self.missed.iter().map(|w| w.count_ones() as u64).sum()
+    }
+
+    /// Heights recorded for the validator in the window
+    pub fn duties(&self) -> u64 {
+        self.signed_count() + self.missed_count()
+    }
+
+    /// Signed share of the window's heights, as a percentage (100 with none yet)
+    pub fn uptime_percent(&self) -> f64 {
+        match self.duties() {
+            0 => 100.0,
+            duties => self.signed_count() as f64 / duties as f64 * 100.0,
+        }
+    }
+
+    /// Whether the validator has enough heights to judge and signed too few of them
+    pub fn is_below_minimum(&self) -> bool {
+        let duties = self.duties();
+        duties >= MIN_WINDOW_DUTIES
+            && (self.signed_count() as f64) < duties as f64 * MIN_SIGNED_PER_WINDOW
+    }
+
+    /// Start over, e.g. after the validator is jailed
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intermittent_downtime_is_caught() {
+        // Up half the time: never more than one consecutive miss
+        let mut window = SignedBlocksWindow::default();
+        for height in 0..MIN_WINDOW_DUTIES {
+            assert!(!window.is_below_minimum());
+            assert!(window.record(height * 10, height % 2 == 0));
+        }
+        assert_eq!(window.signed_count(), MIN_WINDOW_DUTIES / 2);
+        assert_eq!(window.uptime_percent(), 50.0);
+        assert!(window.is_below_minimum());
+
+        window.reset();
+        assert_eq!(window.duties(), 0);
+        assert_eq!(window.uptime_percent(), 100.0);
+    }
+
+    #[test]
+    fn test_window_slides_and_deduplicates() {
+        let mut window = SignedBlocksWindow::default();
+        assert!(window.record(100, false));
+        assert!(!window.record(100, true), "same height twice");
+        assert!(window.record(99, true), "older height still in the window");
+        assert_eq!((window.signed_count(), window.missed_count()), (1, 1));
+
+        // Moving the window past height 100 drops both heights
+        assert!(window.record(100 + SIGNED_BLOCKS_WINDOW, true));
+        assert_eq!((window.signed_count(), window.missed_count()), (1, 0));
+        assert!(!window.record(100, false), "height has left the window");
+
+        // A jump longer than the window clears everything
+        assert!(window.record(10 * SIGNED_BLOCKS_WINDOW, false));
+        assert_eq!((window.signed_count(), window.missed_count()), (0, 1));
+    }
+}
@@ -0,0 +1,263 @@
+//! Prometheus metrics
+//!
+//! Most node metrics (height, shards, mempool, peers, sync, RocksDB) are read from
+//! their owners when `/metrics` is scraped and written out with an `Exposition`.
+//! Only what has to be observed as it happens (block production latency, vote
+//! collection times, RPC requests) is accumulated here, in `NodeMetrics`.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Content type of the text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Histogram bucket bounds in seconds, for work that should take well under a block
+pub const LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0];
+
+/// Label pairs of one sample
+pub type Labels = Vec<(&'static str, String)>;
+
+/// A latency histogram that can be observed concurrently
+#[derive(Debug)]
+pub struct Histogram {
+    bounds: &'static [f64],
+    /// Observations per bucket (not cumulative; anything above the last bound is only in `count`)
+    buckets: Vec<AtomicU64>,
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            buckets: bounds.iter().map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        if let Some(i) = self.bounds.iter().position(|bound| secs <= *bound) {
+            self.buckets[i].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new(LATENCY_BUCKETS)
+    }
+}
+
+/// RPC requests by route and status
+#[derive(Debug, Default)]
+pub struct RpcMetrics {
+    requests: Mutex<HashMap<(String, u16), u64>>,
+    latency: Histogram,
+}
+
+impl RpcMetrics {
+    pub fn record(&self, path: &str, status: u16, elapsed: Duration) {
+        *self.requests.lock().entry((route_label(path, status), status)).or_insert(0) += 1;
+        self.latency.observe(elapsed);
+    }
+}
+
+/// First path segments of the public RPC routes
+const RPC_ROUTES: &[&str] = &[
+    "balance", "beacon", "block", "blocks", "bridge", "bridges", "dex", "economics", "events",
+    "faucet", "governance", "health", "metrics", "openapi.json", "rpc", "simulate", "staking",
+    "stats", "status", "supply", "tokens", "transactions", "tx", "ws",
+];
+
+/// Route label for a request path: its first segment, so addresses, hashes and
+/// heights in the path don't each become a series
+///
+/// Anything outside `RPC_ROUTES`, or that no route matched, is "unmatched";
+/// otherwise a client could mint a new series per request.
+fn route_label(path: &str, status: u16) -> String {
+    let segment = path.trim_start_matches('/').split('/').next().unwrap_or_default();
+    if status == 404 || !RPC_ROUTES.contains(&segment) {
+        return "unmatched".to_string();
+    }
+    format!("/{}", segment)
+}
+
+/// Metrics observed as the node runs
+#[derive(Debug, Default)]
+pub struct NodeMetrics {
+    /// Time to produce, apply and persist a block we proposed
+    pub block_production: Histogram,
+    pub rpc: RpcMetrics,
+}
+
+impl NodeMetrics {
+    /// Write the accumulated metrics
+    pub fn write(&self, out: &mut Exposition) {
+        out.histogram(
+            "sultan_block_production_seconds",
+            "Time to produce, apply and persist a block proposed by this node",
+            &self.block_production,
+        );
+
+        let mut requests: Vec<_> = self.rpc.requests.lock()
+            .iter()
+            .map(|((route, status), count)| (route.clone(), *status, *count))
+            .collect();
+        requests.sort();
+        out.counter_family(
+            "sultan_rpc_requests_total",
+            "RPC requests served, by route and status",
+            requests.into_iter().map(|(route, status, count)| {
+                (vec![("route", route), ("status", status.to_string())], count)
+            }),
+        );
+        out.histogram(
+            "sultan_rpc_request_duration_seconds",
+            "Time to serve an RPC request",
+            &self.rpc.latency,
+        );
+    }
+}
+
+/// Writer for the Prometheus text exposition format
+#[derive(Debug, Default)]
+pub struct Exposition {
+    out: String,
+}
+
+impl Exposition {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn gauge(&mut self, name: &str, help: &str, value: f64) {
+        self.header(name, help, "gauge");
+        self.sample(name, &[], value);
+    }
+
+    pub fn counter(&mut self, name: &str, help: &str, value: u64) {
+        self.header(name, help, "counter");
+        self.sample(name, &[], value as f64);
+    }
+
+    pub fn gauge_family(&mut self, name: &str, help: &str, samples: impl IntoIterator<Item = (Labels, f64)>) {
+        self.header(name, help, "gauge");
+        for (labels, value) in samples {
+            self.sample(name, &labels, value);
+        }
+    }
+
+    pub fn counter_family(&mut self, name: &str, help: &str, samples: impl IntoIterator<Item = (Labels, u64)>) {
+        self.header(name, help, "counter");
+        for (labels, value) in samples {
+            self.sample(name, &labels, value as f64);
+        }
+    }
+
+    pub fn histogram(&mut self, name: &str, help: &str, histogram: &Histogram) {
+        self.header(name, help, "histogram");
+        let bucket = format!("{}_bucket", name);
+        let mut cumulative = 0;
+        for (bound, count) in histogram.bounds.iter().zip(&histogram.buckets) {
+            cumulative += count.load(Ordering::Relaxed);
+            self.sample(&bucket, &[("le", bound.to_string())], cumulative as f64);
+        }
+        let count = histogram.count();
+        self.sample(&bucket, &[("le", "+Inf".to_string())], count as f64);
+        let sum = histogram.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        self.sample(&format!("{}_sum", name), &[], sum);
+        self.sample(&format!("{}_count", name), &[], count as f64);
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+
+    fn header(&mut self, name: &str, help: &str, kind: &str) {
+        let _ = writeln!(self.out, "# HELP {} {}", name, help);
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind);
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&'static str, String)], value: f64) {
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels.iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+                .collect();
+            let _ = write!(self.out, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.out, " {}", value);
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let histogram = Histogram::new(&[0.1, 1.0]);
+        histogram.observe(Duration::from_millis(50));
+        histogram.observe(Duration::from_millis(500));
+        histogram.observe(Duration::from_secs(3));
+
+        let mut out = Exposition::new();
+        out.histogram("test_seconds", "Test", &histogram);
+        let text = out.finish();
+        assert!(text.contains("# TYPE test_seconds histogram\n"));
+        assert!(text.contains("test_seconds_bucket{le=\"0.1\"} 1\n"));
+        assert!(text.contains("test_seconds_bucket{le=\"1\"} 2\n"));
+        assert!(text.contains("test_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(text.contains("test_seconds_sum 3.55\n"));
+        assert!(text.contains("test_seconds_count 3\n"));
+    }
+
+    #[test]
+    fn test_rpc_routes_are_bounded() {
+        let metrics = NodeMetrics::default();
+        metrics.rpc.record("/balance/sultan1abc", 200, Duration::from_millis(2));
+        metrics.rpc.record("/balance/sultan1def", 200, Duration::from_millis(2));
+        metrics.rpc.record("/no/such/route", 404, Duration::from_millis(1));
+        // Rejected before routing, e.g. by the rate limiter
+        metrics.rpc.record("/random-1", 429, Duration::from_millis(1));
+        metrics.rpc.record("/random-2", 429, Duration::from_millis(1));
+
+        let mut out = Exposition::new();
+        metrics.write(&mut out);
+        let text = out.finish();
+        assert!(text.contains("sultan_rpc_requests_total{route=\"/balance\",status=\"200\"} 2\n"));
+        assert!(text.contains("sultan_rpc_requests_total{route=\"unmatched\",status=\"404\"} 1\n"));
+        assert!(text.contains("sultan_rpc_requests_total{route=\"unmatched\",status=\"429\"} 2\n"));
+        assert!(text.contains("sultan_rpc_request_duration_seconds_count 5\n"));
+    }
+
+    #[test]
+    fn test_documented_routes_have_labels() {
+        for path in crate::openapi::document()["paths"].as_object().unwrap().keys() {
+            assert_ne!(route_label(path, 200), "unmatched", "{} has no route label", path);
+        }
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        let mut out = Exposition::new();
+        out.gauge_family("test", "Test", vec![(vec![("peer", "a\"b\\c".to_string())], 1.5)]);
+        assert!(out.finish().contains("test{peer=\"a\\\"b\\\\c\"} 1.5\n"));
+    }
+}
@@ -8,16 +8,19 @@
 
 use anyhow::{Result, bail};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{info, debug, warn};
 
 use crate::token_factory::TokenFactory;
+use crate::api;
+use crate::events::{EventBuffer, ModuleEvent};
 
 /// Liquidity pool using constant product formula (x * y = k)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LiquidityPool {
     pub pair_id: String,
     pub token_a: String,
@@ -56,6 +59,10 @@ pub struct NativeDex {
     pub protocol_fee_address: String,
     /// Path to persist pool data
     pub storage_path: Option<PathBuf>,
+    /// Events emitted since the last block commit
+    pub events: EventBuffer,
+    /// Whether to verify Ed25519 signatures; only `fork` can turn this off
+    verify_signatures: bool,
 }
 
 impl NativeDex {
@@ -70,6 +77,8 @@ impl NativeDex {
             default_protocol_fee_rate: 10, // 0.1% to protocol wallet
             protocol_fee_address: String::new(), // Must be set before use
             storage_path: None,
+            events: EventBuffer::default(),
+            verify_signatures: true,
         }
     }
 
@@ -90,6 +99,24 @@ impl NativeDex {
         self.protocol_fee_address = address;
     }
 
+    /// Copy of the `pair_ids` pools for a dry run, trading against `token_factory`
+    ///
+    /// The copy has no storage path and its own event buffer, so nothing done
+    /// through it is persisted or committed with a block.
+    pub(crate) async fn fork(&self, pair_ids: &[String], token_factory: Arc<TokenFactory>, verify_signatures: bool) -> Self {
+        let pools = self.pools.read().await;
+        let mut fork = Self::new(token_factory);
+        fork.pools = Arc::new(RwLock::new(pair_ids.iter()
+            .filter_map(|pair_id| pools.get(pair_id).map(|pool| (pair_id.clone(), pool.clone())))
+            .collect()));
+        fork.default_fee_rate = self.default_fee_rate;
+        fork.default_lp_fee_rate = self.default_lp_fee_rate;
+        fork.default_protocol_fee_rate = self.default_protocol_fee_rate;
+        fork.protocol_fee_address = self.protocol_fee_address.clone();
+        fork.verify_signatures = verify_signatures;
+        fork
+    }
+
     /// Load pools from persistent storage
     pub async fn load_from_storage(&self) -> Result<()> {
         let Some(path) = &self.storage_path else {
@@ -117,7 +144,11 @@ impl NativeDex {
         let Some(path) = &self.storage_path else {
             return Ok(());
         };
-        
+        self.save_to_dir(path).await
+    }
+
+    /// Save pools as JSON into `path` (used by persistence and backups)
+    pub async fn save_to_dir(&self, path: &Path) -> Result<()> {
         // Ensure directory exists
         tokio::fs::create_dir_all(path).await?;
         
@@ -132,7 +163,7 @@ impl NativeDex {
     
     /// Internal: Create a new liquidity pool
     /// Private method - use `create_pair_with_signature` for public API
-    async fn create_pair_internal(
+    pub(crate) async fn create_pair_internal(
         &self,
         creator: &str,
         token_a: &str,
@@ -220,6 +251,14 @@ impl NativeDex {
         info!("✅ Liquidity pool created: {} ({} + {})", 
             pair_id, reserve_a, reserve_b);
         info!("   LP tokens minted: {} {} to {}", lp_supply, lp_denom, creator);
+        self.events.emit(ModuleEvent::PairCreated {
+            pair_id: pair_id.clone(),
+            creator: creator.to_string(),
+            token_a,
+            token_b,
+            amount_a: reserve_a.to_string(),
+            amount_b: reserve_b.to_string(),
+        });
         
         Ok(pair_id)
     }
@@ -326,6 +365,14 @@ impl NativeDex {
         
         info!("✅ Swap executed: {} {} → {} {} (LP fee: {}, protocol fee: {})", 
             amount_in, token_in, amount_out, token_out_clone, lp_fee, protocol_fee);
+        self.events.emit(ModuleEvent::Swapped {
+            pair_id: pair_id_clone,
+            trader: user.to_string(),
+            token_in: token_in.to_string(),
+            amount_in: amount_in.to_string(),
+            token_out: token_out_clone,
+            amount_out: amount_out.to_string(),
+        });
         
         Ok(amount_out)
     }
@@ -402,6 +449,13 @@ impl NativeDex {
         
         info!("✅ Liquidity added: {} {} + {} {} → {} LP tokens", 
             amount_a, token_a, amount_b, token_b, lp_tokens);
+        self.events.emit(ModuleEvent::LiquidityAdded {
+            pair_id: pair_id_clone,
+            provider: user.to_string(),
+            amount_a: amount_a.to_string(),
+            amount_b: amount_b.to_string(),
+            lp_tokens: lp_tokens.to_string(),
+        });
         
         Ok((amount_a, amount_b, lp_tokens))
     }
@@ -469,6 +523,13 @@ impl NativeDex {
         
         info!("✅ Liquidity removed: {} LP tokens → {} {} + {} {}", 
             lp_tokens, amount_a, token_a, amount_b, token_b);
+        self.events.emit(ModuleEvent::LiquidityRemoved {
+            pair_id: pair_id_clone,
+            provider: user.to_string(),
+            amount_a: amount_a.to_string(),
+            amount_b: amount_b.to_string(),
+            lp_tokens: lp_tokens.to_string(),
+        });
         
         Ok((amount_a, amount_b))
     }
@@ -479,6 +540,11 @@ impl NativeDex {
         pools.get(pair_id).cloned()
     }
     
+    /// All liquidity pools
+    pub async fn list_pools(&self) -> Vec<LiquidityPool> {
+        self.pools.read().await.values().cloned().collect()
+    }
+    
     /// Get price of token A in terms of token B
     pub async fn get_price(&self, pair_id: &str) -> Result<f64> {
         let pools = self.pools.read().await;
@@ -522,8 +588,8 @@ impl NativeDex {
         }
         
         // Verify signature
-        let sign_data = format!("collect_fees:{}:{}", pair_id, caller);
-        if !Self::verify_ed25519_signature(pubkey, sign_data.as_bytes(), signature) {
+        let sign_data = api::collect_fees_message(pair_id, caller);
+        if !self.check_signature(pubkey, sign_data.as_bytes(), signature) {
             bail!("Invalid Ed25519 signature on collect_fees");
         }
         
@@ -583,6 +649,11 @@ impl NativeDex {
         Ok((pool.protocol_fees_a, pool.protocol_fees_b))
     }
 
+    /// Verify the signature of a DEX operation (always passes on unverified forks)
+    fn check_signature(&self, pubkey: &[u8; 32], message: &[u8], signature: &[u8]) -> bool {
+        !self.verify_signatures || Self::verify_ed25519_signature(pubkey, message, signature)
+    }
+
     /// Verify Ed25519 signature for DEX operations
     pub fn verify_ed25519_signature(pubkey: &[u8; 32], message: &[u8], signature: &[u8]) -> bool {
         use ed25519_dalek::{Signature, VerifyingKey, Verifier};
@@ -614,8 +685,8 @@ impl NativeDex {
         pubkey: &[u8; 32],
     ) -> Result<u128> {
         // Verify signature over swap data
-        let sign_data = format!("swap:{}:{}:{}:{}:{}", pair_id, user, token_in, amount_in, min_amount_out);
-        if !Self::verify_ed25519_signature(pubkey, sign_data.as_bytes(), signature) {
+        let sign_data = api::swap_message(pair_id, user, token_in, amount_in, min_amount_out);
+        if !self.check_signature(pubkey, sign_data.as_bytes(), signature) {
             bail!("Invalid Ed25519 signature on swap");
         }
         debug!("✅ Swap signature verified for {} {} in {}", amount_in, token_in, pair_id);
@@ -635,8 +706,8 @@ impl NativeDex {
         signature: &[u8],
         pubkey: &[u8; 32],
     ) -> Result<String> {
-        let sign_data = format!("create_pair:{}:{}:{}:{}:{}", creator, token_a, token_b, amount_a, amount_b);
-        if !Self::verify_ed25519_signature(pubkey, sign_data.as_bytes(), signature) {
+        let sign_data = api::create_pair_message(creator, token_a, token_b, amount_a, amount_b);
+        if !self.check_signature(pubkey, sign_data.as_bytes(), signature) {
             bail!("Invalid Ed25519 signature on create_pair");
         }
         debug!("✅ Create pair signature verified for {}/{}", token_a, token_b);
@@ -656,9 +727,8 @@ impl NativeDex {
         signature: &[u8],
         pubkey: &[u8; 32],
     ) -> Result<(u128, u128, u128)> {
-        let sign_data = format!("add_liquidity:{}:{}:{}:{}:{}:{}", 
-            pair_id, user, amount_a_desired, amount_b_desired, amount_a_min, amount_b_min);
-        if !Self::verify_ed25519_signature(pubkey, sign_data.as_bytes(), signature) {
+        let sign_data = api::add_liquidity_message(pair_id, user, amount_a_desired, amount_b_desired, amount_a_min, amount_b_min);
+        if !self.check_signature(pubkey, sign_data.as_bytes(), signature) {
             bail!("Invalid Ed25519 signature on add_liquidity");
         }
         debug!("✅ Add liquidity signature verified for {} in {}", user, pair_id);
@@ -677,9 +747,8 @@ impl NativeDex {
         signature: &[u8],
         pubkey: &[u8; 32],
     ) -> Result<(u128, u128)> {
-        let sign_data = format!("remove_liquidity:{}:{}:{}:{}:{}", 
-            pair_id, user, lp_tokens, amount_a_min, amount_b_min);
-        if !Self::verify_ed25519_signature(pubkey, sign_data.as_bytes(), signature) {
+        let sign_data = api::remove_liquidity_message(pair_id, user, lp_tokens, amount_a_min, amount_b_min);
+        if !self.check_signature(pubkey, sign_data.as_bytes(), signature) {
             bail!("Invalid Ed25519 signature on remove_liquidity");
         }
         debug!("✅ Remove liquidity signature verified for {} in {}", user, pair_id);
@@ -837,6 +906,20 @@ mod tests {
         // So we should expect ~986, not 990+
         assert!(amount_out > 980, "amount_out should be > 980, got {}", amount_out);
         assert!(amount_out < 1000, "amount_out should be < 1000, got {}", amount_out);
+
+        // The swap is recorded after the token transfers that carried it out
+        let events = crate::events::collect_block_events(1, &[&token_factory.events, &dex.events]);
+        let dex_events: Vec<_> = events.iter().filter(|e| e.module == "dex").collect();
+        assert_eq!(dex_events.len(), 2);
+        assert!(matches!(dex_events[0].event, ModuleEvent::PairCreated { .. }));
+        assert_eq!(events.last().unwrap().event, ModuleEvent::Swapped {
+            pair_id: pair_id.clone(),
+            trader: "sultan1bob".to_string(),
+            token_in: token_a.clone(),
+            amount_in: "1000".to_string(),
+            token_out: token_b.clone(),
+            amount_out: amount_out.to_string(),
+        });
     }
 
     #[tokio::test]
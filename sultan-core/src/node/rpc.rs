@@ -0,0 +1,2970 @@
+use super::*;
+use crate::subscriptions::{EventRecord, Topic};
+use crate::jsonrpc::{self, Body, Params, Response, RpcError};
+use crate::events::{self, EventFilter};
+use serde_json::Value;
+use warp::Filter;
+use std::net::SocketAddr;
+use std::collections::HashMap;
+use std::time::Instant;
+use std::sync::OnceLock;
+
+/// How often the health report served on `/health` is refreshed
+const HEALTH_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Simple token bucket rate limiter
+/// Limits requests per IP to prevent spam/DDoS
+struct RateLimiter {
+    requests: HashMap<String, Vec<Instant>>,
+    max_requests: usize,
+    window_secs: u64,
+}
+
+impl RateLimiter {
+    fn new(max_requests: usize, window_secs: u64) -> Self {
+        Self {
+            requests: HashMap::new(),
+            max_requests,
+            window_secs,
+        }
+    }
+
+    /// Count `cost` requests from `ip`, if that keeps it under the limit
+    fn check_rate_limit(&mut self, ip: &str, cost: usize) -> bool {
+        let now = Instant::now();
+        let cutoff = now - std::time::Duration::from_secs(self.window_secs);
+        
+        let entry = self.requests.entry(ip.to_string()).or_insert_with(Vec::new);
+        
+        // Remove old requests outside the window
+        entry.retain(|&t| t > cutoff);
+        
+        // Check if under limit
+        if entry.len() + cost <= self.max_requests {
+            entry.extend(std::iter::repeat_n(now, cost));
+            true
+        } else {
+            false
+        }
+    }
+
+    // Clean up old entries periodically
+    fn cleanup(&mut self) {
+        let now = Instant::now();
+        let cutoff = now - std::time::Duration::from_secs(self.window_secs * 2);
+        self.requests.retain(|_, times| {
+            times.retain(|&t| t > cutoff);
+            !times.is_empty()
+        });
+    }
+}
+
+// Global rate limiter using OnceLock (no external crate needed)
+static RATE_LIMITER: OnceLock<Arc<tokio::sync::RwLock<RateLimiter>>> = OnceLock::new();
+
+fn get_rate_limiter() -> &'static Arc<tokio::sync::RwLock<RateLimiter>> {
+    RATE_LIMITER.get_or_init(|| {
+        Arc::new(tokio::sync::RwLock::new(RateLimiter::new(
+            100,  // 100 requests
+            10,   // per 10 seconds
+        )))
+    })
+}
+
+/// Rate-limit weight of one simulation, which executes module code under the chain lock
+const SIMULATE_COST: usize = 10;
+
+/// Rate limiting filter - applies to all endpoints
+fn with_rate_limit() -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+    with_rate_limit_cost(1)
+}
+
+/// Rate limiting filter counting each request `cost` times
+fn with_rate_limit_cost(cost: usize) -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+    warp::addr::remote()
+        .and_then(move |addr: Option<SocketAddr>| async move {
+            let ip = addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+            
+            let limiter_ref = get_rate_limiter();
+            let mut limiter = limiter_ref.write().await;
+            
+            // Periodic cleanup every ~1000 requests
+            if limiter.requests.len() > 1000 {
+                limiter.cleanup();
+            }
+            
+            if limiter.check_rate_limit(&ip, cost) {
+                Ok(())
+            } else {
+                warn!("Rate limit exceeded for IP: {}", ip);
+                Err(warp::reject::custom(super::RateLimitExceeded))
+            }
+        })
+        .untuple_one()
+}
+
+pub async fn run_rpc_server(
+    addr: SocketAddr,
+    state: Arc<NodeState>,
+) -> Result<()> {
+    info!("Starting RPC server on {} (rate limit: 100 req/10s per IP)", addr);
+    let routes = routes(state.clone());
+
+    // Probes read the cached report, so polling them never touches storage
+    let health_state = state.clone();
+    tokio::spawn(async move {
+        let mut refresh = interval(HEALTH_REFRESH_INTERVAL);
+        loop {
+            refresh.tick().await;
+            let report = health_report(&health_state).await;
+            *health_state.health.write() = Some(report);
+        }
+    });
+
+    // Start server with or without TLS
+    if let Some(ref tls_config) = state.tls_config {
+        info!("🔒 Starting RPC server with TLS on {}", addr);
+        warp::serve(routes)
+            .tls()
+            .cert_path(&tls_config.cert_path)
+            .key_path(&tls_config.key_path)
+            .run(addr)
+            .await;
+    } else {
+        info!("⚠️  Starting RPC server WITHOUT TLS on {} (use --enable-tls for production)", addr);
+        warp::serve(routes).run(addr).await;
+    }
+
+    Ok(())
+}
+
+/// The RPC API as served: every route behind CORS, the rate limiter (health
+/// probes excepted), rejection handling and request metrics
+pub fn routes(
+    state: Arc<NodeState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = std::convert::Infallible> + Clone + Send + Sync + 'static {
+    // GET /status
+    let status_route = warp::path("status")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_status);
+
+    // POST /tx
+    let tx_route = warp::path("tx")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_submit_tx);
+
+    // GET /block/:height
+    let block_route = warp::path!("block" / u64)
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_block);
+
+    // GET /block/latest - Latest block for explorers
+    let block_latest_route = warp::path!("block" / "latest")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_latest_block);
+
+    // GET /blocks?cursor=&limit=&order= - List blocks (cursor paginated, `offset` still accepted)
+    let blocks_list_route = warp::path!("blocks")
+        .and(warp::get())
+        .and(warp::query::<BlocksListQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_blocks_list);
+
+    // GET /events?type=&module=&attr=key:value&tx=&from_height=&limit= - Module events
+    let events_route = warp::path!("events")
+        .and(warp::get())
+        .and(warp::query::<EventsQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_events);
+
+    // POST /simulate - Dry-run a transfer, staking, token or DEX operation
+    let simulate_route = warp::path!("simulate")
+        .and(warp::post())
+        .and(with_rate_limit_cost(SIMULATE_COST - 1))
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_simulate);
+
+    // GET /stats - Network statistics for dashboards
+    let stats_route = warp::path!("stats")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_stats);
+
+    // GET /metrics - Prometheus metrics
+    let metrics_route = warp::path!("metrics")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_metrics);
+
+    // GET /health, /health/live, /health/ready - Probes for load balancers and orchestrators
+    let health_route = warp::path!("health")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_health);
+
+    let health_live_route = warp::path!("health" / "live")
+        .and(warp::get())
+        .and_then(handle_health_live);
+
+    let health_ready_route = warp::path!("health" / "ready")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_health_ready);
+
+    // GET /openapi.json - OpenAPI 3 document of these routes
+    let openapi_route = warp::path!("openapi.json")
+        .and(warp::get())
+        .map(openapi_document);
+
+    // GET /ws - WebSocket event subscriptions
+    let ws_route = warp::path!("ws")
+        .and(warp::ws())
+        .and(with_state(state.clone()))
+        .and_then(handle_ws);
+
+    // POST /rpc - JSON-RPC 2.0 over the same handlers (single calls and batches)
+    let jsonrpc_route = warp::path!("rpc")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_JSONRPC_BODY))
+        .and(warp::body::bytes())
+        .and(warp::addr::remote())
+        .and(with_state(state.clone()))
+        .and_then(handle_jsonrpc);
+
+    // GET /beacon - Randomness beacon seed for the next block
+    let beacon_route = warp::path!("beacon")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_beacon);
+
+    // GET /balance/:address
+    let balance_route = warp::path!("balance" / String)
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_balance);
+
+    // GET /transactions/:address?direction=&from_height=&to_height=&cursor=&limit=&order= - Transaction history for an address
+    let tx_history_route = warp::path!("transactions" / String)
+        .and(warp::get())
+        .and(warp::query::<TxHistoryQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_tx_history);
+
+    // GET /tx/:hash - Get single transaction by hash
+    let tx_by_hash_route = warp::path!("tx" / String)
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_tx_by_hash);
+
+    // GET /economics
+    let economics_route = warp::path("economics")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_economics);
+
+    // GET /supply/total - Total supply endpoint for block explorers
+    let supply_total_route = warp::path!("supply" / "total")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_total_supply);
+
+    // GET /bridges
+    let bridges_route = warp::path("bridges")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_bridges);
+
+    // GET /bridge/:chain
+    let bridge_status_route = warp::path!("bridge" / String)
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_bridge_status);
+
+    // POST /bridge/submit
+    let bridge_tx_route = warp::path!("bridge" / "submit")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_submit_bridge_tx);
+
+    // GET /bridge/:chain/fee?amount=X
+    let bridge_fee_route = warp::path!("bridge" / String / "fee")
+        .and(warp::get())
+        .and(warp::query::<FeeQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_bridge_fee);
+
+    // GET /bridge/fees/treasury
+    let treasury_route = warp::path!("bridge" / "fees" / "treasury")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_treasury);
+
+    // GET /bridge/fees/statistics
+    let fee_stats_route = warp::path!("bridge" / "fees" / "statistics")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_fee_stats);
+
+    // ========= STAKING ROUTES =========
+    
+    // POST /staking/create_validator
+    let create_validator_route = warp::path!("staking" / "create_validator")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_create_validator);
+
+    // POST /staking/delegate (accepts raw bytes and parses manually for better error handling)
+    let delegate_route = warp::path!("staking" / "delegate")
+        .and(warp::post())
+        .and(warp::body::bytes())
+        .and(with_state(state.clone()))
+        .and_then(handle_delegate);
+
+    // GET /staking/validators?status=active|jailed&cursor=&limit=&order=
+    let validators_route = warp::path!("staking" / "validators")
+        .and(warp::get())
+        .and(warp::query::<ValidatorsQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_validators);
+
+    // GET /staking/delegations/:address
+    let delegations_route = warp::path!("staking" / "delegations" / String)
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_delegations);
+
+    // POST /staking/withdraw_rewards
+    let withdraw_rewards_route = warp::path!("staking" / "withdraw_rewards")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_withdraw_rewards);
+
+    // POST /staking/set_reward_wallet - Set wallet address for validator rewards
+    let set_reward_wallet_route = warp::path!("staking" / "set_reward_wallet")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_set_reward_wallet);
+
+    // POST /staking/rotate_key - Switch a validator's consensus key at a future height
+    let rotate_key_route = warp::path!("staking" / "rotate_key")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_rotate_key);
+
+    // GET /staking/reward_wallet/:validator_address - Get reward wallet for a validator
+    let get_reward_wallet_route = warp::path!("staking" / "reward_wallet" / String)
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_reward_wallet);
+
+    // POST /staking/undelegate (unstake - starts 21-day unbonding period)
+    let undelegate_route = warp::path!("staking" / "undelegate")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_undelegate);
+
+    // GET /staking/statistics
+    let staking_stats_route = warp::path!("staking" / "statistics")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_staking_statistics);
+
+    // ========= GOVERNANCE ROUTES =========
+
+    // POST /governance/propose
+    let propose_route = warp::path!("governance" / "propose")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_submit_proposal);
+
+    // POST /governance/vote
+    let vote_route = warp::path!("governance" / "vote")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_vote);
+
+    // GET /governance/proposals?status=&cursor=&limit=&order=
+    let proposals_route = warp::path!("governance" / "proposals")
+        .and(warp::get())
+        .and(warp::query::<ProposalsQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_proposals);
+
+    // GET /governance/proposal/:id
+    let proposal_route = warp::path!("governance" / "proposal" / u64)
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_proposal);
+
+    // POST /governance/tally/:id
+    let tally_route = warp::path!("governance" / "tally" / u64)
+        .and(warp::post())
+        .and(with_state(state.clone()))
+        .and_then(handle_tally_proposal);
+
+    // GET /governance/statistics
+    let gov_stats_route = warp::path!("governance" / "statistics")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_governance_statistics);
+
+    // POST /governance/execute/:id - Execute a passed proposal (hot-activation)
+    let execute_route = warp::path!("governance" / "execute" / u64)
+        .and(warp::post())
+        .and(with_state(state.clone()))
+        .and_then(handle_execute_proposal);
+
+    // GET /governance/features - Get current feature flags
+    let features_route = warp::path!("governance" / "features")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_get_features);
+
+    // Token Factory Routes
+    // POST /tokens/create
+    let create_token_route = warp::path!("tokens" / "create")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_create_token);
+
+    // POST /tokens/mint
+    let mint_token_route = warp::path!("tokens" / "mint")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_mint_token);
+
+    // POST /tokens/transfer
+    let transfer_token_route = warp::path!("tokens" / "transfer")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_transfer_token);
+
+    // POST /tokens/burn
+    let burn_token_route = warp::path!("tokens" / "burn")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_burn_token);
+
+    // GET /tokens/:denom/metadata
+    let token_metadata_route = warp::path!("tokens" / PathSegment / "metadata")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(|denom: PathSegment, state| handle_get_token_metadata(denom.0, state));
+
+    // GET /tokens/:denom/balance/:address
+    let token_balance_route = warp::path!("tokens" / PathSegment / "balance" / PathSegment)
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(|denom: PathSegment, address: PathSegment, state| handle_get_token_balance(denom.0, address.0, state));
+
+    // GET /tokens/list?creator=&cursor=&limit=&order=
+    let list_tokens_route = warp::path!("tokens" / "list")
+        .and(warp::get())
+        .and(warp::query::<TokensQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_list_tokens);
+
+    // === Faucet Routes (Challenge-Response Anti-Sybil) ===
+    
+    // GET /faucet/challenge/:address - Get challenge nonce to sign (step 1)
+    let faucet_challenge_route = warp::path!("faucet" / "challenge" / String)
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_faucet_challenge);
+    
+    // POST /faucet/claim - Claim SLTN with signed challenge (step 2)
+    let faucet_claim_route = warp::path!("faucet" / "claim")
+        .and(warp::post())
+        .and(warp::body::json::<FaucetClaimRequest>())
+        .and(with_state(state.clone()))
+        .and_then(handle_faucet_claim);
+    
+    // GET /faucet/status - Get faucet status and stats
+    let faucet_status_route = warp::path!("faucet" / "status")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_faucet_status);
+
+
+    // DEX Routes
+    // POST /dex/create_pair
+    let create_pair_route = warp::path!("dex" / "create_pair")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_create_pair);
+
+    // POST /dex/swap
+    let swap_route = warp::path!("dex" / "swap")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_swap);
+
+    // POST /dex/add_liquidity
+    let add_liquidity_route = warp::path!("dex" / "add_liquidity")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_add_liquidity);
+
+    // POST /dex/remove_liquidity
+    let remove_liquidity_route = warp::path!("dex" / "remove_liquidity")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_remove_liquidity);
+
+    // GET /dex/pool/:pair_id
+    let get_pool_route = warp::path!("dex" / "pool" / PathSegment)
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(|pair_id: PathSegment, state| handle_get_pool(pair_id.0, state));
+
+    // GET /dex/pools?cursor=&limit=&order=
+    let list_pools_route = warp::path!("dex" / "pools")
+        .and(warp::get())
+        .and(warp::query::<PoolsQuery>())
+        .and(with_state(state.clone()))
+        .and_then(handle_list_pools);
+
+    // GET /dex/price/:pair_id
+    let get_price_route = warp::path!("dex" / "price" / PathSegment)
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(|pair_id: PathSegment, state| handle_get_price(pair_id.0, state));
+
+    // Group routes to avoid type complexity limits
+    let core_routes = status_route
+        .or(tx_route)
+        .or(block_latest_route)
+        .or(block_route)
+        .or(blocks_list_route)
+        .or(events_route)
+        .or(simulate_route)
+        .or(stats_route)
+        .or(metrics_route)
+        .or(openapi_route)
+        .or(ws_route)
+        .or(jsonrpc_route)
+        .or(beacon_route)
+        .or(balance_route)
+        .or(tx_history_route)
+        .or(tx_by_hash_route)
+        .or(economics_route)
+        .or(supply_total_route)
+        .boxed();
+    
+    let bridge_routes = bridges_route
+        .or(bridge_status_route)
+        .or(bridge_tx_route)
+        .or(bridge_fee_route)
+        .or(treasury_route)
+        .or(fee_stats_route)
+        .boxed();
+    
+    let staking_routes = create_validator_route
+        .or(delegate_route)
+        .or(undelegate_route)
+        .or(validators_route)
+        .or(delegations_route)
+        .or(withdraw_rewards_route)
+        .or(set_reward_wallet_route)
+        .or(rotate_key_route)
+        .or(get_reward_wallet_route)
+        .or(staking_stats_route)
+        .boxed();
+    
+    let gov_routes = propose_route
+        .or(vote_route)
+        .or(proposals_route)
+        .or(proposal_route)
+        .or(tally_route)
+        .or(gov_stats_route)
+        .or(execute_route)
+        .or(features_route)
+        .boxed();
+    
+    let token_routes = create_token_route
+        .or(mint_token_route)
+        .or(transfer_token_route)
+        .or(burn_token_route)
+        .or(token_metadata_route)
+        .or(token_balance_route)
+        .or(list_tokens_route)
+        .boxed();
+    
+    let faucet_routes = faucet_challenge_route
+        .or(faucet_claim_route)
+        .or(faucet_status_route)
+        .boxed();
+    
+    let dex_routes = create_pair_route
+        .or(swap_route)
+        .or(add_liquidity_route)
+        .or(remove_liquidity_route)
+        .or(get_pool_route)
+        .or(list_pools_route)
+        .or(get_price_route)
+        .boxed();
+    
+    // Probes are polled constantly, so they sit outside the rate limiter
+    let health_routes = health_route
+        .or(health_live_route)
+        .or(health_ready_route)
+        .boxed();
+    
+    // Combine route groups with additional boxing to prevent type depth overflow
+    let api_routes_1 = core_routes
+        .or(bridge_routes)
+        .or(staking_routes)
+        .boxed();
+    
+    let api_routes_2 = gov_routes
+        .or(token_routes)
+        .or(faucet_routes)
+        .or(dex_routes)
+        .boxed();
+    
+    // Build secure CORS configuration from allowed_origins
+    let cors_config = {
+        let mut cors = warp::cors()
+            .allow_methods(vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"])
+            .allow_headers(vec!["Content-Type", "Authorization", "Accept"]);
+        
+        if state.allowed_origins.len() == 1 && state.allowed_origins[0] == "*" {
+            warn!("⚠️ CORS: allow_any_origin is INSECURE - use only for development!");
+            cors = cors.allow_any_origin();
+        } else {
+            for origin in &state.allowed_origins {
+                cors = cors.allow_origin(origin.as_str());
+            }
+            info!("🔒 CORS: Restricting to origins: {:?}", state.allowed_origins);
+        }
+        cors
+    };
+    
+    health_routes
+        .or(with_rate_limit()
+            .and(
+                api_routes_1
+                    .or(api_routes_2)
+            ))
+        .with(cors_config)
+        .recover(handle_rejection)
+        .with(warp::log::custom(move |info: warp::log::Info| {
+            state.metrics.rpc.record(info.path(), info.status().as_u16(), info.elapsed());
+        }))
+}
+
+/// Serve the admin API on its own listener
+///
+/// Every request needs the bearer token (when one is configured); on a TCP
+/// listener with mTLS the client certificate is checked during the handshake.
+/// Requests are logged for auditing.
+pub async fn run_admin_server(
+    config: AdminConfig,
+    state: Arc<NodeState>,
+    log_level: LogLevelHandle,
+) -> Result<()> {
+    let auth = Arc::new(config.auth.clone());
+    let authorized = warp::header::optional::<String>("authorization")
+        .and_then(move |authorization: Option<String>| {
+            let auth = auth.clone();
+            async move {
+                if auth.authorize(authorization.as_deref()) {
+                    Ok(())
+                } else {
+                    Err(warp::reject::custom(super::AdminUnauthorized))
+                }
+            }
+        })
+        .untuple_one();
+
+    // GET /peers - Gossipsub peer scores, reputation and bans
+    let peers_route = warp::path!("peers")
+        .and(warp::get())
+        .and(with_state(state.clone()))
+        .and_then(handle_admin_peers);
+
+    // POST /peers/ban {"peer_id", "reason"?}
+    let ban_route = warp::path!("peers" / "ban")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_admin_ban_peer);
+
+    // POST /peers/unban {"peer_id"}
+    let unban_route = warp::path!("peers" / "unban")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_admin_unban_peer);
+
+    // POST /faucet {"enabled"}
+    let faucet_route = warp::path!("faucet")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_faucet_toggle);
+
+    // POST /shards/expand {"additional"}
+    let expand_route = warp::path!("shards" / "expand")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_admin_expand_shards);
+
+    // POST /log_level {"level"}
+    let log_level_route = warp::path!("log_level")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(warp::any().map(move || log_level.clone()))
+        .and_then(handle_admin_log_level);
+
+    // POST /storage/prune {"height"?}
+    let prune_route = warp::path!("storage" / "prune")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(with_state(state.clone()))
+        .and_then(handle_admin_prune);
+
+    // POST /storage/compact
+    let compact_route = warp::path!("storage" / "compact")
+        .and(warp::post())
+        .and(with_state(state.clone()))
+        .and_then(handle_admin_compact);
+
+    // POST /snapshot - Online backup of the latest block
+    let snapshot_route = warp::path!("snapshot")
+        .and(warp::post())
+        .and(with_state(state.clone()))
+        .and_then(handle_admin_snapshot);
+
+    // POST /halt - Graceful shutdown
+    let halt_route = warp::path!("halt")
+        .and(warp::post())
+        .and(with_state(state.clone()))
+        .and_then(handle_admin_halt);
+
+    let routes = authorized
+        .and(
+            peers_route
+                .or(ban_route)
+                .or(unban_route)
+                .or(faucet_route)
+                .or(expand_route)
+                .or(log_level_route)
+                .or(prune_route)
+                .or(compact_route)
+                .or(snapshot_route)
+                .or(halt_route)
+        )
+        .recover(handle_rejection)
+        .with(warp::log::custom(|info: warp::log::Info| {
+            info!("🔐 Admin {} {} -> {}", info.method(), info.path(), info.status().as_u16());
+        }));
+
+    info!("🔐 Starting admin RPC on {}", config.listen);
+    match (&config.listen, &config.tls) {
+        (AdminListen::Tcp(addr), Some(tls)) => {
+            warp::serve(routes)
+                .tls()
+                .cert_path(&tls.cert_path)
+                .key_path(&tls.key_path)
+                .client_auth_required_path(&tls.client_ca_path)
+                .run(*addr)
+                .await;
+        }
+        (AdminListen::Tcp(addr), None) => warp::serve(routes).run(*addr).await,
+        (AdminListen::Unix(path), _) => {
+            let listener = crate::admin::bind_unix_socket(path)?;
+            let incoming = futures::stream::unfold(listener, |listener| async move {
+                let connection = listener.accept().await.map(|(stream, _)| stream);
+                Some((connection, listener))
+            });
+            warp::serve(routes).run_incoming(incoming).await;
+        }
+    }
+
+    Ok(())
+}
+
+fn with_state(
+    state: Arc<NodeState>,
+) -> impl Filter<Extract = (Arc<NodeState>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || state.clone())
+}
+
+/// Open WebSocket connections, bounded so subscribers can't exhaust the node
+static WS_CONNECTIONS: AtomicU64 = AtomicU64::new(0);
+const MAX_WS_CONNECTIONS: u64 = 1_000;
+const MAX_WS_SUBSCRIPTIONS: usize = 32;
+
+/// Decrements the connection count when a WebSocket (or its upgrade) ends
+struct WsConnectionSlot;
+
+impl Drop for WsConnectionSlot {
+    fn drop(&mut self) {
+        WS_CONNECTIONS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Client messages on `/ws`
+#[derive(serde::Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+enum WsRequest {
+    /// Subscribe to `topic` under the client-chosen `id`, optionally replaying
+    /// retained events from `from_height` first
+    Subscribe {
+        id: String,
+        topic: Topic,
+        #[serde(default)]
+        from_height: Option<u64>,
+    },
+    Unsubscribe {
+        id: String,
+    },
+}
+
+async fn handle_ws(
+    ws: warp::ws::Ws,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if WS_CONNECTIONS.fetch_add(1, Ordering::Relaxed) >= MAX_WS_CONNECTIONS {
+        WS_CONNECTIONS.fetch_sub(1, Ordering::Relaxed);
+        warn!("WebSocket connection limit ({}) reached", MAX_WS_CONNECTIONS);
+        return Err(warp::reject::custom(super::RateLimitExceeded));
+    }
+    let slot = WsConnectionSlot;
+    Ok(ws.on_upgrade(move |socket| async move {
+        let _slot = slot;
+        if let Err(e) = run_ws_connection(socket, &state).await {
+            debug!("WebSocket connection closed: {}", e);
+        }
+    }))
+}
+
+fn ws_event(id: &str, record: &EventRecord) -> warp::ws::Message {
+    warp::ws::Message::text(serde_json::json!({
+        "type": "event",
+        "id": id,
+        "seq": record.seq,
+        "height": record.height,
+        "event": record.event,
+    }).to_string())
+}
+
+fn ws_reply(reply: serde_json::Value) -> warp::ws::Message {
+    warp::ws::Message::text(reply.to_string())
+}
+
+/// Serve one subscriber
+///
+/// Events are written with back-pressure: while the client is slow to read,
+/// live events queue up in the hub's channel. If it falls behind that channel
+/// it is caught up from the hub's history, and if it falls behind the history
+/// it is told the height to resume from and disconnected.
+async fn run_ws_connection(socket: warp::ws::WebSocket, state: &NodeState) -> Result<()> {
+    use futures::{SinkExt, StreamExt};
+    use tokio::sync::broadcast::error::RecvError;
+
+    let (mut sink, mut stream) = socket.split();
+    let (mut live, mut cursor) = state.events.subscribe();
+    let mut last_height = state.events.earliest_height();
+    let mut subscriptions: HashMap<String, Topic> = HashMap::new();
+
+    loop {
+        tokio::select! {
+            message = stream.next() => {
+                let Some(message) = message else { break };
+                let message = message?;
+                if message.is_close() {
+                    break;
+                }
+                // Pings are answered by warp; binary frames are ignored
+                let Ok(text) = message.to_str() else { continue };
+                match serde_json::from_str::<WsRequest>(text) {
+                    Ok(WsRequest::Subscribe { id, topic, from_height }) => {
+                        if !subscriptions.contains_key(&id) && subscriptions.len() >= MAX_WS_SUBSCRIPTIONS {
+                            sink.send(ws_reply(serde_json::json!({
+                                "type": "error",
+                                "id": id,
+                                "message": format!("At most {} subscriptions per connection", MAX_WS_SUBSCRIPTIONS),
+                            }))).await?;
+                            continue;
+                        }
+                        // Replay only up to the cursor; later events arrive live
+                        let replay = match from_height {
+                            Some(height) => match state.events.replay(height, cursor) {
+                                Ok(records) => records,
+                                Err(earliest) => {
+                                    sink.send(ws_reply(serde_json::json!({
+                                        "type": "error",
+                                        "id": id,
+                                        "message": format!("Events before height {} are not retained", earliest),
+                                        "earliest_height": earliest,
+                                    }))).await?;
+                                    continue;
+                                }
+                            },
+                            None => Vec::new(),
+                        };
+                        let replay: Vec<_> = replay.into_iter().filter(|r| topic.matches(&r.event)).collect();
+                        sink.send(ws_reply(serde_json::json!({
+                            "type": "subscribed",
+                            "id": id,
+                            "replayed": replay.len(),
+                        }))).await?;
+                        for record in &replay {
+                            sink.send(ws_event(&id, record)).await?;
+                        }
+                        subscriptions.insert(id, topic);
+                    }
+                    Ok(WsRequest::Unsubscribe { id }) => {
+                        let reply = if subscriptions.remove(&id).is_some() {
+                            serde_json::json!({ "type": "unsubscribed", "id": id })
+                        } else {
+                            serde_json::json!({ "type": "error", "id": id, "message": "Unknown subscription" })
+                        };
+                        sink.send(ws_reply(reply)).await?;
+                    }
+                    Err(e) => {
+                        sink.send(ws_reply(serde_json::json!({
+                            "type": "error",
+                            "message": format!("Invalid request: {}", e),
+                        }))).await?;
+                    }
+                }
+            }
+            received = live.recv() => {
+                let records = match received {
+                    Ok(record) => vec![record],
+                    Err(RecvError::Lagged(_)) => match state.events.since(cursor) {
+                        Some(records) => records,
+                        None => {
+                            sink.send(ws_reply(serde_json::json!({
+                                "type": "error",
+                                "message": "Subscriber fell too far behind",
+                                "resume_from_height": last_height,
+                            }))).await?;
+                            break;
+                        }
+                    },
+                    Err(RecvError::Closed) => break,
+                };
+                let delivered = cursor;
+                for record in records.into_iter().filter(|r| r.seq > delivered) {
+                    cursor = record.seq;
+                    last_height = record.height;
+                    for (id, topic) in &subscriptions {
+                        if topic.matches(&record.event) {
+                            sink.send(ws_event(id, &record)).await?;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = sink.close().await;
+    Ok(())
+}
+
+async fn handle_status(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match state.get_status().await {
+        Ok(status) => Ok(warp::reply::json(&status)),
+        Err(e) => {
+            error!("Status query failed: {}", e);
+            Err(warp::reject())
+        }
+    }
+}
+
+async fn handle_submit_tx(
+    tx_request: TxRequest,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Convert either format to internal Transaction
+    let tx = match tx_request {
+        TxRequest::Wallet(wallet_tx) => {
+            info!("Processing wallet transaction: from={}, to={}, amount={}", 
+                wallet_tx.tx.from, wallet_tx.tx.to, wallet_tx.tx.amount);
+            Transaction {
+                from: wallet_tx.tx.from,
+                to: wallet_tx.tx.to,
+                amount: wallet_tx.tx.amount,
+                gas_fee: 0, // Zero-fee network
+                timestamp: wallet_tx.tx.timestamp,
+                nonce: wallet_tx.tx.nonce,
+                signature: Some(wallet_tx.signature),
+                public_key: Some(wallet_tx.public_key),
+                memo: None,
+            }
+        }
+        TxRequest::Simple(simple_tx) => {
+            info!("Processing simple transaction: from={}, to={}, amount={}", 
+                simple_tx.from, simple_tx.to, simple_tx.amount);
+            Transaction {
+                from: simple_tx.from,
+                to: simple_tx.to,
+                amount: simple_tx.amount,
+                gas_fee: simple_tx.gas_fee,
+                timestamp: simple_tx.timestamp,
+                nonce: simple_tx.nonce,
+                signature: simple_tx.signature,
+                public_key: None,
+                memo: None,
+            }
+        }
+    };
+    
+    match state.submit_transaction(tx).await {
+        Ok(hash) => Ok(warp::reply::json(&TxSubmitted { hash })),
+        Err(e) => {
+            warn!("Transaction rejected: {}", e);
+            Ok(warp::reply::json(&serde_json::json!({
+                "error": e.to_string(),
+                "status": 400
+            })))
+        }
+    }
+}
+
+async fn handle_get_block(
+    height: u64,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    use warp::http::StatusCode;
+    
+    {
+        let storage = state.storage.read().await;
+        if storage.is_pruned(height) {
+            return Ok(warp::reply::with_status(
+                warp::reply::json(&BlockPruned {
+                    error: storage.pruned_error(height).to_string(),
+                    code: "pruned".to_string(),
+                    height,
+                    header: storage.get_block_header(height).ok().flatten(),
+                    pruned_ranges: storage.pruned_ranges(),
+                }),
+                StatusCode::GONE
+            ));
+        }
+    }
+    
+    let blockchain = state.blockchain.read().await;
+    
+    match blockchain.get_block(height).await {
+        Some(block) => Ok(warp::reply::with_status(warp::reply::json(&block), StatusCode::OK)),
+        None => Err(warp::reject()),
+    }
+}
+
+async fn handle_get_latest_block(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let blockchain = state.blockchain.read().await;
+    let height = blockchain.get_height().await;
+    
+    let block = blockchain.get_block(height).await;
+    let message = block.is_none().then(|| "Genesis block".to_string());
+    Ok(warp::reply::json(&LatestBlockResponse { block, height, message }))
+}
+
+async fn handle_get_blocks_list(
+    query: BlocksListQuery,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let page = PageParams::new(
+        query.cursor,
+        Some(query.limit.unwrap_or(DEFAULT_BLOCKS_LIMIT)),
+        query.order.unwrap_or_default(),
+    );
+    let cursor = match page.cursor::<u64>() {
+        Ok(cursor) => cursor,
+        Err(e) => return Ok(invalid_cursor_reply(e)),
+    };
+    let limit = page.limit();
+
+    let blockchain = state.blockchain.read().await;
+    let current_height = blockchain.get_height().await;
+
+    // Heights still to visit, in page order
+    let mut heights: Box<dyn Iterator<Item = u64> + Send> = match (page.order, cursor) {
+        (Order::Desc, Some(cursor)) => Box::new((1..cursor.min(current_height + 1)).rev()),
+        (Order::Desc, None) => Box::new((1..=current_height.saturating_sub(query.offset)).rev()),
+        (Order::Asc, cursor) => Box::new(cursor.map_or(1, |c| c + 1)..=current_height),
+    };
+
+    let storage = state.storage.read().await;
+    let mut blocks = Vec::new();
+    let mut last_height = None;
+    for height in heights.by_ref() {
+        if storage.is_pruned(height) {
+            // Body is gone; report the header kept in storage
+            if let Ok(Some(header)) = storage.get_block_header(height) {
+                blocks.push(BlockSummary {
+                    height: header.index,
+                    hash: header.hash,
+                    timestamp: header.timestamp,
+                    tx_count: header.tx_count,
+                    validator: header.validator,
+                    pruned: true,
+                });
+            }
+        } else if let Some(block) = blockchain.get_block(height).await {
+            blocks.push(BlockSummary {
+                height: block.index,
+                hash: block.hash,
+                timestamp: block.timestamp,
+                tx_count: block.transactions.len() as u64,
+                validator: block.validator,
+                pruned: false,
+            });
+        }
+        last_height = Some(height);
+        if blocks.len() >= limit {
+            break;
+        }
+    }
+    let next_cursor = match heights.next() {
+        Some(_) => last_height.map(|h| h.to_string()),
+        None => None,
+    };
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&BlocksPage {
+            count: blocks.len(),
+            blocks,
+            total_height: current_height,
+            limit,
+            next_cursor,
+        }),
+        warp::http::StatusCode::OK
+    ))
+}
+
+async fn handle_get_events(
+    query: EventsQuery,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let attribute = match query.attr.as_deref() {
+        Some(attr) => match EventFilter::parse_attribute(attr) {
+            Some(attribute) => Some(attribute),
+            None => {
+                return Ok(warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({
+                        "error": "attr must be key:value",
+                        "status": 400
+                    })),
+                    warp::http::StatusCode::BAD_REQUEST
+                ));
+            }
+        },
+        None => None,
+    };
+    let filter = EventFilter {
+        event_type: query.event_type,
+        module: query.module,
+        attribute,
+        tx_hash: query.tx,
+    };
+    let limit = query.limit.clamp(1, MAX_EVENTS_LIMIT);
+
+    let storage = state.storage.read().await;
+    match storage.query_events(&filter, query.from_height, limit) {
+        Ok(page) => Ok(warp::reply::with_status(
+            warp::reply::json(&EventsPage {
+                count: page.events.len(),
+                events: page.events,
+                from_height: query.from_height,
+                next_height: page.next_height,
+            }),
+            warp::http::StatusCode::OK
+        )),
+        Err(e) => {
+            warn!("Event query failed: {}", e);
+            Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({
+                    "error": format!("Event query failed: {}", e),
+                    "status": 500
+                })),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR
+            ))
+        }
+    }
+}
+
+/// Dry-run an operation against a copy-on-write view of current state
+///
+/// Runs the same checks and module code as the real route and returns the
+/// balance changes and events it would produce, or its error. Nothing is committed.
+async fn handle_simulate(
+    request: SimulateRequest,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let features = state.get_feature_flags().await;
+    // Like /staking/delegate, consensus validators are registered in staking on first delegation
+    let consensus_stake = match &request.tx {
+        SimulatedTx::Delegate { validator_address, .. } => {
+            state.consensus.read().await.get_validator(validator_address).map(|v| v.stake)
+        }
+        _ => None,
+    };
+    let blockchain = state.blockchain.read().await;
+    let height = blockchain.get_height().await;
+
+    let disabled = match &request.tx {
+        SimulatedTx::CreateToken { .. } | SimulatedTx::MintToken { .. }
+        | SimulatedTx::TransferToken { .. } | SimulatedTx::BurnToken { .. }
+            if !features.token_factory_enabled => Some("Token Factory not enabled. Enable via governance proposal."),
+        SimulatedTx::Swap { .. } | SimulatedTx::AddLiquidity { .. } | SimulatedTx::RemoveLiquidity { .. }
+            if !features.native_dex_enabled => Some("Native DEX not enabled. Enable via governance proposal."),
+        _ => None,
+    };
+    if let Some(error) = disabled {
+        return Ok(warp::reply::json(&SimulationResult::rejected(height, error)));
+    }
+
+    // Transfers get the submission checks; the signature only when one is given
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    if let Some(tx) = request.transaction(now) {
+        let checked = NodeState::check_reserved_recipient(&tx).and_then(|_| {
+            if request.is_signed() { NodeState::verify_transaction_signature(&tx) } else { Ok(()) }
+        });
+        if let Err(e) = checked {
+            return Ok(warp::reply::json(&SimulationResult::rejected(height, e)));
+        }
+    }
+
+    let simulation = Simulation::new(
+        &blockchain,
+        &state.staking_manager,
+        &state.token_factory,
+        &state.native_dex,
+        &request,
+    ).await;
+    if let (SimulatedTx::Delegate { validator_address, .. }, Some(stake)) = (&request.tx, consensus_stake) {
+        let _ = simulation.staking().create_validator(
+            validator_address.clone(),
+            stake,
+            0.05, // Default 5% commission
+        ).await;
+    }
+
+    Ok(warp::reply::json(&simulation.run(&request).await))
+}
+
+async fn handle_get_stats(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let blockchain = state.blockchain.read().await;
+    let height = blockchain.get_height().await;
+    let stats = blockchain.get_stats().await;
+    
+    // Validator count
+    let validators = state.staking_manager.get_validators().await;
+    let validator_count = validators.len();
+    
+    // Get config for shard info
+    let config = state.config.read().await;
+    
+    let (pruning_mode, pruned_ranges) = {
+        let storage = state.storage.read().await;
+        (storage.pruning_mode().to_string(), storage.pruned_ranges())
+    };
+    
+    Ok(warp::reply::json(&StatsResponse {
+        height,
+        total_transactions: stats.total_transactions,
+        total_processed: stats.total_processed,
+        estimated_tps: stats.estimated_tps,
+        current_load: format!("{:.1}%", stats.current_load * 100.0),
+        validator_count,
+        shard_count: stats.shard_count,
+        healthy_shards: stats.healthy_shards,
+        max_shards: stats.max_shards,
+        pending_cross_shard: stats.pending_cross_shard,
+        total_accounts: stats.total_accounts,
+        should_expand: stats.should_expand,
+        pruning: pruning_mode,
+        pruned_ranges,
+        sharding_enabled: config.features.sharding_enabled,
+        block_time_seconds: 2,
+        gas_fees: "zero".to_string(),
+        network: "Sultan L1 Mainnet".to_string(),
+    }))
+}
+
+/// Run the readiness checks
+async fn health_report(state: &NodeState) -> HealthReport {
+    let (height, stats) = {
+        let blockchain = state.blockchain.read().await;
+        (blockchain.get_height().await, blockchain.get_stats().await)
+    };
+
+    let sync = match &state.block_sync_manager {
+        Some(block_sync) => {
+            let behind = block_sync.read().await.blocks_behind().await;
+            Check::sync(height, behind, state.health_max_sync_lag)
+        }
+        None => Check::skipped("sync", "P2P disabled, nothing to sync from"),
+    };
+
+    let storage = state.storage.read().await.check_writable();
+    let storage = Check::storage(storage.err().map(|e| format!("{:#}", e)));
+
+    let peers = match &state.p2p_network {
+        Some(p2p) => {
+            let p2p = p2p.read().await;
+            Check::peers(p2p.peer_count().await, p2p.is_healthy().await)
+        }
+        None => Check::skipped("peers", "P2P disabled"),
+    };
+
+    HealthReport::new(height, vec![
+        sync,
+        storage,
+        Check::shards(stats.healthy_shards, stats.shard_count),
+        peers,
+    ])
+}
+
+/// Health report the RPC server last refreshed (checked now if it hasn't yet)
+async fn cached_health_report(state: &NodeState) -> HealthReport {
+    if let Some(report) = state.health.read().clone() {
+        return report;
+    }
+    health_report(state).await
+}
+
+/// The OpenAPI document, built on first use
+fn openapi_document() -> warp::reply::Json {
+    static DOCUMENT: OnceLock<serde_json::Value> = OnceLock::new();
+    warp::reply::json(DOCUMENT.get_or_init(crate::openapi::document))
+}
+
+/// Detailed health: every check and its outcome, always 200
+async fn handle_health(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&cached_health_report(&state).await))
+}
+
+/// Liveness: the RPC server is up and serving
+async fn handle_health_live() -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::json(&Liveness { status: "alive".to_string() }))
+}
+
+/// Readiness: 200 when every check passes, 503 otherwise
+async fn handle_health_ready(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let report = cached_health_report(&state).await;
+    let status = if report.ready {
+        warp::http::StatusCode::OK
+    } else {
+        debug!("Not ready, failed checks: {:?}", report.failed());
+        warp::http::StatusCode::SERVICE_UNAVAILABLE
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&report), status))
+}
+
+/// Prometheus metrics, read from each subsystem at scrape time
+async fn handle_metrics(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut out = Exposition::new();
+
+    let (height, latest, stats, shards, mempool) = {
+        let blockchain = state.blockchain.read().await;
+        (
+            blockchain.get_height().await,
+            blockchain.get_latest_block().await.ok(),
+            blockchain.get_stats().await,
+            blockchain.get_shard_metrics().await,
+            blockchain.pending_count().await,
+        )
+    };
+    out.gauge("sultan_block_height", "Height of the latest block", height as f64);
+    if let Some(block) = latest {
+        out.gauge("sultan_last_block_timestamp_seconds", "Unix time of the latest block", block.timestamp as f64);
+    }
+    state.metrics.write(&mut out);
+    out.gauge("sultan_mempool_transactions", "Transactions waiting for a block", mempool as f64);
+    out.gauge("sultan_shards", "Active shards", stats.shard_count as f64);
+    out.gauge("sultan_shards_healthy", "Shards currently healthy", stats.healthy_shards as f64);
+    out.gauge("sultan_cross_shard_pending", "Cross-shard transfers queued", stats.pending_cross_shard as f64);
+    out.counter_family(
+        "sultan_shard_transactions_total",
+        "Transactions processed, by shard",
+        shards.iter().map(|shard| (vec![("shard", shard.id.to_string())], shard.processed)),
+    );
+    out.gauge_family(
+        "sultan_shard_healthy",
+        "1 if the shard is healthy",
+        shards.iter().map(|shard| (vec![("shard", shard.id.to_string())], if shard.healthy { 1.0 } else { 0.0 })),
+    );
+
+    if let Some(p2p) = &state.p2p_network {
+        let p2p = p2p.read().await;
+        out.gauge("sultan_p2p_peers", "Connected peers", p2p.peer_count().await as f64);
+        out.gauge("sultan_p2p_banned_peers", "Peers currently banned", p2p.banned_peer_count().await as f64);
+    }
+
+    if let Some(block_sync) = &state.block_sync_manager {
+        let sync = block_sync.read().await;
+        let stats = sync.get_statistics().await;
+        let current = match stats.sync_state {
+            SyncState::Synced => "synced",
+            SyncState::Syncing { .. } => "syncing",
+            SyncState::Ahead => "ahead",
+        };
+        out.gauge_family(
+            "sultan_sync_state",
+            "1 for the node's current sync state",
+            ["synced", "syncing", "ahead"].into_iter()
+                .map(|s| (vec![("state", s.to_string())], if s == current { 1.0 } else { 0.0 })),
+        );
+        out.gauge("sultan_sync_max_peer_height", "Highest block height reported by a peer", stats.max_peer_height as f64);
+        out.gauge("sultan_sync_pending_blocks", "Blocks waiting for votes", stats.pending_blocks as f64);
+        out.counter("sultan_sync_blocks_finalized_total", "Blocks finalized by vote", stats.blocks_synced);
+        out.counter("sultan_sync_votes_recorded_total", "Block votes accepted", stats.votes_recorded);
+        out.counter("sultan_sync_votes_rejected_total", "Block votes rejected", stats.votes_rejected);
+        out.histogram(
+            "sultan_vote_collection_seconds",
+            "Time from a block arriving for votes to its finalization",
+            sync.vote_collection_times(),
+        );
+    }
+
+    let storage = state.storage.read().await;
+    match storage.column_family_stats() {
+        Ok(families) => {
+            let by_family = |value: fn(&ColumnFamilyStats) -> u64| {
+                families.iter()
+                    .map(move |f| (vec![("cf", f.name.to_string())], value(f) as f64))
+                    .collect::<Vec<_>>()
+            };
+            out.gauge_family("sultan_rocksdb_estimated_keys", "Estimated keys, by column family", by_family(|f| f.estimated_keys));
+            out.gauge_family("sultan_rocksdb_live_data_bytes", "Estimated live data size, by column family", by_family(|f| f.live_data_bytes));
+            out.gauge_family("sultan_rocksdb_sst_files_bytes", "Total SST file size, by column family", by_family(|f| f.sst_files_bytes));
+            out.gauge_family("sultan_rocksdb_memtable_bytes", "Memtable size, by column family", by_family(|f| f.memtable_bytes));
+        }
+        Err(e) => warn!("Failed to read RocksDB stats for metrics: {}", e),
+    }
+    out.gauge("sultan_storage_block_cache_blocks", "Blocks in the storage block cache", storage.block_cache_len() as f64);
+    drop(storage);
+
+    Ok(warp::reply::with_header(out.finish(), "content-type", metrics::CONTENT_TYPE))
+}
+
+async fn handle_get_beacon(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let height = state.blockchain.read().await.get_height().await + 1;
+    let consensus = state.consensus.read().await;
+    Ok(warp::reply::json(&BeaconResponse {
+        height,
+        epoch: height / crate::beacon::BEACON_EPOCH_BLOCKS,
+        seed: hex::encode(consensus.beacon.seed_for_height(height)),
+        from_reveals: consensus.beacon.has_seed_for_height(height),
+    }))
+}
+
+async fn handle_get_balance(
+    address: String,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Sultan Chain unified blockchain - always uses sharded architecture
+    let blockchain = state.blockchain.read().await;
+    let balance = blockchain.get_balance(&address).await;
+    let nonce = blockchain.get_nonce(&address).await;
+    
+    Ok(warp::reply::json(&BalanceResponse { address, balance, nonce }))
+}
+
+async fn handle_get_tx_history(
+    address: String,
+    query: TxHistoryQuery,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let page = PageParams::new(query.cursor, query.limit, query.order.unwrap_or_default());
+
+    // First try in-memory cache; filters and cursors apply to the whole retained history
+    let blockchain = state.blockchain.read().await;
+    let mut transactions = blockchain.get_transaction_history(&address, MAX_HISTORY_PER_ADDRESS).await;
+    drop(blockchain);
+
+    // If empty, try persistent storage
+    if transactions.is_empty() {
+        let storage = state.storage.read().await;
+        if let Ok(stored_txs) = storage.get_transaction_history(&address, MAX_HISTORY_PER_ADDRESS) {
+            transactions = stored_txs;
+        }
+    }
+
+    // A self-transfer is indexed under the address once as sender and once as receiver
+    let mut seen = std::collections::HashSet::new();
+    transactions.retain(|tx| {
+        seen.insert(tx.hash.clone())
+            && match query.direction {
+                Some(TxDirection::Sent) => tx.from == address,
+                Some(TxDirection::Received) => tx.to == address,
+                None => true,
+            }
+            && query.from_height.is_none_or(|h| tx.block_height >= h)
+            && query.to_height.is_none_or(|h| tx.block_height <= h)
+    });
+
+    let page = match page.paginate(transactions, |tx| (tx.block_height, tx.hash.clone())) {
+        Ok(page) => page,
+        Err(e) => return Ok(invalid_cursor_reply(e)),
+    };
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&TxHistoryResponse {
+            address,
+            count: page.items.len(),
+            transactions: page.items,
+            next_cursor: page.next_cursor,
+        }),
+        warp::http::StatusCode::OK
+    ))
+}
+
+async fn handle_get_tx_by_hash(
+    hash: String,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let blockchain = state.blockchain.read().await;
+    
+    if let Some(tx) = blockchain.get_transaction_by_hash(&hash).await {
+        Ok(warp::reply::json(&tx))
+    } else {
+        Ok(warp::reply::json(&serde_json::json!({
+            "error": "Transaction not found",
+            "hash": hash
+        })))
+    }
+}
+
+async fn handle_get_economics(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let economics = state.economics.read().await;
+    
+    // Calculate total supply (genesis + inflation - burned)
+    let genesis_supply: u64 = 500_000_000_000_000_000; // 500M SLTN in base units
+    let total_supply = genesis_supply.saturating_sub(economics.total_burned);
+    
+    Ok(warp::reply::json(&EconomicsResponse {
+        current_inflation_rate: economics.current_inflation_rate,
+        inflation_percentage: format!("{:.1}%", economics.current_inflation_rate * 100.0),
+        current_burn_rate: economics.current_burn_rate,
+        burn_percentage: format!("{:.1}%", economics.current_burn_rate * 100.0),
+        validator_apy: economics.validator_apy,
+        apy_percentage: format!("{:.2}%", economics.validator_apy * 100.0),
+        total_burned: economics.total_burned,
+        years_since_genesis: economics.years_since_genesis,
+        is_deflationary: economics.is_deflationary(),
+        inflation_rate: "4.0% (fixed forever)".to_string(),
+        inflation_policy: "Fixed 4% annual inflation guarantees zero gas fees sustainable at 76M+ TPS".to_string(),
+        total_supply,
+        total_supply_formatted: format!("{:.0}", total_supply as f64 / 1_000_000_000.0),
+        circulating_supply: total_supply,
+        genesis_supply,
+    }))
+}
+
+/// Handler for /supply/total - Returns total supply for block explorers
+async fn handle_get_total_supply(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let economics = state.economics.read().await;
+    
+    // Genesis supply: 500M SLTN in base units (9 decimals)
+    let genesis_supply: u64 = 500_000_000_000_000_000;
+    let total_supply = genesis_supply.saturating_sub(economics.total_burned);
+    
+    Ok(warp::reply::json(&TotalSupplyResponse {
+        total_supply,
+        total_supply_sltn: total_supply as f64 / 1_000_000_000.0,
+        circulating_supply: total_supply,
+        circulating_supply_sltn: total_supply as f64 / 1_000_000_000.0,
+        genesis_supply,
+        genesis_supply_sltn: 500_000_000.0,
+        total_burned: economics.total_burned,
+        decimals: 9,
+        denom: "sltn".to_string(),
+    }))
+}
+
+async fn handle_get_bridges(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let bridges = state.bridge_manager.get_all_bridges().await;
+    let stats = state.bridge_manager.get_statistics().await;
+    
+    Ok(warp::reply::json(&BridgesResponse { bridges, statistics: stats }))
+}
+
+async fn handle_get_bridge_status(
+    chain: String,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match state.bridge_manager.get_bridge(&chain).await {
+        Some(bridge) => Ok(warp::reply::json(&bridge)),
+        None => Err(warp::reject()),
+    }
+}
+
+/// Most events one `/events` page returns (whole blocks may add a few more)
+const MAX_EVENTS_LIMIT: usize = 1_000;
+
+/// Blocks per `/blocks` page when the request gives no `limit`
+const DEFAULT_BLOCKS_LIMIT: usize = 20;
+
+async fn handle_get_bridge_fee(
+    chain: String,
+    query: FeeQuery,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match state.bridge_manager.calculate_fee(&chain, query.amount).await {
+        Ok(fee_breakdown) => Ok(warp::reply::json(&fee_breakdown)),
+        Err(e) => {
+            warn!("Fee calculation failed: {}", e);
+            Err(warp::reject())
+        }
+    }
+}
+
+async fn handle_get_treasury(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let treasury = state.bridge_manager.get_treasury_address().await;
+    Ok(warp::reply::json(&TreasuryResponse {
+        treasury_address: treasury,
+        description: "Sultan L1 Bridge Treasury - Receives all cross-chain bridge fees".to_string(),
+        usage: "Development, maintenance, security audits, and ecosystem growth".to_string(),
+    }))
+}
+
+async fn handle_get_fee_stats(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match state.bridge_manager.get_fee_statistics().await {
+        Ok(stats) => Ok(warp::reply::json(&stats)),
+        Err(e) => {
+            error!("Fee statistics query failed: {}", e);
+            Err(warp::reject())
+        }
+    }
+}
+
+async fn handle_submit_bridge_tx(
+    req: BridgeTxRequest,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Parse signature
+    let signature = match hex::decode(&req.signature) {
+        Ok(s) if s.len() == 64 => s,
+        _ => return Ok(warp::reply::json(&serde_json::json!({
+            "error": "Invalid signature (must be 64 bytes hex)"
+        }))),
+    };
+    
+    // Parse pubkey
+    let pubkey: [u8; 32] = match hex::decode(&req.pubkey) {
+        Ok(p) if p.len() == 32 => {
+            let mut arr = [0u8; 32];
+            arr.copy_from_slice(&p);
+            arr
+        },
+        _ => return Ok(warp::reply::json(&serde_json::json!({
+            "error": "Invalid pubkey (must be 32 bytes hex)"
+        }))),
+    };
+    
+    match state.bridge_manager.submit_bridge_transaction_with_signature(
+        req.source_chain,
+        req.dest_chain,
+        req.source_tx,
+        req.amount,
+        req.recipient,
+        signature,
+        pubkey,
+    ).await {
+        Ok(tx_id) => Ok(warp::reply::json(&BridgeTxSubmitted {
+            tx_id,
+            status: "pending".to_string(),
+        })),
+        Err(e) => {
+            warn!("Bridge transaction failed: {}", e);
+            Ok(warp::reply::json(&serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+// ========= STAKING HANDLERS =========
+
+async fn handle_create_validator(
+    req: CreateValidatorRequest,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Parse and validate Ed25519 public key (required for secure validator registration)
+    let pubkey: [u8; 32] = if let Some(ref pk_hex) = req.public_key {
+        // Validate hex length
+        if pk_hex.len() != 64 {
+            return Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({
+                    "error": "public_key must be 64 hex characters (32 bytes Ed25519)"
+                })),
+                warp::http::StatusCode::BAD_REQUEST,
+            ));
+        }
+        // Decode hex to bytes
+        match hex::decode(pk_hex) {
+            Ok(bytes) if bytes.len() == 32 => {
+                let mut arr = [0u8; 32];
+                arr.copy_from_slice(&bytes);
+                // Validate it's a valid Ed25519 public key
+                if ed25519_dalek::VerifyingKey::from_bytes(&arr).is_err() {
+                    return Ok(warp::reply::with_status(
+                        warp::reply::json(&serde_json::json!({
+                            "error": "public_key is not a valid Ed25519 public key"
+                        })),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    ));
+                }
+                arr
+            }
+            _ => {
+                return Ok(warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({
+                        "error": "Failed to decode public_key hex"
+                    })),
+                    warp::http::StatusCode::BAD_REQUEST,
+                ));
+            }
+        }
+    } else {
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({
+                "error": "public_key is required for validator registration"
+            })),
+            warp::http::StatusCode::BAD_REQUEST,
+        ));
+    };
+    
+    // Add to staking manager for rewards/delegation
+    match state.staking_manager.create_validator(
+        req.validator_address.clone(),
+        req.initial_stake,
+        req.commission_rate,
+    ).await {
+        Ok(_) => {
+            // Set reward wallet: use provided wallet or default to validator's own address
+            let reward_wallet = req.reward_wallet.clone().unwrap_or_else(|| req.validator_address.clone());
+            if let Err(e) = state.staking_manager.set_reward_wallet(&req.validator_address, reward_wallet.clone()).await {
+                warn!("Failed to set reward wallet for {}: {}", req.validator_address, e);
+            } else {
+                info!("💰 New validator {} rewards → {}", req.validator_address, reward_wallet);
+            }
+
+            // Also add to consensus engine for block production
+            // This unifies staking validators with consensus validators
+            // CRITICAL: Drop consensus lock BEFORE acquiring blockchain lock to prevent deadlock
+            // Lock ordering: always release consensus before acquiring blockchain
+            {
+                let mut consensus = state.consensus.write().await;
+                if let Err(e) = consensus.add_validator(req.validator_address.clone(), req.initial_stake, pubkey) {
+                    warn!("Validator {} added to staking but not consensus: {}", req.validator_address, e);
+                }
+            } // consensus lock dropped here
+            
+            // Record as transaction for history
+            {
+                let blockchain = state.blockchain.read().await;
+                let current_height = blockchain.get_height().await;
+                let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+                
+                use sha2::{Sha256, Digest};
+                let mut hasher = Sha256::new();
+                hasher.update(format!("create_validator_{}{}", req.validator_address, timestamp));
+                let hash_bytes = hasher.finalize();
+                let hash = format!("validator_{}", hex::encode(&hash_bytes[..16]));
+                
+                let tx = crate::sharded_blockchain_production::ConfirmedTransaction {
+                    hash,
+                    from: req.validator_address.clone(),
+                    to: "network".to_string(),
+                    amount: req.initial_stake,
+                    memo: Some("Create validator".to_string()),
+                    nonce: 0,
+                    timestamp,
+                    block_height: current_height,
+                    status: "confirmed".to_string(),
+                };
+                blockchain.record_transaction(tx).await;
+            }
+            
+            // Return both snake_case and camelCase for compatibility
+            Ok(warp::reply::with_status(
+                warp::reply::json(&ValidatorCreated {
+                    validator_address: req.validator_address.clone(),
+                    validator_address_camel: req.validator_address,
+                    stake: req.initial_stake.to_string(),
+                    commission: req.commission_rate,
+                    status: "active".to_string(),
+                    consensus: true,
+                }),
+                warp::http::StatusCode::OK,
+            ))
+        },
+        Err(e) => {
+            warn!("Create validator failed: {}", e);
+            Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({
+                    "error": format!("Failed to create validator: {}", e)
+                })),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
+async fn handle_delegate(
+    body: warp::hyper::body::Bytes,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Parse the JSON body here rather than in the filter, so a bad body gets the format hint
+    debug!("Delegate request body: {}", String::from_utf8_lossy(&body));
+    let request: DelegateRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            warn!("Failed to parse delegate request: {}", e);
+            return Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({
+                    "error": format!("Invalid request format ({}). Expected either {{ tx: {{ from, to, amount }}, signature, public_key }} or {{ delegator_address, validator_address, amount }}", e),
+                    "status": 400
+                })),
+                warp::http::StatusCode::BAD_REQUEST
+            ));
+        }
+    };
+
+    // Wallet requests are authenticated when they carry a signature
+    if let DelegateRequest::Wallet(wallet_tx) = &request {
+        if !wallet_tx.signature.is_empty() && !wallet_tx.public_key.is_empty() {
+            let memo = wallet_tx.tx.memo.as_deref().unwrap_or("delegate");
+            let message = api::delegate_message(&wallet_tx.tx.from, &wallet_tx.tx.to, wallet_tx.tx.amount, memo);
+            if let Err(e) = verify_request_signature(&wallet_tx.public_key, &wallet_tx.signature, &message) {
+                return Ok(warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({
+                        "error": format!("Signature verification failed: {}", e),
+                        "status": 401
+                    })),
+                    warp::http::StatusCode::UNAUTHORIZED
+                ));
+            }
+            info!("✅ Wallet delegation signature verified: from={}", wallet_tx.tx.from);
+        }
+    }
+
+    let (delegator, validator, amount) = request.stake();
+    let (delegator, validator) = (delegator.to_string(), validator.to_string());
+    if let Err(e) = validate_address(&delegator) {
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({
+                "error": format!("Invalid delegator address: {}", e),
+                "status": 400
+            })),
+            warp::http::StatusCode::BAD_REQUEST
+        ));
+    }
+    info!("Processing delegation: delegator={}, validator={}, amount={}", delegator, validator, amount);
+    
+    // Validate the request
+    if delegator.is_empty() || validator.is_empty() || amount == 0 {
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({
+                "error": "Missing required fields: delegator, validator, and amount must all be provided",
+                "status": 400
+            })),
+            warp::http::StatusCode::BAD_REQUEST
+        ));
+    }
+    
+    // Check if delegator has sufficient balance
+    let current_balance = {
+        let blockchain = state.blockchain.read().await;
+        blockchain.get_balance(&delegator).await
+    };
+    
+    if current_balance < amount {
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({
+                "error": format!("Insufficient balance: {} SLTN available, {} SLTN required", current_balance, amount),
+                "status": 400,
+                "available_balance": current_balance,
+                "required_amount": amount
+            })),
+            warp::http::StatusCode::BAD_REQUEST
+        ));
+    }
+    
+    // Deduct balance from delegator's account BEFORE staking
+    // This ensures the staked amount is removed from their available balance
+    {
+        let blockchain = state.blockchain.read().await;
+        if let Err(e) = blockchain.deduct_balance(&delegator, amount).await {
+            return Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({
+                    "error": format!("Failed to deduct balance: {}", e),
+                    "status": 500
+                })),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR
+            ));
+        }
+    }
+    
+    // Auto-register consensus validators in staking manager if not already registered
+    {
+        let consensus = state.consensus.read().await;
+        if consensus.is_validator(&validator) {
+            if let Some(v) = consensus.get_validator(&validator) {
+                // Register in staking manager if not exists
+                let _ = state.staking_manager.create_validator(
+                    validator.clone(),
+                    v.stake,
+                    0.05, // Default 5% commission
+                ).await;
+            }
+        }
+    }
+
+    // Generate a hash for the delegation transaction (its events are tagged with it)
+    use sha2::{Sha256, Digest};
+    let mut hasher = Sha256::new();
+    let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+    hasher.update(format!("{}{}{}{}", delegator, validator, amount, timestamp));
+    let hash_bytes = hasher.finalize();
+    let hash = format!("stake_{}", hex::encode(&hash_bytes[..16]));
+
+    match events::in_tx(hash.clone(), state.staking_manager.delegate(
+        delegator.clone(),
+        validator.clone(),
+        amount,
+    )).await {
+        Ok(_) => {
+            // Record this as a transaction in history
+            {
+                let blockchain = state.blockchain.read().await;
+                let current_height = blockchain.get_height().await;
+                
+                // Create a confirmed transaction record for the staking action
+                let stake_tx = crate::sharded_blockchain_production::ConfirmedTransaction {
+                    hash: hash.clone(),
+                    from: delegator.clone(),
+                    to: validator.clone(),
+                    amount,
+                    memo: Some("Stake delegation".to_string()),
+                    nonce: 0,
+                    timestamp,
+                    block_height: current_height,
+                    status: "confirmed".to_string(),
+                };
+                blockchain.record_transaction(stake_tx).await;
+            }
+            
+            // Persist staking state after successful delegation
+            {
+                let storage = state.storage.read().await;
+                if let Err(e) = state.staking_manager.persist_to_storage(&storage).await {
+                    warn!("⚠️ Failed to persist staking state: {}", e);
+                }
+            }
+            
+            Ok(warp::reply::with_status(
+                warp::reply::json(&Delegated {
+                    delegator,
+                    validator,
+                    amount,
+                    status: "delegated".to_string(),
+                    hash,
+                }),
+                warp::http::StatusCode::OK
+            ))
+        },
+        Err(e) => {
+            warn!("Delegation failed: {}", e);
+            Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({
+                    "error": e.to_string(),
+                    "status": 400
+                })),
+                warp::http::StatusCode::BAD_REQUEST
+            ))
+        }
+    }
+}
+
+/// Handle undelegate (unstake) - starts 21-day unbonding period
+/// When unbonding completes, tokens are returned to delegator's balance
+async fn handle_undelegate(
+    request: DelegateRequest,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let (delegator, validator, amount) = request.stake();
+    let (delegator, mut validator) = (delegator.to_string(), validator.to_string());
+
+    // If validator is not specified, find from delegations
+    if validator.is_empty() && !delegator.is_empty() {
+        let delegations = state.staking_manager.get_delegations(&delegator).await;
+        if let Some(first_delegation) = delegations.first() {
+            validator = first_delegation.validator_address.clone();
+        }
+    }
+
+    // Validate the request
+    if delegator.is_empty() || validator.is_empty() || amount == 0 {
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({
+                "error": "Missing required fields: delegator, validator, and amount must all be provided",
+                "status": 400,
+                "hint": "If validator is not provided, ensure delegator has an active delegation"
+            })),
+            warp::http::StatusCode::BAD_REQUEST
+        ));
+    }
+
+    // Get current block height for transaction recording
+    let current_height = {
+        let blockchain = state.blockchain.read().await;
+        blockchain.get_height().await
+    };
+
+    // Generate a hash for the unstaking transaction (its events are tagged with it)
+    use sha2::{Sha256, Digest};
+    let mut hasher = Sha256::new();
+    let timestamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
+    hasher.update(format!("unstake_{}{}{}{}", delegator, validator, amount, timestamp));
+    let hash_bytes = hasher.finalize();
+    let hash = format!("unstake_{}", hex::encode(&hash_bytes[..16]));
+
+    // Undelegate - this starts the 21-day unbonding period
+    match events::in_tx(hash.clone(), state.staking_manager.undelegate(
+        delegator.to_string(),
+        validator.to_string(),
+        amount,
+    )).await {
+        Ok(unbonding_entry) => {
+            // Tokens are now in the unbonding queue
+            // They will be returned to the delegator's balance after 21 days
+            // by the unbonding processor in produce_block
+            info!(
+                "🔓 Unbonding started: {} SLTN from {} → available at block {}",
+                amount / 1_000_000_000,
+                delegator,
+                unbonding_entry.completion_height
+            );
+            
+            // Record transaction
+            {
+                let blockchain = state.blockchain.read().await;
+                let stake_tx = crate::sharded_blockchain_production::ConfirmedTransaction {
+                    hash: hash.clone(),
+                    from: validator.to_string(),
+                    to: delegator.to_string(),
+                    amount,
+                    memo: Some("Unstake initiated - 21 day unbonding".to_string()),
+                    nonce: 0,
+                    timestamp,
+                    block_height: current_height,
+                    status: "unbonding".to_string(),
+                };
+                blockchain.record_transaction(stake_tx).await;
+            }
+            
+            // Persist staking state after successful undelegation
+            {
+                let storage = state.storage.read().await;
+                if let Err(e) = state.staking_manager.persist_to_storage(&storage).await {
+                    warn!("⚠️ Failed to persist staking state: {}", e);
+                }
+            }
+
+            Ok(warp::reply::with_status(
+                warp::reply::json(&Undelegated {
+                    delegator,
+                    validator,
+                    amount,
+                    status: "unbonding".to_string(),
+                    completion_height: unbonding_entry.completion_height,
+                    completion_time: unbonding_entry.completion_time,
+                    hash,
+                    message: "Unstaking initiated. Tokens will be available after 21-day unbonding period.".to_string(),
+                }),
+                warp::http::StatusCode::OK
+            ))
+        },
+        Err(e) => {
+            warn!("Undelegation failed: {}", e);
+            Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({
+                    "error": e.to_string(),
+                    "status": 400
+                })),
+                warp::http::StatusCode::BAD_REQUEST
+            ))
+        }
+    }
+}
+
+async fn handle_get_validators(
+    query: ValidatorsQuery,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut validators = state.staking_manager.get_validators().await;
+    
+    // If no staking validators registered, return consensus validators
+    if validators.is_empty() {
+        let consensus = state.consensus.read().await;
+        let consensus_validators = consensus.get_active_validators();
+        
+        // Convert consensus validators to staking format
+        validators = consensus_validators.iter().map(|v| {
+            crate::staking::ValidatorStake {
+                validator_address: v.address.clone(),
+                reward_wallet: None, // Genesis validators have no wallet until set
+                self_stake: v.stake,
+                delegated_stake: 0,
+                total_stake: v.stake,
+                commission_rate: 0.05, // Default 5%
+                rewards_accumulated: 0,
+                blocks_signed: v.blocks_signed,
+                blocks_missed: v.missed_blocks,
+                total_blocks_missed: v.missed_blocks, // Consensus doesn't track total separately
+                signed_window: v.signed_window.clone(),
+                jailed: !v.is_active,
+                jailed_until: 0,
+                created_at: 0,
+                last_reward_height: 0,
+            }
+        }).collect();
+    }
+    
+    // Calculate total stake for voting power percentages
+    let total_network_stake: u64 = validators.iter().map(|v| v.total_stake).sum();
+    
+    // Convert to enhanced info with computed fields
+    let enhanced: Vec<ValidatorInfo> = validators.iter().map(|v| {
+        // Uptime: blocks_signed / (blocks_signed + total_blocks_missed) * 100
+        // Uses total_blocks_missed (cumulative) not blocks_missed (consecutive)
+        // If no blocks yet, show 100% (new validator)
+        let total_blocks = v.blocks_signed + v.total_blocks_missed;
+        let uptime_percent = if total_blocks > 0 {
+            (v.blocks_signed as f64 / total_blocks as f64) * 100.0
+        } else {
+            100.0 // New validator, no blocks yet
+        };
+        
+        // Voting power: this validator's stake / total network stake * 100
+        let voting_power_percent = if total_network_stake > 0 {
+            (v.total_stake as f64 / total_network_stake as f64) * 100.0
+        } else {
+            0.0
+        };
+        
+        ValidatorInfo {
+            validator_address: v.validator_address.clone(),
+            reward_wallet: v.reward_wallet.clone(),
+            self_stake: v.self_stake,
+            delegated_stake: v.delegated_stake,
+            total_stake: v.total_stake,
+            commission_rate: v.commission_rate,
+            rewards_accumulated: v.rewards_accumulated,
+            blocks_signed: v.blocks_signed,
+            blocks_missed: v.blocks_missed,
+            jailed: v.jailed,
+            jailed_until: v.jailed_until,
+            created_at: v.created_at,
+            last_reward_height: v.last_reward_height,
+            uptime_percent,
+            signed_in_window: v.signed_window.signed_count(),
+            missed_in_window: v.signed_window.missed_count(),
+            window_uptime_percent: v.signed_window.uptime_percent(),
+            voting_power_percent,
+        }
+    }).collect();
+
+    // Voting power above is over the whole set; filter only what is listed
+    let enhanced: Vec<ValidatorInfo> = enhanced.into_iter()
+        .filter(|v| match query.status {
+            Some(ValidatorStatusFilter::Active) => !v.jailed,
+            Some(ValidatorStatusFilter::Jailed) => v.jailed,
+            None => true,
+        })
+        .collect();
+    let page = PageParams::new(query.cursor, query.limit, query.order.unwrap_or(Order::Asc));
+    let page = match page.paginate(enhanced, |v| v.validator_address.clone()) {
+        Ok(page) => page,
+        Err(e) => return Ok(invalid_cursor_reply(e)),
+    };
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&ValidatorsPage {
+            count: page.items.len(),
+            validators: page.items,
+            total_network_stake,
+            next_cursor: page.next_cursor,
+        }),
+        warp::http::StatusCode::OK
+    ))
+}
+
+async fn handle_get_delegations(
+    address: String,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let delegations = state.staking_manager.get_delegations(&address).await;
+    Ok(warp::reply::json(&delegations))
+}
+
+async fn handle_withdraw_rewards(
+    req: WithdrawRewardsRequest,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if req.is_validator {
+        match state.staking_manager.withdraw_validator_rewards(&req.address).await {
+            Ok((rewards, wallet)) => {
+                // Credit the rewards to the reward_wallet (minted via inflation)
+                let blockchain = state.blockchain.read().await;
+                if let Err(e) = blockchain.add_balance(&wallet, rewards).await {
+                    warn!("Failed to credit rewards to wallet {}: {}", wallet, e);
+                    return Ok(warp::reply::json(&serde_json::json!({
+                        "error": format!("Failed to credit rewards: {}", e),
+                        "status": "error"
+                    })));
+                }
+                info!("Validator {} rewards ({} SLTN) credited to wallet {}", 
+                      req.address, rewards / 1_000_000_000, wallet);
+                Ok(warp::reply::json(&RewardsWithdrawn::Validator {
+                    validator_address: req.address,
+                    reward_wallet: wallet,
+                    rewards_withdrawn: rewards,
+                    status: "success".to_string(),
+                }))
+            }
+            Err(e) => {
+                warn!("Withdraw validator rewards failed: {}", e);
+                Ok(warp::reply::json(&serde_json::json!({
+                    "error": e.to_string(),
+                    "status": "error"
+                })))
+            }
+        }
+    } else if let Some(validator) = req.validator_address {
+        match state.staking_manager.withdraw_delegator_rewards(&req.address, &validator).await {
+            Ok(rewards) => {
+                // Credit delegator rewards to their address
+                let blockchain = state.blockchain.read().await;
+                if let Err(e) = blockchain.add_balance(&req.address, rewards).await {
+                    warn!("Failed to credit delegator rewards: {}", e);
+                    return Ok(warp::reply::json(&serde_json::json!({
+                        "error": format!("Failed to credit rewards: {}", e),
+                        "status": "error"
+                    })));
+                }
+                Ok(warp::reply::json(&RewardsWithdrawn::Delegator {
+                    address: req.address,
+                    rewards_withdrawn: rewards,
+                    status: "success".to_string(),
+                }))
+            }
+            Err(e) => {
+                warn!("Withdraw delegator rewards failed: {}", e);
+                Ok(warp::reply::json(&serde_json::json!({
+                    "error": e.to_string(),
+                    "status": "error"
+                })))
+            }
+        }
+    } else {
+        Ok(warp::reply::json(&serde_json::json!({
+            "error": "validator_address required for delegator withdrawals",
+            "status": "error"
+        })))
+    }
+}
+
+/// Handle setting a validator's reward wallet
+/// 
+/// This allows validators to designate a wallet where their accumulated 
+/// APY rewards should be sent when withdrawn.
+/// 
+/// SECURITY: Requires signature verification to prevent unauthorized changes.
+async fn handle_set_reward_wallet(
+    req: SetRewardWalletRequest,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Verify timestamp is within 5 minutes to prevent replay attacks
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    
+    if req.timestamp > now + 300 || req.timestamp < now.saturating_sub(300) {
+        return Ok(warp::reply::json(&serde_json::json!({
+            "error": "Timestamp must be within 5 minutes of current time",
+            "status": "error"
+        })));
+    }
+
+    // Validate and decode public key
+    if req.public_key.len() != 64 {
+        return Ok(warp::reply::json(&serde_json::json!({
+            "error": "public_key must be 64 hex characters",
+            "status": "error"
+        })));
+    }
+    
+    let pubkey_bytes = match hex::decode(&req.public_key) {
+        Ok(b) if b.len() == 32 => {
+            let mut arr = [0u8; 32];
+            arr.copy_from_slice(&b);
+            arr
+        }
+        _ => {
+            return Ok(warp::reply::json(&serde_json::json!({
+                "error": "Invalid public_key hex",
+                "status": "error"
+            })));
+        }
+    };
+
+    let verifying_key = match ed25519_dalek::VerifyingKey::from_bytes(&pubkey_bytes) {
+        Ok(k) => k,
+        Err(_) => {
+            return Ok(warp::reply::json(&serde_json::json!({
+                "error": "Invalid Ed25519 public key",
+                "status": "error"
+            })));
+        }
+    };
+
+    // Verify the public key matches the validator address (or is registered for this validator)
+    // For genesis validators, we check if the pubkey is registered in consensus
+    let consensus = state.consensus.read().await;
+    let validator_pubkey = consensus.get_validator_pubkey(&req.validator_address);
+    drop(consensus);
+    
+    if let Some(registered_pubkey) = validator_pubkey {
+        if registered_pubkey != pubkey_bytes {
+            return Ok(warp::reply::json(&serde_json::json!({
+                "error": "Public key does not match registered validator key",
+                "status": "error"
+            })));
+        }
+    }
+    // Note: For new validators without registered pubkeys, we allow the operation
+    // since they just registered with this pubkey
+
+    // Verify signature
+    let message = api::set_reward_wallet_message(&req.validator_address, &req.reward_wallet, req.timestamp);
+    
+    use sha2::{Sha256, Digest};
+    let message_hash = Sha256::digest(message.as_bytes());
+    
+    let sig_bytes = match hex::decode(&req.signature) {
+        Ok(b) if b.len() == 64 => {
+            let mut arr = [0u8; 64];
+            arr.copy_from_slice(&b);
+            arr
+        }
+        _ => {
+            return Ok(warp::reply::json(&serde_json::json!({
+                "error": "Invalid signature hex (must be 128 chars)",
+                "status": "error"
+            })));
+        }
+    };
+
+    let signature = ed25519_dalek::Signature::from_bytes(&sig_bytes);
+
+    if verifying_key.verify_strict(&message_hash, &signature).is_err() {
+        return Ok(warp::reply::json(&serde_json::json!({
+            "error": "Invalid signature - must sign: set_reward_wallet:{validator}:{wallet}:{timestamp}",
+            "status": "error"
+        })));
+    }
+
+    // Signature verified - update reward wallet
+    match state.staking_manager.set_reward_wallet(&req.validator_address, req.reward_wallet.clone()).await {
+        Ok(()) => {
+            info!("Validator {} set reward wallet to {}", req.validator_address, req.reward_wallet);
+            Ok(warp::reply::json(&RewardWalletResponse {
+                validator_address: req.validator_address,
+                reward_wallet: Some(req.reward_wallet),
+                status: "success".to_string(),
+            }))
+        }
+        Err(e) => {
+            warn!("Set reward wallet failed: {}", e);
+            Ok(warp::reply::json(&serde_json::json!({
+                "error": e.to_string(),
+                "status": "error"
+            })))
+        }
+    }
+}
+
+/// Handle a validator consensus key rotation
+/// 
+/// The rotation goes on-chain as a system transaction; every node checks it
+/// against the validator's current key when the block arrives.
+/// `sultan-node rotate-key` prints a ready-made request body.
+async fn handle_rotate_key(
+    req: RotateKeyRequest,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    fn decode<const N: usize>(field: &str, value: &str) -> Result<[u8; N]> {
+        hex::decode(value).ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .with_context(|| format!("{} must be {} hex characters", field, N * 2))
+    }
+    fn parse(req: &RotateKeyRequest) -> Result<KeyRotation> {
+        Ok(KeyRotation {
+            validator: req.validator_address.clone(),
+            new_pubkey: decode("new_pubkey", &req.new_pubkey)?,
+            activation_height: req.activation_height,
+            signature: decode("signature", &req.signature)?,
+            new_key_signature: decode("new_key_signature", &req.new_key_signature)?,
+        })
+    }
+
+    let result = match parse(&req) {
+        Ok(rotation) => state.submit_key_rotation(rotation).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(tx_hash) => {
+            info!("🔑 Key rotation for {} queued (activates at height {})",
+                  req.validator_address, req.activation_height);
+            Ok(warp::reply::json(&KeyRotationQueued {
+                validator_address: req.validator_address,
+                activation_height: req.activation_height,
+                tx_hash,
+                status: "success".to_string(),
+            }))
+        }
+        Err(e) => {
+            warn!("Key rotation for {} rejected: {:#}", req.validator_address, e);
+            Ok(warp::reply::json(&serde_json::json!({
+                "error": format!("{:#}", e),
+                "status": "error"
+            })))
+        }
+    }
+}
+
+/// Handle getting a validator's reward wallet
+async fn handle_get_reward_wallet(
+    validator_address: String,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match state.staking_manager.get_reward_wallet(&validator_address).await {
+        Ok(wallet) => {
+            Ok(warp::reply::json(&RewardWalletResponse {
+                validator_address,
+                reward_wallet: wallet,
+                status: "success".to_string(),
+            }))
+        }
+        Err(e) => {
+            warn!("Get reward wallet failed: {}", e);
+            Ok(warp::reply::json(&serde_json::json!({
+                "error": e.to_string(),
+                "status": "error"
+            })))
+        }
+    }
+}
+
+async fn handle_staking_statistics(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let stats = state.staking_manager.get_statistics().await;
+    Ok(warp::reply::json(&stats))
+}
+
+// ========= GOVERNANCE HANDLERS =========
+
+async fn handle_submit_proposal(
+    req: SubmitProposalRequest,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    use crate::governance::ProposalType;
+
+    let proposal_type = match req.proposal_type.as_str() {
+        "parameter_change" => ProposalType::ParameterChange,
+        "software_upgrade" => ProposalType::SoftwareUpgrade,
+        "community_pool" => ProposalType::CommunityPool,
+        "text" => ProposalType::TextProposal,
+        _ => return Err(warp::reject()),
+    };
+
+    match state.governance_manager.submit_proposal(
+        req.proposer,
+        req.title,
+        req.description,
+        proposal_type,
+        req.initial_deposit,
+        req.parameters,
+        req.telegram_discussion_url,
+        req.discord_discussion_url,
+    ).await {
+        Ok(proposal_id) => {
+            state.publish_proposal_changes(state.blockchain.read().await.get_height().await).await;
+            Ok(warp::reply::json(&ProposalSubmitted {
+                proposal_id,
+                status: "submitted".to_string(),
+            }))
+        }
+        Err(e) => {
+            warn!("Submit proposal failed: {}", e);
+            Err(warp::reject())
+        }
+    }
+}
+
+async fn handle_vote(
+    req: VoteRequest,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    use crate::governance::VoteOption;
+
+    let vote_option = match req.option.as_str() {
+        "yes" => VoteOption::Yes,
+        "no" => VoteOption::No,
+        "abstain" => VoteOption::Abstain,
+        "no_with_veto" => VoteOption::NoWithVeto,
+        _ => return Err(warp::reject()),
+    };
+
+    match state.governance_manager.vote(
+        req.proposal_id,
+        req.voter.clone(),
+        vote_option,
+        req.voting_power,
+    ).await {
+        Ok(_) => Ok(warp::reply::json(&VoteRecorded {
+            proposal_id: req.proposal_id,
+            voter: req.voter,
+            status: "voted".to_string(),
+        })),
+        Err(e) => {
+            warn!("Vote failed: {}", e);
+            Err(warp::reject())
+        }
+    }
+}
+
+async fn handle_get_proposals(
+    query: ProposalsQuery,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut proposals = state.governance_manager.get_all_proposals().await;
+    if let Some(status) = &query.status {
+        proposals.retain(|p| &p.status == status);
+    }
+    let page = PageParams::new(query.cursor, query.limit, query.order.unwrap_or_default());
+    let page = match page.paginate(proposals, |p| p.id) {
+        Ok(page) => page,
+        Err(e) => return Ok(invalid_cursor_reply(e)),
+    };
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&ProposalsPage {
+            count: page.items.len(),
+            proposals: page.items,
+            next_cursor: page.next_cursor,
+        }),
+        warp::http::StatusCode::OK
+    ))
+}
+
+async fn handle_get_proposal(
+    proposal_id: u64,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match state.governance_manager.get_proposal(proposal_id).await {
+        Some(proposal) => Ok(warp::reply::json(&proposal)),
+        None => Err(warp::reject()),
+    }
+}
+
+async fn handle_tally_proposal(
+    proposal_id: u64,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match state.governance_manager.tally_proposal(proposal_id).await {
+        Ok(tally) => {
+            state.publish_proposal_changes(state.blockchain.read().await.get_height().await).await;
+            Ok(warp::reply::json(&tally))
+        }
+        Err(e) => {
+            warn!("Tally failed: {}", e);
+            Err(warp::reject())
+        }
+    }
+}
+
+async fn handle_governance_statistics(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let stats = state.governance_manager.get_statistics().await;
+    Ok(warp::reply::json(&stats))
+}
+
+/// Execute a passed proposal - this is where hot-activation happens
+/// 
+/// When a ParameterChange proposal with `features.*` keys passes,
+/// this endpoint will:
+/// 1. Execute the proposal in governance
+/// 2. Call NodeState::activate_feature() to actually enable the feature
+/// 3. Persist the config change to disk
+async fn handle_execute_proposal(
+    proposal_id: u64,
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    use warp::http::StatusCode;
+    
+    // First, get the proposal to check for feature flags
+    let proposal = match state.governance_manager.get_proposal(proposal_id).await {
+        Some(p) => p,
+        None => {
+            return Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({
+                    "error": "Proposal not found",
+                    "proposal_id": proposal_id
+                })),
+                StatusCode::NOT_FOUND
+            ));
+        }
+    };
+    
+    // Check if proposal has passed
+    if proposal.status != crate::governance::ProposalStatus::Passed {
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({
+                "error": "Proposal has not passed",
+                "proposal_id": proposal_id,
+                "status": format!("{:?}", proposal.status)
+            })),
+            StatusCode::BAD_REQUEST
+        ));
+    }
+    
+    // Execute the proposal with staking integration
+    if let Err(e) = state.governance_manager.execute_proposal_with_staking(
+        proposal_id,
+        &state.staking_manager,
+    ).await {
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({
+                "error": format!("Execution failed: {}", e),
+                "proposal_id": proposal_id
+            })),
+            StatusCode::INTERNAL_SERVER_ERROR
+        ));
+    }
+    state.publish_proposal_changes(state.blockchain.read().await.get_height().await).await;
+    
+    // Now handle feature flag activations
+    let mut activated_features = Vec::new();
+    if let Some(params) = &proposal.parameters {
+        for (key, value) in params {
+            if key.starts_with("features.") {
+                let feature_name = key.strip_prefix("features.").unwrap();
+                let enabled: bool = value.parse().unwrap_or(false);
+                
+                if let Err(e) = state.activate_feature(feature_name, enabled).await {
+                    warn!("Failed to activate feature {}: {}", feature_name, e);
+                } else {
+                    activated_features.push(FeatureActivation {
+                        feature: feature_name.to_string(),
+                        enabled,
+                    });
+                }
+            }
+        }
+    }
+    
+    info!("✅ Proposal #{} executed successfully", proposal_id);
+    
+    Ok(warp::reply::with_status(
+        warp::reply::json(&ProposalExecuted {
+            success: true,
+            proposal_id,
+            title: proposal.title,
+            activated_features,
+        }),
+        StatusCode::OK
+    ))
+}
+
+/// Get current feature flags configuration
+async fn handle_get_features(
+    state: Arc<NodeState>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let features = state.get_feature_flags().await;
+    
+    Ok(warp::reply::json(&FeaturesResponse {
+        features: EnabledFeatures {
+            sharding_enabled: features.sharding_enabled,
+            governance_enabled: features.governance_enabled,
+            bridges_enabled: features.bridges_enabled,
+            smart_contracts_enabled: features.wasm_contracts_enabled,
+            evm_contracts_enabled: features.evm_contracts_enabled,
+            quantum_signatures_enabled: features.quantum_signatures_enabled,
+            ibc_enabled: features.ibc_enabled,
+        },
+    }))
+}
+
+// ========= JSON-RPC 2.0 =========
+
+/// Largest `/rpc` request body (a full batch of signed transactions fits easily)
+const MAX_JSONRPC_BODY: u64 = 1024 * 1024;
+
+async fn handle_jsonrpc(
+    body: warp::hyper::body::Bytes,
+    addr: Option<SocketAddr>,
+    state: Arc<NodeState>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    use warp::http::StatusCode;
+    use warp::Reply;
+
+    let (calls, single) = match jsonrpc::parse_body(&body) {
+        Ok(Body::Batch(calls)) => (calls, false),
+        Ok(Body::Single(call)) => (vec![call], true),
+        Err(response) => return Ok(warp::reply::json(&response).into_response()),
+    };
+
+    // The route counted the request once; every further call in the batch counts
+    // too, and simulations count as much as on `/simulate`
+    let simulations = calls.iter().filter(|call| call["method"] == "chain_simulate").count();
+    let cost = calls.len() - 1 + simulations * (SIMULATE_COST - 1);
+    let ip = addr.map(|a| a.ip().to_string()).unwrap_or_else(|| "unknown".to_string());
+    if !get_rate_limiter().write().await.check_rate_limit(&ip, cost) {
+        warn!("Rate limit exceeded for IP: {} (JSON-RPC batch of {})", ip, calls.len());
+        let error = RpcError::new(jsonrpc::RATE_LIMITED, "Rate limit exceeded - try again later");
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&Response::failure(Value::Null, error)),
+            StatusCode::TOO_MANY_REQUESTS,
+        ).into_response());
+    }
+
+    if single {
+        let call = calls.into_iter().next().expect("one call");
+        return Ok(match run_jsonrpc_call(call, &state).await {
+            Some(response) => warp::reply::json(&response).into_response(),
+            None => StatusCode::NO_CONTENT.into_response(),
+        });
+    }
+
+    // Calls run in order so a batch can carry consecutive nonces
+    let mut responses = Vec::with_capacity(calls.len());
+    for call in calls {
+        responses.extend(run_jsonrpc_call(call, &state).await);
+    }
+    if responses.is_empty() {
+        return Ok(StatusCode::NO_CONTENT.into_response());
+    }
+    Ok(warp::reply::json(&responses).into_response())
+}
+
+/// Run one call, returning its response unless it is a notification
+async fn run_jsonrpc_call(call: Value, state: &Arc<NodeState>) -> Option<Response> {
+    let request = match jsonrpc::parse_request(call) {
+        Ok(request) => request,
+        Err(response) => return Some(response),
+    };
+    let outcome = dispatch_jsonrpc(&request.method, Params::new(request.params), state.clone()).await;
+    if let Err(e) = &outcome {
+        debug!("JSON-RPC {} failed: {}", request.method, e);
+    }
+    request.id.map(|id| Response { id, outcome })
+}
+
+/// Call the REST handler behind `method`
+///
+/// Methods are named `<namespace>_<operation>` after the REST route. Named
+/// parameters use the REST path and query names; POST methods take the REST
+/// request body as params. Admin operations live on the admin listener and are not exposed.
+async fn dispatch_jsonrpc(method: &str, params: Params, state: Arc<NodeState>) -> Result<Value, RpcError> {
+    use warp::Reply;
+
+    let reply = match method {
+        // Chain
+        "chain_getStatus" => handle_status(state).await.map(Reply::into_response),
+        "chain_submitTransaction" => handle_submit_tx(params.body_as()?, state).await.map(Reply::into_response),
+        "chain_getBlock" => handle_get_block(params.get(0, "height")?, state).await.map(Reply::into_response),
+        "chain_getLatestBlock" => handle_get_latest_block(state).await.map(Reply::into_response),
+        "chain_getBlocks" => handle_get_blocks_list(params.options(0)?, state).await.map(Reply::into_response),
+        "chain_getEvents" => handle_get_events(params.options(0)?, state).await.map(Reply::into_response),
+        "chain_simulate" => handle_simulate(params.body_as()?, state).await.map(Reply::into_response),
+        "chain_getStats" => handle_get_stats(state).await.map(Reply::into_response),
+        "chain_getBeacon" => handle_get_beacon(state).await.map(Reply::into_response),
+        "chain_getBalance" => handle_get_balance(params.get(0, "address")?, state).await.map(Reply::into_response),
+        "chain_getTransactions" => {
+            handle_get_tx_history(params.get(0, "address")?, params.options(1)?, state).await.map(Reply::into_response)
+        }
+        "chain_getTransaction" => handle_get_tx_by_hash(params.get(0, "hash")?, state).await.map(Reply::into_response),
+        "chain_getEconomics" => handle_get_economics(state).await.map(Reply::into_response),
+        "chain_getTotalSupply" => handle_get_total_supply(state).await.map(Reply::into_response),
+
+        // Bridges
+        "bridge_list" => handle_get_bridges(state).await.map(Reply::into_response),
+        "bridge_getStatus" => handle_get_bridge_status(params.get(0, "chain")?, state).await.map(Reply::into_response),
+        "bridge_submit" => handle_submit_bridge_tx(params.body_as()?, state).await.map(Reply::into_response),
+        "bridge_getFee" => {
+            handle_get_bridge_fee(params.get(0, "chain")?, params.options(1)?, state).await.map(Reply::into_response)
+        }
+        "bridge_getTreasury" => handle_get_treasury(state).await.map(Reply::into_response),
+        "bridge_getFeeStatistics" => handle_get_fee_stats(state).await.map(Reply::into_response),
+
+        // Staking
+        "staking_createValidator" => handle_create_validator(params.body_as()?, state).await.map(Reply::into_response),
+        "staking_delegate" => {
+            handle_delegate(params.body()?.to_string().into(), state).await.map(Reply::into_response)
+        }
+        "staking_undelegate" => handle_undelegate(params.body_as()?, state).await.map(Reply::into_response),
+        "staking_getValidators" => handle_get_validators(params.options(0)?, state).await.map(Reply::into_response),
+        "staking_getDelegations" => {
+            handle_get_delegations(params.get(0, "address")?, state).await.map(Reply::into_response)
+        }
+        "staking_withdrawRewards" => handle_withdraw_rewards(params.body_as()?, state).await.map(Reply::into_response),
+        "staking_setRewardWallet" => handle_set_reward_wallet(params.body_as()?, state).await.map(Reply::into_response),
+        "staking_rotateKey" => handle_rotate_key(params.body_as()?, state).await.map(Reply::into_response),
+        "staking_getRewardWallet" => {
+            handle_get_reward_wallet(params.get(0, "validator_address")?, state).await.map(Reply::into_response)
+        }
+        "staking_getStatistics" => handle_staking_statistics(state).await.map(Reply::into_response),
+
+        // Governance
+        "governance_propose" => handle_submit_proposal(params.body_as()?, state).await.map(Reply::into_response),
+        "governance_vote" => handle_vote(params.body_as()?, state).await.map(Reply::into_response),
+        "governance_getProposals" => handle_get_proposals(params.options(0)?, state).await.map(Reply::into_response),
+        "governance_getProposal" => {
+            handle_get_proposal(params.get(0, "proposal_id")?, state).await.map(Reply::into_response)
+        }
+        "governance_tally" => handle_tally_proposal(params.get(0, "proposal_id")?, state).await.map(Reply::into_response),
+        "governance_getStatistics" => handle_governance_statistics(state).await.map(Reply::into_response),
+        "governance_execute" => {
+            handle_execute_proposal(params.get(0, "proposal_id")?, state).await.map(Reply::into_response)
+        }
+        "governance_getFeatures" => handle_get_features(state).await.map(Reply::into_response),
+
+        // Token factory
+        "tokens_create" => handle_create_token(params.body_as()?, state).await.map(Reply::into_response),
+        "tokens_mint" => handle_mint_token(params.body_as()?, state).await.map(Reply::into_response),
+        "tokens_transfer" => handle_transfer_token(params.body_as()?, state).await.map(Reply::into_response),
+        "tokens_burn" => handle_burn_token(params.body_as()?, state).await.map(Reply::into_response),
+        "tokens_getMetadata" => handle_get_token_metadata(params.get(0, "denom")?, state).await.map(Reply::into_response),
+        "tokens_getBalance" => {
+            handle_get_token_balance(params.get(0, "denom")?, params.get(1, "address")?, state).await.map(Reply::into_response)
+        }
+        "tokens_list" => handle_list_tokens(params.options(0)?, state).await.map(Reply::into_response),
+
+        // Faucet
+        "faucet_getChallenge" => handle_faucet_challenge(params.get(0, "address")?, state).await.map(Reply::into_response),
+        "faucet_claim" => handle_faucet_claim(params.body_as()?, state).await.map(Reply::into_response),
+        "faucet_getStatus" => handle_faucet_status(state).await.map(Reply::into_response),
+
+        // DEX
+        "dex_createPair" => handle_create_pair(params.body_as()?, state).await.map(Reply::into_response),
+        "dex_swap" => handle_swap(params.body_as()?, state).await.map(Reply::into_response),
+        "dex_addLiquidity" => handle_add_liquidity(params.body_as()?, state).await.map(Reply::into_response),
+        "dex_removeLiquidity" => handle_remove_liquidity(params.body_as()?, state).await.map(Reply::into_response),
+        "dex_getPool" => handle_get_pool(params.get(0, "pair_id")?, state).await.map(Reply::into_response),
+        "dex_listPools" => handle_list_pools(params.options(0)?, state).await.map(Reply::into_response),
+        "dex_getPrice" => handle_get_price(params.get(0, "pair_id")?, state).await.map(Reply::into_response),
+
+        // Node (transport routes and the Prometheus text are REST only)
+        "node_getHealth" => handle_health(state).await.map(Reply::into_response),
+        "node_getLiveness" => handle_health_live().await.map(Reply::into_response),
+        "node_getReadiness" => handle_health_ready(state).await.map(Reply::into_response),
+        "node_getOpenApi" => Ok(openapi_document().into_response()),
+
+        _ => return Err(RpcError::method_not_found(method)),
+    };
+    jsonrpc_result(reply).await
+}
+
+/// Turn a REST handler's reply into a JSON-RPC result
+///
+/// REST handlers report failures as an HTTP error status or as a body with an
+/// `error` message; both become a JSON-RPC error carrying the REST body as `data`.
+async fn jsonrpc_result(reply: Result<warp::reply::Response, warp::Rejection>) -> Result<Value, RpcError> {
+    use warp::http::StatusCode;
+
+    let response = match reply {
+        Ok(response) => response,
+        Err(rejection) if rejection.is_not_found() => {
+            return Err(RpcError::new(jsonrpc::NOT_FOUND, "Not found"));
+        }
+        Err(rejection) => {
+            warn!("Unhandled JSON-RPC rejection: {:?}", rejection);
+            return Err(RpcError::new(jsonrpc::INTERNAL_ERROR, "Internal error"));
+        }
+    };
+    let status = response.status();
+    let bytes = warp::hyper::body::to_bytes(response.into_body()).await
+        .map_err(|e| RpcError::new(jsonrpc::INTERNAL_ERROR, e.to_string()))?;
+    let body: Value = serde_json::from_slice(&bytes)
+        .map_err(|e| RpcError::new(jsonrpc::INTERNAL_ERROR, format!("Invalid handler response: {}", e)))?;
+
+    let error = body.get("error").and_then(Value::as_str).map(str::to_string);
+    if status.is_success() && error.is_none() {
+        return Ok(body);
+    }
+    let code = match status {
+        StatusCode::NOT_FOUND => jsonrpc::NOT_FOUND,
+        StatusCode::GONE => jsonrpc::PRUNED,
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => jsonrpc::UNAUTHORIZED,
+        StatusCode::TOO_MANY_REQUESTS => jsonrpc::RATE_LIMITED,
+        status if status.is_server_error() => jsonrpc::INTERNAL_ERROR,
+        _ => jsonrpc::REJECTED,
+    };
+    let message = error.unwrap_or_else(|| status.canonical_reason().unwrap_or("Request failed").to_string());
+    Err(RpcError::new(code, message).with_data(body))
+}
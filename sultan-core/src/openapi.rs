@@ -0,0 +1,480 @@
+//! OpenAPI 3 document of the REST routes
+//!
+//! Schemas are generated from the request and reply types in `api` and the
+//! module types they embed, so the document follows the structs. The route
+//! table in `document` is kept by hand next to the warp filters in `node::rpc`
+//! (`tests/rpc_routes.rs` checks it against them). Each operation is named
+//! after its JSON-RPC method, so `operationId` doubles as the method to call
+//! over `/rpc`, except for the [`REST_ONLY`] ones.
+
+use crate::api::*;
+use crate::blockchain::Block;
+use crate::bridge_fees::{FeeBreakdown, FeeStatistics};
+use crate::bridge_integration::BridgeStatus;
+use crate::governance::{GovernanceStatistics, Proposal, TallyResult};
+use crate::health::{HealthReport, Liveness};
+use crate::sharded_blockchain_production::ConfirmedTransaction;
+use crate::simulate::{SimulateRequest, SimulationResult};
+use crate::staking::{Delegation, StakingStatistics};
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+
+/// Operations with no JSON-RPC method: the transports themselves and the
+/// Prometheus text exposition
+pub const REST_ONLY: &[&str] = &["node_jsonRpc", "node_subscribe", "node_getMetrics"];
+
+/// Builds an OpenAPI 3 document, collecting component schemas as operations are added
+pub struct ApiDoc {
+    gen: SchemaGenerator,
+    paths: Map<String, Value>,
+}
+
+impl Default for ApiDoc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApiDoc {
+    pub fn new() -> Self {
+        Self {
+            gen: SchemaSettings::openapi3().into_generator(),
+            paths: Map::new(),
+        }
+    }
+
+    /// Add a GET operation; `path` uses OpenAPI templates (`/block/{height}`)
+    pub fn get(&mut self, path: &str, operation_id: &str, summary: &str) -> Operation<'_> {
+        self.operation("get", path, operation_id, summary)
+    }
+
+    /// Add a POST operation
+    pub fn post(&mut self, path: &str, operation_id: &str, summary: &str) -> Operation<'_> {
+        self.operation("post", path, operation_id, summary)
+    }
+
+    fn operation(&mut self, method: &'static str, path: &str, operation_id: &str, summary: &str) -> Operation<'_> {
+        let error = self.schema::<ErrorResponse>();
+        let tag = operation_id.split('_').next().unwrap_or(operation_id);
+        let item = self.paths.entry(path.to_string()).or_insert_with(|| json!({}));
+        item[method] = json!({
+            "operationId": operation_id,
+            "summary": summary,
+            "tags": [tag],
+            "responses": {
+                "default": {
+                    "description": "Error",
+                    "content": { "application/json": { "schema": error } }
+                }
+            }
+        });
+        Operation { doc: self, path: path.to_string(), method }
+    }
+
+    /// Schema of `T`, a `$ref` into the components for named types
+    fn schema<T: JsonSchema>(&mut self) -> Value {
+        let mut schema = self.gen.subschema_for::<T>();
+        // The generator only runs its visitors over root schemas
+        for visitor in self.gen.visitors_mut() {
+            visitor.visit_schema(&mut schema);
+        }
+        serde_json::to_value(schema).expect("schemas serialize")
+    }
+
+    /// Inline schema of `T`, for query structs whose properties become parameters
+    fn inline_schema<T: JsonSchema>(&mut self) -> Value {
+        let mut schema = T::json_schema(&mut self.gen);
+        for visitor in self.gen.visitors_mut() {
+            visitor.visit_schema(&mut schema);
+        }
+        serde_json::to_value(schema).expect("schemas serialize")
+    }
+
+    pub fn finish(mut self) -> Value {
+        let mut schemas = Map::new();
+        for (name, mut schema) in self.gen.take_definitions() {
+            for visitor in self.gen.visitors_mut() {
+                visitor.visit_schema(&mut schema);
+            }
+            schemas.insert(name, serde_json::to_value(schema).expect("schemas serialize"));
+        }
+        json!({
+            "openapi": "3.0.3",
+            "info": {
+                "title": "Sultan RPC",
+                "version": env!("CARGO_PKG_VERSION"),
+                "description": format!(
+                    "REST routes of a Sultan node. Every operation except {} is also available \
+                    over JSON-RPC 2.0 at POST /rpc, with its operationId as the method.",
+                    REST_ONLY.join(", "),
+                )
+            },
+            "paths": self.paths,
+            "components": { "schemas": schemas }
+        })
+    }
+}
+
+/// One operation being described; every call writes straight into the document
+pub struct Operation<'a> {
+    doc: &'a mut ApiDoc,
+    path: String,
+    method: &'static str,
+}
+
+impl Operation<'_> {
+    fn op(&mut self) -> &mut Value {
+        &mut self.doc.paths[&self.path][self.method]
+    }
+
+    fn parameter(&mut self, parameter: Value) {
+        let op = self.op();
+        if op.get("parameters").is_none() {
+            op["parameters"] = json!([]);
+        }
+        op["parameters"].as_array_mut().expect("parameters is an array").push(parameter);
+    }
+
+    /// Path segment `{name}`
+    pub fn path<T: JsonSchema>(mut self, name: &str, description: &str) -> Self {
+        let schema = self.doc.schema::<T>();
+        self.parameter(json!({
+            "name": name,
+            "in": "path",
+            "required": true,
+            "description": description,
+            "schema": schema
+        }));
+        self
+    }
+
+    /// Query string parameters, one per field of `Q`
+    pub fn query<Q: JsonSchema>(mut self) -> Self {
+        let schema = self.doc.inline_schema::<Q>();
+        let required: Vec<&str> = schema["required"]
+            .as_array()
+            .map(|names| names.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let Some(properties) = schema["properties"].as_object() else {
+            return self;
+        };
+        for (name, property) in properties {
+            let mut parameter = json!({
+                "name": name,
+                "in": "query",
+                "required": required.contains(&name.as_str()),
+                "schema": property
+            });
+            if let Some(description) = property.get("description") {
+                parameter["description"] = description.clone();
+            }
+            self.parameter(parameter);
+        }
+        self
+    }
+
+    /// JSON request body
+    pub fn body<T: JsonSchema>(mut self) -> Self {
+        let schema = self.doc.schema::<T>();
+        self.op()["requestBody"] = json!({
+            "required": true,
+            "content": { "application/json": { "schema": schema } }
+        });
+        self
+    }
+
+    /// JSON reply on success
+    pub fn reply<T: JsonSchema>(self) -> Self {
+        self.response::<T>(200, "Success")
+    }
+
+    /// JSON reply with another status code
+    pub fn response<T: JsonSchema>(mut self, code: u16, description: &str) -> Self {
+        let schema = self.doc.schema::<T>();
+        self.op()["responses"][code.to_string()] = json!({
+            "description": description,
+            "content": { "application/json": { "schema": schema } }
+        });
+        self
+    }
+
+    /// Reply without a body schema, e.g. plain text or a protocol switch
+    pub fn raw(mut self, code: u16, description: &str, content_type: Option<&str>) -> Self {
+        let mut response = json!({ "description": description });
+        if let Some(content_type) = content_type {
+            response["content"] = json!({ (content_type): { "schema": { "type": "string" } } });
+        }
+        self.op()["responses"][code.to_string()] = response;
+        self
+    }
+}
+
+/// The document served at `/openapi.json`
+pub fn document() -> Value {
+    let mut doc = ApiDoc::new();
+
+    // Chain
+    doc.get("/status", "chain_getStatus", "Node status").reply::<NodeStatus>();
+    doc.post("/tx", "chain_submitTransaction", "Submit a signed transfer")
+        .body::<TxRequest>()
+        .reply::<TxSubmitted>();
+    doc.get("/tx/{hash}", "chain_getTransaction", "Confirmed transaction by hash")
+        .path::<String>("hash", "Transaction hash")
+        .reply::<ConfirmedTransaction>();
+    doc.get("/block/{height}", "chain_getBlock", "Block by height")
+        .path::<u64>("height", "Block height")
+        .reply::<Block>()
+        .raw(404, "No block at this height", None)
+        .response::<BlockPruned>(410, "Block body was pruned");
+    doc.get("/block/latest", "chain_getLatestBlock", "Latest block").reply::<LatestBlockResponse>();
+    doc.get("/blocks", "chain_getBlocks", "List block summaries")
+        .query::<BlocksListQuery>()
+        .reply::<BlocksPage>();
+    doc.get("/events", "chain_getEvents", "Module events by height")
+        .query::<EventsQuery>()
+        .reply::<EventsPage>();
+    doc.post("/simulate", "chain_simulate", "Dry-run a transaction against current state")
+        .body::<SimulateRequest>()
+        .reply::<SimulationResult>();
+    doc.get("/stats", "chain_getStats", "Chain statistics").reply::<StatsResponse>();
+    doc.get("/beacon", "chain_getBeacon", "Randomness beacon").reply::<BeaconResponse>();
+    doc.get("/balance/{address}", "chain_getBalance", "Native balance and nonce")
+        .path::<String>("address", "Account address")
+        .reply::<BalanceResponse>();
+    doc.get("/transactions/{address}", "chain_getTransactions", "Transaction history of an address")
+        .path::<String>("address", "Account address")
+        .query::<TxHistoryQuery>()
+        .reply::<TxHistoryResponse>();
+    doc.get("/economics", "chain_getEconomics", "Inflation and supply").reply::<EconomicsResponse>();
+    doc.get("/supply/total", "chain_getTotalSupply", "Total supply").reply::<TotalSupplyResponse>();
+
+    // Bridges
+    doc.get("/bridges", "bridge_list", "All bridges").reply::<BridgesResponse>();
+    doc.get("/bridge/{chain}", "bridge_getStatus", "Bridge status")
+        .path::<String>("chain", "External chain name")
+        .reply::<BridgeStatus>();
+    doc.post("/bridge/submit", "bridge_submit", "Submit a bridge transaction")
+        .body::<BridgeTxRequest>()
+        .reply::<BridgeTxSubmitted>();
+    doc.get("/bridge/{chain}/fee", "bridge_getFee", "Bridge fee for an amount")
+        .path::<String>("chain", "External chain name")
+        .query::<FeeQuery>()
+        .reply::<FeeBreakdown>();
+    doc.get("/bridge/fees/treasury", "bridge_getTreasury", "Bridge fee treasury").reply::<TreasuryResponse>();
+    doc.get("/bridge/fees/statistics", "bridge_getFeeStatistics", "Bridge fee statistics")
+        .reply::<FeeStatistics>();
+
+    // Staking
+    doc.post("/staking/create_validator", "staking_createValidator", "Register a validator")
+        .body::<CreateValidatorRequest>()
+        .reply::<ValidatorCreated>();
+    doc.post("/staking/delegate", "staking_delegate", "Delegate to a validator")
+        .body::<DelegateRequest>()
+        .reply::<Delegated>();
+    doc.post("/staking/undelegate", "staking_undelegate", "Start unbonding a delegation")
+        .body::<DelegateRequest>()
+        .reply::<Undelegated>();
+    doc.get("/staking/validators", "staking_getValidators", "List validators")
+        .query::<ValidatorsQuery>()
+        .reply::<ValidatorsPage>();
+    doc.get("/staking/delegations/{address}", "staking_getDelegations", "Delegations of a delegator")
+        .path::<String>("address", "Delegator address")
+        .reply::<Vec<Delegation>>();
+    doc.post("/staking/withdraw_rewards", "staking_withdrawRewards", "Withdraw staking rewards")
+        .body::<WithdrawRewardsRequest>()
+        .reply::<RewardsWithdrawn>();
+    doc.post("/staking/set_reward_wallet", "staking_setRewardWallet", "Set a validator's reward wallet")
+        .body::<SetRewardWalletRequest>()
+        .reply::<RewardWalletResponse>();
+    doc.get("/staking/reward_wallet/{validator_address}", "staking_getRewardWallet", "Reward wallet of a validator")
+        .path::<String>("validator_address", "Validator address")
+        .reply::<RewardWalletResponse>();
+    doc.post("/staking/rotate_key", "staking_rotateKey", "Queue a validator key rotation")
+        .body::<RotateKeyRequest>()
+        .reply::<KeyRotationQueued>();
+    doc.get("/staking/statistics", "staking_getStatistics", "Staking statistics").reply::<StakingStatistics>();
+
+    // Governance
+    doc.post("/governance/propose", "governance_propose", "Submit a proposal")
+        .body::<SubmitProposalRequest>()
+        .reply::<ProposalSubmitted>();
+    doc.post("/governance/vote", "governance_vote", "Vote on a proposal")
+        .body::<VoteRequest>()
+        .reply::<VoteRecorded>();
+    doc.get("/governance/proposals", "governance_getProposals", "List proposals")
+        .query::<ProposalsQuery>()
+        .reply::<ProposalsPage>();
+    doc.get("/governance/proposal/{proposal_id}", "governance_getProposal", "Proposal by ID")
+        .path::<u64>("proposal_id", "Proposal ID")
+        .reply::<Proposal>();
+    doc.post("/governance/tally/{proposal_id}", "governance_tally", "Tally a proposal's votes")
+        .path::<u64>("proposal_id", "Proposal ID")
+        .reply::<TallyResult>();
+    doc.post("/governance/execute/{proposal_id}", "governance_execute", "Execute a passed proposal")
+        .path::<u64>("proposal_id", "Proposal ID")
+        .reply::<ProposalExecuted>();
+    doc.get("/governance/statistics", "governance_getStatistics", "Governance statistics")
+        .reply::<GovernanceStatistics>();
+    doc.get("/governance/features", "governance_getFeatures", "Feature flags").reply::<FeaturesResponse>();
+
+    // Token factory
+    doc.post("/tokens/create", "tokens_create", "Create a token")
+        .body::<CreateTokenRequest>()
+        .reply::<TokenCreated>();
+    doc.post("/tokens/mint", "tokens_mint", "Mint tokens")
+        .body::<MintTokenRequest>()
+        .reply::<SuccessResponse>();
+    doc.post("/tokens/transfer", "tokens_transfer", "Transfer tokens")
+        .body::<TransferTokenRequest>()
+        .reply::<SuccessResponse>();
+    doc.post("/tokens/burn", "tokens_burn", "Burn tokens")
+        .body::<BurnTokenRequest>()
+        .reply::<SuccessResponse>();
+    doc.get("/tokens/{denom}/metadata", "tokens_getMetadata", "Token metadata")
+        .path::<String>("denom", "Token denom")
+        .reply::<TokenMetadataResponse>();
+    doc.get("/tokens/{denom}/balance/{address}", "tokens_getBalance", "Token balance of an address")
+        .path::<String>("denom", "Token denom")
+        .path::<String>("address", "Account address")
+        .reply::<TokenBalanceResponse>();
+    doc.get("/tokens/list", "tokens_list", "List tokens")
+        .query::<TokensQuery>()
+        .reply::<TokensPage>();
+
+    // Faucet
+    doc.get("/faucet/challenge/{address}", "faucet_getChallenge", "Nonce to sign for a faucet claim")
+        .path::<String>("address", "Recipient address")
+        .reply::<FaucetChallenge>();
+    doc.post("/faucet/claim", "faucet_claim", "Claim from the faucet with a signed challenge")
+        .body::<FaucetClaimRequest>()
+        .reply::<FaucetClaimed>();
+    doc.get("/faucet/status", "faucet_getStatus", "Faucet status").reply::<FaucetStatus>();
+
+    // DEX
+    doc.post("/dex/create_pair", "dex_createPair", "Create a pair")
+        .body::<CreatePairRequest>()
+        .reply::<PairCreated>();
+    doc.post("/dex/swap", "dex_swap", "Swap through a pool")
+        .body::<SwapRequest>()
+        .reply::<SwapResponse>();
+    doc.post("/dex/add_liquidity", "dex_addLiquidity", "Add liquidity")
+        .body::<AddLiquidityRequest>()
+        .reply::<LiquidityAdded>();
+    doc.post("/dex/remove_liquidity", "dex_removeLiquidity", "Remove liquidity")
+        .body::<RemoveLiquidityRequest>()
+        .reply::<LiquidityRemoved>();
+    doc.get("/dex/pool/{pair_id}", "dex_getPool", "Pool by pair ID")
+        .path::<String>("pair_id", "Pair ID")
+        .reply::<PoolResponse>();
+    doc.get("/dex/pools", "dex_listPools", "List pools")
+        .query::<PoolsQuery>()
+        .reply::<PoolsPage>();
+    doc.get("/dex/price/{pair_id}", "dex_getPrice", "Spot price of a pair")
+        .path::<String>("pair_id", "Pair ID")
+        .reply::<PriceResponse>();
+
+    // Node
+    doc.post("/rpc", "node_jsonRpc", "JSON-RPC 2.0 call or batch over the operations above")
+        .body::<Value>()
+        .reply::<Value>();
+    doc.get("/ws", "node_subscribe", "WebSocket event subscriptions")
+        .raw(101, "Switching to WebSocket", None);
+    doc.get("/health", "node_getHealth", "Readiness checks and which of them failed").reply::<HealthReport>();
+    doc.get("/health/live", "node_getLiveness", "Liveness probe").reply::<Liveness>();
+    doc.get("/health/ready", "node_getReadiness", "Readiness probe")
+        .reply::<HealthReport>()
+        .response::<HealthReport>(503, "Not ready; see the failed checks");
+    doc.get("/metrics", "node_getMetrics", "Prometheus metrics")
+        .raw(200, "Text exposition format", Some("text/plain"));
+    doc.get("/openapi.json", "node_getOpenApi", "This document").reply::<Value>();
+
+    doc.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn refs<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(target)) = map.get("$ref") {
+                    out.push(target);
+                }
+                map.values().for_each(|v| refs(v, out));
+            }
+            Value::Array(items) => items.iter().for_each(|v| refs(v, out)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_every_ref_resolves() {
+        let doc = document();
+        let schemas = doc["components"]["schemas"].as_object().unwrap();
+        assert!(schemas.contains_key("ErrorResponse"));
+
+        let mut targets = Vec::new();
+        refs(&doc, &mut targets);
+        assert!(!targets.is_empty());
+        for target in targets {
+            let name = target
+                .strip_prefix("#/components/schemas/")
+                .unwrap_or_else(|| panic!("unexpected ref {}", target));
+            assert!(schemas.contains_key(name), "{} is not in components", name);
+        }
+    }
+
+    #[test]
+    fn test_operation_ids_are_unique() {
+        let doc = document();
+        let mut seen = HashSet::new();
+        for item in doc["paths"].as_object().unwrap().values() {
+            for op in item.as_object().unwrap().values() {
+                let id = op["operationId"].as_str().unwrap();
+                assert!(seen.insert(id.to_string()), "duplicate operationId {}", id);
+            }
+        }
+        assert!(seen.contains("staking_delegate"));
+        assert!(seen.contains("dex_getPrice"));
+    }
+
+    #[test]
+    fn test_path_templates_declare_their_parameters() {
+        let doc = document();
+        for (path, item) in doc["paths"].as_object().unwrap() {
+            let names: Vec<&str> = path
+                .split('/')
+                .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+                .collect();
+            for op in item.as_object().unwrap().values() {
+                let declared: Vec<&str> = op["parameters"]
+                    .as_array()
+                    .map(|params| {
+                        params
+                            .iter()
+                            .filter(|p| p["in"] == "path")
+                            .filter_map(|p| p["name"].as_str())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                assert_eq!(names, declared, "path parameters of {}", path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_query_fields_become_parameters() {
+        let doc = document();
+        let params = doc["paths"]["/bridge/{chain}/fee"]["get"]["parameters"].as_array().unwrap();
+        let amount = params.iter().find(|p| p["name"] == "amount").unwrap();
+        assert_eq!(amount["in"], "query");
+        assert_eq!(amount["required"], true);
+
+        let params = doc["paths"]["/blocks"]["get"]["parameters"].as_array().unwrap();
+        let cursor = params.iter().find(|p| p["name"] == "cursor").unwrap();
+        assert_eq!(cursor["required"], false);
+    }
+}
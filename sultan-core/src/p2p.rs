@@ -12,14 +12,21 @@ use libp2p::{
     gossipsub::{self, IdentTopic, MessageAuthenticity, ValidationMode},
     identity::Keypair,
     kad::{self, store::MemoryStore},
-    noise, yamux,
-    swarm::{NetworkBehaviour, SwarmEvent},
+    mdns, noise, yamux,
+    swarm::{behaviour::toggle::Toggle, NetworkBehaviour, SwarmEvent},
     tcp, Multiaddr, PeerId, Swarm,
 };
 use serde::{Deserialize, Serialize};
 use sha2::Digest;
+use crate::config::Config;
+use crate::handshake::{self, HandshakeInfo};
+use crate::tx_fetch::{self, tx_id, FetchRequest, FetchedTx, TxCache, TxId, MAX_FETCH_BATCH, FETCH_TIMEOUT};
+use crate::compact_block::{self, CompactBlock, PartialBlock};
+use crate::blockchain::Block;
+use crate::key_rotation::{KeyHistory, RetiredKey};
 use std::collections::{HashSet, HashMap};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{mpsc, RwLock};
@@ -28,6 +35,12 @@ use tracing::{info, warn, debug};
 /// Default filename for persistent node identity key
 pub const NODE_KEY_FILE: &str = "node_key.bin";
 
+/// Default filename for persisted peer reputation and bans
+pub const PEER_REPUTATION_FILE: &str = "peer_reputation.json";
+
+/// Default filename for the persisted address book
+pub const ADDRESS_BOOK_FILE: &str = "address_book.json";
+
 /// Load or generate a persistent keypair for stable PeerId
 /// 
 /// This ensures the node's PeerId remains stable across restarts,
@@ -79,8 +92,17 @@ pub fn load_or_generate_keypair(data_dir: &Path) -> Result<Keypair> {
 }
 
 /// Topics for gossipsub messaging
+///
+/// Full `BlockProposal`s for protocol 1.0 peers; new nodes use `COMPACT_BLOCK_TOPIC`
 pub const BLOCK_TOPIC: &str = "sultan/blocks/1.0.0";
 pub const TX_TOPIC: &str = "sultan/transactions/1.0.0";
+/// Transaction IDs, announced hop by hop; bodies are fetched over `tx_fetch`
+///
+/// Full transactions only go out on `TX_TOPIC` while a 1.0 peer (no `tx_fetch`)
+/// is connected; see `legacy_topics`.
+pub const TX_ANNOUNCE_TOPIC: &str = "sultan/tx-announce/1.0.0";
+/// `CompactBlockProposal`s, which 1.0 nodes can't decode
+pub const COMPACT_BLOCK_TOPIC: &str = "sultan/compact-blocks/1.0.0";
 pub const VALIDATOR_TOPIC: &str = "sultan/validators/1.0.0";
 pub const CONSENSUS_TOPIC: &str = "sultan/consensus/1.0.0";
 
@@ -140,6 +162,20 @@ pub enum NetworkMessage {
         /// List of all known validators with their registration data
         validators: Vec<ValidatorInfo>,
     },
+    /// Content IDs of transactions the sender can serve over `tx_fetch`
+    TxAnnounce {
+        ids: Vec<TxId>,
+    },
+    /// Block proposal as header plus short transaction IDs; receivers rebuild the
+    /// block and hand it on as a `BlockProposal`
+    CompactBlockProposal {
+        height: u64,
+        proposer: String,
+        block_hash: String,
+        block: CompactBlock,
+        /// Ed25519 signature over block_hash by proposer
+        proposer_signature: Vec<u8>,
+    },
 }
 
 /// Validator information for P2P sync
@@ -155,6 +191,12 @@ pub struct ValidatorInfo {
 pub struct SultanBehaviour {
     pub gossipsub: gossipsub::Behaviour,
     pub kademlia: kad::Behaviour<MemoryStore>,
+    /// Local network discovery (devnets), off unless enabled
+    pub mdns: Toggle<mdns::tokio::Behaviour>,
+    /// Chain ID / genesis / protocol version check on every connection
+    pub handshake: handshake::Behaviour,
+    /// Pulls announced transaction bodies from peers
+    pub tx_fetch: tx_fetch::Behaviour,
 }
 
 /// Maximum message size (1 MB)
@@ -169,6 +211,156 @@ const MIN_PEERS_REQUIRED: usize = 2;
 const MIN_VALIDATOR_STAKE: u64 = 10_000_000_000_000;
 /// Rate limit cleanup interval (clean up stale entries every N checks)
 const RATE_LIMIT_CLEANUP_THRESHOLD: usize = 100;
+/// Maximum transaction IDs in one `TxAnnounce`
+const MAX_ANNOUNCE_BATCH: usize = 1000;
+/// How often queued transaction IDs are announced
+const TX_ANNOUNCE_INTERVAL_MS: u64 = 200;
+/// Compact blocks waiting on transaction fetches at once
+const MAX_PENDING_COMPACT_BLOCKS: usize = 16;
+
+/// A compact proposal waiting on missing transactions. Its gossip verdict is held
+/// back until it's rebuilt, so peers only fetch from nodes that have the bodies.
+struct PendingCompactBlock {
+    height: u64,
+    proposer: String,
+    block_hash: String,
+    proposer_signature: Vec<u8>,
+    partial: PartialBlock,
+    message_id: gossipsub::MessageId,
+    source: PeerId,
+    received: std::time::Instant,
+    /// Set once the whole block has been requested after a failed rebuild
+    fetched_in_full: bool,
+}
+
+impl PendingCompactBlock {
+    /// The full proposal and its transaction IDs, if the rebuilt block hashes to
+    /// what the proposer signed
+    fn proposal(&self) -> Option<(NetworkMessage, Vec<TxId>)> {
+        let block = self.partial.clone().into_block()?;
+        let block_data = bincode::serialize(&block).ok()?;
+        if compact_block::block_hash(&block_data) != self.block_hash {
+            return None;
+        }
+        let ids = block.transactions.iter()
+            .filter_map(|tx| compact_block::encode_tx(tx).ok())
+            .map(|body| tx_id(&body.tx_data))
+            .collect();
+        let proposal = NetworkMessage::BlockProposal {
+            height: self.height,
+            proposer: self.proposer.clone(),
+            block_hash: self.block_hash.clone(),
+            block_data,
+            proposer_signature: self.proposer_signature.clone(),
+        };
+        Some((proposal, ids))
+    }
+
+    /// Report the gossip verdict and pass a rebuilt proposal on to consensus
+    ///
+    /// A block that doesn't hash to what the proposer signed (a short ID
+    /// collision swapped a transaction) is handed back so the caller can fetch
+    /// it in full, unless that already happened.
+    /// With `relay_in_full` the rebuilt proposal is also published on `BLOCK_TOPIC`
+    /// for protocol 1.0 peers.
+    fn finish(
+        self,
+        gossipsub: &mut gossipsub::Behaviour,
+        message_tx: &Option<mpsc::UnboundedSender<NetworkMessage>>,
+        cache: &mut TxCache,
+        relay_in_full: bool,
+    ) -> Option<Self> {
+        let acceptance = match self.proposal() {
+            Some((proposal, ids)) => {
+                debug!("🧱 Rebuilt compact block {} from {}", self.height, self.source);
+                // Peers whose rebuild fails can fetch the whole block from us
+                cache.insert_block(self.block_hash.clone(), ids);
+                if relay_in_full {
+                    if let Ok(data) = bincode::serialize(&proposal) {
+                        if let Err(e) = gossipsub.publish(IdentTopic::new(BLOCK_TOPIC), data) {
+                            debug!("Failed to relay block {} in full: {}", self.height, e);
+                        }
+                    }
+                }
+                if let Some(sender) = message_tx {
+                    let _ = sender.send(proposal);
+                }
+                gossipsub::MessageAcceptance::Accept
+            }
+            None if !self.fetched_in_full => {
+                warn!("⚠️ Compact block {} from {} did not rebuild to the signed hash, fetching it in full", self.height, self.source);
+                return Some(self);
+            }
+            None => {
+                warn!("⚠️ Compact block {} from {} did not match the signed hash even in full", self.height, self.source);
+                gossipsub::MessageAcceptance::Ignore
+            }
+        };
+        let _ = gossipsub.report_message_validation_result(&self.message_id, &self.source, acceptance);
+        None
+    }
+
+    /// Ask the relaying peer for every transaction of the block, in order
+    fn fetch_in_full(mut self, tx_fetch: &mut tx_fetch::Behaviour) -> Self {
+        tx_fetch.fetch_block(self.source, self.block_hash.clone());
+        self.fetched_in_full = true;
+        self.received = std::time::Instant::now();
+        self
+    }
+}
+
+/// Compact form of a full `BlockProposal`, caching its bodies so peers can fetch them from us
+///
+/// `None` if `proposal` isn't a full proposal or its block doesn't decode.
+fn compact_proposal(proposal: &NetworkMessage, cache: &mut TxCache) -> Option<NetworkMessage> {
+    let NetworkMessage::BlockProposal { height, proposer, block_hash, block_data, proposer_signature } = proposal else {
+        return None;
+    };
+    let block: Block = bincode::deserialize(block_data).ok()?;
+    let (compact, bodies) = CompactBlock::from_block(&block).ok()?;
+    cache.insert_block(block_hash.clone(), bodies.iter().map(|(id, _)| *id).collect());
+    for (id, tx) in bodies {
+        cache.insert(id, tx);
+    }
+    Some(NetworkMessage::CompactBlockProposal {
+        height: *height,
+        proposer: proposer.clone(),
+        block_hash: block_hash.clone(),
+        block: compact,
+        proposer_signature: proposer_signature.clone(),
+    })
+}
+
+/// 1.0 topics we need to follow for the peers that passed the handshake
+///
+/// New nodes only subscribe to these while a peer that lacks the feature
+/// replacing them is connected, so full bodies flow between old nodes and the
+/// new nodes next to them instead of across the whole mesh.
+fn legacy_topics(handshakes: &HashMap<PeerId, HandshakeInfo>) -> Vec<&'static str> {
+    let mut topics = Vec::new();
+    if handshakes.values().any(|info| !info.supports_compact_blocks()) {
+        topics.push(BLOCK_TOPIC);
+    }
+    if handshakes.values().any(|info| !info.supports_tx_fetch()) {
+        topics.push(TX_TOPIC);
+    }
+    topics
+}
+
+/// Subscribe to the 1.0 topics our peers need and leave the rest
+fn update_legacy_subscriptions(gossipsub: &mut gossipsub::Behaviour, handshakes: &HashMap<PeerId, HandshakeInfo>) {
+    let needed = legacy_topics(handshakes);
+    for topic in [BLOCK_TOPIC, TX_TOPIC] {
+        let ident = IdentTopic::new(topic);
+        if needed.contains(&topic) {
+            if let Ok(true) = gossipsub.subscribe(&ident) {
+                info!("🔁 Following {} for protocol 1.0 peers", topic);
+            }
+        } else if let Ok(true) = gossipsub.unsubscribe(&ident) {
+            info!("🔁 No protocol 1.0 peers left, leaving {}", topic);
+        }
+    }
+}
 
 /// Banned peer entry with expiration
 #[derive(Debug, Clone)]
@@ -211,14 +403,474 @@ impl PeerRateLimit {
     }
 }
 
+/// Application score reward for the first delivery of a valid message
+const FIRST_DELIVERY_REWARD: f64 = 0.1;
+/// Application score penalty for an invalid block, vote or announcement
+const INVALID_MESSAGE_PENALTY: f64 = 20.0;
+/// Highest application score a peer can earn
+const MAX_APP_SCORE: f64 = 10.0;
+/// Peers whose application score falls to this value are banned
+const REPUTATION_BAN_THRESHOLD: f64 = -100.0;
+/// Forget neutral or positive reputations not seen for this long (7 days)
+const REPUTATION_RETENTION_SECS: u64 = 7 * 24 * 3600;
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Gossipsub peer score parameters, configured per topic
+///
+/// First deliveries earn score on every topic (weighted by how much the topic
+/// matters to consensus) and invalid messages cost far more than they can earn.
+/// Mesh delivery-rate penalties are disabled: block and vote traffic is too
+/// bursty for a fixed expected rate. The application score carries the
+/// persisted reputation into gossipsub.
+pub fn peer_score_params() -> (gossipsub::PeerScoreParams, gossipsub::PeerScoreThresholds) {
+    let topic = |weight: f64, first_delivery_cap: f64, invalid_weight: f64| gossipsub::TopicScoreParams {
+        topic_weight: weight,
+        // P1: time in mesh, up to +1 after an hour
+        time_in_mesh_weight: 1.0 / 3600.0,
+        time_in_mesh_quantum: Duration::from_secs(1),
+        time_in_mesh_cap: 3600.0,
+        // P2: first message deliveries
+        first_message_deliveries_weight: 1.0,
+        first_message_deliveries_decay: 0.99,
+        first_message_deliveries_cap: first_delivery_cap,
+        // P3/P3b: disabled (see above)
+        mesh_message_deliveries_weight: 0.0,
+        mesh_failure_penalty_weight: 0.0,
+        // P4: invalid messages, squared by gossipsub so repeat offenders sink fast
+        invalid_message_deliveries_weight: invalid_weight,
+        invalid_message_deliveries_decay: 0.99,
+        ..Default::default()
+    };
+
+    let mut params = gossipsub::PeerScoreParams {
+        app_specific_weight: 1.0,
+        ..Default::default()
+    };
+    params.topics.insert(IdentTopic::new(BLOCK_TOPIC).hash(), topic(1.0, 20.0, -100.0));
+    params.topics.insert(IdentTopic::new(COMPACT_BLOCK_TOPIC).hash(), topic(1.0, 20.0, -100.0));
+    params.topics.insert(IdentTopic::new(CONSENSUS_TOPIC).hash(), topic(1.0, 50.0, -100.0));
+    params.topics.insert(IdentTopic::new(VALIDATOR_TOPIC).hash(), topic(0.5, 10.0, -50.0));
+    params.topics.insert(IdentTopic::new(TX_TOPIC).hash(), topic(0.2, 100.0, -20.0));
+    params.topics.insert(IdentTopic::new(TX_ANNOUNCE_TOPIC).hash(), topic(0.2, 100.0, -20.0));
+
+    let thresholds = gossipsub::PeerScoreThresholds {
+        gossip_threshold: -10.0,
+        publish_threshold: -50.0,
+        graylist_threshold: -80.0,
+        accept_px_threshold: 10.0,
+        opportunistic_graft_threshold: 5.0,
+    };
+    (params, thresholds)
+}
+
+/// Persisted reputation for one peer
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeerReputation {
+    /// Application score fed into gossipsub (survives restarts)
+    pub app_score: f64,
+    /// Last gossipsub score observed (includes the application score)
+    pub last_score: f64,
+    pub first_deliveries: u64,
+    pub invalid_messages: u64,
+    /// Unix time the peer was last seen
+    pub last_seen: u64,
+}
+
+/// Ban entry as stored on disk (absolute expiry so it survives restarts)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PersistedBan {
+    peer_id: String,
+    reason: String,
+    expires_at: u64,
+}
+
+/// On-disk layout of `peer_reputation.json`
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ReputationFile {
+    peers: HashMap<String, PeerReputation>,
+    bans: Vec<PersistedBan>,
+}
+
+/// Peer reputations, optionally backed by a JSON file
+#[derive(Debug, Default)]
+pub struct PeerReputationStore {
+    path: Option<std::path::PathBuf>,
+    peers: HashMap<PeerId, PeerReputation>,
+}
+
+impl PeerReputationStore {
+    /// Load reputations and unexpired bans from `path` (missing file = empty store)
+    pub fn load(path: &Path) -> Result<(Self, Vec<BannedPeer>)> {
+        let mut store = Self { path: Some(path.to_path_buf()), peers: HashMap::new() };
+        if !path.exists() {
+            return Ok((store, Vec::new()));
+        }
+        let data = std::fs::read(path).context("Failed to read peer reputation file")?;
+        let file: ReputationFile = serde_json::from_slice(&data)
+            .context("Failed to parse peer reputation file")?;
+
+        for (peer, reputation) in file.peers {
+            if let Ok(peer_id) = peer.parse::<PeerId>() {
+                store.peers.insert(peer_id, reputation);
+            }
+        }
+        let now = unix_now();
+        let bans = file.bans.into_iter()
+            .filter(|ban| ban.expires_at > now)
+            .filter_map(|ban| Some(BannedPeer {
+                peer_id: ban.peer_id.parse().ok()?,
+                reason: ban.reason,
+                banned_at: std::time::Instant::now(),
+                duration_secs: ban.expires_at - now,
+            }))
+            .collect();
+        Ok((store, bans))
+    }
+
+    /// Write reputations and bans to disk (no-op without a path)
+    pub fn save(&self, bans: &HashMap<PeerId, BannedPeer>) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let now = unix_now();
+        let file = ReputationFile {
+            peers: self.peers.iter()
+                .filter(|(_, r)| r.app_score < 0.0 || now.saturating_sub(r.last_seen) < REPUTATION_RETENTION_SECS)
+                .map(|(peer, r)| (peer.to_string(), r.clone()))
+                .collect(),
+            bans: bans.values()
+                .filter(|ban| !ban.is_expired())
+                .map(|ban| PersistedBan {
+                    peer_id: ban.peer_id.to_string(),
+                    reason: ban.reason.clone(),
+                    expires_at: now + ban.duration_secs.saturating_sub(ban.banned_at.elapsed().as_secs()),
+                })
+                .collect(),
+        };
+        // Write to a temp file and rename so a crash never leaves a torn file
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&file)?)
+            .context("Failed to write peer reputation file")?;
+        std::fs::rename(&tmp, path).context("Failed to replace peer reputation file")?;
+        Ok(())
+    }
+
+    pub fn get(&self, peer_id: &PeerId) -> Option<&PeerReputation> {
+        self.peers.get(peer_id)
+    }
+
+    /// Reward the first delivery of a valid message, returning the new application score
+    pub fn record_first_delivery(&mut self, peer_id: PeerId) -> f64 {
+        let reputation = self.peers.entry(peer_id).or_default();
+        reputation.first_deliveries += 1;
+        reputation.app_score = (reputation.app_score + FIRST_DELIVERY_REWARD).min(MAX_APP_SCORE);
+        reputation.last_seen = unix_now();
+        reputation.app_score
+    }
+
+    /// Penalize an invalid message, returning the new application score
+    pub fn record_invalid(&mut self, peer_id: PeerId) -> f64 {
+        let reputation = self.peers.entry(peer_id).or_default();
+        reputation.invalid_messages += 1;
+        reputation.app_score -= INVALID_MESSAGE_PENALTY;
+        reputation.last_seen = unix_now();
+        reputation.app_score
+    }
+
+    /// Record the gossipsub score last observed for a connected peer
+    pub fn observe_score(&mut self, peer_id: PeerId, score: f64) {
+        let reputation = self.peers.entry(peer_id).or_default();
+        reputation.last_score = score;
+        reputation.last_seen = unix_now();
+    }
+
+    /// Start a banned peer over on probation (halfway to the ban threshold)
+    fn reset_after_ban(&mut self, peer_id: PeerId) {
+        if let Some(reputation) = self.peers.get_mut(&peer_id) {
+            reputation.app_score = REPUTATION_BAN_THRESHOLD / 2.0;
+        }
+    }
+
+    /// True if this score should get the peer banned
+    pub fn should_ban(app_score: f64) -> bool {
+        app_score <= REPUTATION_BAN_THRESHOLD
+    }
+}
+
+/// Peer score report for the admin RPC
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerScoreInfo {
+    pub peer_id: String,
+    pub connected: bool,
+    /// Last gossipsub score observed (None if never connected this session)
+    pub score: Option<f64>,
+    pub app_score: f64,
+    pub first_deliveries: u64,
+    pub invalid_messages: u64,
+    pub last_seen: u64,
+    pub banned: bool,
+    pub ban_reason: Option<String>,
+    pub ban_remaining_secs: Option<u64>,
+    /// Height from the peer's connection handshake
+    pub height: Option<u64>,
+}
+
+/// Maximum peers remembered in the address book (lowest quality evicted first)
+const MAX_ADDRESS_BOOK_ENTRIES: usize = 1000;
+/// Maximum addresses remembered per peer (most recent first)
+const MAX_ADDRESSES_PER_PEER: usize = 8;
+/// Address book peers dialed on startup and when peer count is low
+const ADDRESS_BOOK_DIAL_COUNT: usize = 8;
+/// Forget peers that keep failing and haven't been reached for this long (3 days)
+const ADDRESS_BOOK_STALE_SECS: u64 = 3 * 24 * 3600;
+/// Consecutive dial failures after which a stale peer is forgotten
+const ADDRESS_BOOK_MAX_FAILURES: u32 = 10;
+/// Kademlia bootstrap (routing table refresh) interval
+const KADEMLIA_BOOTSTRAP_INTERVAL_SECS: u64 = 300;
+/// Seed nodes crawl the DHT this often
+const SEED_CRAWL_INTERVAL_SECS: u64 = 60;
+
+/// Where an address book entry was first learned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressSource {
+    Bootstrap,
+    Kademlia,
+    Mdns,
+    Dialed,
+}
+
+/// Address book entry for one peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressEntry {
+    /// Multiaddrs, most recently seen first
+    pub addrs: Vec<String>,
+    pub source: AddressSource,
+    pub first_seen: u64,
+    /// Unix time of the last successful connection (0 = never connected)
+    pub last_connected: u64,
+    pub last_attempt: u64,
+    pub successes: u32,
+    /// Consecutive failed dials (reset on success)
+    pub failures: u32,
+}
+
+impl AddressEntry {
+    /// Dial priority: proven, recently reached peers first; failing peers last
+    pub fn quality(&self, now: u64) -> i64 {
+        let days_unseen = if self.last_connected == 0 {
+            1
+        } else {
+            (now.saturating_sub(self.last_connected) / 86_400) as i64
+        };
+        i64::from(self.successes.min(100)) * 2 - i64::from(self.failures) * 5 - days_unseen
+    }
+
+    fn is_dead(&self, now: u64) -> bool {
+        self.failures >= ADDRESS_BOOK_MAX_FAILURES
+            && now.saturating_sub(self.last_connected.max(self.first_seen)) >= ADDRESS_BOOK_STALE_SECS
+    }
+}
+
+/// Known peer addresses with last-seen and quality metadata, optionally backed by a JSON file
+#[derive(Debug, Default)]
+pub struct AddressBook {
+    path: Option<std::path::PathBuf>,
+    entries: HashMap<PeerId, AddressEntry>,
+}
+
+impl AddressBook {
+    /// Load the address book from `path` (missing file = empty book)
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut book = Self { path: Some(path.to_path_buf()), entries: HashMap::new() };
+        if !path.exists() {
+            return Ok(book);
+        }
+        let data = std::fs::read(path).context("Failed to read address book")?;
+        let entries: HashMap<String, AddressEntry> = serde_json::from_slice(&data)
+            .context("Failed to parse address book")?;
+        for (peer, entry) in entries {
+            if let Ok(peer_id) = peer.parse::<PeerId>() {
+                book.entries.insert(peer_id, entry);
+            }
+        }
+        Ok(book)
+    }
+
+    /// Write the address book to disk (no-op without a path), dropping dead peers
+    pub fn save(&mut self) -> Result<()> {
+        let now = unix_now();
+        self.entries.retain(|_, entry| !entry.is_dead(now));
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let entries: HashMap<String, &AddressEntry> = self.entries.iter()
+            .map(|(peer, entry)| (peer.to_string(), entry))
+            .collect();
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&entries)?)
+            .context("Failed to write address book")?;
+        std::fs::rename(&tmp, path).context("Failed to replace address book")?;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, peer_id: &PeerId) -> Option<&AddressEntry> {
+        self.entries.get(peer_id)
+    }
+
+    /// Forget a peer (e.g. one on another chain)
+    pub fn remove(&mut self, peer_id: &PeerId) {
+        self.entries.remove(peer_id);
+    }
+
+    /// Remember an address for a peer
+    pub fn add(&mut self, peer_id: PeerId, addr: &Multiaddr, source: AddressSource) {
+        let now = unix_now();
+        let entry = self.entries.entry(peer_id).or_insert_with(|| AddressEntry {
+            addrs: Vec::new(),
+            source,
+            first_seen: now,
+            last_connected: 0,
+            last_attempt: 0,
+            successes: 0,
+            failures: 0,
+        });
+        let addr = addr.to_string();
+        entry.addrs.retain(|a| *a != addr);
+        entry.addrs.insert(0, addr);
+        entry.addrs.truncate(MAX_ADDRESSES_PER_PEER);
+
+        if self.entries.len() > MAX_ADDRESS_BOOK_ENTRIES {
+            let worst = self.entries.iter()
+                .filter(|(peer, _)| **peer != peer_id)
+                .min_by_key(|(_, entry)| entry.quality(now))
+                .map(|(peer, _)| *peer);
+            if let Some(worst) = worst {
+                self.entries.remove(&worst);
+            }
+        }
+    }
+
+    /// Record a successful connection (and the dialed address, if we dialed)
+    pub fn record_success(&mut self, peer_id: PeerId, dialed: Option<&Multiaddr>) {
+        if let Some(addr) = dialed {
+            self.add(peer_id, addr, AddressSource::Dialed);
+        }
+        if let Some(entry) = self.entries.get_mut(&peer_id) {
+            entry.last_connected = unix_now();
+            entry.successes = entry.successes.saturating_add(1);
+            entry.failures = 0;
+        }
+    }
+
+    /// Record a failed dial
+    pub fn record_failure(&mut self, peer_id: PeerId) {
+        if let Some(entry) = self.entries.get_mut(&peer_id) {
+            entry.failures = entry.failures.saturating_add(1);
+        }
+    }
+
+    /// Record a dial attempt
+    pub fn record_attempt(&mut self, peer_id: PeerId) {
+        if let Some(entry) = self.entries.get_mut(&peer_id) {
+            entry.last_attempt = unix_now();
+        }
+    }
+
+    /// The `n` best peers to dial, skipping `exclude`
+    pub fn best(&self, n: usize, exclude: &HashSet<PeerId>) -> Vec<(PeerId, Vec<Multiaddr>)> {
+        let now = unix_now();
+        let mut candidates: Vec<(&PeerId, &AddressEntry)> = self.entries.iter()
+            .filter(|(peer, _)| !exclude.contains(peer))
+            .collect();
+        candidates.sort_by(|a, b| b.1.quality(now).cmp(&a.1.quality(now)).then_with(|| a.0.cmp(b.0)));
+        candidates.into_iter()
+            .take(n)
+            .map(|(peer, entry)| (*peer, entry.addrs.iter().filter_map(|a| a.parse().ok()).collect()))
+            .collect()
+    }
+
+    /// All peers and their addresses (for seeding the DHT routing table)
+    pub fn all(&self) -> impl Iterator<Item = (&PeerId, &AddressEntry)> {
+        self.entries.iter()
+    }
+}
+
+/// Dial up to `n` of the best address book peers not in `exclude`, returning how many were dialed
+fn dial_from_address_book(
+    swarm: &mut Swarm<SultanBehaviour>,
+    book: &mut AddressBook,
+    exclude: &HashSet<PeerId>,
+    local_peer_id: PeerId,
+    n: usize,
+) -> usize {
+    let mut dialed = 0;
+    for (peer, addrs) in book.best(n, exclude) {
+        if peer == local_peer_id || swarm.is_connected(&peer) || addrs.is_empty() {
+            continue;
+        }
+        let opts = libp2p::swarm::dial_opts::DialOpts::peer_id(peer).addresses(addrs).build();
+        book.record_attempt(peer);
+        match swarm.dial(opts) {
+            Ok(()) => dialed += 1,
+            Err(e) => debug!("Address book dial to {} failed: {}", peer, e),
+        }
+    }
+    dialed
+}
+
+/// Dial a persistent peer unless we're already connected to (or dialing) it
+fn dial_persistent_peer(swarm: &mut Swarm<SultanBehaviour>, addr: &Multiaddr) {
+    let Some(peer) = peer_id_from_multiaddr(addr) else { return };
+    let opts = libp2p::swarm::dial_opts::DialOpts::peer_id(peer)
+        .addresses(vec![addr.clone()])
+        .condition(libp2p::swarm::dial_opts::PeerCondition::DisconnectedAndNotDialing)
+        .build();
+    match swarm.dial(opts) {
+        Ok(()) => info!("📌 Dialing persistent peer: {}", addr),
+        Err(libp2p::swarm::DialError::DialPeerConditionFalse(_)) => {}
+        Err(e) => debug!("Persistent peer dial failed for {}: {}", addr, e),
+    }
+}
+
+/// Parse a list of base58 peer IDs
+fn parse_peer_ids(ids: Vec<String>) -> Result<HashSet<PeerId>> {
+    ids.into_iter()
+        .map(|id| id.parse::<PeerId>().with_context(|| format!("Invalid peer ID: {}", id)))
+        .collect()
+}
+
+/// Extract the `/p2p/<peer id>` component of a multiaddr
+pub fn peer_id_from_multiaddr(addr: &Multiaddr) -> Option<PeerId> {
+    addr.iter().find_map(|protocol| match protocol {
+        libp2p::multiaddr::Protocol::P2p(peer_id) => Some(peer_id),
+        _ => None,
+    })
+}
+
 /// P2P Network implementation for Sultan Chain
 pub struct P2PNetwork {
     local_key: Keypair,
     peer_id: PeerId,
+    /// Peers with a connection that passed the handshake
     connected_peers: Arc<RwLock<HashSet<PeerId>>>,
     known_validators: Arc<RwLock<HashSet<String>>>,
-    /// Validator address -> pubkey mapping for signature verification
-    validator_pubkeys: Arc<RwLock<HashMap<String, [u8; 32]>>>,
+    /// Validator address -> consensus keys by height for signature verification
+    validator_pubkeys: Arc<RwLock<HashMap<String, KeyHistory>>>,
     message_tx: Option<mpsc::UnboundedSender<NetworkMessage>>,
     message_rx: Option<mpsc::UnboundedReceiver<NetworkMessage>>,
     broadcast_tx: Option<mpsc::UnboundedSender<(String, Vec<u8>)>>, // (topic, data)
@@ -229,6 +881,36 @@ pub struct P2PNetwork {
     banned_peers: Arc<RwLock<HashMap<PeerId, BannedPeer>>>,
     /// Rate limiting per peer
     peer_rate_limits: Arc<RwLock<HashMap<PeerId, PeerRateLimit>>>,
+    /// Peer reputation (persisted with bans when loaded from a data directory)
+    reputation: Arc<RwLock<PeerReputationStore>>,
+    /// Gossipsub scores observed in the last mesh check
+    observed_scores: Arc<RwLock<HashMap<PeerId, f64>>>,
+    /// Known peer addresses (persisted when loaded from a data directory)
+    address_book: Arc<RwLock<AddressBook>>,
+    /// Seed node: crawl and hand out addresses, no gossip topics
+    seed_mode: bool,
+    /// Discover peers on the local network via mDNS
+    mdns_enabled: bool,
+    /// Peers we always keep a connection to (redialed on disconnect)
+    persistent_peers: Vec<Multiaddr>,
+    /// Peers whose addresses are never gossiped (Kademlia, address book)
+    private_peer_ids: HashSet<PeerId>,
+    /// Peers exempt from bans and rate limits
+    unconditional_peer_ids: HashSet<PeerId>,
+    /// Don't advertise our own peer ID (validator behind sentries)
+    hide_peer_id: bool,
+    /// Chain ID sent in the connection handshake
+    chain_id: String,
+    /// Genesis block hash sent in the connection handshake
+    genesis_hash: String,
+    /// Height sent in the connection handshake
+    local_height: Arc<AtomicU64>,
+    /// Peers that passed the handshake, with what they told us
+    peer_handshakes: Arc<RwLock<HashMap<PeerId, HandshakeInfo>>>,
+    /// Transaction bodies we can serve to peers
+    tx_cache: Arc<RwLock<TxCache>>,
+    /// Transaction IDs waiting for the next `TxAnnounce`
+    pending_tx_announce: Arc<RwLock<Vec<TxId>>>,
 }
 
 impl P2PNetwork {
@@ -256,6 +938,21 @@ impl P2PNetwork {
             bootstrap_peers: Vec::new(),
             banned_peers: Arc::new(RwLock::new(HashMap::new())),
             peer_rate_limits: Arc::new(RwLock::new(HashMap::new())),
+            reputation: Arc::new(RwLock::new(PeerReputationStore::default())),
+            observed_scores: Arc::new(RwLock::new(HashMap::new())),
+            address_book: Arc::new(RwLock::new(AddressBook::default())),
+            seed_mode: false,
+            mdns_enabled: false,
+            persistent_peers: Vec::new(),
+            private_peer_ids: HashSet::new(),
+            unconditional_peer_ids: HashSet::new(),
+            hide_peer_id: false,
+            chain_id: Config::default().chain_id,
+            genesis_hash: String::new(),
+            local_height: Arc::new(AtomicU64::new(0)),
+            peer_handshakes: Arc::new(RwLock::new(HashMap::new())),
+            tx_cache: Arc::new(RwLock::new(TxCache::default())),
+            pending_tx_announce: Arc::new(RwLock::new(Vec::new())),
         })
     }
 
@@ -281,6 +978,21 @@ impl P2PNetwork {
             bootstrap_peers: Vec::new(),
             banned_peers: Arc::new(RwLock::new(HashMap::new())),
             peer_rate_limits: Arc::new(RwLock::new(HashMap::new())),
+            reputation: Arc::new(RwLock::new(PeerReputationStore::default())),
+            observed_scores: Arc::new(RwLock::new(HashMap::new())),
+            address_book: Arc::new(RwLock::new(AddressBook::default())),
+            seed_mode: false,
+            mdns_enabled: false,
+            persistent_peers: Vec::new(),
+            private_peer_ids: HashSet::new(),
+            unconditional_peer_ids: HashSet::new(),
+            hide_peer_id: false,
+            chain_id: Config::default().chain_id,
+            genesis_hash: String::new(),
+            local_height: Arc::new(AtomicU64::new(0)),
+            peer_handshakes: Arc::new(RwLock::new(HashMap::new())),
+            tx_cache: Arc::new(RwLock::new(TxCache::default())),
+            pending_tx_announce: Arc::new(RwLock::new(Vec::new())),
         })
     }
 
@@ -295,10 +1007,135 @@ impl P2PNetwork {
         Ok(())
     }
 
+    /// Load persisted peer reputation and bans from `data_dir` (call before `start`)
+    pub async fn load_reputation(&mut self, data_dir: &Path) -> Result<()> {
+        let (store, bans) = PeerReputationStore::load(&data_dir.join(PEER_REPUTATION_FILE))?;
+        info!("📒 Loaded reputation for {} peers ({} active bans)", store.peers.len(), bans.len());
+        let mut banned = self.banned_peers.write().await;
+        for ban in bans {
+            banned.insert(ban.peer_id, ban);
+        }
+        *self.reputation.write().await = store;
+        Ok(())
+    }
+
+    /// Persist peer reputation and bans
+    pub async fn save_reputation(&self) -> Result<()> {
+        let banned = self.banned_peers.read().await;
+        self.reputation.read().await.save(&banned)
+    }
+
+    /// Load the persisted address book from `data_dir` (call before `start`)
+    pub async fn load_address_book(&mut self, data_dir: &Path) -> Result<()> {
+        let book = AddressBook::load(&data_dir.join(ADDRESS_BOOK_FILE))?;
+        info!("📒 Loaded {} peers from address book", book.len());
+        *self.address_book.write().await = book;
+        Ok(())
+    }
+
+    /// Persist the address book
+    pub async fn save_address_book(&self) -> Result<()> {
+        self.address_book.write().await.save()
+    }
+
+    /// Number of peers in the address book
+    pub async fn address_book_size(&self) -> usize {
+        self.address_book.read().await.len()
+    }
+
+    /// Run as a seed node: crawl the DHT and hand out addresses, no gossip (call before `start`)
+    pub fn set_seed_mode(&mut self, seed_mode: bool) {
+        self.seed_mode = seed_mode;
+    }
+
+    pub fn is_seed_mode(&self) -> bool {
+        self.seed_mode
+    }
+
+    /// Discover peers on the local network via mDNS (call before `start`)
+    pub fn set_mdns(&mut self, enabled: bool) {
+        self.mdns_enabled = enabled;
+    }
+
+    /// Peers to stay connected to at all times; each address needs a `/p2p/<peer id>` suffix
+    pub fn set_persistent_peers(&mut self, peers: Vec<String>) -> Result<()> {
+        let mut parsed = Vec::with_capacity(peers.len());
+        for peer in peers {
+            let addr: Multiaddr = peer.parse()
+                .with_context(|| format!("Invalid persistent peer address: {}", peer))?;
+            if peer_id_from_multiaddr(&addr).is_none() {
+                anyhow::bail!("Persistent peer {} is missing its /p2p/<peer id> suffix", peer);
+            }
+            parsed.push(addr);
+        }
+        self.persistent_peers = parsed;
+        info!("📌 Persistent peers configured: {:?}", self.persistent_peers);
+        Ok(())
+    }
+
+    /// Peers whose addresses are never put in the DHT or address book (e.g. a validator behind this sentry)
+    pub fn set_private_peer_ids(&mut self, ids: Vec<String>) -> Result<()> {
+        self.private_peer_ids = parse_peer_ids(ids)?;
+        info!("🙈 Private peers configured: {:?}", self.private_peer_ids);
+        Ok(())
+    }
+
+    /// Peers that are never banned or rate limited
+    pub fn set_unconditional_peer_ids(&mut self, ids: Vec<String>) -> Result<()> {
+        self.unconditional_peer_ids = parse_peer_ids(ids)?;
+        info!("🛡️ Unconditional peers configured: {:?}", self.unconditional_peer_ids);
+        Ok(())
+    }
+
+    /// Keep our peer ID out of the DHT, gossip and validator announcements (call before `start`).
+    /// The node then only talks to its bootstrap and persistent peers.
+    pub fn set_hide_peer_id(&mut self, hide: bool) {
+        self.hide_peer_id = hide;
+    }
+
+    pub fn is_peer_id_hidden(&self) -> bool {
+        self.hide_peer_id
+    }
+
+    /// Chain identity checked in the connection handshake (call before `start`)
+    pub fn set_chain_info(&mut self, chain_id: &str, genesis_hash: &str) {
+        self.chain_id = chain_id.to_string();
+        self.genesis_hash = genesis_hash.to_string();
+        info!("🪪 Handshake: chain {} genesis {}", self.chain_id, self.genesis_hash);
+    }
+
+    pub fn chain_id(&self) -> &str {
+        &self.chain_id
+    }
+
+    /// Update the height advertised to newly connected peers
+    pub fn set_local_height(&self, height: u64) {
+        self.local_height.store(height, Ordering::Relaxed);
+    }
+
+    /// Shared handle to the advertised height (for callers without access to the network)
+    pub fn height_handle(&self) -> Arc<AtomicU64> {
+        self.local_height.clone()
+    }
+
+    /// Height a peer reported in its handshake
+    pub async fn peer_height(&self, peer_id: &PeerId) -> Option<u64> {
+        self.peer_handshakes.read().await.get(peer_id).map(|info| info.height)
+    }
+
     pub fn peer_id(&self) -> &PeerId {
         &self.peer_id
     }
 
+    /// Peer ID to put in (and sign for) `ValidatorAnnounce`; empty when hidden
+    pub fn announced_peer_id(&self) -> String {
+        if self.hide_peer_id {
+            String::new()
+        } else {
+            self.peer_id.to_string()
+        }
+    }
+
     /// Take the message receiver (can only be called once)
     pub fn take_message_receiver(&mut self) -> Option<mpsc::UnboundedReceiver<NetworkMessage>> {
         self.message_rx.take()
@@ -320,6 +1157,7 @@ impl P2PNetwork {
             .max_transmit_size(MAX_MESSAGE_SIZE) // 1 MB max message
             .max_ihave_length(5000) // Optimize gossip protocol
             .max_messages_per_rpc(Some(100)) // Limit messages per RPC for DoS protection
+            .validate_messages() // Forward only after we accept a message (drives peer scoring)
             .message_id_fn(|msg| {
                 // Include message source to differentiate messages from different peers
                 // This prevents the mesh from treating re-announcements as duplicates
@@ -339,16 +1177,44 @@ impl P2PNetwork {
             .build()
             .map_err(|e| anyhow::anyhow!("Gossipsub config error: {}", e))?;
 
-        let gossipsub = gossipsub::Behaviour::new(
-            MessageAuthenticity::Signed(self.local_key.clone()),
+        // Signed messages carry our peer ID as `source`; hidden nodes publish anonymously
+        let authenticity = if self.hide_peer_id {
+            MessageAuthenticity::Anonymous
+        } else {
+            MessageAuthenticity::Signed(self.local_key.clone())
+        };
+        let mut gossipsub = gossipsub::Behaviour::new(
+            authenticity,
             gossipsub_config,
         ).map_err(|e| anyhow::anyhow!("Gossipsub error: {}", e))?;
 
-        // Configure Kademlia DHT
+        let (score_params, score_thresholds) = peer_score_params();
+        gossipsub.with_peer_score(score_params, score_thresholds)
+            .map_err(|e| anyhow::anyhow!("Gossipsub peer score error: {}", e))?;
+
+        // Configure Kademlia DHT. Nodes never confirm an external address, so
+        // force server mode or nobody would answer peer lookups. Hidden nodes stay
+        // clients so peers never add them to their routing tables.
         let store = MemoryStore::new(self.peer_id);
-        let kademlia = kad::Behaviour::new(self.peer_id, store);
+        let mut kademlia = kad::Behaviour::new(self.peer_id, store);
+        kademlia.set_mode(Some(if self.hide_peer_id { kad::Mode::Client } else { kad::Mode::Server }));
+
+        let mdns = if self.mdns_enabled && !self.hide_peer_id {
+            Some(mdns::tokio::Behaviour::new(mdns::Config::default(), self.peer_id)
+                .context("Failed to start mDNS")?)
+        } else {
+            None
+        };
 
-        let behaviour = SultanBehaviour { gossipsub, kademlia };
+        let handshake = handshake::Behaviour::new(
+            self.chain_id.clone(),
+            self.genesis_hash.clone(),
+            self.local_height.clone(),
+        );
+
+        let tx_fetch = tx_fetch::Behaviour::new(self.tx_cache.clone());
+
+        let behaviour = SultanBehaviour { gossipsub, kademlia, mdns: Toggle::from(mdns), handshake, tx_fetch };
 
         let swarm = libp2p::SwarmBuilder::with_existing_identity(self.local_key.clone())
             .with_tokio()
@@ -377,15 +1243,22 @@ impl P2PNetwork {
         let mut swarm = self.build_swarm()?;
 
         // Subscribe to topics
-        let block_topic = IdentTopic::new(BLOCK_TOPIC);
+        // BLOCK_TOPIC and TX_TOPIC are only followed while a 1.0 peer is connected
+        let compact_block_topic = IdentTopic::new(COMPACT_BLOCK_TOPIC);
         let tx_topic = IdentTopic::new(TX_TOPIC);
         let validator_topic = IdentTopic::new(VALIDATOR_TOPIC);
         let consensus_topic = IdentTopic::new(CONSENSUS_TOPIC);
+        let tx_announce_topic = IdentTopic::new(TX_ANNOUNCE_TOPIC);
 
-        swarm.behaviour_mut().gossipsub.subscribe(&block_topic)?;
-        swarm.behaviour_mut().gossipsub.subscribe(&tx_topic)?;
-        swarm.behaviour_mut().gossipsub.subscribe(&validator_topic)?;
-        swarm.behaviour_mut().gossipsub.subscribe(&consensus_topic)?;
+        // Seed nodes only crawl and serve addresses; they stay out of gossip
+        if self.seed_mode {
+            info!("🌱 Seed mode: not subscribing to gossip topics");
+        } else {
+            swarm.behaviour_mut().gossipsub.subscribe(&compact_block_topic)?;
+            swarm.behaviour_mut().gossipsub.subscribe(&validator_topic)?;
+            swarm.behaviour_mut().gossipsub.subscribe(&consensus_topic)?;
+            swarm.behaviour_mut().gossipsub.subscribe(&tx_announce_topic)?;
+        }
 
         // Start listening
         swarm.listen_on(addr.clone())?;
@@ -399,6 +1272,44 @@ impl P2PNetwork {
             }
         }
 
+        for peer_addr in &self.persistent_peers {
+            dial_persistent_peer(&mut swarm, peer_addr);
+        }
+
+        if self.hide_peer_id {
+            info!("🙈 Peer ID hidden: DHT client mode, anonymous gossip, no address book dials");
+            if self.mdns_enabled {
+                warn!("⚠️ mDNS disabled: it would advertise our hidden peer ID");
+            }
+        }
+
+        // Seed the DHT from bootstrap peers and the address book, then dial the best known peers.
+        // Hidden nodes only talk to their configured peers.
+        if !self.hide_peer_id {
+            let mut book = self.address_book.write().await;
+            for peer_addr in &self.bootstrap_peers {
+                if let Some(peer) = peer_id_from_multiaddr(peer_addr) {
+                    book.add(peer, peer_addr, AddressSource::Bootstrap);
+                }
+            }
+            for (peer, entry) in book.all() {
+                if self.private_peer_ids.contains(peer) {
+                    continue;
+                }
+                for addr in entry.addrs.iter().filter_map(|a| a.parse::<Multiaddr>().ok()) {
+                    swarm.behaviour_mut().kademlia.add_address(peer, addr);
+                }
+            }
+            let bootstrap: HashSet<PeerId> = self.bootstrap_peers.iter()
+                .chain(&self.persistent_peers)
+                .filter_map(peer_id_from_multiaddr)
+                .collect();
+            let dialed = dial_from_address_book(&mut swarm, &mut book, &bootstrap, self.peer_id, ADDRESS_BOOK_DIAL_COUNT);
+            if dialed > 0 {
+                info!("📒 Dialing {} peers from address book ({} known)", dialed, book.len());
+            }
+        }
+
         // Clone Arc references for the event loop
         let connected_peers = self.connected_peers.clone();
         let known_validators = self.known_validators.clone();
@@ -407,6 +1318,19 @@ impl P2PNetwork {
         let message_tx = self.message_tx.clone();
         let bootstrap_peers_for_reconnect = self.bootstrap_peers.clone();
         let peer_rate_limits = self.peer_rate_limits.clone();
+        let banned_peers = self.banned_peers.clone();
+        let reputation = self.reputation.clone();
+        let observed_scores = self.observed_scores.clone();
+        let address_book = self.address_book.clone();
+        let local_peer_id = self.peer_id;
+        let seed_mode = self.seed_mode;
+        let persistent_peers = self.persistent_peers.clone();
+        let private_peer_ids = self.private_peer_ids.clone();
+        let unconditional_peer_ids = self.unconditional_peer_ids.clone();
+        let hide_peer_id = self.hide_peer_id;
+        let peer_handshakes = self.peer_handshakes.clone();
+        let tx_cache = self.tx_cache.clone();
+        let pending_tx_announce = self.pending_tx_announce.clone();
         
         // Create broadcast channel - receiver for event loop, sender stays in self
         let (broadcast_tx, mut broadcast_rx) = mpsc::unbounded_channel::<(String, Vec<u8>)>();
@@ -422,6 +1346,22 @@ impl P2PNetwork {
             let mut mesh_check_interval = tokio::time::interval(std::time::Duration::from_secs(10));
             mesh_check_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
             
+            // DHT refresh timer - seed nodes crawl more often
+            let mut discovery_interval = tokio::time::interval(std::time::Duration::from_secs(
+                if seed_mode { SEED_CRAWL_INTERVAL_SECS } else { KADEMLIA_BOOTSTRAP_INTERVAL_SECS }
+            ));
+            discovery_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            // Transaction announce batching
+            let mut tx_announce_interval = tokio::time::interval(Duration::from_millis(TX_ANNOUNCE_INTERVAL_MS));
+            tx_announce_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            // Transaction IDs we've asked a peer for, so each body is fetched once
+            let mut requested_txs: HashMap<TxId, std::time::Instant> = HashMap::new();
+            // Transactions gossiped to us in full since the last announce; gossipsub already relays them
+            let mut received_in_full: HashSet<TxId> = HashSet::new();
+            // Compact blocks waiting on transactions, by block hash
+            let mut pending_blocks: HashMap<String, PendingCompactBlock> = HashMap::new();
+            
             loop {
                 if !*is_running.read().await {
                     info!("🛑 P2P network stopping");
@@ -432,7 +1372,7 @@ impl P2PNetwork {
                     // Periodic mesh diagnostics
                     _ = mesh_check_interval.tick() => {
                         // Log gossipsub mesh state for each topic
-                        let topics = [BLOCK_TOPIC, VALIDATOR_TOPIC, CONSENSUS_TOPIC, TX_TOPIC];
+                        let topics = [BLOCK_TOPIC, COMPACT_BLOCK_TOPIC, VALIDATOR_TOPIC, CONSENSUS_TOPIC, TX_TOPIC, TX_ANNOUNCE_TOPIC];
                         for topic_str in &topics {
                             let topic = IdentTopic::new(*topic_str);
                             let mesh_peers: Vec<_> = swarm.behaviour().gossipsub.mesh_peers(&topic.hash()).collect();
@@ -440,9 +1380,84 @@ impl P2PNetwork {
                             info!("📊 MESH STATUS: topic={} mesh_peers={} all_gossipsub_peers={}", 
                                   topic_str, mesh_peers.len(), all_peers.len());
                         }
+
+                        // Sample gossipsub scores and (re)apply persisted application scores
+                        let peers: Vec<PeerId> = connected_peers.read().await.iter().copied().collect();
+                        let mut rep = reputation.write().await;
+                        let mut scores = observed_scores.write().await;
+                        scores.clear();
+                        for peer in peers {
+                            let app_score = rep.get(&peer).map(|r| r.app_score).unwrap_or_default();
+                            swarm.behaviour_mut().gossipsub.set_application_score(&peer, app_score);
+                            if let Some(score) = swarm.behaviour().gossipsub.peer_score(&peer) {
+                                rep.observe_score(peer, score);
+                                scores.insert(peer, score);
+                            }
+                        }
                     }
-                    // Periodic reconnection check
+                    // Announce queued transaction IDs to our mesh
+                    _ = tx_announce_interval.tick() => {
+                        requested_txs.retain(|_, at| at.elapsed() < FETCH_TIMEOUT);
+                        pending_blocks.retain(|hash, pending| {
+                            if pending.received.elapsed() < FETCH_TIMEOUT {
+                                return true;
+                            }
+                            debug!("Gave up rebuilding compact block {}", hash);
+                            let _ = swarm.behaviour_mut().gossipsub.report_message_validation_result(
+                                &pending.message_id, &pending.source, gossipsub::MessageAcceptance::Ignore,
+                            );
+                            false
+                        });
+                        let ids = std::mem::take(&mut *pending_tx_announce.write().await);
+                        for batch in ids.chunks(MAX_ANNOUNCE_BATCH) {
+                            let msg = NetworkMessage::TxAnnounce { ids: batch.to_vec() };
+                            let Ok(data) = bincode::serialize(&msg) else { continue };
+                            match swarm.behaviour_mut().gossipsub.publish(tx_announce_topic.clone(), data) {
+                                Ok(_) => debug!("📡 Announced {} transactions", batch.len()),
+                                Err(e) => debug!("Failed to announce transactions: {}", e),
+                            }
+                        }
+                        // 1.0 peers can't fetch, so they get the bodies
+                        if peer_handshakes.read().await.values().any(|info| !info.supports_tx_fetch()) {
+                            let new_ids: Vec<TxId> = ids.iter().filter(|id| !received_in_full.contains(*id)).copied().collect();
+                            let txs = tx_cache.read().await.get_many(&new_ids, usize::MAX);
+                            for tx in txs {
+                                let msg = NetworkMessage::Transaction { tx_hash: tx.tx_hash, tx_data: tx.tx_data };
+                                let Ok(data) = bincode::serialize(&msg) else { continue };
+                                if let Err(e) = swarm.behaviour_mut().gossipsub.publish(tx_topic.clone(), data) {
+                                    debug!("Failed to gossip transaction in full: {}", e);
+                                }
+                            }
+                        }
+                        received_in_full.clear();
+                    }
+                    // Periodic DHT refresh / crawl
+                    _ = discovery_interval.tick(), if !hide_peer_id => {
+                        if swarm.behaviour_mut().kademlia.bootstrap().is_ok() && seed_mode {
+                            // Random lookups walk the whole keyspace, filling the routing table
+                            swarm.behaviour_mut().kademlia.get_closest_peers(PeerId::random());
+                            info!("🌱 Seed crawl: {} peers in address book", address_book.read().await.len());
+                        }
+                    }
+                    // Periodic reconnection check (also enforces bans and saves reputation)
                     _ = reconnect_interval.tick() => {
+                        {
+                            let mut banned = banned_peers.write().await;
+                            banned.retain(|peer, ban| !ban.is_expired() && !unconditional_peer_ids.contains(peer));
+                            for peer in banned.keys() {
+                                if swarm.is_connected(peer) {
+                                    let _ = swarm.disconnect_peer_id(*peer);
+                                }
+                            }
+                            if let Err(e) = reputation.read().await.save(&banned) {
+                                warn!("Failed to save peer reputation: {}", e);
+                            }
+                        }
+
+                        for peer_addr in &persistent_peers {
+                            dial_persistent_peer(&mut swarm, peer_addr);
+                        }
+
                         let peer_count = connected_peers.read().await.len();
                         if peer_count < 2 && !bootstrap_peers_for_reconnect.is_empty() {
                             info!("🔄 Low peer count ({}), attempting reconnection to bootstrap peers...", peer_count);
@@ -452,6 +1467,16 @@ impl P2PNetwork {
                                 }
                             }
                         }
+                        let mut book = address_book.write().await;
+                        if peer_count < 2 && !hide_peer_id {
+                            let connected = connected_peers.read().await.clone();
+                            dial_from_address_book(&mut swarm, &mut book, &connected, local_peer_id, ADDRESS_BOOK_DIAL_COUNT);
+                        }
+                        if let Err(e) = book.save() {
+                            warn!("Failed to save address book: {}", e);
+                        }
+                        drop(book);
+                        tx_cache.write().await.prune();
                     }
                     // Handle broadcast requests
                     Some((topic, data)) = broadcast_rx.recv() => {
@@ -467,24 +1492,72 @@ impl P2PNetwork {
                         SwarmEvent::NewListenAddr { address, .. } => {
                             info!("📡 Listening on {}", address);
                         }
-                        SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                        SwarmEvent::ConnectionEstablished { peer_id, endpoint, .. } => {
+                            if !unconditional_peer_ids.contains(&peer_id)
+                                && banned_peers.read().await.get(&peer_id).is_some_and(|ban| !ban.is_expired())
+                            {
+                                info!("🚫 Dropping connection from banned peer: {}", peer_id);
+                                let _ = swarm.disconnect_peer_id(peer_id);
+                                continue;
+                            }
                             info!("🤝 Connected to peer: {}", peer_id);
-                            connected_peers.write().await.insert(peer_id);
+                            // Gossipsub rejects (and doesn't forward) its messages until the handshake passes
+                            if !peer_handshakes.read().await.contains_key(&peer_id) {
+                                swarm.behaviour_mut().gossipsub.blacklist_peer(&peer_id);
+                            }
+                            // Only dialed addresses are reachable; inbound ones use ephemeral ports
+                            // Private peers never enter the book, so we can't hand them out
+                            if !private_peer_ids.contains(&peer_id) {
+                                let dialed = endpoint.is_dialer().then(|| endpoint.get_remote_address());
+                                address_book.write().await.record_success(peer_id, dialed);
+                            }
+                            if let Some(r) = reputation.read().await.get(&peer_id) {
+                                swarm.behaviour_mut().gossipsub.set_application_score(&peer_id, r.app_score);
+                            }
                         }
-                        SwarmEvent::ConnectionClosed { peer_id, cause, .. } => {
+                        SwarmEvent::ConnectionClosed { peer_id, cause, num_established, .. } => {
                             if let Some(ref err) = cause {
                                 info!("👋 Disconnected from peer: {} (cause: {:?})", peer_id, err);
                             } else {
                                 info!("👋 Disconnected from peer: {}", peer_id);
                             }
-                            connected_peers.write().await.remove(&peer_id);
+                            if num_established == 0 {
+                                connected_peers.write().await.remove(&peer_id);
+                                let mut handshakes = peer_handshakes.write().await;
+                                if handshakes.remove(&peer_id).is_some() && !seed_mode {
+                                    update_legacy_subscriptions(&mut swarm.behaviour_mut().gossipsub, &handshakes);
+                                }
+                                drop(handshakes);
+                                swarm.behaviour_mut().gossipsub.remove_blacklisted_peer(&peer_id);
+                            }
+
+                            // Redial persistent peers straight away (banned ones wait for the reconnect tick)
+                            let banned = banned_peers.read().await.get(&peer_id).is_some_and(|ban| !ban.is_expired());
+                            if num_established == 0 && !banned {
+                                for peer_addr in persistent_peers.iter().filter(|a| peer_id_from_multiaddr(a) == Some(peer_id)) {
+                                    dial_persistent_peer(&mut swarm, peer_addr);
+                                }
+                            }
+                        }
+                        SwarmEvent::OutgoingConnectionError { peer_id: Some(peer_id), error, .. } => {
+                            debug!("Dial to {} failed: {}", peer_id, error);
+                            address_book.write().await.record_failure(peer_id);
                         }
                         SwarmEvent::Behaviour(event) => {
                             match event {
-                                SultanBehaviourEvent::Gossipsub(gossipsub::Event::Message { propagation_source, message, .. }) => {
+                                SultanBehaviourEvent::Gossipsub(gossipsub::Event::Message { propagation_source, message_id, message }) => {
+                                    // Nothing from a peer is processed until it passes the handshake
+                                    if !peer_handshakes.read().await.contains_key(&propagation_source) {
+                                        debug!("Ignoring gossip from {} before handshake", propagation_source);
+                                        let _ = swarm.behaviour_mut().gossipsub.report_message_validation_result(
+                                            &message_id, &propagation_source, gossipsub::MessageAcceptance::Ignore,
+                                        );
+                                        continue;
+                                    }
+
                                     // CRITICAL: Enforce rate limiting to prevent DoS attacks
                                     // Check if this peer has exceeded their message quota
-                                    {
+                                    if !unconditional_peer_ids.contains(&propagation_source) {
                                         let mut limits = peer_rate_limits.write().await;
                                         
                                         // Periodic cleanup of stale rate limit entries
@@ -500,6 +1573,9 @@ impl P2PNetwork {
                                         let limit = limits.entry(propagation_source).or_default();
                                         if !limit.record_message() {
                                             warn!("⚠️ Rate limit exceeded for peer {}, dropping message", propagation_source);
+                                            let _ = swarm.behaviour_mut().gossipsub.report_message_validation_result(
+                                                &message_id, &propagation_source, gossipsub::MessageAcceptance::Ignore,
+                                            );
                                             continue;
                                         }
                                     }
@@ -508,13 +1584,22 @@ impl P2PNetwork {
                                     info!("📥 GOSSIPSUB RAW: topic={} from={} size={} bytes", 
                                           message.topic, propagation_source, message.data.len());
                                     
-                                    // Parse and forward message
+                                    // Parse, validate and forward message. The verdict is reported to
+                                    // gossipsub (forwarding + peer score) and to the reputation store.
+                                    let mut valid = false;
+                                    let mut relay = true;
+                                    let mut deferred = false;
                                     if let Ok(network_msg) = bincode::deserialize::<NetworkMessage>(&message.data) {
+                                        valid = true;
+                                        let mut forward = true;
                                         // Log received messages at INFO level for debugging
                                         match &network_msg {
                                             NetworkMessage::BlockProposal { height, proposer, .. } => {
                                                 info!("📥 GOSSIPSUB RECEIVED BlockProposal: height={} proposer={}", height, proposer);
                                             }
+                                            NetworkMessage::CompactBlockProposal { height, proposer, block, .. } => {
+                                                info!("📥 GOSSIPSUB RECEIVED CompactBlockProposal: height={} proposer={} txs={}", height, proposer, block.short_ids.len());
+                                            }
                                             NetworkMessage::SyncRequest { from_height, to_height } => {
                                                 info!("📥 GOSSIPSUB RECEIVED SyncRequest: {}-{}", from_height, to_height);
                                             }
@@ -529,16 +1614,56 @@ impl P2PNetwork {
                                             }
                                         }
                                         
+                                        // Transaction announcements: fetch the bodies we're missing from the announcer.
+                                        // Announces aren't relayed; peers re-announce once they hold the bodies.
+                                        if let NetworkMessage::TxAnnounce { ref ids } = network_msg {
+                                            forward = false;
+                                            relay = false;
+                                            if ids.len() > MAX_ANNOUNCE_BATCH {
+                                                warn!("⚠️ Rejected TxAnnounce of {} ids from {}", ids.len(), propagation_source);
+                                                valid = false;
+                                            } else {
+                                                let now = std::time::Instant::now();
+                                                let cache = tx_cache.read().await;
+                                                let missing: Vec<TxId> = ids.iter()
+                                                    .filter(|id| !cache.contains(id))
+                                                    .filter(|id| match requested_txs.entry(**id) {
+                                                        std::collections::hash_map::Entry::Vacant(e) => {
+                                                            e.insert(now);
+                                                            true
+                                                        }
+                                                        std::collections::hash_map::Entry::Occupied(_) => false,
+                                                    })
+                                                    .copied()
+                                                    .collect();
+                                                drop(cache);
+                                                for batch in missing.chunks(MAX_FETCH_BATCH) {
+                                                    swarm.behaviour_mut().tx_fetch.fetch(propagation_source, batch.to_vec());
+                                                }
+                                            }
+                                        }
+
+                                        // Full transactions from 1.0 peers: keep the body to serve and announce
+                                        if let NetworkMessage::Transaction { ref tx_hash, ref tx_data } = network_msg {
+                                            let id = tx_id(tx_data);
+                                            let tx = FetchedTx { tx_hash: tx_hash.clone(), tx_data: tx_data.clone() };
+                                            if tx_cache.write().await.insert(id, tx) {
+                                                pending_tx_announce.write().await.push(id);
+                                                received_in_full.insert(id);
+                                            }
+                                        }
+
                                         // Handle validator announcements - verify stake AND signature, then register
                                         if let NetworkMessage::ValidatorAnnounce { ref address, stake, ref peer_id, ref pubkey, ref signature, current_height } = network_msg {
                                             // First verify signature over the announcement data
                                             if !P2PNetwork::verify_announce_signature(pubkey, address, stake, peer_id, signature) {
                                                 warn!("⚠️ Rejected validator {} with invalid announcement signature", address);
+                                                valid = false;
                                             } else if stake >= MIN_VALIDATOR_STAKE {
                                                 info!("🗳️ Validator announced: {} (stake: {}, peer: {}) - registering pubkey for signature verification", address, stake, peer_id);
                                                 known_validators.write().await.insert(address.clone());
                                                 // CRITICAL: Register pubkey so we can verify BlockProposal signatures from this validator
-                                                validator_pubkeys.write().await.insert(address.clone(), *pubkey);
+                                                P2PNetwork::set_current_key(&mut *validator_pubkeys.write().await, address, *pubkey);
                                             } else {
                                                 warn!("⚠️ Rejected validator {} with insufficient stake: {} < {}", 
                                                       address, stake, MIN_VALIDATOR_STAKE);
@@ -546,30 +1671,274 @@ impl P2PNetwork {
                                         }
                                         
                                         // Handle BlockProposal - verify proposer signature
-                                        if let NetworkMessage::BlockProposal { ref proposer, ref block_hash, ref proposer_signature, height, .. } = network_msg {
+                                        if let NetworkMessage::BlockProposal { ref proposer, ref block_hash, ref proposer_signature, height, .. }
+                                            | NetworkMessage::CompactBlockProposal { ref proposer, ref block_hash, ref proposer_signature, height, .. } = network_msg {
                                             // Look up proposer's pubkey and verify signature
-                                            if let Some(pubkey) = validator_pubkeys.read().await.get(proposer) {
-                                                if !P2PNetwork::verify_vote_signature(pubkey, block_hash.as_bytes(), proposer_signature) {
+                                            if let Some(pubkey) = validator_pubkeys.read().await.get(proposer).map(|keys| keys.at(height)) {
+                                                if !P2PNetwork::verify_vote_signature(&pubkey, block_hash.as_bytes(), proposer_signature) {
                                                     warn!("⚠️ Rejected BlockProposal height={} from {} with invalid signature", height, proposer);
-                                                    // Note: In production, would ban peer here
                                                     // Skip forwarding invalid proposals
-                                                    continue;
+                                                    valid = false;
+                                                    forward = false;
+                                                } else {
+                                                    info!("✅ BlockProposal height={} from {} signature verified - forwarding to consensus", height, proposer);
                                                 }
-                                                info!("✅ BlockProposal height={} from {} signature verified - forwarding to consensus", height, proposer);
                                             } else {
                                                 // Unknown proposer - still forward but log at INFO for visibility
                                                 // (proposer may be new/not yet announced, or genesis validator)
                                                 info!("⚠️ BlockProposal height={} from unknown proposer {} (no pubkey registered) - forwarding anyway", height, proposer);
                                             }
                                         }
+
+                                        // Full proposals come from 1.0 peers; pass new ones on as compact blocks
+                                        if let NetworkMessage::BlockProposal { height, ref block_hash, .. } = network_msg {
+                                            if valid && !tx_cache.read().await.has_block(block_hash) {
+                                                let compact = compact_proposal(&network_msg, &mut *tx_cache.write().await);
+                                                if let Some(data) = compact.and_then(|msg| bincode::serialize(&msg).ok()) {
+                                                    if let Err(e) = swarm.behaviour_mut().gossipsub.publish(compact_block_topic.clone(), data) {
+                                                        debug!("Failed to relay block {} as compact: {}", height, e);
+                                                    }
+                                                }
+                                            }
+                                        }
+
+                                        // Compact proposals: rebuild from cached transactions, fetch the rest from the relaying peer.
+                                        // Blocks we already have (say, relayed from 1.0 peers by two nodes) aren't rebuilt again.
+                                        if let NetworkMessage::CompactBlockProposal { height, ref proposer, ref block_hash, ref proposer_signature, ref block } = network_msg {
+                                            forward = false;
+                                            if valid && !pending_blocks.contains_key(block_hash) && !tx_cache.read().await.has_block(block_hash) {
+                                                let pending = PendingCompactBlock {
+                                                    height,
+                                                    proposer: proposer.clone(),
+                                                    block_hash: block_hash.clone(),
+                                                    proposer_signature: proposer_signature.clone(),
+                                                    partial: PartialBlock::new(block.clone(), &*tx_cache.read().await),
+                                                    message_id: message_id.clone(),
+                                                    source: propagation_source,
+                                                    received: std::time::Instant::now(),
+                                                    fetched_in_full: false,
+                                                };
+                                                let missing = pending.partial.missing();
+                                                deferred = true;
+                                                if missing.is_empty() {
+                                                    let relay_in_full = peer_handshakes.read().await.values().any(|info| !info.supports_compact_blocks());
+                                                    let unmatched = pending.finish(&mut swarm.behaviour_mut().gossipsub, &message_tx, &mut *tx_cache.write().await, relay_in_full);
+                                                    if let Some(pending) = unmatched {
+                                                        let pending = pending.fetch_in_full(&mut swarm.behaviour_mut().tx_fetch);
+                                                        pending_blocks.insert(block_hash.clone(), pending);
+                                                    }
+                                                } else if pending_blocks.len() < MAX_PENDING_COMPACT_BLOCKS {
+                                                    debug!("🧱 Compact block {} missing {}/{} transactions, fetching from {}",
+                                                           height, missing.len(), block.short_ids.len(), propagation_source);
+                                                    let key = pending.partial.key();
+                                                    for batch in missing.chunks(MAX_FETCH_BATCH) {
+                                                        swarm.behaviour_mut().tx_fetch.fetch_short(propagation_source, key, batch.to_vec());
+                                                    }
+                                                    pending_blocks.insert(block_hash.clone(), pending);
+                                                } else {
+                                                    warn!("⚠️ Too many compact blocks pending, dropping {} from {}", height, propagation_source);
+                                                    deferred = false;
+                                                    relay = false;
+                                                }
+                                            } else if valid {
+                                                relay = false;
+                                            }
+                                        }
+
+                                        // Handle BlockVote - verify voter signature when the voter is known
+                                        if let NetworkMessage::BlockVote { ref voter, ref block_hash, ref signature, height, .. } = network_msg {
+                                            if let Some(pubkey) = validator_pubkeys.read().await.get(voter).map(|keys| keys.at(height)) {
+                                                if !P2PNetwork::verify_vote_signature(&pubkey, block_hash.as_bytes(), signature) {
+                                                    warn!("⚠️ Rejected BlockVote height={} from {} with invalid signature", height, voter);
+                                                    valid = false;
+                                                    forward = false;
+                                                }
+                                            }
+                                        }
                                         
-                                        if let Some(tx) = &message_tx {
-                                            let _ = tx.send(network_msg);
+                                        if forward {
+                                            if let Some(tx) = &message_tx {
+                                                let _ = tx.send(network_msg);
+                                            }
+                                        }
+                                    } else {
+                                        warn!("⚠️ Undecodable gossipsub message from {} on {}", propagation_source, message.topic);
+                                    }
+
+                                    let acceptance = if valid && relay {
+                                        gossipsub::MessageAcceptance::Accept
+                                    } else if valid {
+                                        gossipsub::MessageAcceptance::Ignore
+                                    } else {
+                                        gossipsub::MessageAcceptance::Reject
+                                    };
+                                    // Compact blocks report their own verdict once rebuilt
+                                    if !deferred {
+                                        let _ = swarm.behaviour_mut().gossipsub.report_message_validation_result(
+                                            &message_id, &propagation_source, acceptance,
+                                        );
+                                    }
+
+                                    // Gossipsub only surfaces the first delivery of each message
+                                    let mut rep = reputation.write().await;
+                                    let app_score = if valid {
+                                        rep.record_first_delivery(propagation_source)
+                                    } else {
+                                        rep.record_invalid(propagation_source)
+                                    };
+                                    swarm.behaviour_mut().gossipsub.set_application_score(&propagation_source, app_score);
+                                    if PeerReputationStore::should_ban(app_score) && !unconditional_peer_ids.contains(&propagation_source) {
+                                        warn!("🚫 Banning peer {} for {}s: reputation {:.1}", propagation_source, PEER_BAN_DURATION_SECS, app_score);
+                                        banned_peers.write().await.insert(propagation_source, BannedPeer {
+                                            peer_id: propagation_source,
+                                            reason: format!("reputation {:.1} after invalid messages", app_score),
+                                            banned_at: std::time::Instant::now(),
+                                            duration_secs: PEER_BAN_DURATION_SECS,
+                                        });
+                                        rep.reset_after_ban(propagation_source);
+                                        connected_peers.write().await.remove(&propagation_source);
+                                        let _ = swarm.disconnect_peer_id(propagation_source);
+                                    }
+                                }
+                                SultanBehaviourEvent::Handshake(handshake::Event::Verified { peer_id, info }) => {
+                                    info!("🪪 Handshake OK with {} (height {}, protocol {})", peer_id, info.height, info.protocol_version);
+                                    // The connection may have closed while the event was queued
+                                    if !swarm.is_connected(&peer_id) {
+                                        continue;
+                                    }
+                                    let mut handshakes = peer_handshakes.write().await;
+                                    handshakes.insert(peer_id, info);
+                                    if !seed_mode {
+                                        update_legacy_subscriptions(&mut swarm.behaviour_mut().gossipsub, &handshakes);
+                                    }
+                                    drop(handshakes);
+                                    connected_peers.write().await.insert(peer_id);
+                                    swarm.behaviour_mut().gossipsub.remove_blacklisted_peer(&peer_id);
+                                }
+                                SultanBehaviourEvent::Handshake(handshake::Event::Mismatch { peer_id, reason, .. }) => {
+                                    // Wrong chain or incompatible binary: drop it and stop dialing it
+                                    warn!("🚫 Disconnecting {}: {}", peer_id, reason);
+                                    peer_handshakes.write().await.remove(&peer_id);
+                                    connected_peers.write().await.remove(&peer_id);
+                                    address_book.write().await.remove(&peer_id);
+                                    swarm.behaviour_mut().kademlia.remove_peer(&peer_id);
+                                    let _ = swarm.disconnect_peer_id(peer_id);
+                                }
+                                SultanBehaviourEvent::Handshake(handshake::Event::Failed { peer_id, error }) => {
+                                    // A second connection to a verified peer (simultaneous dials) doesn't matter
+                                    if peer_handshakes.read().await.contains_key(&peer_id) {
+                                        debug!("Handshake on extra connection to {} failed: {}", peer_id, error);
+                                        continue;
+                                    }
+                                    // Slow or overloaded, not proven to be on another chain: keep it in the
+                                    // address book and DHT so it's dialed again later
+                                    warn!("⚠️ Disconnecting {}: handshake failed: {}", peer_id, error);
+                                    let _ = swarm.disconnect_peer_id(peer_id);
+                                }
+                                SultanBehaviourEvent::TxFetch(tx_fetch::Event::Fetched { peer_id, requested: FetchRequest::ShortIds { ids: requested, .. }, txs }) => {
+                                    debug!("📥 Fetched {}/{} compact block transactions from {}", txs.len(), requested.len(), peer_id);
+                                    // Keep the bodies so peers rebuilding after us can fetch them here
+                                    let mut cache = tx_cache.write().await;
+                                    for tx in &txs {
+                                        cache.insert(tx_id(&tx.tx_data), tx.clone());
+                                    }
+                                    for pending in pending_blocks.values_mut() {
+                                        pending.partial.fill(&txs);
+                                    }
+                                    let complete: Vec<String> = pending_blocks.iter()
+                                        .filter(|(_, pending)| !pending.fetched_in_full && pending.partial.is_complete())
+                                        .map(|(hash, _)| hash.clone())
+                                        .collect();
+                                    let relay_in_full = peer_handshakes.read().await.values().any(|info| !info.supports_compact_blocks());
+                                    for hash in complete {
+                                        let Some(pending) = pending_blocks.remove(&hash) else { continue };
+                                        if let Some(pending) = pending.finish(&mut swarm.behaviour_mut().gossipsub, &message_tx, &mut cache, relay_in_full) {
+                                            let pending = pending.fetch_in_full(&mut swarm.behaviour_mut().tx_fetch);
+                                            pending_blocks.insert(hash, pending);
+                                        }
+                                    }
+                                }
+                                SultanBehaviourEvent::TxFetch(tx_fetch::Event::Fetched { peer_id, requested: FetchRequest::Block(hash), txs }) => {
+                                    debug!("📥 Fetched all {} transactions of compact block {} from {}", txs.len(), hash, peer_id);
+                                    let Some(mut pending) = pending_blocks.remove(&hash) else { continue };
+                                    let mut cache = tx_cache.write().await;
+                                    if pending.partial.fill_in_order(&txs) {
+                                        for tx in &txs {
+                                            cache.insert(tx_id(&tx.tx_data), tx.clone());
+                                        }
+                                    }
+                                    // Already fetched in full, so a mismatch now is final
+                                    let relay_in_full = peer_handshakes.read().await.values().any(|info| !info.supports_compact_blocks());
+                                    let _ = pending.finish(&mut swarm.behaviour_mut().gossipsub, &message_tx, &mut cache, relay_in_full);
+                                }
+                                SultanBehaviourEvent::TxFetch(tx_fetch::Event::Fetched { peer_id, requested: FetchRequest::Ids(requested), txs }) => {
+                                    debug!("📥 Fetched {}/{} announced transactions from {}", txs.len(), requested.len(), peer_id);
+                                    // Anything the peer didn't have can be fetched from the next announcer
+                                    for id in &requested {
+                                        requested_txs.remove(id);
+                                    }
+                                    let mut cache = tx_cache.write().await;
+                                    let mut announce = pending_tx_announce.write().await;
+                                    for tx in txs {
+                                        let id = tx_id(&tx.tx_data);
+                                        if cache.insert(id, tx.clone()) {
+                                            announce.push(id);
+                                            if let Some(sender) = &message_tx {
+                                                let _ = sender.send(NetworkMessage::Transaction { tx_hash: tx.tx_hash, tx_data: tx.tx_data });
+                                            }
                                         }
                                     }
                                 }
-                                SultanBehaviourEvent::Kademlia(kad::Event::RoutingUpdated { peer, .. }) => {
+                                SultanBehaviourEvent::TxFetch(tx_fetch::Event::FetchFailed { peer_id, requested, error }) => {
+                                    debug!("Transaction fetch from {} failed: {}", peer_id, error);
+                                    match requested {
+                                        FetchRequest::Ids(ids) => {
+                                            for id in &ids {
+                                                requested_txs.remove(id);
+                                            }
+                                        }
+                                        // Only the relaying peer was asked; leave the block to block sync
+                                        FetchRequest::ShortIds { .. } | FetchRequest::Block(_) => {
+                                            pending_blocks.retain(|hash, pending| {
+                                                if pending.source != peer_id {
+                                                    return true;
+                                                }
+                                                debug!("Dropping compact block {}: fetch from {} failed", hash, peer_id);
+                                                let _ = swarm.behaviour_mut().gossipsub.report_message_validation_result(
+                                                    &pending.message_id, &pending.source, gossipsub::MessageAcceptance::Ignore,
+                                                );
+                                                false
+                                            });
+                                        }
+                                    }
+                                }
+                                SultanBehaviourEvent::Kademlia(kad::Event::RoutingUpdated { peer, addresses, .. }) => {
                                     debug!("📋 Kademlia routing updated for peer: {}", peer);
+                                    if private_peer_ids.contains(&peer) {
+                                        // Keep private peers out of the DHT so lookups never return them
+                                        swarm.behaviour_mut().kademlia.remove_peer(&peer);
+                                        continue;
+                                    }
+                                    let mut book = address_book.write().await;
+                                    for addr in addresses.iter() {
+                                        book.add(peer, addr, AddressSource::Kademlia);
+                                    }
+                                }
+                                SultanBehaviourEvent::Mdns(mdns::Event::Discovered(found)) => {
+                                    let mut book = address_book.write().await;
+                                    for (peer, addr) in found {
+                                        if private_peer_ids.contains(&peer) {
+                                            continue;
+                                        }
+                                        info!("🔎 mDNS discovered peer {} at {}", peer, addr);
+                                        book.add(peer, &addr, AddressSource::Mdns);
+                                        swarm.behaviour_mut().kademlia.add_address(&peer, addr.clone());
+                                        if !swarm.is_connected(&peer) {
+                                            if let Err(e) = swarm.dial(addr) {
+                                                debug!("mDNS dial to {} failed: {}", peer, e);
+                                            }
+                                        }
+                                    }
                                 }
                                 _ => {}
                             }
@@ -590,7 +1959,9 @@ impl P2PNetwork {
         Ok(())
     }
 
-    /// Broadcast a block proposal to the network (with proposer signature)
+    /// Broadcast a block proposal to the network (with proposer signature).
+    /// Peers get a compact block and fetch the transactions they don't have from us;
+    /// protocol 1.0 peers, if any are connected, also get it in full.
     pub async fn broadcast_block(&self, height: u64, proposer: &str, block_hash: &str, block_data: Vec<u8>, proposer_signature: Vec<u8>) -> Result<()> {
         let msg = NetworkMessage::BlockProposal {
             height,
@@ -599,8 +1970,16 @@ impl P2PNetwork {
             block_data,
             proposer_signature,
         };
-        
-        self.broadcast_message(BLOCK_TOPIC, msg).await
+        let Some(compact_msg) = compact_proposal(&msg, &mut *self.tx_cache.write().await) else {
+            return self.broadcast_message(BLOCK_TOPIC, msg).await;
+        };
+
+        if self.peer_handshakes.read().await.values().any(|info| !info.supports_compact_blocks()) {
+            if let Err(e) = self.gossip(BLOCK_TOPIC, bincode::serialize(&msg)?) {
+                warn!("Failed to send block {} in full to protocol 1.0 peers: {}", height, e);
+            }
+        }
+        self.publish(COMPACT_BLOCK_TOPIC, bincode::serialize(&compact_msg)?, msg)
     }
 
     /// Broadcast a block vote
@@ -616,14 +1995,29 @@ impl P2PNetwork {
         self.broadcast_message(CONSENSUS_TOPIC, msg).await
     }
 
-    /// Broadcast a transaction
+    /// Broadcast a transaction: its ID goes out in the next `TxAnnounce` batch and
+    /// peers fetch the body from us (1.0 peers get it in full with the same batch)
     pub async fn broadcast_transaction(&self, tx_hash: &str, tx_data: Vec<u8>) -> Result<()> {
-        let msg = NetworkMessage::Transaction {
-            tx_hash: tx_hash.to_string(),
-            tx_data,
-        };
-        
-        self.broadcast_message(TX_TOPIC, msg).await
+        if tx_data.len() > MAX_MESSAGE_SIZE {
+            anyhow::bail!("Transaction size {} exceeds maximum {}", tx_data.len(), MAX_MESSAGE_SIZE);
+        }
+
+        let id = tx_id(&tx_data);
+        let tx = FetchedTx { tx_hash: tx_hash.to_string(), tx_data };
+        if self.tx_cache.write().await.insert(id, tx.clone()) {
+            self.pending_tx_announce.write().await.push(id);
+        }
+
+        // Also send to local message channel for processing
+        if let Some(sender) = &self.message_tx {
+            sender.send(NetworkMessage::Transaction { tx_hash: tx.tx_hash, tx_data: tx.tx_data })?;
+        }
+        Ok(())
+    }
+
+    /// Transaction IDs queued for the next announce (cleared when the batch is sent)
+    pub async fn pending_tx_announcements(&self) -> usize {
+        self.pending_tx_announce.read().await.len()
     }
 
     /// Request block sync from peers (for catch-up when behind)
@@ -651,7 +2045,7 @@ impl P2PNetwork {
         let msg = NetworkMessage::ValidatorAnnounce {
             address: address.to_string(),
             stake,
-            peer_id: self.peer_id.to_string(),
+            peer_id: self.announced_peer_id(),
             pubkey,
             signature,
             current_height,
@@ -663,7 +2057,23 @@ impl P2PNetwork {
     /// Internal: broadcast message to a topic
     async fn broadcast_message(&self, topic: &str, msg: NetworkMessage) -> Result<()> {
         let data = bincode::serialize(&msg)?;
+        self.publish(topic, data, msg)
+    }
+
+    /// Publish encoded `data` on `topic` and hand `local` to our own message channel
+    fn publish(&self, topic: &str, data: Vec<u8>, local: NetworkMessage) -> Result<()> {
+        self.gossip(topic, data)?;
+        
+        // Also send to local message channel for processing
+        if let Some(tx) = &self.message_tx {
+            tx.send(local)?;
+        }
         
+        Ok(())
+    }
+
+    /// Publish encoded `data` on `topic` without processing it locally
+    fn gossip(&self, topic: &str, data: Vec<u8>) -> Result<()> {
         // Enforce message size limit
         if data.len() > MAX_MESSAGE_SIZE {
             anyhow::bail!("Message size {} exceeds maximum {}", data.len(), MAX_MESSAGE_SIZE);
@@ -678,12 +2088,6 @@ impl P2PNetwork {
         } else {
             warn!("⚠️ broadcast_tx is None - cannot publish to gossipsub");
         }
-        
-        // Also send to local message channel for processing
-        if let Some(tx) = &self.message_tx {
-            tx.send(msg)?;
-        }
-        
         Ok(())
     }
 
@@ -719,6 +2123,10 @@ impl P2PNetwork {
     
     /// Ban a peer for misbehavior
     pub async fn ban_peer(&self, peer_id: PeerId, reason: &str) {
+        if self.unconditional_peer_ids.contains(&peer_id) {
+            warn!("🛡️ Not banning unconditional peer {}: {}", peer_id, reason);
+            return;
+        }
         let banned = BannedPeer {
             peer_id,
             reason: reason.to_string(),
@@ -729,6 +2137,21 @@ impl P2PNetwork {
         self.banned_peers.write().await.insert(peer_id, banned);
         // Also remove from connected peers
         self.connected_peers.write().await.remove(&peer_id);
+        if let Err(e) = self.save_reputation().await {
+            warn!("Failed to save peer reputation: {}", e);
+        }
+    }
+    
+    /// Lift a ban before it expires; returns false if the peer wasn't banned
+    pub async fn unban_peer(&self, peer_id: &PeerId) -> bool {
+        if self.banned_peers.write().await.remove(peer_id).is_none() {
+            return false;
+        }
+        info!("✅ Unbanned peer {}", peer_id);
+        if let Err(e) = self.save_reputation().await {
+            warn!("Failed to save peer reputation: {}", e);
+        }
+        true
     }
     
     /// Check if a peer is banned
@@ -791,21 +2214,79 @@ use ed25519_dalek::{Signature, Verifier, VerifyingKey};
     pub async fn banned_peer_count(&self) -> usize {
         self.banned_peers.read().await.len()
     }
+
+    /// Scores, reputation and bans for every known peer (lowest score first)
+    pub async fn peer_scores(&self) -> Vec<PeerScoreInfo> {
+        let connected = self.connected_peers.read().await;
+        let banned = self.banned_peers.read().await;
+        let reputation = self.reputation.read().await;
+        let observed = self.observed_scores.read().await;
+        let handshakes = self.peer_handshakes.read().await;
+
+        let peers: HashSet<PeerId> = reputation.peers.keys()
+            .chain(connected.iter())
+            .chain(banned.keys())
+            .copied()
+            .collect();
+        let mut scores: Vec<PeerScoreInfo> = peers.into_iter()
+            .map(|peer| {
+                let r = reputation.get(&peer).cloned().unwrap_or_default();
+                let ban = banned.get(&peer).filter(|ban| !ban.is_expired());
+                PeerScoreInfo {
+                    peer_id: peer.to_string(),
+                    connected: connected.contains(&peer),
+                    score: observed.get(&peer).copied(),
+                    app_score: r.app_score,
+                    first_deliveries: r.first_deliveries,
+                    invalid_messages: r.invalid_messages,
+                    last_seen: r.last_seen,
+                    banned: ban.is_some(),
+                    ban_reason: ban.map(|b| b.reason.clone()),
+                    ban_remaining_secs: ban.map(|b| b.duration_secs.saturating_sub(b.banned_at.elapsed().as_secs())),
+                    height: handshakes.get(&peer).map(|info| info.height),
+                }
+            })
+            .collect();
+        scores.sort_by(|a, b| {
+            let score = |p: &PeerScoreInfo| p.score.unwrap_or(p.app_score);
+            score(a).total_cmp(&score(b)).then_with(|| a.peer_id.cmp(&b.peer_id))
+        });
+        scores
+    }
     
     /// Check if network is healthy (has minimum required peers)
     pub async fn is_healthy(&self) -> bool {
         self.connected_peers.read().await.len() >= MIN_PEERS_REQUIRED
     }
     
-    /// Register a validator's public key for signature verification
+    /// Register a validator's current public key for signature verification
+    ///
+    /// Keys it retired earlier stay registered for the heights they signed.
     pub async fn register_validator_pubkey(&self, address: String, pubkey: [u8; 32]) {
-        self.validator_pubkeys.write().await.insert(address.clone(), pubkey);
+        Self::set_current_key(&mut *self.validator_pubkeys.write().await, &address, pubkey);
         debug!("Registered validator pubkey for {}", address);
     }
     
-    /// Get a validator's public key
+    fn set_current_key(keys: &mut HashMap<String, KeyHistory>, address: &str, pubkey: [u8; 32]) {
+        keys.entry(address.to_string())
+            .and_modify(|history| history.set_current(pubkey))
+            .or_insert_with(|| KeyHistory::new(pubkey));
+    }
+    
+    /// Register a validator's full key history, as rebuilt by consensus
+    pub async fn register_validator_keys(&self, address: String, pubkey: [u8; 32], retired: Vec<RetiredKey>) {
+        debug!("Registered {} retired keys for {}", retired.len(), address);
+        self.validator_pubkeys.write().await.insert(address, KeyHistory::with_retired(pubkey, retired));
+    }
+    
+    /// Get a validator's current public key
     pub async fn get_validator_pubkey(&self, address: &str) -> Option<[u8; 32]> {
-        self.validator_pubkeys.read().await.get(address).copied()
+        self.validator_pubkeys.read().await.get(address).map(KeyHistory::current)
+    }
+    
+    /// Get the public key a validator signed with at `height`
+    pub async fn get_validator_pubkey_at(&self, address: &str, height: u64) -> Option<[u8; 32]> {
+        self.validator_pubkeys.read().await.get(address).map(|keys| keys.at(height))
     }
     
     /// Verify and process a BlockVote - returns true if valid
@@ -876,10 +2357,10 @@ use ed25519_dalek::{Signature, Verifier, VerifyingKey};
         
         validators.iter()
             .filter_map(|addr| {
-                pubkeys.get(addr).map(|pk| ValidatorInfo {
+                pubkeys.get(addr).map(|keys| ValidatorInfo {
                     address: addr.clone(),
                     stake: MIN_VALIDATOR_STAKE, // Will be overridden by consensus
-                    pubkey: *pk,
+                    pubkey: keys.current(),
                 })
             })
             .collect()
@@ -1081,6 +2562,20 @@ mod tests {
         assert_eq!(network.get_validator_pubkey("unknown").await, None);
     }
 
+    #[tokio::test]
+    async fn test_rotated_validator_keys_verify_by_height() {
+        let network = P2PNetwork::new().unwrap();
+        let retired = vec![RetiredKey { pubkey: [1u8; 32], until_height: 500 }];
+        network.register_validator_keys("val1".to_string(), [2u8; 32], retired).await;
+        assert_eq!(network.get_validator_pubkey_at("val1", 499).await, Some([1u8; 32]));
+        assert_eq!(network.get_validator_pubkey_at("val1", 500).await, Some([2u8; 32]));
+
+        // A later announcement of the current key keeps the retired one
+        network.register_validator_pubkey("val1".to_string(), [3u8; 32]).await;
+        assert_eq!(network.get_validator_pubkey("val1").await, Some([3u8; 32]));
+        assert_eq!(network.get_validator_pubkey_at("val1", 10).await, Some([1u8; 32]));
+    }
+
     #[tokio::test]
     async fn test_verify_and_process_vote() {
         use ed25519_dalek::{Signer, SigningKey};
@@ -1223,4 +2718,330 @@ mod tests {
             &[0u8; 64]
         ));
     }
+
+    #[test]
+    fn test_peer_score_params_are_valid() {
+        let (params, thresholds) = peer_score_params();
+        params.validate().unwrap();
+        thresholds.validate().unwrap();
+        for topic in [BLOCK_TOPIC, COMPACT_BLOCK_TOPIC, TX_TOPIC, TX_ANNOUNCE_TOPIC, VALIDATOR_TOPIC, CONSENSUS_TOPIC] {
+            assert!(params.topics.contains_key(&IdentTopic::new(topic).hash()), "{} not scored", topic);
+        }
+    }
+
+    #[test]
+    fn test_legacy_topics_follow_1_0_peers() {
+        let info = |version: &str| HandshakeInfo {
+            chain_id: "sultan-mainnet-1".to_string(),
+            genesis_hash: "abc".to_string(),
+            protocol_version: version.to_string(),
+            height: 1,
+        };
+        let mut peers = HashMap::new();
+        peers.insert(PeerId::random(), info(handshake::PROTOCOL_VERSION));
+        assert!(legacy_topics(&peers).is_empty());
+
+        let old = PeerId::random();
+        peers.insert(old, info("1.0.0"));
+        assert_eq!(legacy_topics(&peers), vec![BLOCK_TOPIC, TX_TOPIC]);
+
+        peers.remove(&old);
+        assert!(legacy_topics(&peers).is_empty());
+    }
+
+    #[test]
+    fn test_reputation_rewards_and_penalties() {
+        let mut store = PeerReputationStore::default();
+        let peer = PeerId::random();
+
+        for _ in 0..1_000 {
+            store.record_first_delivery(peer);
+        }
+        assert_eq!(store.get(&peer).unwrap().app_score, MAX_APP_SCORE);
+
+        // Invalid messages outweigh any amount of good behaviour
+        let mut score = MAX_APP_SCORE;
+        let mut invalid = 0;
+        while !PeerReputationStore::should_ban(score) {
+            score = store.record_invalid(peer);
+            invalid += 1;
+        }
+        assert_eq!(invalid, 6);
+        assert_eq!(store.get(&peer).unwrap().invalid_messages, 6);
+
+        store.reset_after_ban(peer);
+        assert!(!PeerReputationStore::should_ban(store.get(&peer).unwrap().app_score));
+    }
+
+    #[tokio::test]
+    async fn test_reputation_and_bans_survive_restart() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let banned = PeerId::random();
+        let misbehaving = PeerId::random();
+        let expired = PeerId::random();
+
+        let mut network = P2PNetwork::new().unwrap();
+        network.load_reputation(dir.path()).await.unwrap();
+        network.reputation.write().await.record_invalid(misbehaving);
+        network.banned_peers.write().await.insert(expired, BannedPeer {
+            peer_id: expired,
+            reason: "old".to_string(),
+            banned_at: std::time::Instant::now() - Duration::from_secs(PEER_BAN_DURATION_SECS + 1),
+            duration_secs: PEER_BAN_DURATION_SECS,
+        });
+        network.ban_peer(banned, "invalid block").await; // saves
+        assert!(dir.path().join(PEER_REPUTATION_FILE).exists());
+
+        let mut restarted = P2PNetwork::new().unwrap();
+        restarted.load_reputation(dir.path()).await.unwrap();
+        assert!(restarted.is_peer_banned(&banned).await);
+        assert!(!restarted.is_peer_banned(&expired).await);
+        let reputation = restarted.reputation.read().await;
+        assert_eq!(reputation.get(&misbehaving).unwrap().app_score, -INVALID_MESSAGE_PENALTY);
+    }
+
+    #[tokio::test]
+    async fn test_peer_scores_report() {
+        let network = P2PNetwork::new().unwrap();
+        let good = PeerId::random();
+        let bad = PeerId::random();
+        network.reputation.write().await.record_first_delivery(good);
+        network.reputation.write().await.record_invalid(bad);
+        network.ban_peer(bad, "invalid vote").await;
+
+        let scores = network.peer_scores().await;
+        assert_eq!(scores.len(), 2);
+        assert_eq!(scores[0].peer_id, bad.to_string());
+        assert!(scores[0].banned);
+        assert_eq!(scores[0].ban_reason.as_deref(), Some("invalid vote"));
+        assert_eq!(scores[0].invalid_messages, 1);
+        assert_eq!(scores[1].peer_id, good.to_string());
+        assert_eq!(scores[1].first_deliveries, 1);
+        assert!(!scores[1].banned);
+
+        assert!(network.unban_peer(&bad).await);
+        assert!(!network.is_peer_banned(&bad).await);
+        assert!(!network.unban_peer(&bad).await);
+    }
+
+    fn test_addr(port: u16) -> Multiaddr {
+        format!("/ip4/127.0.0.1/tcp/{}", port).parse().unwrap()
+    }
+
+    #[test]
+    fn test_address_book_tracks_addresses_and_quality() {
+        let mut book = AddressBook::default();
+        let reliable = PeerId::random();
+        let flaky = PeerId::random();
+        let unknown = PeerId::random();
+
+        book.add(reliable, &test_addr(4001), AddressSource::Kademlia);
+        book.add(reliable, &test_addr(4001), AddressSource::Kademlia);
+        book.add(reliable, &test_addr(4002), AddressSource::Mdns);
+        let entry = book.get(&reliable).unwrap();
+        assert_eq!(entry.addrs, vec![test_addr(4002).to_string(), test_addr(4001).to_string()]);
+        assert_eq!(entry.source, AddressSource::Kademlia);
+
+        for port in 0..(MAX_ADDRESSES_PER_PEER as u16 + 3) {
+            book.add(flaky, &test_addr(5000 + port), AddressSource::Kademlia);
+        }
+        assert_eq!(book.get(&flaky).unwrap().addrs.len(), MAX_ADDRESSES_PER_PEER);
+
+        book.record_success(reliable, None);
+        book.record_failure(flaky);
+        book.record_failure(unknown); // not in the book: ignored
+        assert_eq!(book.len(), 2);
+
+        let best = book.best(2, &HashSet::new());
+        assert_eq!(best[0].0, reliable);
+        assert_eq!(best[1].0, flaky);
+        assert!(book.best(2, &HashSet::from([reliable]))[0].0 == flaky);
+
+        // A success resets the failure streak
+        book.record_success(flaky, Some(&test_addr(6000)));
+        let entry = book.get(&flaky).unwrap();
+        assert_eq!(entry.failures, 0);
+        assert_eq!(entry.addrs[0], test_addr(6000).to_string());
+    }
+
+    #[test]
+    fn test_address_book_persists_and_forgets_dead_peers() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(ADDRESS_BOOK_FILE);
+        let alive = PeerId::random();
+        let dead = PeerId::random();
+
+        let mut book = AddressBook::load(&path).unwrap();
+        assert!(book.is_empty());
+        book.add(alive, &test_addr(4001), AddressSource::Bootstrap);
+        book.record_success(alive, None);
+        book.add(dead, &test_addr(4002), AddressSource::Kademlia);
+        let entry = book.entries.get_mut(&dead).unwrap();
+        entry.first_seen -= ADDRESS_BOOK_STALE_SECS;
+        entry.failures = ADDRESS_BOOK_MAX_FAILURES;
+        book.save().unwrap();
+
+        let reloaded = AddressBook::load(&path).unwrap();
+        assert_eq!(reloaded.len(), 1);
+        let entry = reloaded.get(&alive).unwrap();
+        assert_eq!(entry.source, AddressSource::Bootstrap);
+        assert_eq!(entry.successes, 1);
+        assert!(entry.last_connected > 0);
+    }
+
+    #[test]
+    fn test_address_book_evicts_lowest_quality() {
+        let mut book = AddressBook::default();
+        let worst = PeerId::random();
+        book.add(worst, &test_addr(1), AddressSource::Kademlia);
+        for _ in 0..3 {
+            book.record_failure(worst);
+        }
+        for i in 0..MAX_ADDRESS_BOOK_ENTRIES {
+            let peer = PeerId::random();
+            book.add(peer, &test_addr(2 + i as u16), AddressSource::Kademlia);
+            book.record_success(peer, None);
+        }
+        assert_eq!(book.len(), MAX_ADDRESS_BOOK_ENTRIES);
+        assert!(book.get(&worst).is_none());
+    }
+
+    #[test]
+    fn test_peer_id_from_multiaddr() {
+        let peer = PeerId::random();
+        let addr: Multiaddr = format!("/ip4/10.0.0.1/tcp/26656/p2p/{}", peer).parse().unwrap();
+        assert_eq!(peer_id_from_multiaddr(&addr), Some(peer));
+        assert_eq!(peer_id_from_multiaddr(&test_addr(26656)), None);
+    }
+
+    #[tokio::test]
+    async fn test_seed_mode_and_mdns_start() {
+        let mut network = P2PNetwork::new().unwrap();
+        network.set_seed_mode(true);
+        network.set_mdns(true);
+        assert!(network.is_seed_mode());
+        network.start("/ip4/127.0.0.1/tcp/0").await.unwrap();
+        assert!(network.is_running().await);
+        network.stop().await.unwrap();
+    }
+
+    #[test]
+    fn test_sentry_peer_settings() {
+        let mut network = P2PNetwork::new().unwrap();
+        let peer = PeerId::random();
+
+        // Persistent peers must name the peer they expect
+        assert!(network.set_persistent_peers(vec!["/ip4/10.0.0.1/tcp/26656".to_string()]).is_err());
+        network.set_persistent_peers(vec![format!("/ip4/10.0.0.1/tcp/26656/p2p/{}", peer)]).unwrap();
+        assert_eq!(network.persistent_peers.len(), 1);
+
+        assert!(network.set_private_peer_ids(vec!["not-a-peer-id".to_string()]).is_err());
+        network.set_private_peer_ids(vec![peer.to_string()]).unwrap();
+        assert!(network.private_peer_ids.contains(&peer));
+        network.set_unconditional_peer_ids(vec![peer.to_string()]).unwrap();
+        assert!(network.unconditional_peer_ids.contains(&peer));
+    }
+
+    #[tokio::test]
+    async fn test_unconditional_peer_is_never_banned() {
+        let mut network = P2PNetwork::new().unwrap();
+        let sentry = PeerId::random();
+        network.set_unconditional_peer_ids(vec![sentry.to_string()]).unwrap();
+
+        network.ban_peer(sentry, "test").await;
+        assert!(!network.is_peer_banned(&sentry).await);
+
+        let other = PeerId::random();
+        network.ban_peer(other, "test").await;
+        assert!(network.is_peer_banned(&other).await);
+    }
+
+    #[tokio::test]
+    async fn test_hidden_peer_id() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let mut network = P2PNetwork::new().unwrap();
+        assert_eq!(network.announced_peer_id(), network.peer_id().to_string());
+
+        network.set_hide_peer_id(true);
+        network.set_mdns(true);
+        assert!(network.is_peer_id_hidden());
+        assert!(network.announced_peer_id().is_empty());
+
+        // Announcements without a peer ID still verify
+        let signing_key = SigningKey::from_bytes(&[9u8; 32]);
+        let message = format!("{}{}{}", "sultan1validator", 1000u64, network.announced_peer_id());
+        let signature = signing_key.sign(message.as_bytes()).to_bytes();
+        let pubkey = signing_key.verifying_key().to_bytes();
+        assert!(P2PNetwork::verify_announce_signature(&pubkey, "sultan1validator", 1000, "", &signature));
+
+        // Anonymous gossip and DHT client mode build fine; mDNS is skipped
+        network.start("/ip4/127.0.0.1/tcp/0").await.unwrap();
+        assert!(network.is_running().await);
+        network.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_transaction_queues_announce() {
+        let network = P2PNetwork::new().unwrap();
+        network.broadcast_transaction("alice:bob:1", vec![1, 2, 3]).await.unwrap();
+        assert_eq!(network.pending_tx_announcements().await, 1);
+        assert!(network.tx_cache.read().await.contains(&tx_id(&[1, 2, 3])));
+
+        // The same body is only announced once
+        network.broadcast_transaction("alice:bob:1", vec![1, 2, 3]).await.unwrap();
+        assert_eq!(network.pending_tx_announcements().await, 1);
+
+        let oversized = vec![0u8; MAX_MESSAGE_SIZE + 1];
+        assert!(network.broadcast_transaction("big", oversized).await.is_err());
+        assert_eq!(network.pending_tx_announcements().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_block_serves_its_transactions() {
+        use crate::blockchain::Transaction;
+
+        let mut network = P2PNetwork::new().unwrap();
+        let mut rx = network.take_message_receiver().unwrap();
+        let tx = Transaction {
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            amount: 5,
+            gas_fee: 0,
+            timestamp: 1_700_000_000,
+            nonce: 1,
+            signature: None,
+            public_key: None,
+            memo: None,
+        };
+        let block = Block {
+            index: 3,
+            timestamp: 1_700_000_000,
+            transactions: vec![tx.clone()],
+            prev_hash: "prev".to_string(),
+            hash: "hash".to_string(),
+            nonce: 0,
+            validator: "sultan1test".to_string(),
+            state_root: "root".to_string(),
+        };
+        let block_data = bincode::serialize(&block).unwrap();
+        let block_hash = compact_block::block_hash(&block_data);
+
+        network.broadcast_block(3, "sultan1test", &block_hash, block_data.clone(), vec![0u8; 64]).await.unwrap();
+
+        // Peers rebuilding the compact block can fetch its transactions from us
+        let body = compact_block::encode_tx(&tx).unwrap();
+        assert!(network.tx_cache.read().await.contains(&tx_id(&body.tx_data)));
+        // ...and copies relayed back to us from 1.0 peers aren't rebuilt again
+        assert!(network.tx_cache.read().await.has_block(&block_hash));
+
+        // Our own consensus still sees the full proposal
+        match rx.try_recv().unwrap() {
+            NetworkMessage::BlockProposal { height, block_data: data, .. } => {
+                assert_eq!(height, 3);
+                assert_eq!(data, block_data);
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,184 @@
+//! Cursor pagination for list endpoints
+//!
+//! List routes take `cursor`, `limit` and `order`. Items are ordered by a key
+//! that is unique within the list (block height, proposal id, denom, ...). A page
+//! holds up to `limit` items after the cursor, and `next_cursor` is the key of its
+//! last item when more remain. Unlike offsets, a cursor keeps pointing at the same
+//! place while new items are added.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+
+/// Items per page when the request gives no `limit`
+pub const DEFAULT_PAGE_LIMIT: usize = 50;
+
+/// Most items a page holds
+pub const MAX_PAGE_LIMIT: usize = 500;
+
+/// Direction a list is walked in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    Asc,
+    /// Newest (or highest) first
+    #[default]
+    Desc,
+}
+
+/// A key items are ordered by and cursors encode
+pub trait CursorKey: Ord + Sized {
+    fn encode(&self) -> String;
+    fn decode(cursor: &str) -> Option<Self>;
+}
+
+impl CursorKey for u64 {
+    fn encode(&self) -> String {
+        self.to_string()
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        cursor.parse().ok()
+    }
+}
+
+impl CursorKey for String {
+    fn encode(&self) -> String {
+        self.clone()
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        (!cursor.is_empty()).then(|| cursor.to_string())
+    }
+}
+
+/// `height:id`, for items ordered by height with an id to break ties
+impl CursorKey for (u64, String) {
+    fn encode(&self) -> String {
+        format!("{}:{}", self.0, self.1)
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        let (height, id) = cursor.split_once(':')?;
+        Some((height.parse().ok()?, id.to_string()))
+    }
+}
+
+/// Pagination parameters of a list request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageParams {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    pub order: Order,
+}
+
+/// One page of a list
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor for the next page, if there is one
+    pub next_cursor: Option<String>,
+}
+
+impl PageParams {
+    pub fn new(cursor: Option<String>, limit: Option<usize>, order: Order) -> Self {
+        Self { cursor, limit, order }
+    }
+
+    /// Page size, clamped to `1..=MAX_PAGE_LIMIT`
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT)
+    }
+
+    /// Decoded cursor, if one was given
+    pub fn cursor<K: CursorKey>(&self) -> Result<Option<K>> {
+        match self.cursor.as_deref() {
+            None => Ok(None),
+            Some(cursor) => match K::decode(cursor) {
+                Some(key) => Ok(Some(key)),
+                None => bail!("Invalid cursor: {}", cursor),
+            },
+        }
+    }
+
+    /// Whether `key` comes after the cursor in this order
+    pub fn is_after<K: CursorKey>(&self, key: &K, cursor: Option<&K>) -> bool {
+        match (cursor, self.order) {
+            (None, _) => true,
+            (Some(cursor), Order::Asc) => key > cursor,
+            (Some(cursor), Order::Desc) => key < cursor,
+        }
+    }
+
+    /// The page of `items` after the cursor, ordered by `key`
+    ///
+    /// `key` must be unique among the items.
+    pub fn paginate<T, K: CursorKey>(&self, mut items: Vec<T>, key: impl Fn(&T) -> K) -> Result<Page<T>> {
+        let cursor = self.cursor::<K>()?;
+        items.sort_by_key(|item| key(item));
+        if self.order == Order::Desc {
+            items.reverse();
+        }
+        let limit = self.limit();
+        let mut page: Vec<T> = items.into_iter()
+            .filter(|item| self.is_after(&key(item), cursor.as_ref()))
+            .take(limit + 1)
+            .collect();
+
+        let next_cursor = if page.len() > limit {
+            page.truncate(limit);
+            page.last().map(|item| key(item).encode())
+        } else {
+            None
+        };
+        Ok(Page { items: page, next_cursor })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(cursor: Option<&str>, limit: usize, order: Order) -> PageParams {
+        PageParams::new(cursor.map(str::to_string), Some(limit), order)
+    }
+
+    #[test]
+    fn test_walks_pages_in_both_orders() {
+        let items: Vec<u64> = vec![4, 1, 5, 2, 3];
+
+        let page = params(None, 2, Order::Desc).paginate(items.clone(), |i| *i).unwrap();
+        assert_eq!(page.items, vec![5, 4]);
+        assert_eq!(page.next_cursor.as_deref(), Some("4"));
+        let page = params(Some("4"), 2, Order::Desc).paginate(items.clone(), |i| *i).unwrap();
+        assert_eq!(page.items, vec![3, 2]);
+        let page = params(Some("2"), 2, Order::Desc).paginate(items.clone(), |i| *i).unwrap();
+        assert_eq!(page.items, vec![1]);
+        assert_eq!(page.next_cursor, None);
+
+        let page = params(Some("3"), 5, Order::Asc).paginate(items, |i| *i).unwrap();
+        assert_eq!(page.items, vec![4, 5]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn test_exact_fit_has_no_next_cursor() {
+        let page = params(None, 3, Order::Asc).paginate(vec![1u64, 2, 3], |i| *i).unwrap();
+        assert_eq!(page.items.len(), 3);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn test_composite_cursor_and_limits() {
+        let items = vec![(7u64, "b".to_string()), (7, "a".to_string()), (9, "c".to_string())];
+        let page = params(None, 2, Order::Desc).paginate(items.clone(), |i| i.clone()).unwrap();
+        assert_eq!(page.next_cursor.as_deref(), Some("7:b"));
+        let page = params(Some("7:b"), 2, Order::Desc).paginate(items, |i| i.clone()).unwrap();
+        assert_eq!(page.items, vec![(7, "a".to_string())]);
+
+        assert!(params(Some("x"), 2, Order::Asc).paginate(vec![1u64], |i| *i).is_err());
+        assert_eq!(PageParams::default().limit(), DEFAULT_PAGE_LIMIT);
+        assert_eq!(params(None, 0, Order::Asc).limit(), 1);
+        assert_eq!(params(None, 10_000, Order::Asc).limit(), MAX_PAGE_LIMIT);
+    }
+}
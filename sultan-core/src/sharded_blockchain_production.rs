@@ -19,15 +19,23 @@
 //! The shard count is configurable (default: 16, max: 8,000).
 
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use sha2::{Sha256, Digest};
 use anyhow::{Result, Context, bail};
 use tracing::{info, warn, error};
 use std::sync::Arc;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use tokio::sync::RwLock;
 
 use crate::blockchain::{Block, Transaction};
-use crate::sharding_production::{ShardingCoordinator, ShardConfig, ShardStats, Shard};
+use crate::beacon::{self, is_beacon_tx};
+use crate::key_rotation::{is_key_rotation_tx, KeyRotation};
+use crate::config::Config;
+#[cfg(feature = "node")]
+use crate::storage::PersistentStorage;
+use crate::transaction_validator::SignatureRules;
+use crate::sharding_production::{ShardingCoordinator, ShardConfig, ShardStats, ShardMetrics, Shard};
 
 /// Maximum history entries per address - a configurable memory bound.
 /// 
@@ -37,7 +45,7 @@ use crate::sharding_production::{ShardingCoordinator, ShardConfig, ShardStats, S
 /// for persistent RocksDB-backed storage.
 /// 
 /// Default: 10,000 entries (~1MB per address at 100 bytes/tx average)
-const MAX_HISTORY_PER_ADDRESS: usize = 10_000;
+pub const MAX_HISTORY_PER_ADDRESS: usize = 10_000;
 /// 
 /// The unified production blockchain for Sultan Chain.
 /// Always uses sharded architecture internally for scalability.
@@ -54,7 +62,7 @@ const MAX_HISTORY_PER_ADDRESS: usize = 10_000;
 /// blockchain.init_account("sultan...".to_string(), 500_000_000_000_000_000).await?;
 /// ```
 /// Confirmed transaction with block info for history
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ConfirmedTransaction {
     pub hash: String,
     pub from: String,
@@ -79,6 +87,14 @@ pub struct SultanBlockchain {
     pub transaction_history: Arc<RwLock<HashMap<String, Vec<ConfirmedTransaction>>>>,
     /// Transaction lookup by hash
     pub transactions_by_hash: Arc<RwLock<HashMap<String, ConfirmedTransaction>>>,
+    /// Height the randomness beacon activates at (see `ConsensusEngine`); blocks
+    /// carry beacon entries only from one commit window before it
+    pub beacon_activation_height: Option<u64>,
+    /// Height strict Ed25519 rules apply from (see `SignatureRules`); unset
+    /// keeps checking each signature on its own
+    pub strict_signatures_activation_height: Option<u64>,
+    /// Chain key rotations must be signed for
+    pub chain_id: String,
 }
 
 /// Backward compatibility alias
@@ -91,13 +107,21 @@ impl SultanBlockchain {
     /// Default config: 16 shards = 64,000 TPS (2-second blocks)
     /// Auto-expands up to 8,000 shards (32M TPS) when load > 80%
     pub fn new(config: ShardConfig) -> Self {
+        Self::with_commit_log(config, None)
+    }
+
+    /// Create blockchain whose cross-shard commit log lives in `commit_log_dir`
+    /// 
+    /// The node uses `<data-dir>/commit-log`. Call `recover_commit_log` after
+    /// restoring blocks from storage to replay interrupted cross-shard commits.
+    pub fn with_commit_log(config: ShardConfig, commit_log_dir: Option<PathBuf>) -> Self {
         info!(
             "🚀 Creating Sultan L1 Blockchain: {} shards, {} TPS capacity, zero gas fees",
             config.shard_count,
             (config.shard_count * config.tx_per_shard) as u64 / 2 // 2-second blocks
         );
 
-        let coordinator = Arc::new(ShardingCoordinator::new(config.clone()));
+        let coordinator = Arc::new(ShardingCoordinator::with_commit_log(config.clone(), commit_log_dir));
 
         // Start health monitoring in background
         let monitor_coordinator = coordinator.clone();
@@ -113,6 +137,9 @@ impl SultanBlockchain {
             transaction_pool: Arc::new(RwLock::new(HashMap::new())),
             transaction_history: Arc::new(RwLock::new(HashMap::new())),
             transactions_by_hash: Arc::new(RwLock::new(HashMap::new())),
+            beacon_activation_height: None,
+            strict_signatures_activation_height: None,
+            chain_id: Config::default().chain_id,
         }
     }
 
@@ -176,9 +203,20 @@ impl SultanBlockchain {
             info!("  -> TX: {} -> {} amount={} nonce={}", tx.from, tx.to, tx.amount, tx.nonce);
         }
 
+        // Beacon entries aren't transfers: keep only the proposer's own and append it unexecuted
+        let (beacon_txs, transactions): (Vec<_>, Vec<_>) = transactions.into_iter().partition(is_beacon_tx);
+        let beacon_tx = beacon_txs.into_iter().rev().find(|tx| tx.from == validator);
+
+        // Key rotations aren't transfers either: append the well-formed ones unexecuted
+        let (rotation_txs, transactions): (Vec<_>, Vec<_>) = transactions.into_iter().partition(is_key_rotation_tx);
+        let rotation_txs: Vec<_> = rotation_txs.into_iter()
+            .filter(|tx| KeyRotation::from_transaction(tx).is_some_and(|r| r.verify_new_key(&self.chain_id).is_ok()))
+            .collect();
+
         // Process same-shard transactions in parallel
+        let rules = SignatureRules::at(self.strict_signatures_activation_height, self.get_height().await + 1);
         let processed_same_shard = self.coordinator
-            .process_parallel(transactions)
+            .process_parallel(transactions, rules)
             .await
             .context("Failed to process same-shard transactions")?;
 
@@ -197,6 +235,7 @@ impl SultanBlockchain {
         // IMPORTANT: Include cross-shard txs for full replication to all nodes
         let mut all_transactions = processed_same_shard.clone();
         all_transactions.extend(committed_cross_shard.clone());
+        all_transactions.extend(rotation_txs);
 
         // Create block
         let blocks = self.blocks.read().await;
@@ -207,6 +246,11 @@ impl SultanBlockchain {
         let prev_timestamp = prev_block.timestamp;
         drop(blocks);
 
+        // Before the beacon's network upgrade, blocks carry no entries
+        if beacon::entries_enabled(self.beacon_activation_height, index) {
+            all_transactions.extend(beacon_tx);
+        }
+
         // Aggregate state root from ALL shards (Merkle of shard roots)
         let shards = self.coordinator.shards.read().await;
         let state_root = if shards.is_empty() {
@@ -271,6 +315,24 @@ impl SultanBlockchain {
         Ok(())
     }
 
+    /// Replay cross-shard transactions interrupted by a crash
+    /// 
+    /// Pending commit log entries go back into the mempool so the next block
+    /// re-runs them through two-phase commit and replicates them to peers.
+    /// Returns the number of transactions resubmitted.
+    pub async fn recover_commit_log(&self) -> Result<usize> {
+        let replay = self.coordinator.recover_commit_log().await
+            .context("Failed to recover cross-shard commit log")?;
+        let count = replay.len();
+        for tx in replay {
+            self.submit_transaction(tx).await?;
+        }
+        if count > 0 {
+            warn!("🔁 Resubmitted {} interrupted cross-shard transactions to mempool", count);
+        }
+        Ok(count)
+    }
+
     /// Drain all pending transactions from mempool for block production
     /// 
     /// Transactions are sorted by timestamp then nonce for deterministic ordering.
@@ -409,6 +471,78 @@ impl SultanBlockchain {
         self.apply_block_internal(block, true).await
     }
 
+    /// Restore from an account snapshot instead of replaying pruned blocks
+    ///
+    /// `headers` are the blocks 1..=snapshot height (bodies may be empty); they
+    /// are appended without executing transactions. `accounts` is the
+    /// (address, balance, nonce) state after the last header.
+    pub async fn restore_from_snapshot(&self, headers: Vec<Block>, accounts: Vec<(String, u64, u64)>) -> Result<()> {
+        let mut blocks = self.blocks.write().await;
+        for header in headers {
+            let last = blocks.last()
+                .ok_or_else(|| anyhow::anyhow!("No blocks in chain"))?;
+            if header.index != last.index + 1 || header.prev_hash != last.hash {
+                bail!(
+                    "Snapshot header {} does not extend block {} ({})",
+                    header.index, last.index, last.hash
+                );
+            }
+            blocks.push(header);
+        }
+        drop(blocks);
+
+        let account_count = accounts.len();
+        for (address, balance, nonce) in accounts {
+            self.coordinator.restore_account(address, balance, nonce).await;
+        }
+        info!("📥 Restored {} accounts from snapshot", account_count);
+        Ok(())
+    }
+
+    /// Rebuild chain state from the blocks persisted in `storage`
+    ///
+    /// Replays every stored block; when older bodies have been pruned, starts
+    /// from the latest account snapshot instead.
+    #[cfg(feature = "node")]
+    pub async fn restore_from_storage(&self, storage: &PersistentStorage) -> Result<()> {
+        let Some(latest_block) = storage.get_latest_block()? else {
+            return Ok(());
+        };
+        warn!("🔄🔄🔄 BLOCKCHAIN RESTORE: Loading {} blocks from storage", latest_block.index);
+        
+        // Pruned bodies can't be replayed: start from the account snapshot instead
+        let mut first_replayed = 1;
+        if !storage.pruned_ranges().is_empty() {
+            let snapshot = storage.load_account_snapshot()?
+                .context("Block bodies are pruned but no account snapshot was found")?;
+            let mut headers = Vec::with_capacity(snapshot.height as usize);
+            for i in 1..=snapshot.height {
+                let header = storage.get_block_header(i)?
+                    .with_context(|| format!("Missing header for block {}", i))?;
+                headers.push(header.into_block());
+            }
+            self.restore_from_snapshot(headers, snapshot.accounts).await
+                .context("Failed to restore account snapshot")?;
+            warn!("🔄 Restored account snapshot at height {}", snapshot.height);
+            first_replayed = snapshot.height + 1;
+        }
+        
+        for i in first_replayed..=latest_block.index {
+            if let Some(block) = storage.get_block_by_height(i)? {
+                // Apply block to restore state (silently to avoid journald rate limiting)
+                if let Err(e) = self.apply_block_silent(block.clone()).await {
+                    warn!("Failed to apply stored block {}: {}", i, e);
+                }
+                // Log progress every 10,000 blocks
+                if i % 10000 == 0 {
+                    warn!("🔄 Restored {} / {} blocks...", i, latest_block.index);
+                }
+            }
+        }
+        warn!("✅✅✅ BLOCKCHAIN RESTORE COMPLETE: Loaded {} blocks", latest_block.index);
+        Ok(())
+    }
+
     /// Internal apply_block implementation with optional logging
     async fn apply_block_internal(&self, block: Block, verbose: bool) -> Result<()> {
         if verbose {
@@ -475,11 +609,15 @@ impl SultanBlockchain {
             } // pending lock released here
             
             // Process transactions through our coordinator (no locks held)
-            let transactions = block.transactions.clone();
+            let transactions: Vec<Transaction> = block.transactions.iter()
+                .filter(|tx| !is_beacon_tx(tx) && !is_key_rotation_tx(tx))
+                .cloned()
+                .collect();
             
             // Process same-shard transactions
+            let rules = SignatureRules::at(self.strict_signatures_activation_height, block.index);
             let _ = self.coordinator
-                .process_parallel(transactions)
+                .process_parallel(transactions, rules)
                 .await
                 .context("Failed to process transactions from synced block")?;
             
@@ -598,6 +736,11 @@ impl SultanBlockchain {
         self.coordinator.get_tps_capacity().await
     }
 
+    /// Get per-shard transaction counts and health
+    pub async fn get_shard_metrics(&self) -> Vec<ShardMetrics> {
+        self.coordinator.shard_metrics().await
+    }
+
     /// Get shard health status
     pub async fn get_shard_health(&self) -> Vec<(usize, bool)> {
         let shards = self.coordinator.shards.read().await;
@@ -827,19 +970,47 @@ impl SultanBlockchain {
         }; // shards lock dropped - we now have owned Arc clones
 
         // Validate all transactions WITHOUT holding coordinator locks
+        let mut by_shard: Vec<Vec<&Transaction>> = vec![Vec::new(); shard_count];
         for tx in &block.transactions {
+            // Beacon entries are signed with the proposer's consensus key, which
+            // consensus checks (`ConsensusEngine::verify_beacon_entries`)
+            if is_beacon_tx(tx) {
+                if !beacon::entries_enabled(self.beacon_activation_height, block.index) {
+                    bail!("Beacon entry in block {} before the beacon activates", block.index);
+                }
+                if tx.from != block.validator || tx.amount != 0 {
+                    bail!("Beacon entry from {} in block proposed by {}", tx.from, block.validator);
+                }
+                continue;
+            }
+
+            // Key rotations carry their own signatures; consensus checks the current key
+            if is_key_rotation_tx(tx) {
+                let rotation = KeyRotation::from_transaction(tx)
+                    .with_context(|| format!("Malformed key rotation from {}", tx.from))?;
+                if tx.amount != 0 || tx.gas_fee != 0 {
+                    bail!("Key rotation from {} moves funds", tx.from);
+                }
+                rotation.verify_new_key(&self.chain_id)?;
+                continue;
+            }
+
             // Zero gas fee enforcement
             if tx.gas_fee != 0 {
                 bail!("Transaction has non-zero gas fee - violates Sultan Chain policy");
             }
             
-            // SECURITY: Full Ed25519 signature verification
             // Route to appropriate shard based on sender address
-            let shard_id = Shard::calculate_shard_id(&tx.from, shard_count);
-            let shard = &shards[shard_id];
-            
-            if let Err(e) = shard.verify_signature(tx) {
-                bail!("Invalid signature for transaction from {}: {}", tx.from, e);
+            by_shard[Shard::calculate_shard_id(&tx.from, shard_count)].push(tx);
+        }
+
+        // SECURITY: Full Ed25519 signature verification, one batch per shard
+        let rules = SignatureRules::at(self.strict_signatures_activation_height, block.index);
+        for (shard_id, txs) in by_shard.iter().enumerate().filter(|(_, txs)| !txs.is_empty()) {
+            for (tx, result) in txs.iter().zip(shards[shard_id].verify_signatures(txs, rules)) {
+                if let Err(e) = result {
+                    bail!("Invalid signature for transaction from {}: {}", tx.from, e);
+                }
             }
         }
 
@@ -1515,4 +1686,97 @@ mod tests {
                 err_msg.contains("expected"),
             "Error should indicate block rejection: {}", err_msg);
     }
+
+    #[tokio::test]
+    async fn test_beacon_entry_carried_in_block() {
+        use crate::beacon::{BeaconEntry, RandomnessBeacon};
+        use ed25519_dalek::SigningKey;
+
+        let mut proposer = SultanBlockchain::new(ShardConfig::default());
+        let mut follower = SultanBlockchain::new(ShardConfig::default());
+        proposer.beacon_activation_height = Some(1);
+        follower.beacon_activation_height = Some(1);
+        let beacon = RandomnessBeacon::new();
+        let (key, mallory) = (SigningKey::from_bytes(&[1u8; 32]), SigningKey::from_bytes(&[2u8; 32]));
+        let ours = beacon.entry_for("validator1", &key, 1)
+            .into_transaction("validator1", &key, 1, 1);
+        let stray = beacon.entry_for("mallory", &mallory, 1)
+            .into_transaction("mallory", &mallory, 1, 1);
+
+        // Only the proposer's own entry makes it into the block
+        let block = proposer.create_block(vec![stray, ours.clone()], "validator1".to_string()).await.unwrap();
+        assert_eq!(block.transactions, vec![ours.clone()]);
+        assert!(BeaconEntry::from_transaction(&block.transactions[0]).is_some());
+
+        // Beacon entries carry no wallet signature and aren't executed as transfers
+        follower.apply_block(block.clone()).await.unwrap();
+        assert_eq!(follower.get_height().await, 1);
+        assert_eq!(follower.get_balance("validator1").await, 0);
+
+        // An entry from anyone but the proposer is rejected
+        let mut forged = block;
+        forged.index = 2;
+        forged.prev_hash = forged.hash.clone();
+        forged.timestamp += 1;
+        forged.transactions[0].from = "mallory".to_string();
+        forged.hash = SultanBlockchain::calculate_block_hash(&forged);
+        assert!(follower.validate_block(&forged).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_no_beacon_entries_without_activation() {
+        use crate::beacon::RandomnessBeacon;
+        use ed25519_dalek::SigningKey;
+
+        let proposer = SultanBlockchain::new(ShardConfig::default());
+        let follower = SultanBlockchain::new(ShardConfig::default());
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let entry = RandomnessBeacon::new().entry_for("validator1", &key, 1)
+            .into_transaction("validator1", &key, 1, 1);
+
+        // Without an activation height the proposer's entry is left out...
+        let block = proposer.create_block(vec![entry.clone()], "validator1".to_string()).await.unwrap();
+        assert!(block.transactions.is_empty());
+
+        // ...and the block is accepted by a node that hasn't activated either
+        follower.apply_block(block.clone()).await.unwrap();
+        assert_eq!(follower.get_height().await, 1);
+
+        // A block carrying an entry before activation is rejected
+        let mut early = block;
+        early.index = 2;
+        early.prev_hash = early.hash.clone();
+        early.timestamp += 1;
+        early.transactions = vec![entry];
+        early.hash = SultanBlockchain::calculate_block_hash(&early);
+        assert!(follower.validate_block(&early).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_key_rotation_carried_in_block() {
+        use ed25519_dalek::SigningKey;
+
+        let proposer = SultanBlockchain::new(ShardConfig::default());
+        let follower = SultanBlockchain::new(ShardConfig::default());
+        let old = SigningKey::from_bytes(&[1u8; 32]);
+        let new = SigningKey::from_bytes(&[2u8; 32]);
+        let rotation = KeyRotation::sign(&proposer.chain_id, "validator2", &old, &new, 500).into_transaction(1);
+        let mut garbage = rotation.clone();
+        garbage.memo = Some("keyrot:v1:nonsense".to_string());
+
+        // Malformed rotations are dropped; well-formed ones are carried unexecuted
+        let block = proposer.create_block(vec![garbage.clone(), rotation.clone()], "validator1".to_string()).await.unwrap();
+        assert_eq!(block.transactions, vec![rotation]);
+        follower.apply_block(block.clone()).await.unwrap();
+        assert_eq!(follower.get_balance("validator2").await, 0);
+
+        // A block carrying a malformed rotation is rejected
+        let mut forged = block;
+        forged.index = 2;
+        forged.prev_hash = forged.hash.clone();
+        forged.timestamp += 1;
+        forged.transactions = vec![garbage];
+        forged.hash = SultanBlockchain::calculate_block_hash(&forged);
+        assert!(follower.validate_block(&forged).await.is_err());
+    }
 }
@@ -12,7 +12,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use std::fs;
-use std::path::Path;
+use std::path::PathBuf;
 use anyhow::{Result, bail, Context};
 use tracing::{info, warn, debug, error};
 use sha2::{Sha256, Digest};
@@ -22,10 +22,12 @@ use ed25519_dalek::{Signature, SigningKey, VerifyingKey, Verifier, SIGNATURE_LEN
 use rand::rngs::OsRng;
 
 use crate::blockchain::{Transaction, Account};
+use crate::transaction_validator::{verify_batch_or_each, SignatureRules};
 
 const CROSS_SHARD_TIMEOUT: Duration = Duration::from_secs(30);
 const MAX_RETRY_ATTEMPTS: u32 = 3;
-const COMMIT_LOG_PATH: &str = "/var/lib/sultan/commit-log";
+/// Commit log directory name, relative to the node data directory
+pub const COMMIT_LOG_DIR: &str = "commit-log";
 const SHARD_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
 
 /// Configuration for production sharding
@@ -207,6 +209,66 @@ impl Shard {
     /// 
     /// SECURITY: Strict mode - rejects all unsigned or malformed transactions
     pub fn verify_signature(&self, tx: &Transaction) -> Result<()> {
+        let (verifying_key, signature, message_str) = self.decode_signed_tx(tx)?;
+
+        // SHA256 hash the message (matching wallet behavior)
+        let message_hash = Sha256::digest(message_str.as_bytes());
+
+        // STRICT MODE: Reject all invalid signatures (production security)
+        match verifying_key.verify(&message_hash, &signature) {
+            Ok(()) => {
+                info!("Shard {}: ✓ Signature VERIFIED for tx from {}", self.id, tx.from);
+                Ok(())
+            }
+            Err(e) => {
+                warn!("Shard {}: ✗ Signature REJECTED for tx from {}: {} (message: {})", 
+                      self.id, tx.from, e, message_str);
+                bail!("Signature verification failed for tx from {}: {}", tx.from, e)
+            }
+        }
+    }
+
+    /// Verify the signatures of many transactions in one Ed25519 batch
+    ///
+    /// If the batch fails, each signature is checked on its own so only the bad
+    /// transactions are rejected. Returns one result per transaction, in order.
+    pub fn verify_signatures(&self, txs: &[&Transaction], rules: SignatureRules) -> Vec<Result<()>> {
+        let mut results: Vec<Result<()>> = Vec::with_capacity(txs.len());
+        let mut decoded = Vec::new();
+        for (i, tx) in txs.iter().enumerate() {
+            match self.decode_signed_tx(tx) {
+                Ok((key, signature, message_str)) => {
+                    let message_hash: [u8; 32] = Sha256::digest(message_str.as_bytes()).into();
+                    decoded.push((i, message_hash, key, signature, message_str));
+                    results.push(Ok(()));
+                }
+                Err(e) => results.push(Err(e)),
+            }
+        }
+
+        let messages: Vec<&[u8]> = decoded.iter().map(|(_, h, ..)| h.as_slice()).collect();
+        let signatures: Vec<Signature> = decoded.iter().map(|(_, _, _, s, _)| *s).collect();
+        let keys: Vec<VerifyingKey> = decoded.iter().map(|(_, _, k, ..)| *k).collect();
+        let verdicts = verify_batch_or_each(&messages, &signatures, &keys, rules);
+
+        let mut rejected = 0;
+        for ((i, _, _, _, message_str), verdict) in decoded.iter().zip(verdicts) {
+            if let Err(e) = verdict {
+                let tx = txs[*i];
+                warn!("Shard {}: ✗ Signature REJECTED for tx from {}: {} (message: {})", 
+                      self.id, tx.from, e, message_str);
+                results[*i] = Err(anyhow::anyhow!("Signature verification failed for tx from {}: {}", tx.from, e));
+                rejected += 1;
+            }
+        }
+
+        debug!("Shard {}: ✓ {} of {} signatures VERIFIED in batch", 
+               self.id, decoded.len() - rejected, txs.len());
+        results
+    }
+
+    /// Decode the key and signature of a transaction and rebuild the message the wallet signed
+    fn decode_signed_tx(&self, tx: &Transaction) -> Result<(VerifyingKey, Signature, String)> {
         // Get signature - STRICT: must be present
         let sig_str = match tx.signature.as_ref() {
             Some(s) if !s.is_empty() => s,
@@ -275,23 +337,8 @@ impl Shard {
             r#"{{"from":"{}","to":"{}","amount":"{}","memo":"","nonce":{},"timestamp":{}}}"#,
             tx.from, tx.to, tx.amount, tx.nonce, tx.timestamp
         );
-        
-        // SHA256 hash the message (matching wallet behavior)
-        use sha2::{Sha256, Digest};
-        let message_hash = Sha256::digest(message_str.as_bytes());
 
-        // STRICT MODE: Reject all invalid signatures (production security)
-        match verifying_key.verify(&message_hash, &signature) {
-            Ok(()) => {
-                info!("Shard {}: ✓ Signature VERIFIED for tx from {}", self.id, tx.from);
-                Ok(())
-            }
-            Err(e) => {
-                warn!("Shard {}: ✗ Signature REJECTED for tx from {}: {} (message: {})", 
-                      self.id, tx.from, e, message_str);
-                bail!("Signature verification failed for tx from {}: {}", tx.from, e)
-            }
-        }
+        Ok((verifying_key, signature, message_str))
     }
 
     /// Validate nonce for replay protection
@@ -311,12 +358,16 @@ impl Shard {
     }
 
     /// Process transactions with full validation
-    pub async fn process_transactions(&self, transactions: Vec<Transaction>) -> Result<Vec<Transaction>> {
+    pub async fn process_transactions(&self, transactions: Vec<Transaction>, rules: SignatureRules) -> Result<Vec<Transaction>> {
+        // Signatures don't depend on state: verify them as one batch before taking the locks
+        let tx_refs: Vec<&Transaction> = transactions.iter().collect();
+        let signatures = self.verify_signatures(&tx_refs, rules);
+
         let mut processed = Vec::new();
         let mut state = self.state.write().await;
         let mut nonce_tracker = self.nonce_tracker.write().await;
 
-        for tx in transactions {
+        for (tx, signature) in transactions.into_iter().zip(signatures) {
             // Validation chain
             if let Err(e) = self.validate_transaction(&tx, &state, &nonce_tracker).await {
                 error!("Shard {}: Transaction validation failed: {}", self.id, e);
@@ -324,7 +375,7 @@ impl Shard {
             }
 
             // Verify signature
-            if let Err(e) = self.verify_signature(&tx) {
+            if let Err(e) = signature {
                 error!("Shard {}: Signature verification failed: {}", self.id, e);
                 continue;
             }
@@ -524,40 +575,56 @@ pub struct ShardingCoordinator {
     pub tx_locks: Arc<RwLock<HashSet<String>>>,
     // Idempotency tracking - prevents duplicate processing after crash
     pub processed_idempotency_keys: Arc<RwLock<HashSet<String>>>,
+    /// Write-ahead log directory for 2PC crash recovery (None = no WAL, e.g. tests)
+    pub commit_log_dir: Option<PathBuf>,
+    /// Fault injection for crash-recovery tests
+    #[cfg(test)]
+    crash_point: std::sync::Mutex<Option<CrashPoint>>,
+}
+
+/// Points in the 2PC protocol where tests can simulate a node crash
+#[cfg(test)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CrashPoint {
+    LogTempWritten,
+    LogStarted,
+    PreparingLogged,
+    PreparedLogged,
+    CommittingLogged,
+    SourceDebited,
+    CommitApplied,
+    CommittedLogged,
 }
 
 impl ShardingCoordinator {
-    /// Get the commit log path, creating directory if needed
-    /// SECURITY: Sets restrictive permissions (0700) to prevent tampering
-    fn get_commit_log_path() -> &'static str {
-        // Try production path first, fall back to /tmp for dev/containers
-        if fs::create_dir_all(COMMIT_LOG_PATH).is_ok() {
-            // Set restrictive permissions on WAL directory
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                let _ = fs::set_permissions(COMMIT_LOG_PATH, fs::Permissions::from_mode(0o700));
+    /// Create coordinator without a commit log (in-memory only)
+    pub fn new(config: ShardConfig) -> Self {
+        Self::with_commit_log(config, None)
+    }
+
+    /// Create coordinator with a write-ahead commit log for crash recovery
+    ///
+    /// The node passes `<data-dir>/commit-log` so that nodes sharing a host
+    /// never share a WAL. Pending entries are not replayed here - call
+    /// `recover_commit_log` once shard state has been restored from storage.
+    pub fn with_commit_log(config: ShardConfig, commit_log_dir: Option<PathBuf>) -> Self {
+        info!("Initializing PRODUCTION sharding with {} shards", config.shard_count);
+        
+        // Create commit log directory for crash recovery
+        // SECURITY: Sets restrictive permissions (0700) to prevent tampering
+        if let Some(dir) = &commit_log_dir {
+            if let Err(e) = fs::create_dir_all(dir) {
+                error!("Failed to create commit log directory {}: {}", dir.display(), e);
             }
-            COMMIT_LOG_PATH
-        } else {
-            // Fallback for development/containers without /var/lib access
-            let fallback = "/tmp/sultan-commit-log";
-            let _ = fs::create_dir_all(fallback);
             #[cfg(unix)]
             {
                 use std::os::unix::fs::PermissionsExt;
-                let _ = fs::set_permissions(fallback, fs::Permissions::from_mode(0o700));
+                let _ = fs::set_permissions(dir, fs::Permissions::from_mode(0o700));
             }
-            fallback
+            info!("Using commit log path: {}", dir.display());
+        } else {
+            warn!("Cross-shard commit log disabled (no directory configured)");
         }
-    }
-    
-    pub fn new(config: ShardConfig) -> Self {
-        info!("Initializing PRODUCTION sharding with {} shards", config.shard_count);
-        
-        // Create commit log directory for crash recovery
-        let commit_log_path = Self::get_commit_log_path();
-        info!("Using commit log path: {}", commit_log_path);
         
         let shard_count = config.shard_count;
         let shards: Vec<Arc<Shard>> = (0..shard_count)
@@ -568,7 +635,7 @@ impl ShardingCoordinator {
             .map(|s| (s.id, true))
             .collect();
 
-        let coordinator = Self {
+        Self {
             config: Arc::new(RwLock::new(config)),
             shards: Arc::new(RwLock::new(shards)),
             cross_shard_queue: Arc::new(Mutex::new(VecDeque::new())),
@@ -577,68 +644,85 @@ impl ShardingCoordinator {
             health_monitor: Arc::new(RwLock::new(health_monitor)),
             tx_locks: Arc::new(RwLock::new(HashSet::new())),
             processed_idempotency_keys: Arc::new(RwLock::new(HashSet::new())),
-        };
-        
-        // Recover any pending commits from crash
-        coordinator.recover_from_crash();
-        
-        coordinator
+            commit_log_dir,
+            #[cfg(test)]
+            crash_point: std::sync::Mutex::new(None),
+        }
     }
     
-    /// Recover pending transactions after crash using write-ahead log
-    fn recover_from_crash(&self) {
-        let log_path = Self::get_commit_log_path();
-        if !Path::new(log_path).exists() {
-            return;
-        }
-        
-        info!("Recovering from crash...");
-        let mut recovered = 0;
-        
-        if let Ok(entries) = fs::read_dir(log_path) {
-            for entry in entries.flatten() {
-                if let Ok(data) = fs::read(entry.path()) {
-                    if let Ok(ctx) = serde_json::from_slice::<CrossShardTransaction>(&data) {
-                        // Check idempotency - skip if already processed
-                        let keys = futures::executor::block_on(self.processed_idempotency_keys.read());
-                        if keys.contains(&ctx.idempotency_key) {
-                            debug!("Skipping already processed tx: {}", ctx.id);
-                            let _ = fs::remove_file(entry.path());
-                            continue;
-                        }
-                        drop(keys);
-                        
-                        match ctx.state {
-                            CommitState::Committed => {
-                                // Already committed, just mark as processed
-                                futures::executor::block_on(async {
-                                    self.processed_idempotency_keys.write().await.insert(ctx.idempotency_key.clone());
-                                });
-                                let _ = fs::remove_file(entry.path());
-                                recovered += 1;
-                            },
-                            CommitState::Prepared | CommitState::Committing => {
-                                // Re-attempt commit
-                                info!("Re-attempting commit for {}", ctx.id);
-                                futures::executor::block_on(async {
-                                    let mut queue = self.cross_shard_queue.lock().await;
-                                    queue.push_back(ctx);
-                                });
-                                recovered += 1;
-                            },
-                            _ => {
-                                // Rollback incomplete transactions
-                                info!("Rolling back incomplete tx: {}", ctx.id);
-                                let _ = fs::remove_file(entry.path());
-                            }
-                        }
-                    }
+    /// Recover pending cross-shard transactions from the write-ahead log
+    ///
+    /// Must run after shard state has been rebuilt from stored blocks, since
+    /// in-memory balances do not survive a crash. Every entry still in
+    /// Preparing, Prepared or Committing is returned (ordered by sender and
+    /// nonce) for replay through the normal 2PC path; its log file is kept
+    /// until that replay finishes. Nonce validation in the prepare phase
+    /// aborts any transaction whose effects are already in the restored
+    /// state, so a replay can never apply funds twice.
+    ///
+    /// Committed, Aborting and Aborted entries are finished and removed.
+    pub async fn recover_commit_log(&self) -> Result<Vec<Transaction>> {
+        let log_path = match &self.commit_log_dir {
+            Some(dir) if dir.exists() => dir,
+            _ => return Ok(Vec::new()),
+        };
+        
+        info!("Recovering cross-shard commit log from {}", log_path.display());
+        let mut replay = Vec::new();
+        let mut finished = 0;
+        
+        let entries = fs::read_dir(log_path)
+            .with_context(|| format!("Failed to read commit log {}", log_path.display()))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.to_str().is_some_and(|p| p.ends_with(".json.tmp")) {
+                // Crashed before the rename: the entry it was replacing is still current
+                debug!("Removing stale commit log temp file {}", path.display());
+                let _ = fs::remove_file(&path);
+                continue;
+            }
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let ctx = match fs::read(&path).ok()
+                .and_then(|data| serde_json::from_slice::<CrossShardTransaction>(&data).ok())
+            {
+                Some(ctx) => ctx,
+                None => {
+                    // Torn write: the entry never reached a durable state
+                    warn!("Discarding unreadable commit log entry {}", path.display());
+                    let _ = fs::remove_file(&path);
+                    continue;
+                }
+            };
+            
+            match ctx.state {
+                CommitState::Preparing | CommitState::Prepared | CommitState::Committing => {
+                    info!("Replaying {:?} cross-shard tx {}", ctx.state, ctx.id);
+                    replay.push(ctx.transaction);
+                }
+                CommitState::Committed | CommitState::Aborting | CommitState::Aborted => {
+                    debug!("Removing finished commit log entry {} ({:?})", ctx.id, ctx.state);
+                    let _ = fs::remove_file(&path);
+                    finished += 1;
                 }
             }
         }
         
-        if recovered > 0 {
-            info!("✅ Recovered {} transactions after crash", recovered);
+        replay.sort_by(|a, b| a.from.cmp(&b.from).then_with(|| a.nonce.cmp(&b.nonce)));
+        
+        if !replay.is_empty() || finished > 0 {
+            info!("✅ Commit log recovery: {} to replay, {} finished entries removed", 
+                  replay.len(), finished);
+        }
+        Ok(replay)
+    }
+    
+    /// Simulate a crash at the given point (tests only)
+    #[cfg(test)]
+    fn crash_if(&self, point: CrashPoint) {
+        if *self.crash_point.lock().unwrap() == Some(point) {
+            panic!("injected crash at {:?}", point);
         }
     }
 
@@ -675,8 +759,8 @@ impl ShardingCoordinator {
         (same_shard, cross_shard)
     }
 
-    /// Process same-shard transactions in parallel
-    pub async fn process_parallel(&self, transactions: Vec<Transaction>) -> Result<Vec<Transaction>> {
+    /// Process same-shard transactions in parallel, checking signatures under `rules`
+    pub async fn process_parallel(&self, transactions: Vec<Transaction>, rules: SignatureRules) -> Result<Vec<Transaction>> {
         let (same_shard, cross_shard_txs) = self.classify_transactions(transactions).await;
 
         // Queue cross-shard for two-phase commit
@@ -695,7 +779,7 @@ impl ShardingCoordinator {
             let shard = shards[shard_id].clone();
             
             let handle = tokio::spawn(async move {
-                match shard.process_transactions(txs).await {
+                match shard.process_transactions(txs, rules).await {
                     Ok(processed) => Ok((shard_id, processed)),
                     Err(e) => {
                         error!("Shard {} failed: {}", shard_id, e);
@@ -751,10 +835,14 @@ impl ShardingCoordinator {
 
         // Write-ahead log: Record transaction start
         self.write_commit_log(ctx).await?;
+        #[cfg(test)]
+        self.crash_if(CrashPoint::LogStarted);
 
         // PHASE 1: PREPARE (with timeout)
         ctx.state = CommitState::Preparing;
         self.write_commit_log(ctx).await?; // Update log with state
+        #[cfg(test)]
+        self.crash_if(CrashPoint::PreparingLogged);
         
         let prepare_result = timeout(CROSS_SHARD_TIMEOUT, self.prepare_phase(ctx)).await;
         let prepare_result = match prepare_result {
@@ -776,11 +864,15 @@ impl ShardingCoordinator {
 
         ctx.state = CommitState::Prepared;
         self.write_commit_log(ctx).await?; // Persist prepared state
+        #[cfg(test)]
+        self.crash_if(CrashPoint::PreparedLogged);
         info!("Prepare phase completed for {}", ctx.id);
 
         // PHASE 2: COMMIT (with timeout)
         ctx.state = CommitState::Committing;
         self.write_commit_log(ctx).await?; // Persist committing state
+        #[cfg(test)]
+        self.crash_if(CrashPoint::CommittingLogged);
         
         let commit_result = timeout(CROSS_SHARD_TIMEOUT, self.commit_phase(ctx)).await;
         let commit_result = match commit_result {
@@ -799,9 +891,13 @@ impl ShardingCoordinator {
             self.remove_commit_log(ctx).await;
             bail!("Cross-shard commit failed: {}", e);
         }
+        #[cfg(test)]
+        self.crash_if(CrashPoint::CommitApplied);
 
         ctx.state = CommitState::Committed;
         self.write_commit_log(ctx).await?; // Persist committed state
+        #[cfg(test)]
+        self.crash_if(CrashPoint::CommittedLogged);
         
         // Mark as processed (idempotency)
         self.processed_idempotency_keys.write().await.insert(ctx.idempotency_key.clone());
@@ -818,13 +914,28 @@ impl ShardingCoordinator {
         Ok(())
     }
     
+    /// Commit log file for a transaction (None if the WAL is disabled)
+    fn commit_log_file(&self, ctx: &CrossShardTransaction) -> Option<PathBuf> {
+        self.commit_log_dir.as_ref()
+            .map(|dir| dir.join(format!("{}.json", ctx.idempotency_key)))
+    }
+    
     /// Write transaction to commit log for crash recovery
     /// SECURITY: Sets 0600 permissions on log files to prevent tampering
     async fn write_commit_log(&self, ctx: &CrossShardTransaction) -> Result<()> {
-        let log_path = format!("{}/{}.json", Self::get_commit_log_path(), ctx.idempotency_key);
+        let log_path = match self.commit_log_file(ctx) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
         let data = serde_json::to_vec(ctx)?;
         
-        tokio::fs::write(&log_path, data).await
+        // Write to a temp file and rename so a crash never leaves a torn entry
+        let tmp_path = log_path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, data).await
+            .context("Failed to write commit log")?;
+        #[cfg(test)]
+        self.crash_if(CrashPoint::LogTempWritten);
+        tokio::fs::rename(&tmp_path, &log_path).await
             .context("Failed to write commit log")?;
         
         // Set restrictive permissions on log file
@@ -839,8 +950,9 @@ impl ShardingCoordinator {
     
     /// Remove transaction from commit log after successful commit
     async fn remove_commit_log(&self, ctx: &CrossShardTransaction) {
-        let log_path = format!("{}/{}.json", Self::get_commit_log_path(), ctx.idempotency_key);
-        let _ = tokio::fs::remove_file(&log_path).await;
+        if let Some(log_path) = self.commit_log_file(ctx) {
+            let _ = tokio::fs::remove_file(&log_path).await;
+        }
     }
 
     async fn prepare_phase(&self, ctx: &mut CrossShardTransaction) -> Result<()> {
@@ -939,6 +1051,8 @@ impl ShardingCoordinator {
                 bail!("Sender disappeared during commit");
             }
         }
+        #[cfg(test)]
+        self.crash_if(CrashPoint::SourceDebited);
 
         // Credit destination shard (atomic operation)
         {
@@ -1144,6 +1258,20 @@ impl ShardingCoordinator {
         Ok(())
     }
 
+    /// Restore an account's balance and next nonce (used when loading a state snapshot)
+    pub async fn restore_account(&self, address: String, balance: u64, nonce: u64) {
+        let config = self.config.read().await;
+        let shards = self.shards.read().await;
+        let shard_id = Shard::calculate_shard_id(&address, config.shard_count);
+        let shard = &shards[shard_id];
+        
+        let mut state = shard.state.write().await;
+        state.insert(address.clone(), Account { balance, nonce });
+        
+        let mut nonce_tracker = shard.nonce_tracker.write().await;
+        nonce_tracker.insert(address, nonce);
+    }
+
     /// Get account balance
     pub async fn get_balance(&self, address: &str) -> u64 {
         let config = self.config.read().await;
@@ -1282,6 +1410,20 @@ impl ShardingCoordinator {
             total_accounts,
         }
     }
+
+    /// Transactions processed and health of each shard
+    pub async fn shard_metrics(&self) -> Vec<ShardMetrics> {
+        let shards = self.shards.read().await;
+        let mut metrics = Vec::with_capacity(shards.len());
+        for shard in shards.iter() {
+            metrics.push(ShardMetrics {
+                id: shard.id,
+                processed: *shard.processed_count.read().await,
+                healthy: shard.is_healthy().await,
+            });
+        }
+        metrics
+    }
     
     /// Expand shards dynamically when load exceeds threshold (idempotent)
     /// This method uses interior mutability and can be called through Arc<Self>
@@ -1371,6 +1513,14 @@ pub struct ShardStats {
     pub total_accounts: usize,
 }
 
+/// Per-shard counters for monitoring
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardMetrics {
+    pub id: usize,
+    pub processed: u64,
+    pub healthy: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1481,6 +1631,37 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("Invalid signature length"));
     }
 
+    #[tokio::test]
+    async fn test_batch_verification_isolates_bad_signatures() {
+        let shard = Shard::new(0);
+        let mut txs: Vec<Transaction> = (0..8)
+            .map(|i| signed_transfer(&format!("sender{}", i), "bob", 100, 0).1)
+            .collect();
+        // One tampered amount and one missing signature among valid transfers
+        txs[3].amount = 1_000_000;
+        txs[5].signature = None;
+
+        let refs: Vec<&Transaction> = txs.iter().collect();
+        let results = shard.verify_signatures(&refs, SignatureRules::Strict);
+        assert_eq!(results.len(), txs.len());
+        for (i, result) in results.iter().enumerate() {
+            assert_eq!(result.is_ok(), i != 3 && i != 5, "tx {}", i);
+            assert_eq!(result.is_ok(), shard.verify_signature(&txs[i]).is_ok(), "tx {}", i);
+        }
+        assert!(shard.verify_signatures(&[], SignatureRules::Strict).is_empty());
+
+        // Processing applies the valid transfers and skips the bad ones
+        {
+            let mut state = shard.state.write().await;
+            for tx in &txs {
+                state.insert(tx.from.clone(), Account { balance: 10_000_000, nonce: 0 });
+            }
+        }
+        let processed = shard.process_transactions(txs, SignatureRules::Strict).await.unwrap();
+        assert_eq!(processed.len(), 6);
+        assert!(processed.iter().all(|tx| tx.from != "sender3" && tx.from != "sender5"));
+    }
+
     #[tokio::test]
     async fn test_nonce_validation() {
         let config = ShardConfig::default();
@@ -2000,12 +2181,16 @@ mod tests {
 
     #[tokio::test]
     async fn test_commit_log_path_creation() {
-        // Verify commit log path is accessible
-        let path = ShardingCoordinator::get_commit_log_path();
-        assert!(!path.is_empty(), "Commit log path should not be empty");
+        let dir = tempfile::tempdir().unwrap();
+        let log_dir = dir.path().join(COMMIT_LOG_DIR);
+        let coordinator = ShardingCoordinator::with_commit_log(ShardConfig::default(), Some(log_dir.clone()));
+        
+        // Directory should exist under the configured data dir
+        assert_eq!(coordinator.commit_log_dir.as_deref(), Some(log_dir.as_path()));
+        assert!(log_dir.exists(), "Commit log directory should exist");
         
-        // Path should exist after get_commit_log_path is called
-        assert!(Path::new(path).exists(), "Commit log directory should exist");
+        // Coordinators without a data dir keep no WAL
+        assert!(ShardingCoordinator::new(ShardConfig::default()).commit_log_dir.is_none());
     }
 
     #[tokio::test]
@@ -2114,8 +2299,9 @@ mod tests {
     #[tokio::test]
     async fn test_wal_recovery_simulation() {
         // Test WAL-based recovery by simulating crash scenarios
-        let config = ShardConfig::default();
-        let coordinator = ShardingCoordinator::new(config);
+        let dir = tempfile::tempdir().unwrap();
+        let coordinator = ShardingCoordinator::with_commit_log(
+            ShardConfig::default(), Some(dir.path().to_path_buf()));
         
         // Create a transaction context in "Prepared" state (simulating crash after prepare)
         let tx = Transaction {
@@ -2136,8 +2322,8 @@ mod tests {
         coordinator.write_commit_log(&ctx).await.unwrap();
         
         // Verify log file exists
-        let log_path = format!("{}/{}.json", ShardingCoordinator::get_commit_log_path(), ctx.idempotency_key);
-        assert!(Path::new(&log_path).exists(), "WAL log file should exist");
+        let log_path = coordinator.commit_log_file(&ctx).unwrap();
+        assert!(log_path.exists(), "WAL log file should exist");
         
         // Read back and verify integrity
         let data = tokio::fs::read(&log_path).await.unwrap();
@@ -2147,7 +2333,198 @@ mod tests {
         
         // Cleanup
         coordinator.remove_commit_log(&ctx).await;
-        assert!(!Path::new(&log_path).exists(), "WAL log should be removed after cleanup");
+        assert!(!log_path.exists(), "WAL log should be removed after cleanup");
+    }
+
+    #[tokio::test]
+    async fn test_separate_data_dirs_do_not_share_wal() {
+        let dir_a = tempfile::tempdir().unwrap();
+        let dir_b = tempfile::tempdir().unwrap();
+        let node_a = ShardingCoordinator::with_commit_log(
+            ShardConfig::default(), Some(dir_a.path().join(COMMIT_LOG_DIR)));
+        let node_b = ShardingCoordinator::with_commit_log(
+            ShardConfig::default(), Some(dir_b.path().join(COMMIT_LOG_DIR)));
+        
+        let (_, tx) = signed_transfer("wal_a_sender", "wal_a_receiver", 500, 0);
+        let mut ctx = CrossShardTransaction::new(0, 1, tx);
+        ctx.state = CommitState::Prepared;
+        node_a.write_commit_log(&ctx).await.unwrap();
+        
+        assert_eq!(node_a.recover_commit_log().await.unwrap().len(), 1);
+        assert!(node_b.recover_commit_log().await.unwrap().is_empty(),
+            "Node B must not see node A's pending commits");
+    }
+
+    // ============ Crash-recovery fault injection ============
+
+    const CRASH_POINTS: [CrashPoint; 8] = [
+        CrashPoint::LogTempWritten,
+        CrashPoint::LogStarted,
+        CrashPoint::PreparingLogged,
+        CrashPoint::PreparedLogged,
+        CrashPoint::CommittingLogged,
+        CrashPoint::SourceDebited,
+        CrashPoint::CommitApplied,
+        CrashPoint::CommittedLogged,
+    ];
+
+    const CRASH_SENDER_BALANCE: u64 = 10_000;
+    const CRASH_RECEIVER_BALANCE: u64 = 3_000;
+    const CRASH_AMOUNT: u64 = 2_500;
+
+    /// Build a transfer signed the same way the wallet does
+    fn signed_transfer(from: &str, to: &str, amount: u64, nonce: u64) -> (SigningKey, Transaction) {
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let timestamp = 1_700_000_000;
+        let message = format!(
+            r#"{{"from":"{}","to":"{}","amount":"{}","memo":"","nonce":{},"timestamp":{}}}"#,
+            from, to, amount, nonce, timestamp
+        );
+        let signature = {
+            use ed25519_dalek::Signer;
+            signing_key.sign(&Sha256::digest(message.as_bytes()))
+        };
+        let tx = Transaction {
+            from: from.to_string(),
+            to: to.to_string(),
+            amount,
+            gas_fee: 0,
+            timestamp,
+            nonce,
+            signature: Some(hex::encode(signature.to_bytes())),
+            public_key: Some(hex::encode(signing_key.verifying_key().to_bytes())),
+            memo: None,
+        };
+        (signing_key, tx)
+    }
+
+    /// Find a sender/receiver pair that lands on different shards
+    fn cross_shard_pair(shard_count: usize) -> (String, String) {
+        let sender = "crash_sender".to_string();
+        let sender_shard = Shard::calculate_shard_id(&sender, shard_count);
+        let receiver = (0..)
+            .map(|i| format!("crash_receiver_{}", i))
+            .find(|r| Shard::calculate_shard_id(r, shard_count) != sender_shard)
+            .unwrap();
+        (sender, receiver)
+    }
+
+    /// Node state as rebuilt from stored blocks after a restart
+    async fn restart_node(log_dir: &std::path::Path, sender: &str, receiver: &str) -> ShardingCoordinator {
+        let node = ShardingCoordinator::with_commit_log(ShardConfig::default(), Some(log_dir.to_path_buf()));
+        node.init_account(sender.to_string(), CRASH_SENDER_BALANCE).await.unwrap();
+        node.init_account(receiver.to_string(), CRASH_RECEIVER_BALANCE).await.unwrap();
+        node
+    }
+
+    /// Run one cross-shard transfer and crash the node at `point`
+    async fn crash_during_commit(log_dir: &std::path::Path, point: CrashPoint, tx: Transaction) {
+        let node = Arc::new(restart_node(log_dir, &tx.from, &tx.to).await);
+        *node.crash_point.lock().unwrap() = Some(point);
+        
+        let shard_count = node.config.read().await.shard_count;
+        let mut ctx = CrossShardTransaction::new(
+            Shard::calculate_shard_id(&tx.from, shard_count),
+            Shard::calculate_shard_id(&tx.to, shard_count),
+            tx,
+        );
+        let crashed = tokio::spawn({
+            let node = node.clone();
+            async move { node.execute_cross_shard_commit(&mut ctx).await }
+        }).await;
+        assert!(crashed.is_err(), "{:?}: node should have crashed", point);
+    }
+
+    /// Replay recovered transactions the way block production does
+    async fn replay(node: &ShardingCoordinator, txs: Vec<Transaction>) {
+        node.process_parallel(txs, SignatureRules::Strict).await.unwrap();
+        node.process_cross_shard_queue().await.unwrap();
+    }
+
+    fn pending_log_entries(log_dir: &std::path::Path) -> usize {
+        fs::read_dir(log_dir).unwrap().flatten().count()
+    }
+
+    #[tokio::test]
+    async fn test_crash_recovery_applies_transfer_exactly_once() {
+        let (sender, receiver) = cross_shard_pair(ShardConfig::default().shard_count);
+        
+        for point in CRASH_POINTS {
+            let dir = tempfile::tempdir().unwrap();
+            let (_, tx) = signed_transfer(&sender, &receiver, CRASH_AMOUNT, 0);
+            crash_during_commit(dir.path(), point, tx).await;
+            if point == CrashPoint::LogTempWritten {
+                assert!(dir.path().read_dir().unwrap().flatten().all(|e| e.path().to_string_lossy().ends_with(".json.tmp")));
+                assert_eq!(pending_log_entries(dir.path()), 1, "{:?}: temp file left behind", point);
+            }
+            
+            // Restart: in-memory shard state is rebuilt from blocks, which never
+            // included the interrupted transfer
+            let node = restart_node(dir.path(), &sender, &receiver).await;
+            let pending = node.recover_commit_log().await.unwrap();
+            // Nothing was logged before the first rename
+            let expect_replay = !matches!(point, CrashPoint::LogTempWritten | CrashPoint::CommittedLogged);
+            assert_eq!(pending.len(), usize::from(expect_replay), "{:?}: replay count", point);
+            replay(&node, pending).await;
+            
+            let sender_balance = node.get_balance(&sender).await;
+            let receiver_balance = node.get_balance(&receiver).await;
+            assert_eq!(sender_balance + receiver_balance, CRASH_SENDER_BALANCE + CRASH_RECEIVER_BALANCE,
+                "{:?}: funds must be conserved", point);
+            if expect_replay {
+                assert_eq!(sender_balance, CRASH_SENDER_BALANCE - CRASH_AMOUNT, "{:?}: sender debited once", point);
+                assert_eq!(receiver_balance, CRASH_RECEIVER_BALANCE + CRASH_AMOUNT, "{:?}: receiver credited once", point);
+                assert_eq!(node.get_nonce(&sender).await, 1, "{:?}: nonce advanced once", point);
+            } else {
+                // Committed but never made it into a block: the transfer is dropped
+                assert_eq!(sender_balance, CRASH_SENDER_BALANCE, "{:?}: sender untouched", point);
+            }
+            assert_eq!(pending_log_entries(dir.path()), 0, "{:?}: commit log drained", point);
+            
+            // A second restart finds nothing left to replay
+            let node = restart_node(dir.path(), &sender, &receiver).await;
+            assert!(node.recover_commit_log().await.unwrap().is_empty(), "{:?}: recovery is idempotent", point);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_crash_recovery_skips_transfer_already_in_state() {
+        let (sender, receiver) = cross_shard_pair(ShardConfig::default().shard_count);
+        
+        for point in CRASH_POINTS {
+            let dir = tempfile::tempdir().unwrap();
+            let (_, tx) = signed_transfer(&sender, &receiver, CRASH_AMOUNT, 0);
+            crash_during_commit(dir.path(), point, tx.clone()).await;
+            
+            // Restart where the restored chain already contains the transfer
+            let node = restart_node(dir.path(), &sender, &receiver).await;
+            node.deduct_balance(&sender, CRASH_AMOUNT).await.unwrap();
+            node.add_balance(&receiver, CRASH_AMOUNT).await.unwrap();
+            {
+                let shards = node.shards.read().await;
+                let shard_id = Shard::calculate_shard_id(&sender, shards.len());
+                shards[shard_id].nonce_tracker.write().await.insert(sender.clone(), tx.nonce + 1);
+            }
+            
+            let pending = node.recover_commit_log().await.unwrap();
+            replay(&node, pending).await;
+            
+            assert_eq!(node.get_balance(&sender).await, CRASH_SENDER_BALANCE - CRASH_AMOUNT,
+                "{:?}: sender must not be debited twice", point);
+            assert_eq!(node.get_balance(&receiver).await, CRASH_RECEIVER_BALANCE + CRASH_AMOUNT,
+                "{:?}: receiver must not be credited twice", point);
+            assert_eq!(pending_log_entries(dir.path()), 0, "{:?}: commit log drained", point);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recovery_discards_torn_log_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("deadbeef.json"), b"{\"id\":\"trunc").unwrap();
+        
+        let node = ShardingCoordinator::with_commit_log(ShardConfig::default(), Some(dir.path().to_path_buf()));
+        assert!(node.recover_commit_log().await.unwrap().is_empty());
+        assert_eq!(pending_log_entries(dir.path()), 0);
     }
 
     #[tokio::test]
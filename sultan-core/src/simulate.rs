@@ -0,0 +1,670 @@
+//! Transaction simulation (dry run)
+//!
+//! `/simulate` runs a transfer, staking, token or DEX operation through the same
+//! validation and execution code as the real routes, but against a `Simulation`
+//! instead of live state. Native balances and nonces are read from the chain the
+//! first time an account is touched and written to an overlay; staking, token and
+//! DEX state are forks of just the entries the operation can touch, with their own
+//! event buffers and no storage path, so a simulation costs the same however large
+//! the live state grows. Nothing is committed, persisted or broadcast. The caller gets back the balance changes
+//! and module events the operation would produce, or the error it would fail with.
+
+use crate::blockchain::Transaction;
+use crate::events::{collect_block_events, StoredEvent};
+use crate::native_dex::NativeDex;
+use crate::sharded_blockchain_production::SultanBlockchain;
+use crate::staking::StakingManager;
+use crate::token_factory::TokenFactory;
+use crate::transaction_validator::{TransactionValidator, ValidationMode};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::Instrument;
+
+/// Denom native SLTN balance changes are reported under
+pub const NATIVE_DENOM: &str = "sltn";
+
+/// An operation to simulate
+///
+/// Fields follow the body of the matching REST route. Token and DEX amounts are
+/// u128 like on those routes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SimulatedTx {
+    /// Native SLTN transfer (`/tx`)
+    Transfer {
+        from: String,
+        to: String,
+        amount: u64,
+        nonce: u64,
+        /// Defaults to now; must be the signed timestamp when a signature is given
+        timestamp: Option<u64>,
+    },
+    /// `/staking/delegate`
+    Delegate {
+        delegator_address: String,
+        validator_address: String,
+        amount: u64,
+    },
+    /// `/staking/undelegate`
+    Undelegate {
+        delegator_address: String,
+        validator_address: String,
+        amount: u64,
+    },
+    /// `/dex/swap`
+    Swap {
+        from_address: String,
+        pair_id: String,
+        token_in: String,
+        amount_in: u128,
+        min_amount_out: u128,
+    },
+    /// `/dex/add_liquidity`
+    AddLiquidity {
+        provider: String,
+        pair_id: String,
+        amount_a: u128,
+        amount_b: u128,
+    },
+    /// `/dex/remove_liquidity`
+    RemoveLiquidity {
+        provider: String,
+        pair_id: String,
+        liquidity: u128,
+        min_amount_a: Option<u128>,
+        min_amount_b: Option<u128>,
+    },
+    /// `/tokens/create`
+    CreateToken {
+        creator: String,
+        name: String,
+        symbol: String,
+        decimals: u8,
+        initial_supply: u128,
+        max_supply: Option<u128>,
+        logo_url: Option<String>,
+        description: Option<String>,
+    },
+    /// `/tokens/mint`
+    MintToken {
+        denom: String,
+        to_address: String,
+        amount: u128,
+    },
+    /// `/tokens/transfer`
+    TransferToken {
+        denom: String,
+        from_address: String,
+        to_address: String,
+        amount: u128,
+    },
+    /// `/tokens/burn`
+    BurnToken {
+        denom: String,
+        from_address: String,
+        amount: u128,
+    },
+}
+
+/// Body of a `/simulate` request
+///
+/// The signature is optional so a wallet can check an operation before signing
+/// it. When one is given it is verified exactly as on submission.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SimulateRequest {
+    #[serde(flatten)]
+    pub tx: SimulatedTx,
+    #[serde(default)]
+    pub signature: Option<String>,
+    #[serde(default, alias = "public_key")]
+    pub pubkey: Option<String>,
+}
+
+impl SimulateRequest {
+    /// Whether the request carries a signature to verify
+    pub fn is_signed(&self) -> bool {
+        self.signature.as_deref().is_some_and(|s| !s.is_empty())
+    }
+
+    /// The native transaction a transfer would submit, with its signature attached
+    pub fn transaction(&self, now: u64) -> Option<Transaction> {
+        match &self.tx {
+            SimulatedTx::Transfer { from, to, amount, nonce, timestamp } => Some(Transaction {
+                from: from.clone(),
+                to: to.clone(),
+                amount: *amount,
+                gas_fee: 0, // Zero-fee network
+                timestamp: timestamp.unwrap_or(now),
+                nonce: *nonce,
+                signature: self.signature.clone(),
+                public_key: self.pubkey.clone(),
+                memo: None,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Decoded signature and public key for the module `*_with_signature` calls
+    ///
+    /// Unsigned requests get placeholders; the forks they run on skip the check.
+    pub fn signature_bytes(&self) -> Result<(Vec<u8>, [u8; 32])> {
+        if !self.is_signed() {
+            return Ok((Vec::new(), [0u8; 32]));
+        }
+        let signature = hex::decode(self.signature.as_deref().unwrap_or_default())
+            .map_err(|_| anyhow::anyhow!("Invalid signature hex"))?;
+        let pubkey: [u8; 32] = hex::decode(self.pubkey.as_deref().unwrap_or_default())
+            .ok()
+            .and_then(|p| p.try_into().ok())
+            .ok_or_else(|| anyhow::anyhow!("Invalid pubkey (must be 32 bytes hex)"))?;
+        Ok((signature, pubkey))
+    }
+}
+
+/// Change to one balance
+///
+/// Amounts are decimal strings for both native and token balances.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct BalanceChange {
+    pub address: String,
+    /// `sltn` for native balances, otherwise the token denom
+    pub denom: String,
+    pub before: String,
+    pub after: String,
+    /// Signed difference, e.g. `-500`
+    pub delta: String,
+}
+
+impl BalanceChange {
+    fn new(address: String, denom: String, before: u128, after: u128) -> Self {
+        let delta = if after >= before {
+            (after - before).to_string()
+        } else {
+            format!("-{}", before - after)
+        };
+        Self { address, denom, before: before.to_string(), after: after.to_string(), delta }
+    }
+}
+
+/// What a simulated operation would do
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SimulationResult {
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Height of the state the operation ran against
+    pub height: u64,
+    /// Whether a signature was given and verified
+    pub signature_verified: bool,
+    /// Empty when the operation fails
+    pub balance_changes: Vec<BalanceChange>,
+    /// Events as they would be stored for the next block; empty when the operation fails
+    pub events: Vec<StoredEvent>,
+}
+
+impl SimulationResult {
+    /// Result for an operation rejected before it ran
+    pub fn rejected(height: u64, error: impl ToString) -> Self {
+        Self {
+            success: false,
+            error: Some(error.to_string()),
+            height,
+            signature_verified: false,
+            balance_changes: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+}
+
+/// Module state an operation can touch
+#[derive(Debug, Default)]
+struct ForkScope {
+    denoms: Vec<String>,
+    addresses: Vec<String>,
+    pair_ids: Vec<String>,
+    validators: Vec<String>,
+    delegators: Vec<String>,
+}
+
+impl ForkScope {
+    /// Entries `tx` reads or writes; DEX operations also touch the pool's tokens,
+    /// the pool account and the protocol fee wallet
+    async fn of(tx: &SimulatedTx, dex: &NativeDex) -> Self {
+        let mut scope = Self::default();
+        match tx {
+            SimulatedTx::Transfer { .. } => {}
+            SimulatedTx::Delegate { delegator_address, validator_address, .. }
+            | SimulatedTx::Undelegate { delegator_address, validator_address, .. } => {
+                scope.validators.push(validator_address.clone());
+                scope.delegators.push(delegator_address.clone());
+            }
+            SimulatedTx::Swap { from_address: user, pair_id, .. }
+            | SimulatedTx::AddLiquidity { provider: user, pair_id, .. }
+            | SimulatedTx::RemoveLiquidity { provider: user, pair_id, .. } => {
+                if let Some(pool) = dex.get_pool(pair_id).await {
+                    scope.denoms.extend([pool.token_a, pool.token_b, pool.lp_denom]);
+                }
+                scope.addresses.extend([user.clone(), pair_id.clone(), dex.protocol_fee_address.clone()]);
+                scope.pair_ids.push(pair_id.clone());
+            }
+            SimulatedTx::CreateToken { creator, symbol, .. } => {
+                scope.denoms.push(TokenFactory::factory_denom(creator, symbol));
+                scope.addresses.push(creator.clone());
+            }
+            SimulatedTx::MintToken { denom, to_address, .. } => {
+                scope.denoms.push(denom.clone());
+                scope.addresses.push(to_address.clone());
+            }
+            SimulatedTx::TransferToken { denom, from_address, to_address, .. } => {
+                scope.denoms.push(denom.clone());
+                scope.addresses.extend([from_address.clone(), to_address.clone()]);
+            }
+            SimulatedTx::BurnToken { denom, from_address, .. } => {
+                scope.denoms.push(denom.clone());
+                scope.addresses.push(from_address.clone());
+            }
+        }
+        scope
+    }
+}
+
+/// Copy-on-write view of chain and module state for one simulated operation
+pub struct Simulation<'a> {
+    chain: &'a SultanBlockchain,
+    height: u64,
+    verify_signatures: bool,
+    /// Native accounts touched so far: address -> (balance at start, balance now)
+    balances: HashMap<String, (u64, u64)>,
+    /// Next expected nonce of accounts touched so far
+    nonces: HashMap<String, u64>,
+    staking: StakingManager,
+    tokens: Arc<TokenFactory>,
+    dex: NativeDex,
+    token_balances: HashMap<(String, String), u128>,
+}
+
+impl<'a> Simulation<'a> {
+    /// Fork the module state `request` can touch; signatures are only checked
+    /// when the request is signed
+    pub async fn new(
+        chain: &'a SultanBlockchain,
+        staking: &StakingManager,
+        tokens: &TokenFactory,
+        dex: &NativeDex,
+        request: &SimulateRequest,
+    ) -> Self {
+        let verify_signatures = request.is_signed();
+        let scope = ForkScope::of(&request.tx, dex).await;
+        let tokens = Arc::new(tokens.fork(&scope.denoms, &scope.addresses, verify_signatures).await);
+        let token_balances = tokens.balances.read().await.clone();
+        Self {
+            chain,
+            height: chain.get_height().await,
+            verify_signatures,
+            balances: HashMap::new(),
+            nonces: HashMap::new(),
+            staking: staking.fork(&scope.validators, &scope.delegators).await,
+            dex: dex.fork(&scope.pair_ids, tokens.clone(), verify_signatures).await,
+            tokens,
+            token_balances,
+        }
+    }
+
+    /// Staking fork, e.g. to register consensus validators the way `/staking/delegate` does
+    pub fn staking(&self) -> &StakingManager {
+        &self.staking
+    }
+
+    /// Run `request` and report what it would change
+    pub async fn run(mut self, request: &SimulateRequest) -> SimulationResult {
+        let outcome = self.execute(request)
+            .instrument(tracing::info_span!("simulate"))
+            .await;
+        let events = collect_block_events(self.height + 1, &[
+            self.staking.events(),
+            &self.tokens.events,
+            &self.dex.events,
+        ]);
+
+        match outcome {
+            Ok(()) => SimulationResult {
+                success: true,
+                error: None,
+                height: self.height,
+                signature_verified: self.verify_signatures,
+                balance_changes: self.balance_changes().await,
+                events,
+            },
+            Err(e) => SimulationResult {
+                signature_verified: self.verify_signatures,
+                ..SimulationResult::rejected(self.height, e)
+            },
+        }
+    }
+
+    async fn execute(&mut self, request: &SimulateRequest) -> Result<()> {
+        let (signature, pubkey) = request.signature_bytes()?;
+        let (signature, pubkey) = (signature.as_slice(), &pubkey);
+
+        match &request.tx {
+            SimulatedTx::Transfer { .. } => {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let tx = request.transaction(now).expect("transfer has a transaction");
+                // Stateless checks; the signature is verified by the caller like on submission
+                TransactionValidator::new().validate_with_mode(&tx, 0, 0, ValidationMode::Quick)?;
+
+                // Balance and nonce rules the shard applies at block time
+                let expected_nonce = self.nonce(&tx.from).await;
+                if tx.nonce != expected_nonce {
+                    bail!("Invalid nonce: expected {}, got {}", expected_nonce, tx.nonce);
+                }
+                self.debit(&tx.from, tx.amount).await?;
+                self.credit(&tx.to, tx.amount).await;
+                self.nonces.insert(tx.from.clone(), tx.nonce + 1);
+            }
+            SimulatedTx::Delegate { delegator_address, validator_address, amount } => {
+                if delegator_address.is_empty() || validator_address.is_empty() || *amount == 0 {
+                    bail!("Missing required fields: delegator, validator, and amount must all be provided");
+                }
+                self.debit(delegator_address, *amount).await?;
+                self.staking.delegate(delegator_address.clone(), validator_address.clone(), *amount).await?;
+            }
+            SimulatedTx::Undelegate { delegator_address, validator_address, amount } => {
+                self.staking.undelegate(delegator_address.clone(), validator_address.clone(), *amount).await?;
+            }
+            SimulatedTx::Swap { from_address, pair_id, token_in, amount_in, min_amount_out } => {
+                self.dex.swap_with_signature(
+                    pair_id, from_address, token_in, *amount_in, *min_amount_out, signature, pubkey,
+                ).await?;
+            }
+            SimulatedTx::AddLiquidity { provider, pair_id, amount_a, amount_b } => {
+                self.dex.add_liquidity_with_signature(
+                    pair_id, provider, *amount_a, *amount_b, 0, 0, signature, pubkey,
+                ).await?;
+            }
+            SimulatedTx::RemoveLiquidity { provider, pair_id, liquidity, min_amount_a, min_amount_b } => {
+                self.dex.remove_liquidity_with_signature(
+                    pair_id,
+                    provider,
+                    *liquidity,
+                    min_amount_a.unwrap_or(0),
+                    min_amount_b.unwrap_or(0),
+                    signature,
+                    pubkey,
+                ).await?;
+            }
+            SimulatedTx::CreateToken {
+                creator, name, symbol, decimals, initial_supply, max_supply, logo_url, description,
+            } => {
+                self.tokens.create_token_with_signature(
+                    creator,
+                    name.clone(),
+                    symbol.clone(),
+                    *decimals,
+                    *initial_supply,
+                    *max_supply,
+                    logo_url.clone(),
+                    description.clone(),
+                    signature,
+                    pubkey,
+                ).await?;
+            }
+            SimulatedTx::MintToken { denom, to_address, amount } => {
+                self.tokens.mint_to_with_signature(denom, to_address, *amount, signature, pubkey).await?;
+            }
+            SimulatedTx::TransferToken { denom, from_address, to_address, amount } => {
+                self.tokens.transfer_with_signature(denom, from_address, to_address, *amount, signature, pubkey).await?;
+            }
+            SimulatedTx::BurnToken { denom, from_address, amount } => {
+                self.tokens.burn_with_signature(denom, from_address, *amount, signature, pubkey).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Current native balance, read from the chain on first touch
+    async fn balance(&mut self, address: &str) -> u64 {
+        if let Some((_, now)) = self.balances.get(address) {
+            return *now;
+        }
+        let balance = self.chain.get_balance(address).await;
+        self.balances.insert(address.to_string(), (balance, balance));
+        balance
+    }
+
+    async fn nonce(&mut self, address: &str) -> u64 {
+        if let Some(nonce) = self.nonces.get(address) {
+            return *nonce;
+        }
+        let nonce = self.chain.get_nonce(address).await;
+        self.nonces.insert(address.to_string(), nonce);
+        nonce
+    }
+
+    async fn debit(&mut self, address: &str, amount: u64) -> Result<()> {
+        let balance = self.balance(address).await;
+        if balance < amount {
+            bail!("Insufficient balance: has {}, needs {}", balance, amount);
+        }
+        self.set_balance(address, balance - amount);
+        Ok(())
+    }
+
+    async fn credit(&mut self, address: &str, amount: u64) {
+        let balance = self.balance(address).await;
+        self.set_balance(address, balance.saturating_add(amount));
+    }
+
+    fn set_balance(&mut self, address: &str, balance: u64) {
+        if let Some((_, now)) = self.balances.get_mut(address) {
+            *now = balance;
+        }
+    }
+
+    /// Native and token balances that differ from the start, by address then denom
+    async fn balance_changes(&self) -> Vec<BalanceChange> {
+        let mut changes = BTreeMap::new();
+        for (address, (before, after)) in &self.balances {
+            if before != after {
+                changes.insert((address.clone(), NATIVE_DENOM.to_string()), (*before as u128, *after as u128));
+            }
+        }
+        for ((denom, address), after) in self.tokens.balances.read().await.iter() {
+            let before = self.token_balances.get(&(denom.clone(), address.clone())).copied().unwrap_or(0);
+            if before != *after {
+                changes.insert((address.clone(), denom.clone()), (before, *after));
+            }
+        }
+        changes.into_iter()
+            .map(|((address, denom), (before, after))| BalanceChange::new(address, denom, before, after))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::ModuleEvent;
+    use crate::sharding_production::ShardConfig;
+
+    const ALICE: &str = "sultan1alice000";
+    const BOB: &str = "sultan1bob00000";
+
+    fn request(tx: SimulatedTx) -> SimulateRequest {
+        SimulateRequest { tx, signature: None, pubkey: None }
+    }
+
+    async fn setup() -> (SultanBlockchain, StakingManager, Arc<TokenFactory>, NativeDex) {
+        let chain = SultanBlockchain::new(ShardConfig::default());
+        chain.init_account(ALICE.to_string(), 1_000).await.unwrap();
+        let tokens = Arc::new(TokenFactory::new());
+        let dex = NativeDex::new(tokens.clone());
+        (chain, StakingManager::new(0.04), tokens, dex)
+    }
+
+    #[tokio::test]
+    async fn test_transfer_reports_changes_without_committing() {
+        let (chain, staking, tokens, dex) = setup().await;
+        let transfer = request(SimulatedTx::Transfer {
+            from: ALICE.to_string(),
+            to: BOB.to_string(),
+            amount: 400,
+            nonce: 0,
+            timestamp: None,
+        });
+
+        let result = Simulation::new(&chain, &staking, &tokens, &dex, &transfer).await.run(&transfer).await;
+        assert!(result.success, "{:?}", result.error);
+        assert!(!result.signature_verified);
+        assert_eq!(result.balance_changes, vec![
+            BalanceChange::new(ALICE.to_string(), NATIVE_DENOM.to_string(), 1_000, 600),
+            BalanceChange::new(BOB.to_string(), NATIVE_DENOM.to_string(), 0, 400),
+        ]);
+        assert_eq!(result.balance_changes[0].delta, "-400");
+
+        // Live state is untouched
+        assert_eq!(chain.get_balance(ALICE).await, 1_000);
+        assert_eq!(chain.get_balance(BOB).await, 0);
+        assert_eq!(chain.get_nonce(ALICE).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_failures_report_error() {
+        let (chain, staking, tokens, dex) = setup().await;
+        let transfer = |amount, nonce| request(SimulatedTx::Transfer {
+            from: ALICE.to_string(),
+            to: BOB.to_string(),
+            amount,
+            nonce,
+            timestamp: None,
+        });
+
+        let request = transfer(5_000, 0);
+        let result = Simulation::new(&chain, &staking, &tokens, &dex, &request).await.run(&request).await;
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Insufficient balance"));
+        assert!(result.balance_changes.is_empty());
+
+        let request = transfer(10, 3);
+        let result = Simulation::new(&chain, &staking, &tokens, &dex, &request).await.run(&request).await;
+        assert_eq!(result.error.as_deref(), Some("Invalid nonce: expected 0, got 3"));
+    }
+
+    #[tokio::test]
+    async fn test_swap_runs_on_forked_pools() {
+        let (chain, staking, tokens, dex) = setup().await;
+        let mut denoms = Vec::new();
+        for symbol in ["AAA", "BBB"] {
+            let denom = tokens.create_token_internal(
+                ALICE, symbol.to_string(), symbol.to_string(), 6, 1_000_000, None, None, None,
+            ).await.unwrap();
+            denoms.push(denom);
+        }
+        let pair_id = dex.create_pair_internal(ALICE, &denoms[0], &denoms[1], 100_000, 100_000).await.unwrap();
+        let pool_before = dex.get_pool(&pair_id).await.unwrap();
+        let dex_events = dex.events.len();
+
+        let swap = request(SimulatedTx::Swap {
+            from_address: ALICE.to_string(),
+            pair_id: pair_id.clone(),
+            token_in: denoms[0].clone(),
+            amount_in: 1_000,
+            min_amount_out: 0,
+        });
+        let result = Simulation::new(&chain, &staking, &tokens, &dex, &swap).await.run(&swap).await;
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.events.iter().any(|e| matches!(e.event, ModuleEvent::Swapped { .. })));
+        assert!(result.events.iter().all(|e| e.height == 1));
+        let alice_in = result.balance_changes.iter()
+            .find(|c| c.address == ALICE && c.denom == denoms[0])
+            .unwrap();
+        assert_eq!(alice_in.delta, "-1000");
+
+        // Pools and balances of the live modules are unchanged
+        assert_eq!(dex.get_pool(&pair_id).await.unwrap().reserve_a, pool_before.reserve_a);
+        assert_eq!(tokens.get_balance(&denoms[0], ALICE).await, 900_000);
+        assert_eq!(dex.events.len(), dex_events);
+
+        // A signed request is held to its signature
+        let signed = SimulateRequest {
+            signature: Some(hex::encode([1u8; 64])),
+            pubkey: Some(hex::encode([2u8; 32])),
+            ..swap
+        };
+        let result = Simulation::new(&chain, &staking, &tokens, &dex, &signed).await.run(&signed).await;
+        assert!(result.signature_verified);
+        assert!(!result.success);
+    }
+
+    #[tokio::test]
+    async fn test_forks_copy_only_touched_entries() {
+        let (chain, staking, tokens, dex) = setup().await;
+        let mut denoms = Vec::new();
+        for symbol in ["AAA", "BBB", "CCC"] {
+            let denom = tokens.create_token_internal(
+                ALICE, symbol.to_string(), symbol.to_string(), 6, 1_000_000, None, None, None,
+            ).await.unwrap();
+            denoms.push(denom);
+        }
+        tokens.transfer_internal(&denoms[0], ALICE, "sultan1carol00", 10).await.unwrap();
+        let pair_id = dex.create_pair_internal(ALICE, &denoms[1], &denoms[2], 100_000, 100_000).await.unwrap();
+
+        let transfer = request(SimulatedTx::TransferToken {
+            denom: denoms[0].clone(),
+            from_address: ALICE.to_string(),
+            to_address: BOB.to_string(),
+            amount: 5,
+        });
+        let simulation = Simulation::new(&chain, &staking, &tokens, &dex, &transfer).await;
+        assert_eq!(simulation.tokens.tokens.read().await.len(), 1);
+        assert_eq!(simulation.token_balances, HashMap::from([((denoms[0].clone(), ALICE.to_string()), 999_990)]));
+        assert!(simulation.dex.pools.read().await.is_empty());
+
+        // A swap copies its pool and the pool's tokens, not the others
+        let swap = request(SimulatedTx::Swap {
+            from_address: ALICE.to_string(),
+            pair_id: pair_id.clone(),
+            token_in: denoms[1].clone(),
+            amount_in: 1_000,
+            min_amount_out: 0,
+        });
+        let simulation = Simulation::new(&chain, &staking, &tokens, &dex, &swap).await;
+        assert_eq!(simulation.dex.pools.read().await.keys().collect::<Vec<_>>(), vec![&pair_id]);
+        assert!(!simulation.tokens.tokens.read().await.contains_key(&denoms[0]));
+        let result = simulation.run(&swap).await;
+        assert!(result.success, "{:?}", result.error);
+    }
+
+    #[test]
+    fn test_request_wire_format() {
+        let body = serde_json::json!({
+            "type": "delegate",
+            "delegator_address": ALICE,
+            "validator_address": BOB,
+            "amount": 5,
+        });
+        let parsed: SimulateRequest = serde_json::from_value(body).unwrap();
+        assert_eq!(parsed.tx, SimulatedTx::Delegate {
+            delegator_address: ALICE.to_string(),
+            validator_address: BOB.to_string(),
+            amount: 5,
+        });
+        assert!(!parsed.is_signed());
+
+        let body = serde_json::json!({
+            "type": "transfer", "from": ALICE, "to": BOB, "amount": 1, "nonce": 0,
+            "signature": "ab", "public_key": "cd",
+        });
+        let parsed: SimulateRequest = serde_json::from_value(body).unwrap();
+        assert!(parsed.is_signed());
+        let tx = parsed.transaction(42).unwrap();
+        assert_eq!(tx.timestamp, 42);
+        assert_eq!(tx.public_key.as_deref(), Some("cd"));
+    }
+}
@@ -0,0 +1,2538 @@
+//! RocksDB-backed `PersistentStorage`
+
+use anyhow::{Result, Context};
+use rocksdb::{
+    BlockBasedOptions, ColumnFamily, ColumnFamilyDescriptor, DBCompressionType, Direction,
+    IteratorMode, Options, WriteBatch, DB,
+};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tracing::{debug, info, warn};
+use lru::LruCache;
+use std::num::NonZeroUsize;
+
+use super::{
+    AccountStateSnapshot, BlockHeader, FinalityProof, GovernanceStateSnapshot, PrunedRange,
+    StakingStateSnapshot,
+};
+use crate::blockchain::Block;
+use crate::key_rotation::KeyRotation;
+use crate::events::{EventFilter, StoredEvent};
+
+/// Compact database every N blocks
+const AUTO_COMPACT_INTERVAL_BLOCKS: u64 = 10_000;
+
+/// Column families, one per data kind
+const CF_BLOCKS: &str = "blocks";
+const CF_TRANSACTIONS: &str = "transactions";
+const CF_WALLETS: &str = "wallets";
+const CF_STAKING: &str = "staking";
+const CF_GOVERNANCE: &str = "governance";
+const CF_META: &str = "meta";
+const COLUMN_FAMILIES: &[&str] = &[CF_BLOCKS, CF_TRANSACTIONS, CF_WALLETS, CF_STAKING, CF_GOVERNANCE, CF_META];
+
+/// Key prefixes for different data types (prevent collisions)
+const PREFIX_BLOCK: &str = "block:";
+const PREFIX_HEIGHT: &str = "height:";
+const PREFIX_WALLET: &str = "wallet:";
+const PREFIX_TX: &str = "tx:";
+const PREFIX_TX_INDEX: &str = "txindex:";
+const PREFIX_SLASH: &str = "slash:";
+const PREFIX_KEY_ROTATION: &str = "keyrot:";
+const PREFIX_GOV_PROPOSAL: &str = "gov:proposal:";
+const PREFIX_GOV_VOTES: &str = "gov:votes:";
+const PREFIX_GOV_STATE: &str = "gov:state";
+const PREFIX_HEADER: &str = "header:";
+const PREFIX_TX_HEIGHT: &str = "txheight:";
+const PREFIX_FINALITY: &str = "finality:";
+const PREFIX_EVENTS: &str = "events:";
+const PREFIX_TX_EVENTS: &str = "txevents:";
+const KEY_PRUNED_RANGES: &str = "meta:pruned";
+const KEY_ACCOUNT_SNAPSHOT: &str = "state:accounts";
+const KEY_SCHEMA_VERSION: &str = "meta:schema_version";
+const KEY_HEALTH_PROBE: &str = "meta:health_probe";
+
+/// Check whether a pruning pass is due every N blocks
+const PRUNE_INTERVAL_BLOCKS: u64 = 100;
+
+/// Heights deleted per atomic write batch while pruning
+const PRUNE_BATCH_SIZE: u64 = 1_000;
+
+/// Blocks one `/events` query scans before returning a continuation height
+const MAX_EVENT_SCAN_BLOCKS: usize = 10_000;
+
+/// Persist an account snapshot every N blocks when pruning is enabled.
+/// Block bodies are only pruned up to the latest snapshot, so a restart
+/// can restore state from the snapshot instead of replaying pruned blocks.
+pub const ACCOUNT_SNAPSHOT_INTERVAL_BLOCKS: u64 = 1_000;
+
+/// Smallest `keep_recent` accepted from the CLI (block sync serves recent blocks)
+pub const MIN_PRUNING_KEEP_RECENT: u64 = 1_000;
+
+/// Which historical block bodies the node keeps
+///
+/// Headers, finality proofs and the height index are never pruned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PruningMode {
+    /// Keep every block body, transaction and tx index entry
+    #[default]
+    Archive,
+    /// Keep the last 100,000 blocks plus every 10,000th block
+    Default,
+    /// Keep the last `keep_recent` blocks plus every `keep_every`th block (0 = none)
+    Custom { keep_recent: u64, keep_every: u64 },
+}
+
+impl PruningMode {
+    /// Parse `--pruning archive|default|custom` with the custom parameters
+    pub fn from_args(mode: &str, keep_recent: Option<u64>, keep_every: Option<u64>) -> Result<Self> {
+        match mode {
+            "archive" | "default" if keep_recent.is_some() || keep_every.is_some() => {
+                anyhow::bail!("--pruning-keep-recent/--pruning-keep-every require --pruning custom")
+            }
+            "archive" => Ok(Self::Archive),
+            "default" => Ok(Self::Default),
+            "custom" => {
+                let keep_recent = keep_recent
+                    .context("--pruning custom requires --pruning-keep-recent")?;
+                if keep_recent < MIN_PRUNING_KEEP_RECENT {
+                    anyhow::bail!(
+                        "--pruning-keep-recent must be at least {} (got {})",
+                        MIN_PRUNING_KEEP_RECENT, keep_recent
+                    );
+                }
+                Ok(Self::Custom { keep_recent, keep_every: keep_every.unwrap_or(0) })
+            }
+            other => anyhow::bail!("Unknown pruning mode '{}' (expected archive, default or custom)", other),
+        }
+    }
+
+    pub fn is_archive(&self) -> bool {
+        matches!(self, Self::Archive)
+    }
+
+    /// Number of most recent blocks whose bodies are always kept
+    pub fn keep_recent(&self) -> u64 {
+        match self {
+            Self::Archive => u64::MAX,
+            Self::Default => 100_000,
+            Self::Custom { keep_recent, .. } => *keep_recent,
+        }
+    }
+
+    /// Bodies at multiples of this height are kept (0 = keep none)
+    pub fn keep_every(&self) -> u64 {
+        match self {
+            Self::Archive => 0,
+            Self::Default => 10_000,
+            Self::Custom { keep_every, .. } => *keep_every,
+        }
+    }
+}
+
+impl std::fmt::Display for PruningMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Archive => write!(f, "archive"),
+            Self::Default => write!(f, "default"),
+            Self::Custom { keep_recent, keep_every } => {
+                write!(f, "custom(keep_recent={}, keep_every={})", keep_recent, keep_every)
+            }
+        }
+    }
+}
+
+/// RocksDB size estimates for one column family
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnFamilyStats {
+    pub name: &'static str,
+    pub estimated_keys: u64,
+    pub live_data_bytes: u64,
+    pub sst_files_bytes: u64,
+    pub memtable_bytes: u64,
+}
+
+/// AES-256-GCM authenticated encryption for sensitive data
+/// Provides confidentiality, integrity, and authenticity guarantees
+#[derive(Clone)]
+pub struct StorageEncryption {
+    key: [u8; 32], // 256-bit key for AES-256-GCM
+}
+
+impl StorageEncryption {
+    /// Nonce size for AES-GCM (96 bits / 12 bytes)
+    const NONCE_SIZE: usize = 12;
+    
+    /// HKDF info context for storage encryption
+    const HKDF_INFO: &'static [u8] = b"sultan-storage-encryption-v1";
+    
+    /// HKDF salt for additional security (can be stored alongside encrypted data)
+    const HKDF_SALT: &'static [u8] = b"sultan-l1-blockchain-storage";
+    
+    /// Create new encryption with HKDF-SHA256 key derivation (RFC 5869)
+    /// HKDF provides cryptographically secure key derivation with:
+    /// - Extract: Produces pseudorandom key from input key material
+    /// - Expand: Derives output key with domain separation via info
+    /// In production, derive from a secure key management system (HSM, KMS)
+    pub fn new(key: &[u8]) -> Self {
+        use hkdf::Hkdf;
+        use sha2::Sha256;
+        
+        // HKDF-SHA256 key derivation (RFC 5869)
+        // Extract phase: Create pseudorandom key from input key material
+        // Expand phase: Derive 32-byte output key with domain separation
+        let hk = Hkdf::<Sha256>::new(Some(Self::HKDF_SALT), key);
+        let mut derived_key = [0u8; 32];
+        hk.expand(Self::HKDF_INFO, &mut derived_key)
+            .expect("32 bytes is valid output length for HKDF-SHA256");
+        
+        Self { key: derived_key }
+    }
+    
+    /// Create encryption with custom salt for multi-tenant isolation
+    pub fn with_salt(key: &[u8], salt: &[u8]) -> Self {
+        use hkdf::Hkdf;
+        use sha2::Sha256;
+        
+        let hk = Hkdf::<Sha256>::new(Some(salt), key);
+        let mut derived_key = [0u8; 32];
+        hk.expand(Self::HKDF_INFO, &mut derived_key)
+            .expect("32 bytes is valid output length for HKDF-SHA256");
+        
+        Self { key: derived_key }
+    }
+    
+    /// Generate a cryptographically secure random nonce
+    fn generate_nonce() -> [u8; 12] {
+        use rand::RngCore;
+        let mut nonce = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        nonce
+    }
+    
+    /// Encrypt data using AES-256-GCM with authenticated encryption
+    /// Returns: nonce (12 bytes) || ciphertext || auth_tag (16 bytes)
+    pub fn encrypt(&self, data: &[u8]) -> Vec<u8> {
+        use aes_gcm::{
+            aead::{Aead, KeyInit},
+            Aes256Gcm, Nonce,
+        };
+        
+        let cipher = Aes256Gcm::new_from_slice(&self.key)
+            .expect("Valid 32-byte key");
+        
+        let nonce_bytes = Self::generate_nonce();
+        let nonce = Nonce::from(nonce_bytes);
+        
+        // Encrypt with authentication tag appended
+        let ciphertext = cipher
+            .encrypt(&nonce, data)
+            .expect("Encryption should not fail with valid inputs");
+        
+        // Prepend nonce to ciphertext for storage
+        let mut result = Vec::with_capacity(Self::NONCE_SIZE + ciphertext.len());
+        result.extend_from_slice(&nonce_bytes);
+        result.extend_from_slice(&ciphertext);
+        result
+    }
+    
+    /// Decrypt data and verify authentication tag
+    /// Input format: nonce (12 bytes) || ciphertext || auth_tag (16 bytes)
+    /// Returns decrypted plaintext or error on authentication failure
+    /// SECURITY: Uses try_decrypt internally to avoid panics on corrupted data
+    pub fn decrypt(&self, data: &[u8]) -> Vec<u8> {
+        // Use try_decrypt and convert error to empty vec to maintain API compatibility
+        // Callers should prefer try_decrypt for proper error handling
+        self.try_decrypt(data).unwrap_or_else(|e| {
+            tracing::error!("Decryption failed: {}", e);
+            Vec::new()
+        })
+    }
+    
+    /// Try to decrypt data, returning Result instead of panicking
+    /// Useful for graceful error handling in production
+    pub fn try_decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        use aes_gcm::{
+            aead::{Aead, KeyInit},
+            Aes256Gcm, Nonce,
+        };
+        
+        if data.len() < Self::NONCE_SIZE + 16 {
+            return Err(anyhow::anyhow!("Ciphertext too short"));
+        }
+        
+        let cipher = Aes256Gcm::new_from_slice(&self.key)
+            .map_err(|_| anyhow::anyhow!("Invalid key"))?;
+        
+        // Extract nonce and ciphertext - convert slice to fixed array
+        let mut nonce_arr = [0u8; 12];
+        nonce_arr.copy_from_slice(&data[..Self::NONCE_SIZE]);
+        let nonce = Nonce::from(nonce_arr);
+        let ciphertext = &data[Self::NONCE_SIZE..];
+        
+        cipher
+            .decrypt(&nonce, ciphertext)
+            .map_err(|_| anyhow::anyhow!("Decryption failed: authentication error"))
+    }
+}
+
+/// Production-grade persistent storage using RocksDB
+/// 
+/// Thread-safe storage backend with LRU caching and auto-compaction.
+/// Supports optional encryption for sensitive data.
+pub struct PersistentStorage {
+    db: Arc<DB>,
+    block_cache: parking_lot::Mutex<LruCache<String, Block>>,
+    /// Track last compaction height for auto-compaction scheduling
+    last_compaction_height: AtomicU64,
+    /// Optional encryption for sensitive data
+    encryption: Option<StorageEncryption>,
+    /// Which block bodies to keep
+    pruning: PruningMode,
+    /// Background pruning state (shared with the pruning thread)
+    pruner: Pruner,
+}
+
+/// State shared between `PersistentStorage` and its background pruning thread
+#[derive(Clone)]
+struct Pruner {
+    db: Arc<DB>,
+    /// Pruned height ranges (mirrors `meta:pruned`)
+    ranges: Arc<parking_lot::RwLock<Vec<PrunedRange>>>,
+    /// Height of the latest account snapshot (bodies above it are never pruned)
+    snapshot_height: Arc<AtomicU64>,
+    /// Set while a pruning pass is running
+    running: Arc<AtomicBool>,
+    /// Serializes read-modify-write of `txindex:` entries
+    tx_index_lock: Arc<parking_lot::Mutex<()>>,
+}
+
+impl Pruner {
+    fn cf(&self, name: &str) -> &ColumnFamily {
+        cf_handle(&self.db, name)
+    }
+
+    /// Highest pruned height (0 = nothing pruned)
+    fn pruned_to(&self) -> u64 {
+        self.ranges.read().last().map(|r| r.end).unwrap_or(0)
+    }
+
+    /// Prune bodies for heights `pruned_to + 1 ..= target` in batches
+    fn run(&self, target: u64, keep_every: u64) -> Result<u64> {
+        let mut pruned = 0;
+        let mut from = self.pruned_to() + 1;
+        while from <= target {
+            let to = target.min(from + PRUNE_BATCH_SIZE - 1);
+            pruned += self.prune_batch(from, to, keep_every)?;
+            from = to + 1;
+        }
+        Ok(pruned)
+    }
+
+    /// Atomically prune one batch of heights and record the new pruned range
+    fn prune_batch(&self, from: u64, to: u64, keep_every: u64) -> Result<u64> {
+        let mut batch = WriteBatch::default();
+        let mut removed_by_address: std::collections::HashMap<String, Vec<String>> =
+            std::collections::HashMap::new();
+        let mut pruned = 0;
+
+        for height in from..=to {
+            if keep_every > 0 && height.is_multiple_of(keep_every) {
+                continue;
+            }
+            let Some(hash_bytes) = self.db.get_cf(self.cf(CF_BLOCKS), format!("{}{}", PREFIX_HEIGHT, height).as_bytes())? else {
+                continue;
+            };
+            let hash = String::from_utf8(hash_bytes).context("Invalid UTF-8 in block hash")?;
+            let block_key = format!("{}{}", PREFIX_BLOCK, hash);
+            if let Some(data) = self.db.get_cf(self.cf(CF_BLOCKS), block_key.as_bytes())? {
+                let block: Block = bincode::deserialize(&data)
+                    .context("Failed to deserialize block")?;
+                let header = bincode::serialize(&BlockHeader::from(&block))?;
+                batch.put_cf(self.cf(CF_BLOCKS), format!("{}{}", PREFIX_HEADER, height).as_bytes(), header);
+                batch.delete_cf(self.cf(CF_BLOCKS), block_key.as_bytes());
+                pruned += 1;
+            }
+
+            let events_key = events_key(height);
+            if let Some(data) = self.db.get_cf(self.cf(CF_BLOCKS), events_key.as_bytes())? {
+                let events: Vec<StoredEvent> = serde_json::from_slice(&data)
+                    .context("Failed to deserialize block events")?;
+                for tx_hash in events.iter().filter_map(|e| e.tx_hash.as_ref()) {
+                    batch.delete_cf(self.cf(CF_BLOCKS), format!("{}{}", PREFIX_TX_EVENTS, tx_hash).as_bytes());
+                }
+                batch.delete_cf(self.cf(CF_BLOCKS), events_key.as_bytes());
+            }
+
+            let prefix = format!("{}{}:", PREFIX_TX_HEIGHT, height);
+            for item in self.db.prefix_iterator_cf(self.cf(CF_TRANSACTIONS), prefix.as_bytes()) {
+                let (key, value) = item?;
+                let Some(tx_hash) = key.strip_prefix(prefix.as_bytes()) else {
+                    break;
+                };
+                let tx_hash = String::from_utf8(tx_hash.to_vec())
+                    .context("Invalid UTF-8 in tx hash")?;
+                let (from_addr, to_addr): (String, String) = bincode::deserialize(&value)
+                    .context("Failed to deserialize tx height index")?;
+                batch.delete_cf(self.cf(CF_TRANSACTIONS), format!("{}{}", PREFIX_TX, tx_hash).as_bytes());
+                batch.delete_cf(self.cf(CF_TRANSACTIONS), &key);
+                removed_by_address.entry(from_addr).or_default().push(tx_hash.clone());
+                removed_by_address.entry(to_addr).or_default().push(tx_hash);
+            }
+        }
+
+        let mut ranges = self.ranges.read().clone();
+        match ranges.last_mut() {
+            Some(last) if last.keep_every == keep_every && last.end + 1 >= from => last.end = to,
+            _ => ranges.push(PrunedRange { start: from, end: to, keep_every }),
+        }
+        batch.put_cf(self.cf(CF_META), KEY_PRUNED_RANGES.as_bytes(), serde_json::to_vec(&ranges)?);
+
+        // Hold the index lock until the batch lands so concurrent appends aren't lost
+        let _guard = self.tx_index_lock.lock();
+        for (address, removed) in removed_by_address {
+            let index_key = format!("{}{}", PREFIX_TX_INDEX, address);
+            let Some(data) = self.db.get_cf(self.cf(CF_TRANSACTIONS), index_key.as_bytes())? else {
+                continue;
+            };
+            let mut hashes: Vec<String> = serde_json::from_slice(&data).unwrap_or_default();
+            hashes.retain(|h| !removed.contains(h));
+            if hashes.is_empty() {
+                batch.delete_cf(self.cf(CF_TRANSACTIONS), index_key.as_bytes());
+            } else {
+                batch.put_cf(self.cf(CF_TRANSACTIONS), index_key.as_bytes(), serde_json::to_vec(&hashes)?);
+            }
+        }
+        self.db.write(batch)?;
+        *self.ranges.write() = ranges;
+
+        debug!("🗑️ Pruned block bodies {}..={} ({} blocks)", from, to, pruned);
+        Ok(pruned)
+    }
+}
+
+/// Key of a block's events (zero-padded so keys sort by height)
+fn events_key(height: u64) -> String {
+    format!("{}{:020}", PREFIX_EVENTS, height)
+}
+
+/// One page of an event query
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct EventPage {
+    pub events: Vec<StoredEvent>,
+    /// Height to continue from, if the query stopped before the chain tip
+    pub next_height: Option<u64>,
+}
+
+/// Handle for one of our column families (all are opened in `with_encryption`)
+fn cf_handle<'a>(db: &'a DB, name: &str) -> &'a ColumnFamily {
+    db.cf_handle(name)
+        .unwrap_or_else(|| panic!("column family '{}' not opened", name))
+}
+
+/// Tuned options for each column family
+fn column_family_options(name: &str) -> Options {
+    let mut opts = Options::default();
+    match name {
+        // Large, append-only, looked up by hash/height
+        CF_BLOCKS => {
+            let mut table = BlockBasedOptions::default();
+            table.set_bloom_filter(10.0, false);
+            table.set_block_size(32 * 1024);
+            table.set_cache_index_and_filter_blocks(true);
+            opts.set_block_based_table_factory(&table);
+            opts.set_compression_type(DBCompressionType::Lz4);
+            opts.set_write_buffer_size(64 * 1024 * 1024);
+            opts.set_target_file_size_base(64 * 1024 * 1024);
+        }
+        // Highest write volume (tx, index and height entries per block)
+        CF_TRANSACTIONS => {
+            let mut table = BlockBasedOptions::default();
+            table.set_bloom_filter(10.0, false);
+            table.set_cache_index_and_filter_blocks(true);
+            opts.set_block_based_table_factory(&table);
+            opts.set_compression_type(DBCompressionType::Lz4);
+            opts.set_write_buffer_size(64 * 1024 * 1024);
+            opts.set_max_write_buffer_number(4);
+        }
+        // Small, hot values read on every balance query
+        CF_WALLETS => {
+            opts.optimize_for_point_lookup(64);
+            opts.set_compression_type(DBCompressionType::None);
+        }
+        // Staking, governance and metadata: small and rarely written
+        _ => {
+            opts.set_compression_type(DBCompressionType::Lz4);
+        }
+    }
+    opts
+}
+
+impl PersistentStorage {
+    /// Create new persistent storage instance (unencrypted)
+    pub fn new(path: &str) -> Result<Self> {
+        Self::with_encryption(path, None)
+    }
+
+    /// Create new persistent storage instance with a pruning mode
+    pub fn with_pruning(path: &str, pruning: PruningMode) -> Result<Self> {
+        let mut storage = Self::with_encryption(path, None)?;
+        storage.pruning = pruning;
+        info!("🗂️ Block pruning mode: {}", pruning);
+        Ok(storage)
+    }
+    
+    /// Create new persistent storage instance with optional encryption
+    pub fn with_encryption(path: &str, encryption_key: Option<&[u8]>) -> Result<Self> {
+        info!("Initializing RocksDB at: {}", path);
+        
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_max_open_files(10000);
+        opts.set_use_fsync(false); // Speed over paranoia for development
+        opts.set_bytes_per_sync(8388608); // 8MB
+        opts.set_level_compaction_dynamic_level_bytes(true);
+        opts.set_max_background_jobs(4);
+        opts.create_missing_column_families(true);
+        
+        // RocksDB requires every existing column family to be opened, including
+        // ones this release doesn't know about (e.g. created by a newer node)
+        let existing = DB::list_cf(&opts, path).unwrap_or_default();
+        let descriptors: Vec<ColumnFamilyDescriptor> = COLUMN_FAMILIES.iter()
+            .map(|name| ColumnFamilyDescriptor::new(*name, column_family_options(name)))
+            .chain(existing.into_iter()
+                .filter(|name| name != "default" && !COLUMN_FAMILIES.contains(&name.as_str()))
+                .map(|name| ColumnFamilyDescriptor::new(name, Options::default())))
+            .collect();
+        
+        let db = DB::open_cf_descriptors(&opts, path, descriptors)?;
+        let schema_version = migrate(&db)?;
+        let db = Arc::new(db);
+        
+        let ranges: Vec<PrunedRange> = match db.get_cf(cf_handle(&db, CF_META), KEY_PRUNED_RANGES.as_bytes())? {
+            Some(data) => serde_json::from_slice(&data).context("Failed to deserialize pruned ranges")?,
+            None => Vec::new(),
+        };
+        let snapshot_height = match db.get_cf(cf_handle(&db, CF_META), KEY_ACCOUNT_SNAPSHOT.as_bytes())? {
+            Some(data) => bincode::deserialize::<AccountStateSnapshot>(&data)
+                .context("Failed to deserialize account snapshot")?
+                .height,
+            None => 0,
+        };
+        let pruner = Pruner {
+            db: Arc::clone(&db),
+            ranges: Arc::new(parking_lot::RwLock::new(ranges)),
+            snapshot_height: Arc::new(AtomicU64::new(snapshot_height)),
+            running: Arc::new(AtomicBool::new(false)),
+            tx_index_lock: Arc::new(parking_lot::Mutex::new(())),
+        };
+        
+        let encryption = encryption_key.map(StorageEncryption::new);
+        
+        if encryption.is_some() {
+            info!("✅ RocksDB initialized with encryption enabled (schema v{})", schema_version);
+        } else {
+            info!("✅ RocksDB initialized successfully (schema v{})", schema_version);
+        }
+        
+        Ok(Self {
+            db,
+            block_cache: parking_lot::Mutex::new(LruCache::new(NonZeroUsize::new(1000).unwrap())),
+            last_compaction_height: AtomicU64::new(0),
+            encryption,
+            pruning: PruningMode::Archive,
+            pruner,
+        })
+    }
+    
+    /// Check if encryption is enabled
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption.is_some()
+    }
+    
+    fn cf(&self, name: &str) -> &ColumnFamily {
+        cf_handle(&self.db, name)
+    }
+    
+    /// On-disk schema version (always `SCHEMA_VERSION` once opened)
+    pub fn schema_version(&self) -> Result<u32> {
+        read_schema_version(&self.db)
+    }
+    
+    /// Save block to persistent storage
+    /// 
+    /// Also triggers auto-compaction every AUTO_COMPACT_INTERVAL_BLOCKS.
+    pub fn save_block(&self, block: &Block) -> Result<()> {
+        let key = format!("{}{}", PREFIX_BLOCK, block.hash);
+        let value = bincode::serialize(block)?;
+        
+        // Save block data
+        self.db.put_cf(self.cf(CF_BLOCKS), key.as_bytes(), value)?;
+        
+        // Update height index for fast lookup
+        let height_key = format!("{}{}", PREFIX_HEIGHT, block.index);
+        self.db.put_cf(self.cf(CF_BLOCKS), height_key.as_bytes(), block.hash.as_bytes())?;
+        
+        // Update latest block pointer
+        self.db.put_cf(self.cf(CF_BLOCKS), b"latest", block.hash.as_bytes())?;
+        
+        // Key rotations outlive pruning: consensus replays them on startup
+        for (key, value) in key_rotation_index(block)? {
+            self.db.put_cf(self.cf(CF_STAKING), key.as_bytes(), value)?;
+        }
+        
+        // Cache the block
+        self.block_cache.lock().put(block.hash.clone(), block.clone());
+        
+        // Auto-compaction check
+        self.maybe_auto_compact(block.index);
+        
+        // Background pruning check
+        self.maybe_prune(block.index);
+        
+        Ok(())
+    }
+    
+    /// Start a background pruning pass every PRUNE_INTERVAL_BLOCKS
+    ///
+    /// Bodies are pruned up to `current_height - keep_recent`, but never past
+    /// the latest account snapshot (needed to restore state on restart).
+    fn maybe_prune(&self, current_height: u64) {
+        if self.pruning.is_archive() || !current_height.is_multiple_of(PRUNE_INTERVAL_BLOCKS) {
+            return;
+        }
+        let target = current_height
+            .saturating_sub(self.pruning.keep_recent())
+            .min(self.pruner.snapshot_height.load(Ordering::SeqCst));
+        if target <= self.pruner.pruned_to() {
+            return;
+        }
+        // Only one pruning pass at a time
+        if self.pruner.running
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+        let pruner = self.pruner.clone();
+        let keep_every = self.pruning.keep_every();
+        std::thread::spawn(move || {
+            match pruner.run(target, keep_every) {
+                Ok(pruned) => info!("✅ Pruned {} block bodies up to height {}", pruned, target),
+                Err(e) => warn!("⚠️ Block pruning failed: {}", e),
+            }
+            pruner.running.store(false, Ordering::SeqCst);
+        });
+    }
+    
+    /// Prune synchronously up to `target` (capped by the account snapshot)
+    ///
+    /// Returns the number of block bodies removed.
+    pub fn prune_to(&self, target: u64) -> Result<u64> {
+        if self.pruning.is_archive() {
+            anyhow::bail!("Pruning is disabled (archive mode)");
+        }
+        let target = target.min(self.pruner.snapshot_height.load(Ordering::SeqCst));
+        self.pruner.run(target, self.pruning.keep_every())
+    }
+    
+    /// Current pruning mode
+    pub fn pruning_mode(&self) -> PruningMode {
+        self.pruning
+    }
+    
+    /// Height ranges whose block bodies have been pruned
+    pub fn pruned_ranges(&self) -> Vec<PrunedRange> {
+        self.pruner.ranges.read().clone()
+    }
+    
+    /// Whether the body of the block at `height` has been pruned
+    pub fn is_pruned(&self, height: u64) -> bool {
+        self.pruner.ranges.read().iter().any(|r| r.contains(height))
+    }
+    
+    /// Error returned when a pruned block body is requested
+    pub fn pruned_error(&self, height: u64) -> anyhow::Error {
+        anyhow::anyhow!(
+            "Block {} has been pruned (pruning mode: {}); query an archive node",
+            height, self.pruning
+        )
+    }
+    
+    /// Get a block header by height (available even after the body is pruned)
+    pub fn get_block_header(&self, height: u64) -> Result<Option<BlockHeader>> {
+        let key = format!("{}{}", PREFIX_HEADER, height);
+        if let Some(data) = self.db.get_cf(self.cf(CF_BLOCKS), key.as_bytes())? {
+            let header: BlockHeader = bincode::deserialize(&data)
+                .context("Failed to deserialize block header")?;
+            return Ok(Some(header));
+        }
+        Ok(self.get_block_by_height(height)?.map(|block| BlockHeader::from(&block)))
+    }
+    
+    /// Save a finality proof (never pruned)
+    pub fn save_finality_proof(&self, proof: &FinalityProof) -> Result<()> {
+        let key = format!("{}{}", PREFIX_FINALITY, proof.height);
+        self.db.put_cf(self.cf(CF_BLOCKS), key.as_bytes(), bincode::serialize(proof)?)?;
+        Ok(())
+    }
+    
+    /// Get the finality proof for a height
+    pub fn get_finality_proof(&self, height: u64) -> Result<Option<FinalityProof>> {
+        let key = format!("{}{}", PREFIX_FINALITY, height);
+        if let Some(data) = self.db.get_cf(self.cf(CF_BLOCKS), key.as_bytes())? {
+            let proof: FinalityProof = bincode::deserialize(&data)
+                .context("Failed to deserialize finality proof")?;
+            return Ok(Some(proof));
+        }
+        Ok(None)
+    }
+    
+    /// Whether an account snapshot should be taken at this height
+    pub fn account_snapshot_due(&self, height: u64) -> bool {
+        !self.pruning.is_archive() && height > 0 && height.is_multiple_of(ACCOUNT_SNAPSHOT_INTERVAL_BLOCKS)
+    }
+    
+    /// Save the account state snapshot that pruned nodes restore from
+    pub fn save_account_snapshot(&self, snapshot: &AccountStateSnapshot) -> Result<()> {
+        self.db.put_cf(self.cf(CF_META), KEY_ACCOUNT_SNAPSHOT.as_bytes(), bincode::serialize(snapshot)?)?;
+        self.pruner.snapshot_height.store(snapshot.height, Ordering::SeqCst);
+        info!("💾 Account snapshot persisted at height {} ({} accounts)",
+            snapshot.height, snapshot.accounts.len());
+        Ok(())
+    }
+    
+    /// Load the latest account state snapshot
+    pub fn load_account_snapshot(&self) -> Result<Option<AccountStateSnapshot>> {
+        if let Some(data) = self.db.get_cf(self.cf(CF_META), KEY_ACCOUNT_SNAPSHOT.as_bytes())? {
+            let snapshot: AccountStateSnapshot = bincode::deserialize(&data)
+                .context("Failed to deserialize account snapshot")?;
+            return Ok(Some(snapshot));
+        }
+        Ok(None)
+    }
+    
+    /// Check if auto-compaction should run based on block height
+    fn maybe_auto_compact(&self, current_height: u64) {
+        let last_compaction = self.last_compaction_height.load(Ordering::Relaxed);
+        if current_height >= last_compaction + AUTO_COMPACT_INTERVAL_BLOCKS {
+            // Try to update atomically - only one thread should compact
+            if self.last_compaction_height
+                .compare_exchange(last_compaction, current_height, Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok()
+            {
+                info!("🗂️ Auto-compaction triggered at height {}", current_height);
+                // Run compaction in background (non-blocking)
+                let db = Arc::clone(&self.db);
+                std::thread::spawn(move || {
+                    for name in COLUMN_FAMILIES {
+                        db.compact_range_cf(cf_handle(&db, name), None::<&[u8]>, None::<&[u8]>);
+                    }
+                    info!("✅ Auto-compaction complete");
+                });
+            }
+        }
+    }
+    
+    /// Get block by hash (checks cache first)
+    pub fn get_block(&self, hash: &str) -> Result<Option<Block>> {
+        // Check cache first for speed
+        if let Some(block) = self.block_cache.lock().get(hash) {
+            return Ok(Some(block.clone()));
+        }
+        
+        // Query database
+        let key = format!("{}{}", PREFIX_BLOCK, hash);
+        if let Some(data) = self.db.get_cf(self.cf(CF_BLOCKS), key.as_bytes())? {
+            let block: Block = bincode::deserialize(&data)
+                .context("Failed to deserialize block")?;
+            
+            // Cache for next time
+            self.block_cache.lock().put(hash.to_string(), block.clone());
+            
+            return Ok(Some(block));
+        }
+        
+        Ok(None)
+    }
+    
+    /// Get block by height (None if the body has been pruned)
+    pub fn get_block_by_height(&self, height: u64) -> Result<Option<Block>> {
+        if self.is_pruned(height) {
+            return Ok(None);
+        }
+        let height_key = format!("{}{}", PREFIX_HEIGHT, height);
+        
+        if let Some(hash_bytes) = self.db.get_cf(self.cf(CF_BLOCKS), height_key.as_bytes())? {
+            let hash = String::from_utf8(hash_bytes)
+                .context("Invalid UTF-8 in block hash")?;
+            return self.get_block(&hash);
+        }
+        
+        Ok(None)
+    }
+    
+    /// Get latest block
+    pub fn get_latest_block(&self) -> Result<Option<Block>> {
+        if let Some(hash_bytes) = self.db.get_cf(self.cf(CF_BLOCKS), b"latest")? {
+            let hash = String::from_utf8(hash_bytes)?;
+            return self.get_block(&hash);
+        }
+        
+        Ok(None)
+    }
+    
+    /// Save wallet balance (encrypted if encryption is enabled)
+    pub fn save_wallet(&self, address: &str, balance: i64) -> Result<()> {
+        let key = format!("{}{}", PREFIX_WALLET, address);
+        let balance_bytes = balance.to_le_bytes();
+        
+        // Encrypt balance if encryption is enabled
+        let value = if let Some(ref enc) = self.encryption {
+            enc.encrypt(&balance_bytes)
+        } else {
+            balance_bytes.to_vec()
+        };
+        
+        self.db.put_cf(self.cf(CF_WALLETS), key.as_bytes(), value)?;
+        Ok(())
+    }
+    
+    /// Get wallet balance (decrypted if encryption is enabled)
+    pub fn get_wallet(&self, address: &str) -> Result<Option<i64>> {
+        let key = format!("{}{}", PREFIX_WALLET, address);
+        
+        if let Some(data) = self.db.get_cf(self.cf(CF_WALLETS), key.as_bytes())? {
+            // Decrypt if encryption is enabled
+            let decrypted = if let Some(ref enc) = self.encryption {
+                enc.decrypt(&data)
+            } else {
+                data.to_vec()
+            };
+            
+            let bytes: [u8; 8] = decrypted.as_slice().try_into()
+                .context("Invalid wallet balance data")?;
+            let balance = i64::from_le_bytes(bytes);
+            return Ok(Some(balance));
+        }
+        
+        Ok(None)
+    }
+    
+    /// Batch update wallets (atomic operation, encrypted if enabled)
+    pub fn batch_update_wallets(&self, updates: Vec<(String, i64)>) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        
+        for (address, balance) in updates {
+            let key = format!("{}{}", PREFIX_WALLET, address);
+            let balance_bytes = balance.to_le_bytes();
+            
+            // Encrypt balance if encryption is enabled
+            let value = if let Some(ref enc) = self.encryption {
+                enc.encrypt(&balance_bytes)
+            } else {
+                balance_bytes.to_vec()
+            };
+            
+            batch.put_cf(self.cf(CF_WALLETS), key.as_bytes(), value);
+        }
+        
+        self.db.write(batch)?;
+        Ok(())
+    }
+    
+    /// Get blockchain height
+    pub fn get_height(&self) -> Result<u64> {
+        if let Some(block) = self.get_latest_block()? {
+            return Ok(block.index);
+        }
+        
+        Ok(0)
+    }
+    
+    /// Write and delete a probe key, failing if the database no longer accepts writes
+    /// (full disk, read-only filesystem, background error)
+    pub fn check_writable(&self) -> Result<()> {
+        let cf = self.cf(CF_META);
+        self.db.put_cf(cf, KEY_HEALTH_PROBE.as_bytes(), b"ok")
+            .context("Storage write failed")?;
+        self.db.delete_cf(cf, KEY_HEALTH_PROBE.as_bytes())
+            .context("Storage delete failed")?;
+        Ok(())
+    }
+
+    /// Checkpoint (force flush to disk)
+    pub fn checkpoint(&self) -> Result<()> {
+        for name in COLUMN_FAMILIES {
+            self.db.flush_cf(self.cf(name))?;
+        }
+        info!("✅ Database checkpoint complete");
+        Ok(())
+    }
+
+    /// Create a consistent on-disk copy of the database at `path` (hard-linked where possible)
+    ///
+    /// `path` must not exist yet. The copy opens like any other data directory.
+    pub fn create_checkpoint(&self, path: &std::path::Path) -> Result<()> {
+        self.checkpoint()?;
+        rocksdb::checkpoint::Checkpoint::new(&self.db)?
+            .create_checkpoint(path)
+            .with_context(|| format!("Failed to create RocksDB checkpoint at {:?}", path))?;
+        info!("📸 RocksDB checkpoint created at {:?}", path);
+        Ok(())
+    }
+
+    /// RocksDB size estimates for each column family
+    pub fn column_family_stats(&self) -> Result<Vec<ColumnFamilyStats>> {
+        let property = |name: &str, property: &str| -> Result<u64> {
+            Ok(self.db.property_int_value_cf(self.cf(name), property)?.unwrap_or(0))
+        };
+        COLUMN_FAMILIES.iter()
+            .map(|name| Ok(ColumnFamilyStats {
+                name: *name,
+                estimated_keys: property(name, "rocksdb.estimate-num-keys")?,
+                live_data_bytes: property(name, "rocksdb.estimate-live-data-size")?,
+                sst_files_bytes: property(name, "rocksdb.total-sst-files-size")?,
+                memtable_bytes: property(name, "rocksdb.cur-size-all-mem-tables")?,
+            }))
+            .collect()
+    }
+
+    /// Blocks held in the in-memory block cache
+    pub fn block_cache_len(&self) -> usize {
+        self.block_cache.lock().len()
+    }
+
+    /// Get database statistics
+    pub fn stats(&self) -> Result<String> {
+        // Get approximate sizes per column family
+        let families = self.column_family_stats()?;
+        let total_keys: u64 = families.iter().map(|f| f.estimated_keys).sum();
+        let family_keys: Vec<String> = families.iter()
+            .map(|f| format!("{} ~{}", f.name, f.estimated_keys))
+            .collect();
+        
+        let ranges = self.pruned_ranges();
+        let pruned = if ranges.is_empty() {
+            "none".to_string()
+        } else {
+            ranges.iter()
+                .map(|r| if r.keep_every > 0 {
+                    format!("{}-{} (every {}th kept)", r.start, r.end, r.keep_every)
+                } else {
+                    format!("{}-{}", r.start, r.end)
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        
+        // Read these before formatting: get_height() locks the block cache too
+        let cache_size = self.block_cache_len();
+        let height = self.get_height()?;
+        
+        Ok(format!(
+            "RocksDB Stats:\n\
+             - Schema version: {}\n\
+             - Total keys: ~{}\n\
+             - Keys per column family: {}\n\
+             - Cache size: {}\n\
+             - Height: {}\n\
+             - Pruning: {}\n\
+             - Pruned ranges: {}",
+            self.schema_version()?,
+            total_keys,
+            family_keys.join(", "),
+            cache_size,
+            height,
+            self.pruning,
+            pruned
+        ))
+    }
+    
+    /// Compact database (reduce disk usage)
+    pub fn compact(&self) -> Result<()> {
+        info!("Starting database compaction...");
+        for name in COLUMN_FAMILIES {
+            self.db.compact_range_cf(self.cf(name), None::<&[u8]>, None::<&[u8]>);
+        }
+        info!("✅ Database compaction complete");
+        Ok(())
+    }
+
+    /// Save a confirmed transaction to storage
+    /// Stores: tx by hash, and indexes by sender/receiver address
+    pub fn save_transaction(&self, tx: &crate::sharded_blockchain_production::ConfirmedTransaction) -> Result<()> {
+        // Store transaction by hash
+        let tx_key = format!("{}{}", PREFIX_TX, tx.hash);
+        let tx_data = serde_json::to_vec(tx)
+            .context("Failed to serialize transaction")?;
+        self.db.put_cf(self.cf(CF_TRANSACTIONS), tx_key.as_bytes(), &tx_data)?;
+
+        // Index by block height so pruning can find the tx and its addresses
+        let height_key = format!("{}{}:{}", PREFIX_TX_HEIGHT, tx.block_height, tx.hash);
+        self.db.put_cf(self.cf(CF_TRANSACTIONS), height_key.as_bytes(), bincode::serialize(&(&tx.from, &tx.to))?)?;
+
+        // Add to sender's transaction list
+        self.append_tx_to_address(&tx.from, &tx.hash)?;
+
+        // Add to receiver's transaction list (if different)
+        if tx.from != tx.to {
+            self.append_tx_to_address(&tx.to, &tx.hash)?;
+        }
+
+        Ok(())
+    }
+
+    /// Append a transaction hash to an address's transaction index
+    fn append_tx_to_address(&self, address: &str, tx_hash: &str) -> Result<()> {
+        let index_key = format!("{}{}", PREFIX_TX_INDEX, address);
+        let _guard = self.pruner.tx_index_lock.lock();
+        
+        // Get existing hashes
+        let mut hashes: Vec<String> = if let Some(data) = self.db.get_cf(self.cf(CF_TRANSACTIONS), index_key.as_bytes())? {
+            serde_json::from_slice(&data).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        // Add new hash (avoid duplicates)
+        if !hashes.contains(&tx_hash.to_string()) {
+            hashes.push(tx_hash.to_string());
+            let data = serde_json::to_vec(&hashes)
+                .context("Failed to serialize tx index")?;
+            self.db.put_cf(self.cf(CF_TRANSACTIONS), index_key.as_bytes(), &data)?;
+        }
+
+        Ok(())
+    }
+
+    /// Get transaction by hash
+    pub fn get_transaction(&self, hash: &str) -> Result<Option<crate::sharded_blockchain_production::ConfirmedTransaction>> {
+        let key = format!("{}{}", PREFIX_TX, hash);
+        if let Some(data) = self.db.get_cf(self.cf(CF_TRANSACTIONS), key.as_bytes())? {
+            let tx: crate::sharded_blockchain_production::ConfirmedTransaction = 
+                serde_json::from_slice(&data).context("Failed to deserialize transaction")?;
+            return Ok(Some(tx));
+        }
+        Ok(None)
+    }
+
+    /// Get transaction history for an address (most recent first)
+    pub fn get_transaction_history(&self, address: &str, limit: usize) -> Result<Vec<crate::sharded_blockchain_production::ConfirmedTransaction>> {
+        let index_key = format!("{}{}", PREFIX_TX_INDEX, address);
+        
+        // Get transaction hashes for this address
+        let hashes: Vec<String> = if let Some(data) = self.db.get_cf(self.cf(CF_TRANSACTIONS), index_key.as_bytes())? {
+            serde_json::from_slice(&data).unwrap_or_default()
+        } else {
+            return Ok(Vec::new());
+        };
+
+        // Fetch transactions (most recent last in storage, so reverse)
+        let mut transactions = Vec::new();
+        for hash in hashes.iter().rev().take(limit) {
+            if let Some(tx) = self.get_transaction(hash)? {
+                transactions.push(tx);
+            }
+        }
+
+        // Sort by block height descending (most recent first)
+        transactions.sort_by(|a, b| b.block_height.cmp(&a.block_height));
+
+        Ok(transactions)
+    }
+    
+    /// Save the module events emitted in a block (nothing is written if there are none)
+    pub fn save_block_events(&self, height: u64, events: &[StoredEvent]) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let mut batch = WriteBatch::default();
+        let data = serde_json::to_vec(events).context("Failed to serialize block events")?;
+        batch.put_cf(self.cf(CF_BLOCKS), events_key(height).as_bytes(), data);
+        for tx_hash in events.iter().filter_map(|e| e.tx_hash.as_ref()) {
+            let key = format!("{}{}", PREFIX_TX_EVENTS, tx_hash);
+            batch.put_cf(self.cf(CF_BLOCKS), key.as_bytes(), height.to_be_bytes());
+        }
+        self.db.write(batch)?;
+        Ok(())
+    }
+
+    /// Module events emitted in a block
+    pub fn get_block_events(&self, height: u64) -> Result<Vec<StoredEvent>> {
+        match self.db.get_cf(self.cf(CF_BLOCKS), events_key(height).as_bytes())? {
+            Some(data) => serde_json::from_slice(&data).context("Failed to deserialize block events"),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Module events emitted by one transaction
+    pub fn get_tx_events(&self, tx_hash: &str) -> Result<Vec<StoredEvent>> {
+        let key = format!("{}{}", PREFIX_TX_EVENTS, tx_hash);
+        let Some(data) = self.db.get_cf(self.cf(CF_BLOCKS), key.as_bytes())? else {
+            return Ok(Vec::new());
+        };
+        let height = u64::from_be_bytes(data.as_slice().try_into().context("Invalid tx events index")?);
+        let mut events = self.get_block_events(height)?;
+        events.retain(|e| e.tx_hash.as_deref() == Some(tx_hash));
+        Ok(events)
+    }
+
+    /// Events matching `filter` from `from_height` upwards, oldest first
+    ///
+    /// A page never splits a block, so it can hold more than `limit` events.
+    /// `next_height` is set when the limit or the scan cap stopped the query.
+    pub fn query_events(&self, filter: &EventFilter, from_height: u64, limit: usize) -> Result<EventPage> {
+        if let Some(tx_hash) = &filter.tx_hash {
+            let mut events = self.get_tx_events(tx_hash)?;
+            events.retain(|e| e.height >= from_height && filter.matches(e));
+            return Ok(EventPage { events, next_height: None });
+        }
+
+        let mut page = EventPage::default();
+        let start = events_key(from_height);
+        let mode = IteratorMode::From(start.as_bytes(), Direction::Forward);
+        for (scanned, item) in self.db.iterator_cf(self.cf(CF_BLOCKS), mode).enumerate() {
+            let (key, value) = item?;
+            let Some(height) = key.strip_prefix(PREFIX_EVENTS.as_bytes()) else {
+                break;
+            };
+            let height: u64 = std::str::from_utf8(height).ok()
+                .and_then(|h| h.parse().ok())
+                .context("Invalid events key")?;
+            if page.events.len() >= limit || scanned >= MAX_EVENT_SCAN_BLOCKS {
+                page.next_height = Some(height);
+                break;
+            }
+            let events: Vec<StoredEvent> = serde_json::from_slice(&value)
+                .context("Failed to deserialize block events")?;
+            page.events.extend(events.into_iter().filter(|e| filter.matches(e)));
+        }
+        Ok(page)
+    }
+
+    /// Clear all data (DANGEROUS - for testing only)
+    #[cfg(test)]
+    pub fn clear_all(&self) -> Result<()> {
+        warn!("⚠️  Clearing all data!");
+        
+        for name in COLUMN_FAMILIES {
+            let keys: Vec<Vec<u8>> = self.db
+                .iterator_cf(self.cf(name), IteratorMode::Start)
+                .map(|item| item.unwrap().0.to_vec())
+                .collect();
+            
+            for key in keys {
+                self.db.delete_cf(self.cf(name), &key)?;
+            }
+        }
+        
+        self.block_cache.lock().clear();
+        
+        Ok(())
+    }
+    
+    // ============ Staking Persistence ============
+    
+    /// Save staking state to persistent storage
+    /// Called after every staking operation (delegate, undelegate, slash)
+    pub fn save_staking_state(&self, state: &StakingStateSnapshot) -> Result<()> {
+        let value = bincode::serialize(state)?;
+        self.db.put_cf(self.cf(CF_STAKING), b"staking:state", value)?;
+        info!("💾 Staking state persisted ({} validators, {} unbonding entries)", 
+            state.validators.len(), state.unbonding_queue.len());
+        Ok(())
+    }
+    
+    /// Load staking state from persistent storage
+    /// Called on node startup to restore state
+    pub fn load_staking_state(&self) -> Result<Option<StakingStateSnapshot>> {
+        if let Some(data) = self.db.get_cf(self.cf(CF_STAKING), b"staking:state")? {
+            let state: StakingStateSnapshot = bincode::deserialize(&data)?;
+            info!("📥 Loaded staking state: {} validators, {} delegations, {} unbonding",
+                state.validators.len(), state.delegations.len(), state.unbonding_queue.len());
+            Ok(Some(state))
+        } else {
+            info!("📭 No existing staking state found");
+            Ok(None)
+        }
+    }
+
+    /// Delete staking state from persistent storage
+    /// Used when resetting staking state (--reset-staking flag)
+    pub fn delete_staking_state(&self) -> Result<()> {
+        self.db.delete_cf(self.cf(CF_STAKING), b"staking:state")?;
+        info!("🗑️ Staking state deleted from storage");
+        Ok(())
+    }
+    
+    /// Save slashing history (append-only log for audit)
+    pub fn append_slashing_event(&self, event: &crate::staking::SlashingEvent) -> Result<()> {
+        let key = format!("{}{}:{}", PREFIX_SLASH, event.height, event.validator_address);
+        let value = bincode::serialize(event)
+            .context("Failed to serialize slashing event")?;
+        self.db.put_cf(self.cf(CF_STAKING), key.as_bytes(), value)?;
+        warn!("⚔️  Slashing event persisted: {} slashed {} at height {}", 
+            event.validator_address, event.amount_slashed, event.height);
+        Ok(())
+    }
+    
+    /// Get slashing history for a validator
+    pub fn get_slashing_history(&self, validator_address: &str) -> Result<Vec<crate::staking::SlashingEvent>> {
+        let mut events = Vec::new();
+        
+        for item in self.db.prefix_iterator_cf(self.cf(CF_STAKING), PREFIX_SLASH.as_bytes()) {
+            let (_key, value) = item?;
+            // Deserialize and filter by validator address
+            if let Ok(event) = bincode::deserialize::<crate::staking::SlashingEvent>(&value) {
+                if event.validator_address == validator_address {
+                    events.push(event);
+                }
+            }
+        }
+        
+        events.sort_by_key(|e| e.height);
+        Ok(events)
+    }
+    
+    /// Every key rotation the chain has carried, as `(inclusion height, rotation)`
+    /// in height order (kept for pruned blocks too)
+    pub fn get_key_rotations(&self) -> Result<Vec<(u64, KeyRotation)>> {
+        let mut rotations = Vec::new();
+        for item in self.db.prefix_iterator_cf(self.cf(CF_STAKING), PREFIX_KEY_ROTATION.as_bytes()) {
+            let (key, value) = item?;
+            if !key.starts_with(PREFIX_KEY_ROTATION.as_bytes()) {
+                break;
+            }
+            let (height, tx): (u64, crate::blockchain::Transaction) = bincode::deserialize(&value)
+                .context("Invalid key rotation index entry")?;
+            if let Some(rotation) = KeyRotation::from_transaction(&tx) {
+                rotations.push((height, rotation));
+            }
+        }
+        Ok(rotations)
+    }
+    
+    /// Get all slashing events (for auditing)
+    pub fn get_all_slashing_events(&self) -> Result<Vec<crate::staking::SlashingEvent>> {
+        let mut events = Vec::new();
+        
+        for item in self.db.prefix_iterator_cf(self.cf(CF_STAKING), PREFIX_SLASH.as_bytes()) {
+            let (_key, value) = item?;
+            if let Ok(event) = bincode::deserialize::<crate::staking::SlashingEvent>(&value) {
+                events.push(event);
+            }
+        }
+        
+        events.sort_by_key(|e| e.height);
+        Ok(events)
+    }
+    
+    // ============ Governance Persistence ============
+    
+    /// Save governance proposal to persistent storage
+    pub fn save_proposal(&self, proposal: &crate::governance::Proposal) -> Result<()> {
+        let key = format!("{}{}", PREFIX_GOV_PROPOSAL, proposal.id);
+        let value = serde_json::to_vec(proposal)
+            .context("Failed to serialize proposal")?;
+        self.db.put_cf(self.cf(CF_GOVERNANCE), key.as_bytes(), value)?;
+        info!("💾 Proposal #{} persisted: {}", proposal.id, proposal.title);
+        Ok(())
+    }
+    
+    /// Load a proposal by ID
+    pub fn load_proposal(&self, proposal_id: u64) -> Result<Option<crate::governance::Proposal>> {
+        let key = format!("{}{}", PREFIX_GOV_PROPOSAL, proposal_id);
+        if let Some(data) = self.db.get_cf(self.cf(CF_GOVERNANCE), key.as_bytes())? {
+            let proposal: crate::governance::Proposal = serde_json::from_slice(&data)
+                .context("Failed to deserialize proposal")?;
+            return Ok(Some(proposal));
+        }
+        Ok(None)
+    }
+    
+    /// Load all proposals from storage
+    pub fn load_all_proposals(&self) -> Result<Vec<crate::governance::Proposal>> {
+        let mut proposals = Vec::new();
+        
+        for item in self.db.prefix_iterator_cf(self.cf(CF_GOVERNANCE), PREFIX_GOV_PROPOSAL.as_bytes()) {
+            let (_key, value) = item?;
+            if let Ok(proposal) = serde_json::from_slice::<crate::governance::Proposal>(&value) {
+                proposals.push(proposal);
+            }
+        }
+        
+        // Sort by ID descending (newest first)
+        proposals.sort_by(|a, b| b.id.cmp(&a.id));
+        Ok(proposals)
+    }
+    
+    /// Save votes for a proposal
+    pub fn save_proposal_votes(&self, proposal_id: u64, votes: &[crate::governance::Vote]) -> Result<()> {
+        let key = format!("{}{}", PREFIX_GOV_VOTES, proposal_id);
+        let value = serde_json::to_vec(votes)
+            .context("Failed to serialize votes")?;
+        self.db.put_cf(self.cf(CF_GOVERNANCE), key.as_bytes(), value)?;
+        Ok(())
+    }
+    
+    /// Load votes for a proposal
+    pub fn load_proposal_votes(&self, proposal_id: u64) -> Result<Vec<crate::governance::Vote>> {
+        let key = format!("{}{}", PREFIX_GOV_VOTES, proposal_id);
+        if let Some(data) = self.db.get_cf(self.cf(CF_GOVERNANCE), key.as_bytes())? {
+            let votes: Vec<crate::governance::Vote> = serde_json::from_slice(&data)
+                .context("Failed to deserialize votes")?;
+            return Ok(votes);
+        }
+        Ok(Vec::new())
+    }
+    
+    /// Save governance state (next_proposal_id, etc.)
+    pub fn save_governance_state(&self, state: &GovernanceStateSnapshot) -> Result<()> {
+        let value = serde_json::to_vec(state)
+            .context("Failed to serialize governance state")?;
+        self.db.put_cf(self.cf(CF_GOVERNANCE), PREFIX_GOV_STATE.as_bytes(), value)?;
+        info!("💾 Governance state persisted: next_proposal_id={}", state.next_proposal_id);
+        Ok(())
+    }
+    
+    /// Load governance state
+    pub fn load_governance_state(&self) -> Result<Option<GovernanceStateSnapshot>> {
+        if let Some(data) = self.db.get_cf(self.cf(CF_GOVERNANCE), PREFIX_GOV_STATE.as_bytes())? {
+            let state: GovernanceStateSnapshot = serde_json::from_slice(&data)
+                .context("Failed to deserialize governance state")?;
+            return Ok(Some(state));
+        }
+        Ok(None)
+    }
+    
+    // ============ Encrypted Governance Storage ============
+    // For sensitive proposals (slashing, emergency actions, etc.)
+    
+    /// Save proposal with encryption for sensitive content
+    /// Use this for proposals containing validator addresses being slashed,
+    /// security-sensitive parameter changes, or confidential governance actions
+    pub fn save_proposal_encrypted(&self, proposal: &crate::governance::Proposal) -> Result<()> {
+        let encryption = self.encryption.as_ref()
+            .context("Encryption not enabled - use with_encryption() constructor")?;
+        
+        let key = format!("{}enc:{}", PREFIX_GOV_PROPOSAL, proposal.id);
+        let plaintext = serde_json::to_vec(proposal)
+            .context("Failed to serialize proposal")?;
+        let ciphertext = encryption.encrypt(&plaintext);
+        
+        self.db.put_cf(self.cf(CF_GOVERNANCE), key.as_bytes(), ciphertext)?;
+        info!("🔐 Proposal #{} encrypted and persisted: {}", proposal.id, proposal.title);
+        Ok(())
+    }
+    
+    /// Load encrypted proposal by ID
+    pub fn load_proposal_encrypted(&self, proposal_id: u64) -> Result<Option<crate::governance::Proposal>> {
+        let encryption = self.encryption.as_ref()
+            .context("Encryption not enabled - use with_encryption() constructor")?;
+        
+        let key = format!("{}enc:{}", PREFIX_GOV_PROPOSAL, proposal_id);
+        if let Some(ciphertext) = self.db.get_cf(self.cf(CF_GOVERNANCE), key.as_bytes())? {
+            let plaintext = encryption.try_decrypt(&ciphertext)
+                .context("Failed to decrypt proposal - possible key mismatch or data corruption")?;
+            let proposal: crate::governance::Proposal = serde_json::from_slice(&plaintext)
+                .context("Failed to deserialize proposal")?;
+            return Ok(Some(proposal));
+        }
+        Ok(None)
+    }
+    
+    /// Check if a proposal is stored encrypted
+    pub fn is_proposal_encrypted(&self, proposal_id: u64) -> bool {
+        let key = format!("{}enc:{}", PREFIX_GOV_PROPOSAL, proposal_id);
+        self.db.get_cf(self.cf(CF_GOVERNANCE), key.as_bytes()).ok().flatten().is_some()
+    }
+}
+
+// ============ Schema Versioning ============
+
+/// Current on-disk schema version
+///
+/// Bump this and append a `Migration` whenever the key layout or the
+/// serialized form of a stored struct (`Block`, `StakingStateSnapshot`, ...)
+/// changes, so existing data directories are upgraded instead of misread.
+pub const SCHEMA_VERSION: u32 = 4;
+
+/// Keys moved per write batch by migrations
+const MIGRATION_BATCH_SIZE: usize = 10_000;
+
+/// In-place upgrade of the data directory from schema `from` to `from + 1`
+///
+/// Each step must be safe to re-run: the version is only bumped after it
+/// completes, so a crash mid-migration repeats the step on next startup.
+struct Migration {
+    from: u32,
+    description: &'static str,
+    run: fn(&DB) -> Result<()>,
+}
+
+/// All migrations, in order
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        description: "move prefixed keys into per-kind column families",
+        run: migrate_v0_split_column_families,
+    },
+    Migration {
+        from: 1,
+        description: "add signed-blocks window to stored validator stakes",
+        run: migrate_v1_validator_signed_window,
+    },
+    Migration {
+        from: 2,
+        description: "index key rotations from stored blocks",
+        run: migrate_v2_index_key_rotations,
+    },
+    Migration {
+        from: 3,
+        description: "index stored transactions by block height for pruning",
+        run: migrate_v3_index_tx_heights,
+    },
+];
+
+fn read_schema_version(db: &DB) -> Result<u32> {
+    match db.get_cf(cf_handle(db, CF_META), KEY_SCHEMA_VERSION.as_bytes())? {
+        Some(data) => {
+            let bytes: [u8; 4] = data.as_slice().try_into()
+                .context("Invalid schema version data")?;
+            Ok(u32::from_le_bytes(bytes))
+        }
+        None => Ok(0),
+    }
+}
+
+fn write_schema_version(db: &DB, version: u32) -> Result<()> {
+    db.put_cf(cf_handle(db, CF_META), KEY_SCHEMA_VERSION.as_bytes(), version.to_le_bytes())?;
+    Ok(())
+}
+
+/// Bring the data directory up to `SCHEMA_VERSION`
+fn migrate(db: &DB) -> Result<u32> {
+    migrate_with(db, MIGRATIONS, SCHEMA_VERSION)
+}
+
+fn migrate_with(db: &DB, migrations: &[Migration], target: u32) -> Result<u32> {
+    let mut version = read_schema_version(db)?;
+    if version > target {
+        anyhow::bail!(
+            "Data directory uses storage schema v{} but this node only supports up to v{}; upgrade sultan-node",
+            version, target
+        );
+    }
+    
+    // A brand new data directory starts at the current schema
+    let is_empty = db.iterator(IteratorMode::Start).next().is_none()
+        && COLUMN_FAMILIES.iter()
+            .all(|name| db.iterator_cf(cf_handle(db, name), IteratorMode::Start).next().is_none());
+    if version == 0 && is_empty {
+        write_schema_version(db, target)?;
+        return Ok(target);
+    }
+    
+    while version < target {
+        let migration = migrations.iter()
+            .find(|m| m.from == version)
+            .with_context(|| format!("No storage migration from schema v{}", version))?;
+        warn!("🔧 Migrating storage schema v{} → v{}: {}", version, version + 1, migration.description);
+        (migration.run)(db)
+            .with_context(|| format!("Storage migration v{} → v{} failed", version, version + 1))?;
+        version += 1;
+        write_schema_version(db, version)?;
+        info!("✅ Storage schema is now v{}", version);
+    }
+    Ok(version)
+}
+
+/// Key rotation index entries for `block`, in the order the block carries them
+///
+/// Keys sort by height, so iterating the prefix yields rotations in chain order.
+fn key_rotation_index(block: &Block) -> Result<Vec<(String, Vec<u8>)>> {
+    block.transactions.iter()
+        .enumerate()
+        .filter(|(_, tx)| KeyRotation::from_transaction(tx).is_some())
+        .map(|(i, tx)| {
+            let key = format!("{}{:020}:{:06}", PREFIX_KEY_ROTATION, block.index, i);
+            Ok((key, bincode::serialize(&(block.index, tx))?))
+        })
+        .collect()
+}
+
+/// Column family for a key written by schema v0 (single keyspace)
+fn legacy_key_family(key: &[u8]) -> Option<&'static str> {
+    const ROUTES: &[(&str, &str)] = &[
+        (PREFIX_BLOCK, CF_BLOCKS),
+        (PREFIX_HEIGHT, CF_BLOCKS),
+        (PREFIX_HEADER, CF_BLOCKS),
+        (PREFIX_FINALITY, CF_BLOCKS),
+        ("latest", CF_BLOCKS),
+        (PREFIX_TX, CF_TRANSACTIONS),
+        (PREFIX_TX_INDEX, CF_TRANSACTIONS),
+        (PREFIX_TX_HEIGHT, CF_TRANSACTIONS),
+        (PREFIX_WALLET, CF_WALLETS),
+        (PREFIX_SLASH, CF_STAKING),
+        ("staking:", CF_STAKING),
+        ("gov:", CF_GOVERNANCE),
+        ("meta:", CF_META),
+        ("state:", CF_META),
+    ];
+    ROUTES.iter()
+        .find(|(prefix, _)| key.starts_with(prefix.as_bytes()))
+        .map(|(_, family)| *family)
+}
+
+/// v0 → v1: move every key out of the default column family into the
+/// column family for its data kind (keys and values are unchanged)
+fn migrate_v0_split_column_families(db: &DB) -> Result<()> {
+    let mut moved = 0u64;
+    let mut batch = WriteBatch::default();
+    for item in db.iterator(IteratorMode::Start) {
+        let (key, value) = item?;
+        let Some(family) = legacy_key_family(&key) else {
+            warn!("⚠️ Leaving unrecognized key '{}' in default column family", String::from_utf8_lossy(&key));
+            continue;
+        };
+        batch.put_cf(cf_handle(db, family), &key, &value);
+        batch.delete(&key);
+        moved += 1;
+        if batch.len() >= MIGRATION_BATCH_SIZE {
+            db.write(std::mem::take(&mut batch))?;
+        }
+    }
+    db.write(batch)?;
+    info!("📦 Moved {} keys into column families", moved);
+    Ok(())
+}
+
+/// `ValidatorStake` as stored by schema v1 (no signed-blocks window)
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ValidatorStakeV1 {
+    validator_address: String,
+    reward_wallet: Option<String>,
+    self_stake: u64,
+    delegated_stake: u64,
+    total_stake: u64,
+    commission_rate: f64,
+    rewards_accumulated: u64,
+    blocks_signed: u64,
+    blocks_missed: u64,
+    total_blocks_missed: u64,
+    jailed: bool,
+    jailed_until: u64,
+    created_at: u64,
+    last_reward_height: u64,
+}
+
+/// `StakingStateSnapshot` as stored by schema v1
+#[derive(serde::Serialize, serde::Deserialize)]
+struct StakingStateSnapshotV1 {
+    validators: std::collections::HashMap<String, ValidatorStakeV1>,
+    delegations: std::collections::HashMap<String, Vec<crate::staking::Delegation>>,
+    unbonding_queue: Vec<crate::staking::UnbondingEntry>,
+    total_staked: u64,
+    current_height: u64,
+    snapshot_time: u64,
+}
+
+/// v1 → v2: re-encode the staking snapshot with an empty signed-blocks window
+/// per validator
+///
+/// The bincode layouts can't be told apart, so the new snapshot and the schema
+/// version are written in one batch and the step never sees its own output.
+fn migrate_v1_validator_signed_window(db: &DB) -> Result<()> {
+    let cf = cf_handle(db, CF_STAKING);
+    let Some(data) = db.get_cf(cf, b"staking:state")? else {
+        return Ok(());
+    };
+    let old: StakingStateSnapshotV1 = bincode::deserialize(&data)
+        .context("Failed to decode v1 staking state")?;
+    let validators = old.validators.into_iter()
+        .map(|(address, v)| (address, crate::staking::ValidatorStake {
+            validator_address: v.validator_address,
+            reward_wallet: v.reward_wallet,
+            self_stake: v.self_stake,
+            delegated_stake: v.delegated_stake,
+            total_stake: v.total_stake,
+            commission_rate: v.commission_rate,
+            rewards_accumulated: v.rewards_accumulated,
+            blocks_signed: v.blocks_signed,
+            blocks_missed: v.blocks_missed,
+            total_blocks_missed: v.total_blocks_missed,
+            signed_window: Default::default(),
+            jailed: v.jailed,
+            jailed_until: v.jailed_until,
+            created_at: v.created_at,
+            last_reward_height: v.last_reward_height,
+        }))
+        .collect::<std::collections::HashMap<_, _>>();
+    let count = validators.len();
+    let new = StakingStateSnapshot {
+        validators,
+        delegations: old.delegations,
+        unbonding_queue: old.unbonding_queue,
+        total_staked: old.total_staked,
+        current_height: old.current_height,
+        snapshot_time: old.snapshot_time,
+    };
+
+    let mut batch = WriteBatch::default();
+    batch.put_cf(cf, b"staking:state", bincode::serialize(&new)?);
+    batch.put_cf(cf_handle(db, CF_META), KEY_SCHEMA_VERSION.as_bytes(), 2u32.to_le_bytes());
+    db.write(batch)?;
+    info!("📦 Re-encoded staking state for {} validators", count);
+    Ok(())
+}
+
+/// v2 → v3: index the key rotations in every stored block body
+///
+/// Rotations in bodies already pruned can't be recovered here; the node logs
+/// how many heights it couldn't read so an operator can resync from an archive
+/// node if a validator rotated in that range.
+fn migrate_v2_index_key_rotations(db: &DB) -> Result<()> {
+    let blocks = cf_handle(db, CF_BLOCKS);
+    let mut indexed = 0u64;
+    let mut missing = 0u64;
+    let mut batch = WriteBatch::default();
+    for item in db.prefix_iterator_cf(blocks, PREFIX_HEIGHT.as_bytes()) {
+        let (key, hash) = item?;
+        if !key.starts_with(PREFIX_HEIGHT.as_bytes()) {
+            break;
+        }
+        let block_key = [PREFIX_BLOCK.as_bytes(), &hash[..]].concat();
+        let Some(data) = db.get_cf(blocks, block_key)? else {
+            missing += 1;
+            continue;
+        };
+        let block: Block = bincode::deserialize(&data)
+            .context("Failed to decode stored block")?;
+        for (key, value) in key_rotation_index(&block)? {
+            batch.put_cf(cf_handle(db, CF_STAKING), key.as_bytes(), value);
+            indexed += 1;
+        }
+        if batch.len() >= MIGRATION_BATCH_SIZE {
+            db.write(std::mem::take(&mut batch))?;
+        }
+    }
+    db.write(batch)?;
+    if missing > 0 {
+        warn!("⚠️ {} block bodies are pruned; key rotations in them are not indexed", missing);
+    }
+    info!("📦 Indexed {} key rotations", indexed);
+    Ok(())
+}
+
+/// v3 → v4: add the `txheight:` entry for every transaction stored before the
+/// index existed, so the pruner can remove them with their block bodies
+///
+/// Each stored transaction records its block height and addresses, which is
+/// all the index holds; rebuilding from the records rather than the block
+/// bodies also covers transactions whose block was pruned before the upgrade.
+fn migrate_v3_index_tx_heights(db: &DB) -> Result<()> {
+    let cf = cf_handle(db, CF_TRANSACTIONS);
+    let mut indexed = 0u64;
+    let mut batch = WriteBatch::default();
+    for item in db.prefix_iterator_cf(cf, PREFIX_TX.as_bytes()) {
+        let (key, value) = item?;
+        if !key.starts_with(PREFIX_TX.as_bytes()) {
+            break;
+        }
+        let tx: crate::sharded_blockchain_production::ConfirmedTransaction = serde_json::from_slice(&value)
+            .context("Failed to decode stored transaction")?;
+        let height_key = format!("{}{}:{}", PREFIX_TX_HEIGHT, tx.block_height, tx.hash);
+        batch.put_cf(cf, height_key.as_bytes(), bincode::serialize(&(&tx.from, &tx.to))?);
+        indexed += 1;
+        if batch.len() >= MIGRATION_BATCH_SIZE {
+            db.write(std::mem::take(&mut batch))?;
+        }
+    }
+    db.write(batch)?;
+    info!("📦 Indexed {} transactions by block height", indexed);
+    Ok(())
+}
+
+impl Clone for PersistentStorage {
+    fn clone(&self) -> Self {
+        Self {
+            db: Arc::clone(&self.db),
+            block_cache: parking_lot::Mutex::new(LruCache::new(NonZeroUsize::new(1000).unwrap())),
+            last_compaction_height: AtomicU64::new(self.last_compaction_height.load(Ordering::Relaxed)),
+            encryption: self.encryption.clone(),
+            pruning: self.pruning,
+            pruner: self.pruner.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    
+    #[test]
+    fn test_storage_persistence() {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        
+        // Create and save block
+        let block = Block {
+            index: 1,
+            hash: "test_hash".to_string(),
+            prev_hash: "genesis".to_string(),
+            timestamp: 1234567890,
+            transactions: vec![],
+            nonce: 0,
+            validator: "test".to_string(),
+            state_root: "root".to_string(),
+        };
+        
+        storage.save_block(&block).unwrap();
+        
+        // Retrieve block
+        let retrieved = storage.get_block("test_hash").unwrap().unwrap();
+        assert_eq!(retrieved.hash, "test_hash");
+        assert_eq!(retrieved.index, 1);
+    }
+    
+    #[test]
+    fn test_wallet_operations() {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        
+        // Save wallet
+        storage.save_wallet("sultan1abc123", 1000000).unwrap();
+        
+        // Retrieve wallet
+        let balance = storage.get_wallet("sultan1abc123").unwrap().unwrap();
+        assert_eq!(balance, 1000000);
+        
+        // Update wallet
+        storage.save_wallet("sultan1abc123", 500000).unwrap();
+        let updated = storage.get_wallet("sultan1abc123").unwrap().unwrap();
+        assert_eq!(updated, 500000);
+    }
+    
+    #[test]
+    fn test_check_writable_leaves_no_probe() {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        
+        storage.check_writable().unwrap();
+        assert!(storage.db.get_cf(storage.cf(CF_META), KEY_HEALTH_PROBE.as_bytes()).unwrap().is_none());
+    }
+    
+    #[test]
+    fn test_batch_wallet_update() {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        
+        let updates = vec![
+            ("sultan1aaa".to_string(), 1000),
+            ("sultan1bbb".to_string(), 2000),
+            ("sultan1ccc".to_string(), 3000),
+        ];
+        
+        storage.batch_update_wallets(updates).unwrap();
+        
+        assert_eq!(storage.get_wallet("sultan1aaa").unwrap().unwrap(), 1000);
+        assert_eq!(storage.get_wallet("sultan1bbb").unwrap().unwrap(), 2000);
+        assert_eq!(storage.get_wallet("sultan1ccc").unwrap().unwrap(), 3000);
+    }
+    
+    #[test]
+    fn test_height_index() {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        
+        for i in 1..=10 {
+            let block = Block {
+                index: i,
+                hash: format!("hash_{}", i),
+                prev_hash: if i == 1 { "genesis".to_string() } else { format!("hash_{}", i - 1) },
+                timestamp: 1234567890 + i,
+                transactions: vec![],
+                nonce: 0,
+                validator: "test".to_string(),
+                state_root: "root".to_string(),
+            };
+            
+            storage.save_block(&block).unwrap();
+        }
+        
+        // Query by height
+        let block_5 = storage.get_block_by_height(5).unwrap().unwrap();
+        assert_eq!(block_5.index, 5);
+        assert_eq!(block_5.hash, "hash_5");
+        
+        // Get latest
+        let latest = storage.get_latest_block().unwrap().unwrap();
+        assert_eq!(latest.index, 10);
+    }
+    
+    #[test]
+    fn test_staking_state_persistence() {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        
+        // Create staking state snapshot
+        let mut validators = std::collections::HashMap::new();
+        validators.insert("validator1".to_string(), crate::staking::ValidatorStake {
+            validator_address: "validator1".to_string(),
+            self_stake: 10_000_000_000_000,
+            delegated_stake: 5_000_000_000_000,
+            total_stake: 15_000_000_000_000,
+            commission_rate: 0.10,
+            rewards_accumulated: 100_000_000,
+            blocks_signed: 1000,
+            blocks_missed: 5,
+            total_blocks_missed: 10,
+            signed_window: Default::default(),
+            jailed: false,
+            jailed_until: 0,
+            created_at: 1700000000,
+            last_reward_height: 5000,
+            reward_wallet: None,
+        });
+        
+        let snapshot = StakingStateSnapshot {
+            validators,
+            delegations: std::collections::HashMap::new(),
+            unbonding_queue: Vec::new(),
+            total_staked: 15_000_000_000_000,
+            current_height: 5000,
+            snapshot_time: 1700000000,
+        };
+        
+        // Save and reload
+        storage.save_staking_state(&snapshot).unwrap();
+        let loaded = storage.load_staking_state().unwrap().unwrap();
+        
+        assert_eq!(loaded.total_staked, 15_000_000_000_000);
+        assert_eq!(loaded.current_height, 5000);
+        assert!(loaded.validators.contains_key("validator1"));
+    }
+    
+    #[test]
+    fn test_slashing_event_persistence() {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        
+        // Create slashing events
+        let event1 = crate::staking::SlashingEvent {
+            validator_address: "validator1".to_string(),
+            height: 1000,
+            timestamp: 1700000000,
+            reason: crate::staking::SlashReason::Downtime,
+            amount_slashed: 100_000_000_000,
+            jail_duration: 3600,
+        };
+        
+        let event2 = crate::staking::SlashingEvent {
+            validator_address: "validator1".to_string(),
+            height: 2000,
+            timestamp: 1700001000,
+            reason: crate::staking::SlashReason::DoubleSign,
+            amount_slashed: 500_000_000_000,
+            jail_duration: 10000,
+        };
+        
+        let event3 = crate::staking::SlashingEvent {
+            validator_address: "validator2".to_string(),
+            height: 1500,
+            timestamp: 1700000500,
+            reason: crate::staking::SlashReason::Downtime,
+            amount_slashed: 50_000_000_000,
+            jail_duration: 3600,
+        };
+        
+        storage.append_slashing_event(&event1).unwrap();
+        storage.append_slashing_event(&event2).unwrap();
+        storage.append_slashing_event(&event3).unwrap();
+        
+        // Get history for validator1
+        let history = storage.get_slashing_history("validator1").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].height, 1000); // Sorted by height
+        assert_eq!(history[1].height, 2000);
+        
+        // Get all events
+        let all_events = storage.get_all_slashing_events().unwrap();
+        assert_eq!(all_events.len(), 3);
+    }
+    
+    #[test]
+    fn test_governance_state_persistence() {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        
+        // Create governance state
+        let mut last_proposal_by_address = std::collections::HashMap::new();
+        last_proposal_by_address.insert("proposer1".to_string(), 1000u64);
+        
+        let state = GovernanceStateSnapshot {
+            next_proposal_id: 5,
+            current_height: 10000,
+            total_bonded_tokens: 1_000_000_000_000_000,
+            last_proposal_by_address,
+            snapshot_time: 1700000000,
+        };
+        
+        // Save and reload
+        storage.save_governance_state(&state).unwrap();
+        let loaded = storage.load_governance_state().unwrap().unwrap();
+        
+        assert_eq!(loaded.next_proposal_id, 5);
+        assert_eq!(loaded.current_height, 10000);
+        assert_eq!(loaded.total_bonded_tokens, 1_000_000_000_000_000);
+        assert_eq!(*loaded.last_proposal_by_address.get("proposer1").unwrap(), 1000);
+    }
+    
+    #[test]
+    fn test_compaction() {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        
+        // Manual compaction should not fail
+        storage.compact().unwrap();
+        
+        // Stats should work
+        let stats = storage.stats().unwrap();
+        assert!(stats.contains("RocksDB Stats"));
+        let families = storage.column_family_stats().unwrap();
+        assert_eq!(families.len(), COLUMN_FAMILIES.len());
+        assert!(families.iter().any(|f| f.name == CF_BLOCKS));
+    }
+    
+    #[test]
+    fn test_checkpoint() {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        
+        // Save some data
+        storage.save_wallet("test", 1000).unwrap();
+        
+        // Checkpoint should flush to disk
+        storage.checkpoint().unwrap();
+        
+        // Data should still be accessible
+        assert_eq!(storage.get_wallet("test").unwrap().unwrap(), 1000);
+    }
+    
+    #[test]
+    fn test_encrypted_wallet_storage() {
+        let dir = tempdir().unwrap();
+        let encryption_key = b"super_secret_key_for_testing_123";
+        let storage = PersistentStorage::with_encryption(
+            dir.path().to_str().unwrap(), 
+            Some(encryption_key)
+        ).unwrap();
+        
+        assert!(storage.is_encrypted());
+        
+        // Save encrypted wallet
+        storage.save_wallet("encrypted_addr", 999_999_999).unwrap();
+        
+        // Retrieve and verify decryption works
+        let balance = storage.get_wallet("encrypted_addr").unwrap().unwrap();
+        assert_eq!(balance, 999_999_999);
+        
+        // Batch update with encryption
+        storage.batch_update_wallets(vec![
+            ("enc1".to_string(), 100),
+            ("enc2".to_string(), 200),
+        ]).unwrap();
+        
+        assert_eq!(storage.get_wallet("enc1").unwrap().unwrap(), 100);
+        assert_eq!(storage.get_wallet("enc2").unwrap().unwrap(), 200);
+    }
+    
+    #[test]
+    fn test_encryption_helper() {
+        let enc = StorageEncryption::new(b"test_key");
+        
+        let original = b"sensitive data 12345";
+        let encrypted = enc.encrypt(original);
+        
+        // Encrypted should differ from original
+        assert_ne!(encrypted, original.to_vec());
+        
+        // Decryption should restore original
+        let decrypted = enc.decrypt(&encrypted);
+        assert_eq!(decrypted, original.to_vec());
+    }
+    
+    #[test]
+    fn test_proposal_persistence() {
+        use crate::governance::{Proposal, ProposalStatus, ProposalType};
+        
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        
+        // Create a proposal
+        let proposal = Proposal {
+            id: 1,
+            proposer: "sultan1prpsr2qqqqqqqqqqqqqqqqqqqqqqqqqqqqprpsaa".to_string(),
+            title: "Test Proposal".to_string(),
+            description: "A test governance proposal".to_string(),
+            proposal_type: ProposalType::ParameterChange,
+            status: ProposalStatus::VotingPeriod,
+            submit_height: 1000,
+            submit_time: 1700000000,
+            deposit_end_height: 2000,
+            discussion_end_height: 87400,
+            voting_start_height: 87400,
+            voting_end_height: 389800,
+            total_deposit: 1_000_000_000_000,
+            depositors: vec![],
+            final_tally: None,
+            parameters: Some([("inflation_rate".to_string(), "0.05".to_string())].into()),
+            voting_power_snapshot: None,
+            telegram_discussion_url: Some("https://t.me/test".to_string()),
+            discord_discussion_url: None,
+            validator_signatures: vec![],
+            emergency_pause_votes: vec![],
+        };
+        
+        // Save and reload
+        storage.save_proposal(&proposal).unwrap();
+        let loaded = storage.load_proposal(1).unwrap().unwrap();
+        
+        assert_eq!(loaded.id, 1);
+        assert_eq!(loaded.title, "Test Proposal");
+        assert_eq!(loaded.proposer, proposal.proposer);
+        assert_eq!(loaded.status, ProposalStatus::VotingPeriod);
+        assert!(loaded.parameters.unwrap().contains_key("inflation_rate"));
+    }
+    
+    #[test]
+    fn test_proposal_votes_persistence() {
+        use crate::governance::{Vote, VoteOption};
+        
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        
+        let votes = vec![
+            Vote {
+                proposal_id: 1,
+                voter: "sultan1vtr2qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqvtraa".to_string(),
+                option: VoteOption::Yes,
+                voting_power: 5_000_000_000_000,
+                time: 1700000000,
+            },
+            Vote {
+                proposal_id: 1,
+                voter: "sultan1vtr3qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqvtrcc".to_string(),
+                option: VoteOption::No,
+                voting_power: 2_000_000_000_000,
+                time: 1700001000,
+            },
+        ];
+        
+        // Save and reload
+        storage.save_proposal_votes(1, &votes).unwrap();
+        let loaded = storage.load_proposal_votes(1).unwrap();
+        
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].option, VoteOption::Yes);
+        assert_eq!(loaded[1].option, VoteOption::No);
+        assert_eq!(loaded[0].voting_power, 5_000_000_000_000);
+    }
+    
+    #[test]
+    fn test_load_all_proposals() {
+        use crate::governance::{Proposal, ProposalStatus, ProposalType};
+        
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        
+        // Create multiple proposals
+        for i in 1..=5 {
+            let proposal = Proposal {
+                id: i,
+                proposer: format!("sultan1prp{}qqqqqqqqqqqqqqqqqqqqqqqqqqqqqprp{:02}", i, i),
+                title: format!("Proposal #{}", i),
+                description: format!("Description for proposal {}", i),
+                proposal_type: ProposalType::TextProposal,
+                status: ProposalStatus::VotingPeriod,
+                submit_height: i * 1000,
+                submit_time: 1700000000 + i,
+                deposit_end_height: i * 1000 + 1000,
+                discussion_end_height: 87400,
+                voting_start_height: 87400,
+                voting_end_height: 389800,
+                total_deposit: 1_000_000_000_000,
+                depositors: vec![],
+                final_tally: None,
+                parameters: None,
+                voting_power_snapshot: None,
+                telegram_discussion_url: Some("https://t.me/test".to_string()),
+                discord_discussion_url: None,
+                validator_signatures: vec![],
+                emergency_pause_votes: vec![],
+            };
+            storage.save_proposal(&proposal).unwrap();
+        }
+        
+        // Load all
+        let all = storage.load_all_proposals().unwrap();
+        assert_eq!(all.len(), 5);
+        
+        // Should be sorted by ID descending (newest first)
+        assert_eq!(all[0].id, 5);
+        assert_eq!(all[4].id, 1);
+    }
+    
+    /// Save blocks 1..=count, each with one alice -> bob transfer
+    fn save_chain_with_txs(storage: &PersistentStorage, count: u64) {
+        for i in 1..=count {
+            let tx = crate::blockchain::Transaction {
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                amount: 10,
+                gas_fee: 0,
+                timestamp: 1234567890 + i,
+                nonce: i - 1,
+                signature: None,
+                public_key: None,
+                memo: None,
+            };
+            let block = Block {
+                index: i,
+                hash: format!("hash_{}", i),
+                prev_hash: if i == 1 { "genesis".to_string() } else { format!("hash_{}", i - 1) },
+                timestamp: 1234567890 + i,
+                transactions: vec![tx],
+                nonce: 0,
+                validator: "test".to_string(),
+                state_root: "root".to_string(),
+            };
+            storage.save_block(&block).unwrap();
+            storage.save_transaction(&crate::sharded_blockchain_production::ConfirmedTransaction {
+                hash: format!("tx_{}", i),
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                amount: 10,
+                memo: None,
+                nonce: i - 1,
+                timestamp: 1234567890 + i,
+                block_height: i,
+                status: "confirmed".to_string(),
+            }).unwrap();
+        }
+    }
+    
+    fn account_snapshot(height: u64) -> AccountStateSnapshot {
+        AccountStateSnapshot {
+            height,
+            block_hash: format!("hash_{}", height),
+            accounts: vec![("alice".to_string(), 800, height), ("bob".to_string(), 200, 0)],
+            snapshot_time: 1700000000,
+        }
+    }
+    
+    #[test]
+    fn test_pruning_mode_from_args() {
+        assert_eq!(PruningMode::from_args("archive", None, None).unwrap(), PruningMode::Archive);
+        assert_eq!(PruningMode::from_args("default", None, None).unwrap(), PruningMode::Default);
+        assert_eq!(
+            PruningMode::from_args("custom", Some(5_000), Some(100)).unwrap(),
+            PruningMode::Custom { keep_recent: 5_000, keep_every: 100 }
+        );
+        
+        // Custom needs keep_recent, and it can't be too small for block sync
+        assert!(PruningMode::from_args("custom", None, Some(100)).is_err());
+        assert!(PruningMode::from_args("custom", Some(10), None).is_err());
+        // Custom parameters only apply to custom mode
+        assert!(PruningMode::from_args("archive", Some(5_000), None).is_err());
+        assert!(PruningMode::from_args("everything", None, None).is_err());
+    }
+    
+    #[test]
+    fn test_pruning_removes_bodies_and_keeps_headers() {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::with_pruning(
+            dir.path().to_str().unwrap(),
+            PruningMode::Custom { keep_recent: 10, keep_every: 10 },
+        ).unwrap();
+        save_chain_with_txs(&storage, 30);
+        
+        // Nothing can be pruned before an account snapshot exists
+        assert_eq!(storage.prune_to(20).unwrap(), 0);
+        assert!(storage.pruned_ranges().is_empty());
+        
+        storage.save_account_snapshot(&account_snapshot(15)).unwrap();
+        // Capped at the snapshot height; height 10 is kept by keep_every
+        assert_eq!(storage.prune_to(20).unwrap(), 14);
+        
+        assert!(storage.is_pruned(5));
+        assert!(!storage.is_pruned(10));
+        assert!(!storage.is_pruned(16));
+        assert!(storage.get_block_by_height(5).unwrap().is_none());
+        assert!(storage.get_block_by_height(10).unwrap().is_some());
+        assert!(storage.get_block_by_height(16).unwrap().is_some());
+        
+        // Header survives with the original tx count
+        let header = storage.get_block_header(5).unwrap().unwrap();
+        assert_eq!(header.hash, "hash_5");
+        assert_eq!(header.tx_count, 1);
+        
+        // Transactions and their index entries are gone for pruned heights
+        assert!(storage.get_transaction("tx_5").unwrap().is_none());
+        assert!(storage.get_transaction("tx_10").unwrap().is_some());
+        let history = storage.get_transaction_history("bob", 100).unwrap();
+        assert_eq!(history.len(), 16); // heights 10 and 16..=30
+        
+        assert_eq!(
+            storage.pruned_ranges(),
+            vec![PrunedRange { start: 1, end: 15, keep_every: 10 }]
+        );
+        let stats = storage.stats().unwrap();
+        assert!(stats.contains("Pruned ranges: 1-15 (every 10th kept)"));
+        assert!(storage.pruned_error(5).to_string().contains("pruned"));
+    }
+    
+    #[test]
+    fn test_block_events_query_and_pruning() {
+        use crate::events::ModuleEvent;
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::with_pruning(
+            dir.path().to_str().unwrap(),
+            PruningMode::Custom { keep_recent: 10, keep_every: 0 },
+        ).unwrap();
+        save_chain_with_txs(&storage, 30);
+        let delegated = |height: u64, delegator: &str| StoredEvent {
+            height,
+            index: 0,
+            tx_hash: Some(format!("stake_{}", height)),
+            module: "staking".to_string(),
+            event: ModuleEvent::Delegated {
+                delegator: delegator.to_string(),
+                validator: "validator1".to_string(),
+                amount: height,
+            },
+        };
+        for height in [2, 5, 9, 12, 20] {
+            let delegator = if height % 2 == 0 { "alice" } else { "bob" };
+            storage.save_block_events(height, &[delegated(height, delegator)]).unwrap();
+        }
+        storage.save_block_events(3, &[]).unwrap();
+
+        assert_eq!(storage.get_block_events(5).unwrap(), vec![delegated(5, "bob")]);
+        assert!(storage.get_block_events(3).unwrap().is_empty());
+        assert_eq!(storage.get_tx_events("stake_9").unwrap(), vec![delegated(9, "bob")]);
+
+        // Heights sort numerically and the page stops at a block boundary
+        let all = EventFilter::default();
+        let page = storage.query_events(&all, 3, 2).unwrap();
+        assert_eq!(page.events.iter().map(|e| e.height).collect::<Vec<_>>(), vec![5, 9]);
+        assert_eq!(page.next_height, Some(12));
+        let rest = storage.query_events(&all, 12, 100).unwrap();
+        assert_eq!(rest.events.len(), 2);
+        assert_eq!(rest.next_height, None);
+
+        let alice = EventFilter {
+            attribute: EventFilter::parse_attribute("delegator:alice"),
+            ..Default::default()
+        };
+        let page = storage.query_events(&alice, 0, 100).unwrap();
+        assert_eq!(page.events.iter().map(|e| e.height).collect::<Vec<_>>(), vec![2, 12, 20]);
+        let by_tx = EventFilter { tx_hash: Some("stake_12".to_string()), ..Default::default() };
+        assert_eq!(storage.query_events(&by_tx, 0, 100).unwrap().events, vec![delegated(12, "alice")]);
+
+        // Events go with the block bodies they belong to
+        storage.save_account_snapshot(&account_snapshot(10)).unwrap();
+        storage.prune_to(10).unwrap();
+        assert!(storage.get_block_events(5).unwrap().is_empty());
+        assert!(storage.get_tx_events("stake_5").unwrap().is_empty());
+        let page = storage.query_events(&all, 0, 100).unwrap();
+        assert_eq!(page.events.iter().map(|e| e.height).collect::<Vec<_>>(), vec![12, 20]);
+    }
+    
+    #[test]
+    fn test_pruned_ranges_persist_across_restart() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        let mode = PruningMode::Custom { keep_recent: 10, keep_every: 0 };
+        {
+            let storage = PersistentStorage::with_pruning(path, mode).unwrap();
+            save_chain_with_txs(&storage, 30);
+            storage.save_account_snapshot(&account_snapshot(10)).unwrap();
+            storage.prune_to(20).unwrap();
+            
+            // A later pass extends the same range
+            storage.save_account_snapshot(&account_snapshot(20)).unwrap();
+            storage.prune_to(20).unwrap();
+        }
+        
+        let storage = PersistentStorage::with_pruning(path, mode).unwrap();
+        assert_eq!(
+            storage.pruned_ranges(),
+            vec![PrunedRange { start: 1, end: 20, keep_every: 0 }]
+        );
+        assert!(storage.is_pruned(20));
+        assert_eq!(storage.load_account_snapshot().unwrap().unwrap().height, 20);
+    }
+    
+    #[test]
+    fn test_finality_proof_survives_pruning() {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::with_pruning(
+            dir.path().to_str().unwrap(),
+            PruningMode::Custom { keep_recent: 10, keep_every: 0 },
+        ).unwrap();
+        save_chain_with_txs(&storage, 20);
+        
+        let mut signatures = std::collections::BTreeMap::new();
+        signatures.insert("validator1".to_string(), vec![7u8; 64]);
+        let proof = FinalityProof { height: 3, block_hash: [3u8; 32], signatures, voting_power: 100 };
+        storage.save_finality_proof(&proof).unwrap();
+        
+        storage.save_account_snapshot(&account_snapshot(10)).unwrap();
+        storage.prune_to(10).unwrap();
+        
+        assert!(storage.is_pruned(3));
+        assert_eq!(storage.get_finality_proof(3).unwrap().unwrap(), proof);
+    }
+    
+    #[test]
+    fn test_archive_mode_never_prunes() {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        save_chain_with_txs(&storage, 5);
+        storage.save_account_snapshot(&account_snapshot(5)).unwrap();
+        
+        assert!(storage.prune_to(5).is_err());
+        assert!(!storage.account_snapshot_due(ACCOUNT_SNAPSHOT_INTERVAL_BLOCKS));
+        assert!(storage.get_block_by_height(1).unwrap().is_some());
+        assert!(storage.stats().unwrap().contains("Pruning: archive"));
+    }
+    
+    #[test]
+    fn test_fresh_database_starts_at_current_schema() {
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        
+        assert_eq!(storage.schema_version().unwrap(), SCHEMA_VERSION);
+        let stats = storage.stats().unwrap();
+        assert!(stats.contains(&format!("Schema version: {}", SCHEMA_VERSION)));
+        assert!(stats.contains("blocks ~"));
+    }
+    
+    #[test]
+    fn test_migrates_legacy_single_keyspace() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        
+        // Schema v0: everything in the default column family, no version key
+        let block = Block {
+            index: 1,
+            hash: "legacy_hash".to_string(),
+            prev_hash: "genesis".to_string(),
+            timestamp: 1234567890,
+            transactions: vec![],
+            nonce: 0,
+            validator: "test".to_string(),
+            state_root: "root".to_string(),
+        };
+        {
+            let mut opts = Options::default();
+            opts.create_if_missing(true);
+            let db = DB::open(&opts, path).unwrap();
+            db.put(b"block:legacy_hash", bincode::serialize(&block).unwrap()).unwrap();
+            db.put(b"height:1", b"legacy_hash").unwrap();
+            db.put(b"latest", b"legacy_hash").unwrap();
+            db.put(b"wallet:alice", 42i64.to_le_bytes()).unwrap();
+            db.put(b"gov:state", serde_json::to_vec(&GovernanceStateSnapshot {
+                next_proposal_id: 7,
+                current_height: 1,
+                total_bonded_tokens: 0,
+                last_proposal_by_address: std::collections::HashMap::new(),
+                snapshot_time: 0,
+            }).unwrap()).unwrap();
+            db.put(b"unknown:key", b"kept").unwrap();
+        }
+        
+        let storage = PersistentStorage::new(path).unwrap();
+        assert_eq!(storage.schema_version().unwrap(), SCHEMA_VERSION);
+        
+        // Data is readable through the normal API after the move
+        assert_eq!(storage.get_block_by_height(1).unwrap().unwrap().hash, "legacy_hash");
+        assert_eq!(storage.get_latest_block().unwrap().unwrap().index, 1);
+        assert_eq!(storage.get_wallet("alice").unwrap().unwrap(), 42);
+        assert_eq!(storage.load_governance_state().unwrap().unwrap().next_proposal_id, 7);
+        
+        // Only unrecognized keys remain in the default column family
+        let leftover: Vec<_> = storage.db.iterator(IteratorMode::Start)
+            .map(|item| item.unwrap().0.to_vec())
+            .collect();
+        assert_eq!(leftover, vec![b"unknown:key".to_vec()]);
+        
+        // Reopening does not migrate again
+        drop(storage);
+        let storage = PersistentStorage::new(path).unwrap();
+        assert_eq!(storage.get_wallet("alice").unwrap().unwrap(), 42);
+    }
+    
+    #[test]
+    fn test_migrates_v1_staking_state() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        
+        // Schema v1: validator stakes without a signed-blocks window
+        let mut validators = std::collections::HashMap::new();
+        validators.insert("validator1".to_string(), ValidatorStakeV1 {
+            validator_address: "validator1".to_string(),
+            reward_wallet: Some("sultan1wallet".to_string()),
+            self_stake: 10_000_000_000_000,
+            delegated_stake: 0,
+            total_stake: 10_000_000_000_000,
+            commission_rate: 0.05,
+            rewards_accumulated: 7,
+            blocks_signed: 1000,
+            blocks_missed: 2,
+            total_blocks_missed: 9,
+            jailed: false,
+            jailed_until: 0,
+            created_at: 1700000000,
+            last_reward_height: 5000,
+        });
+        let legacy = StakingStateSnapshotV1 {
+            validators,
+            delegations: std::collections::HashMap::new(),
+            unbonding_queue: Vec::new(),
+            total_staked: 10_000_000_000_000,
+            current_height: 5000,
+            snapshot_time: 1700000000,
+        };
+        {
+            let storage = PersistentStorage::new(path).unwrap();
+            storage.db.put_cf(storage.cf(CF_STAKING), b"staking:state", bincode::serialize(&legacy).unwrap()).unwrap();
+            write_schema_version(&storage.db, 1).unwrap();
+        }
+        
+        let storage = PersistentStorage::new(path).unwrap();
+        assert_eq!(storage.schema_version().unwrap(), SCHEMA_VERSION);
+        let state = storage.load_staking_state().unwrap().unwrap();
+        let v = &state.validators["validator1"];
+        assert_eq!(v.reward_wallet.as_deref(), Some("sultan1wallet"));
+        assert_eq!((v.blocks_signed, v.total_blocks_missed, v.last_reward_height), (1000, 9, 5000));
+        assert_eq!(v.signed_window.duties(), 0);
+        assert_eq!(state.current_height, 5000);
+    }
+    
+    #[test]
+    fn test_key_rotations_indexed_and_migrated_from_v2() {
+        use ed25519_dalek::SigningKey;
+        
+        let dir = tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        let rotation = KeyRotation::sign("sultan-mainnet-1", "validator1", &SigningKey::from_bytes(&[1u8; 32]), &SigningKey::from_bytes(&[2u8; 32]), 500);
+        let block = Block {
+            index: 7,
+            hash: "rotation_block".to_string(),
+            prev_hash: "prev".to_string(),
+            timestamp: 1234567890,
+            transactions: vec![rotation.clone().into_transaction(1234567890)],
+            nonce: 0,
+            validator: "validator1".to_string(),
+            state_root: "root".to_string(),
+        };
+        {
+            let storage = PersistentStorage::new(path).unwrap();
+            storage.save_block(&block).unwrap();
+            assert_eq!(storage.get_key_rotations().unwrap(), vec![(7, rotation.clone())]);
+            
+            // Schema v2: blocks stored without the rotation index
+            let keys: Vec<_> = storage.db.prefix_iterator_cf(storage.cf(CF_STAKING), PREFIX_KEY_ROTATION.as_bytes())
+                .map(|item| item.unwrap().0)
+                .filter(|key| key.starts_with(PREFIX_KEY_ROTATION.as_bytes()))
+                .collect();
+            for key in keys {
+                storage.db.delete_cf(storage.cf(CF_STAKING), key).unwrap();
+            }
+            assert!(storage.get_key_rotations().unwrap().is_empty());
+            write_schema_version(&storage.db, 2).unwrap();
+        }
+        
+        let storage = PersistentStorage::new(path).unwrap();
+        assert_eq!(storage.schema_version().unwrap(), SCHEMA_VERSION);
+        assert_eq!(storage.get_key_rotations().unwrap(), vec![(7, rotation)]);
+    }
+    
+    #[test]
+    fn test_prunes_transactions_stored_before_v4() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        {
+            let storage = PersistentStorage::new(path).unwrap();
+            save_chain_with_txs(&storage, 30);
+            
+            // Schema v3: transactions stored without the height index
+            let keys: Vec<_> = storage.db.prefix_iterator_cf(storage.cf(CF_TRANSACTIONS), PREFIX_TX_HEIGHT.as_bytes())
+                .map(|item| item.unwrap().0)
+                .filter(|key| key.starts_with(PREFIX_TX_HEIGHT.as_bytes()))
+                .collect();
+            assert_eq!(keys.len(), 30);
+            for key in keys {
+                storage.db.delete_cf(storage.cf(CF_TRANSACTIONS), key).unwrap();
+            }
+            write_schema_version(&storage.db, 3).unwrap();
+        }
+        
+        let storage = PersistentStorage::with_pruning(path, PruningMode::Custom { keep_recent: 10, keep_every: 0 }).unwrap();
+        assert_eq!(storage.schema_version().unwrap(), SCHEMA_VERSION);
+        storage.save_account_snapshot(&account_snapshot(20)).unwrap();
+        assert_eq!(storage.prune_to(20).unwrap(), 20);
+        
+        assert!(storage.get_transaction("tx_5").unwrap().is_none());
+        assert!(storage.get_transaction("tx_20").unwrap().is_none());
+        assert!(storage.get_transaction("tx_21").unwrap().is_some());
+        assert_eq!(storage.get_transaction_history("alice", 100).unwrap().len(), 10);
+    }
+    
+    #[test]
+    fn test_migration_framework_runs_pending_steps() {
+        fn bump_wallets(db: &DB) -> Result<()> {
+            // Example layout change: wallet balances stored as u64 instead of i64
+            let cf = cf_handle(db, CF_WALLETS);
+            for item in db.iterator_cf(cf, IteratorMode::Start) {
+                let (key, value) = item?;
+                let old = i64::from_le_bytes(value.as_ref().try_into()?);
+                db.put_cf(cf, &key, (old as u64 + 1).to_le_bytes())?;
+            }
+            Ok(())
+        }
+        let migrations = [Migration { from: SCHEMA_VERSION, description: "test", run: bump_wallets }];
+        
+        let dir = tempdir().unwrap();
+        let storage = PersistentStorage::new(dir.path().to_str().unwrap()).unwrap();
+        storage.save_wallet("alice", 10).unwrap();
+        
+        assert_eq!(migrate_with(&storage.db, &migrations, SCHEMA_VERSION + 1).unwrap(), SCHEMA_VERSION + 1);
+        assert_eq!(storage.get_wallet("alice").unwrap().unwrap(), 11);
+        
+        // Already at target: the step is not re-run
+        assert_eq!(migrate_with(&storage.db, &migrations, SCHEMA_VERSION + 1).unwrap(), SCHEMA_VERSION + 1);
+        assert_eq!(storage.get_wallet("alice").unwrap().unwrap(), 11);
+        
+        // Missing step is an error, not a silent skip
+        write_schema_version(&storage.db, SCHEMA_VERSION).unwrap();
+        assert!(migrate_with(&storage.db, &[], SCHEMA_VERSION + 1).is_err());
+    }
+    
+    #[test]
+    fn test_refuses_newer_schema() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        {
+            let storage = PersistentStorage::new(path).unwrap();
+            write_schema_version(&storage.db, SCHEMA_VERSION + 1).unwrap();
+        }
+        
+        let err = PersistentStorage::new(path).err().unwrap();
+        assert!(err.to_string().contains("upgrade sultan-node"));
+    }
+}
@@ -0,0 +1,291 @@
+//! Event subscriptions for WebSocket clients
+//!
+//! The node publishes chain events (new blocks, finalized heights, transactions,
+//! token transfers, DEX swaps and proposal status changes) to a `SubscriptionHub`.
+//! Each event gets a sequence number and the height it happened at, and the most
+//! recent `EVENT_HISTORY` events are kept so that a client can resume from a height
+//! and a slow client can be caught up instead of silently missing events.
+
+use crate::governance::ProposalStatus;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Events kept for resume and catch-up
+pub const EVENT_HISTORY: usize = 10_000;
+
+/// Live events buffered per subscriber before it counts as lagging
+const LIVE_CAPACITY: usize = 1_024;
+
+/// Whether a transaction is in the mempool or in a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxStatus {
+    Pending,
+    Confirmed,
+}
+
+/// An event published to subscribers
+///
+/// Token and DEX amounts are u128 and sent as decimal strings, like the REST routes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChainEvent {
+    NewBlock {
+        height: u64,
+        hash: String,
+        proposer: String,
+        timestamp: u64,
+        tx_count: usize,
+    },
+    /// Blocks are final once committed, so this follows every `NewBlock`
+    Finalized {
+        height: u64,
+    },
+    Transaction {
+        status: TxStatus,
+        /// Same hash as `/tx/{hash}` and `/transactions/{address}`
+        hash: String,
+        from: String,
+        to: String,
+        amount: u64,
+        nonce: u64,
+    },
+    TokenTransfer {
+        denom: String,
+        from: String,
+        to: String,
+        amount: String,
+    },
+    DexSwap {
+        pair_id: String,
+        trader: String,
+        token_in: String,
+        amount_in: String,
+        amount_out: String,
+    },
+    ProposalStatus {
+        proposal_id: u64,
+        status: ProposalStatus,
+    },
+}
+
+/// A published event with its position in the stream
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventRecord {
+    pub seq: u64,
+    pub height: u64,
+    pub event: ChainEvent,
+}
+
+/// Filter a client subscribes with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Topic {
+    NewBlocks,
+    FinalizedHeights,
+    /// Transactions sent or received by `address`, optionally only one status
+    Transactions {
+        address: String,
+        #[serde(default)]
+        status: Option<TxStatus>,
+    },
+    TokenTransfers {
+        denom: String,
+    },
+    DexSwaps {
+        pair_id: String,
+    },
+    /// Status changes of one proposal, or of all proposals
+    ProposalStatus {
+        #[serde(default)]
+        proposal_id: Option<u64>,
+    },
+}
+
+impl Topic {
+    pub fn matches(&self, event: &ChainEvent) -> bool {
+        match (self, event) {
+            (Topic::NewBlocks, ChainEvent::NewBlock { .. }) => true,
+            (Topic::FinalizedHeights, ChainEvent::Finalized { .. }) => true,
+            (Topic::Transactions { address, status: wanted }, ChainEvent::Transaction { status, from, to, .. }) => {
+                (from == address || to == address) && wanted.is_none_or(|w| w == *status)
+            }
+            (Topic::TokenTransfers { denom }, ChainEvent::TokenTransfer { denom: d, .. }) => denom == d,
+            (Topic::DexSwaps { pair_id }, ChainEvent::DexSwap { pair_id: p, .. }) => pair_id == p,
+            (Topic::ProposalStatus { proposal_id }, ChainEvent::ProposalStatus { proposal_id: id, .. }) => {
+                proposal_id.is_none_or(|wanted| wanted == *id)
+            }
+            _ => false,
+        }
+    }
+}
+
+struct History {
+    records: VecDeque<Arc<EventRecord>>,
+    next_seq: u64,
+    /// Lowest height whose events are all still in `records`
+    earliest_height: u64,
+}
+
+/// Fan-out of chain events to subscribers, with a bounded replay history
+pub struct SubscriptionHub {
+    sender: broadcast::Sender<Arc<EventRecord>>,
+    history: Mutex<History>,
+}
+
+impl SubscriptionHub {
+    /// Create a hub whose history starts at `start_height`
+    pub fn new(start_height: u64) -> Self {
+        let (sender, _) = broadcast::channel(LIVE_CAPACITY);
+        Self {
+            sender,
+            history: Mutex::new(History {
+                records: VecDeque::new(),
+                next_seq: 1,
+                earliest_height: start_height,
+            }),
+        }
+    }
+
+    /// Publish an event that happened at `height`
+    pub fn publish(&self, height: u64, event: ChainEvent) {
+        // Send under the lock so live order always matches sequence order
+        let mut history = self.history.lock();
+        let record = Arc::new(EventRecord { seq: history.next_seq, height, event });
+        history.next_seq += 1;
+        history.records.push_back(record.clone());
+        if history.records.len() > EVENT_HISTORY {
+            if let Some(evicted) = history.records.pop_front() {
+                history.earliest_height = history.earliest_height.max(evicted.height + 1);
+            }
+        }
+        // No receivers is fine: nobody is subscribed yet
+        let _ = self.sender.send(record);
+    }
+
+    /// Receive live events, along with the sequence number of the last event
+    /// published before the receiver was created
+    pub fn subscribe(&self) -> (broadcast::Receiver<Arc<EventRecord>>, u64) {
+        let history = self.history.lock();
+        (self.sender.subscribe(), history.next_seq - 1)
+    }
+
+    /// Events at `from_height` or later, up to and including sequence `up_to_seq`
+    ///
+    /// Fails with the earliest height still available if `from_height` is older.
+    pub fn replay(&self, from_height: u64, up_to_seq: u64) -> Result<Vec<Arc<EventRecord>>, u64> {
+        let history = self.history.lock();
+        if from_height < history.earliest_height {
+            return Err(history.earliest_height);
+        }
+        Ok(history.records.iter()
+            .filter(|r| r.height >= from_height && r.seq <= up_to_seq)
+            .cloned()
+            .collect())
+    }
+
+    /// Every event after sequence `after_seq`, or None if some were already evicted
+    pub fn since(&self, after_seq: u64) -> Option<Vec<Arc<EventRecord>>> {
+        let history = self.history.lock();
+        let oldest = history.records.front().map_or(history.next_seq, |r| r.seq);
+        if oldest > after_seq + 1 {
+            return None;
+        }
+        Some(history.records.iter().filter(|r| r.seq > after_seq).cloned().collect())
+    }
+
+    /// Lowest height a client can resume from
+    pub fn earliest_height(&self) -> u64 {
+        self.history.lock().earliest_height
+    }
+
+    /// Number of connected live receivers
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(height: u64) -> ChainEvent {
+        ChainEvent::NewBlock {
+            height,
+            hash: format!("hash{}", height),
+            proposer: "validator1".to_string(),
+            timestamp: height,
+            tx_count: 0,
+        }
+    }
+
+    fn transfer(from: &str, to: &str, status: TxStatus) -> ChainEvent {
+        ChainEvent::Transaction {
+            status,
+            hash: format!("{}{}", from, to),
+            from: from.to_string(),
+            to: to.to_string(),
+            amount: 1,
+            nonce: 0,
+        }
+    }
+
+    #[test]
+    fn test_topic_filters() {
+        let alice = Topic::Transactions { address: "alice".to_string(), status: None };
+        assert!(alice.matches(&transfer("alice", "bob", TxStatus::Pending)));
+        assert!(alice.matches(&transfer("bob", "alice", TxStatus::Confirmed)));
+        assert!(!alice.matches(&transfer("bob", "carol", TxStatus::Confirmed)));
+
+        let confirmed = Topic::Transactions { address: "alice".to_string(), status: Some(TxStatus::Confirmed) };
+        assert!(!confirmed.matches(&transfer("alice", "bob", TxStatus::Pending)));
+        assert!(confirmed.matches(&transfer("alice", "bob", TxStatus::Confirmed)));
+
+        let proposal = ChainEvent::ProposalStatus { proposal_id: 7, status: ProposalStatus::Passed };
+        assert!(Topic::ProposalStatus { proposal_id: None }.matches(&proposal));
+        assert!(Topic::ProposalStatus { proposal_id: Some(7) }.matches(&proposal));
+        assert!(!Topic::ProposalStatus { proposal_id: Some(8) }.matches(&proposal));
+        assert!(!Topic::NewBlocks.matches(&proposal));
+
+        // Topics are what clients send over the socket
+        let topic: Topic = serde_json::from_str(r#"{"type":"dex_swaps","pair_id":"sltn-usdc"}"#).unwrap();
+        assert_eq!(topic, Topic::DexSwaps { pair_id: "sltn-usdc".to_string() });
+    }
+
+    #[tokio::test]
+    async fn test_resume_and_catch_up() {
+        let hub = SubscriptionHub::new(1);
+        for height in 1..=5 {
+            hub.publish(height, block(height));
+        }
+
+        // Resume from height 3 without duplicating what the live receiver will deliver
+        let (mut live, cursor) = hub.subscribe();
+        hub.publish(6, block(6));
+        let replayed = hub.replay(3, cursor).unwrap();
+        assert_eq!(replayed.iter().map(|r| r.height).collect::<Vec<_>>(), vec![3, 4, 5]);
+        assert_eq!(live.recv().await.unwrap().height, 6);
+
+        // A lagging subscriber catches up from history
+        let caught_up = hub.since(4).unwrap();
+        assert_eq!(caught_up.iter().map(|r| r.seq).collect::<Vec<_>>(), vec![5, 6]);
+        assert_eq!(hub.replay(0, cursor), Err(1));
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let hub = SubscriptionHub::new(0);
+        for i in 0..(EVENT_HISTORY as u64 + 10) {
+            hub.publish(i, ChainEvent::Finalized { height: i });
+        }
+        assert_eq!(hub.earliest_height(), 10);
+        assert!(hub.replay(9, u64::MAX).is_err());
+        assert_eq!(hub.replay(10, u64::MAX).unwrap().len(), EVENT_HISTORY);
+        // Events 1..=10 were evicted, so a subscriber that stopped at 5 can't catch up
+        assert!(hub.since(5).is_none());
+        assert!(hub.since(10).is_some());
+    }
+}
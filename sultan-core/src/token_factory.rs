@@ -9,14 +9,17 @@
 
 use anyhow::{Result, bail};
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{info, debug};
+use crate::api;
+use crate::events::{EventBuffer, ModuleEvent};
 
 /// Token metadata stored on-chain
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TokenMetadata {
     pub creator: String,
     pub name: String,
@@ -78,6 +81,12 @@ pub struct TokenFactory {
     
     /// Max claims per minute (rate limit)
     pub faucet_rate_limit: u32,
+    
+    /// Events emitted since the last block commit
+    pub events: EventBuffer,
+
+    /// Whether to verify Ed25519 signatures; only `fork` can turn this off
+    verify_signatures: bool,
 }
 
 impl TokenFactory {
@@ -99,6 +108,8 @@ impl TokenFactory {
             faucet_challenges: Arc::new(RwLock::new(HashMap::new())),
             faucet_claim_timestamps: Arc::new(RwLock::new(std::collections::VecDeque::new())),
             faucet_rate_limit: 30, // 30 claims per minute max
+            events: EventBuffer::default(),
+            verify_signatures: true,
         }
     }
 
@@ -118,6 +129,8 @@ impl TokenFactory {
             faucet_challenges: Arc::new(RwLock::new(HashMap::new())),
             faucet_claim_timestamps: Arc::new(RwLock::new(std::collections::VecDeque::new())),
             faucet_rate_limit: 30,
+            events: EventBuffer::default(),
+            verify_signatures: true,
         }
     }
     
@@ -142,6 +155,8 @@ impl TokenFactory {
             faucet_challenges: Arc::new(RwLock::new(HashMap::new())),
             faucet_claim_timestamps: Arc::new(RwLock::new(std::collections::VecDeque::new())),
             faucet_rate_limit: 30,
+            events: EventBuffer::default(),
+            verify_signatures: true,
         }
     }
 
@@ -149,6 +164,28 @@ impl TokenFactory {
     pub fn set_storage_path(&mut self, path: PathBuf) {
         self.storage_path = Some(path);
     }
+
+    /// Copy of the `denoms` registry entries and their balances held by
+    /// `addresses`, for a dry run touching nothing else
+    ///
+    /// The copy has no storage path and its own event buffer, so nothing done
+    /// through it is persisted or committed with a block.
+    pub(crate) async fn fork(&self, denoms: &[String], addresses: &[String], verify_signatures: bool) -> Self {
+        let tokens = self.tokens.read().await;
+        let balances = self.balances.read().await;
+        let mut fork = Self::new();
+        fork.tokens = Arc::new(RwLock::new(denoms.iter()
+            .filter_map(|denom| tokens.get(denom).map(|metadata| (denom.clone(), metadata.clone())))
+            .collect()));
+        fork.balances = Arc::new(RwLock::new(denoms.iter()
+            .flat_map(|denom| addresses.iter().map(move |address| (denom.clone(), address.clone())))
+            .filter_map(|key| balances.get(&key).map(|balance| (key, *balance)))
+            .collect()));
+        fork.creation_fee = self.creation_fee;
+        fork.min_initial_supply = self.min_initial_supply;
+        fork.verify_signatures = verify_signatures;
+        fork
+    }
     
     // === Phase Control Methods ===
     
@@ -260,7 +297,7 @@ impl TokenFactory {
         }
         
         // Verify signature over the nonce
-        if !Self::verify_ed25519_signature(pubkey, nonce.as_bytes(), signature) {
+        if !self.check_signature(pubkey, nonce.as_bytes(), signature) {
             bail!("Invalid signature. Sign the challenge nonce with your wallet.");
         }
         
@@ -311,6 +348,11 @@ impl TokenFactory {
         info!("🚰 Faucet: {} SLTN claimed by {} (verified signature, total: {}/{})", 
               self.faucet_amount / 1_000_000, address,
               new_total / 1_000_000, self.faucet_max_cap / 1_000_000);
+        self.events.emit(ModuleEvent::TokenMinted {
+            denom: Self::SLTN_DENOM.to_string(),
+            recipient: address.to_string(),
+            amount: self.faucet_amount.to_string(),
+        });
         Ok(self.faucet_amount)
     }
     
@@ -395,6 +437,11 @@ impl TokenFactory {
         info!("🚰 Faucet: {} SLTN claimed by {} (total: {}/{})", 
               self.faucet_amount / 1_000_000, address,
               new_total / 1_000_000, self.faucet_max_cap / 1_000_000);
+        self.events.emit(ModuleEvent::TokenMinted {
+            denom: Self::SLTN_DENOM.to_string(),
+            recipient: address.to_string(),
+            amount: self.faucet_amount.to_string(),
+        });
         Ok(self.faucet_amount)
     }
     
@@ -456,7 +503,11 @@ impl TokenFactory {
         let Some(path) = &self.storage_path else {
             return Ok(());
         };
-        
+        self.save_to_dir(path).await
+    }
+
+    /// Save tokens and balances as JSON into `path` (used by persistence and backups)
+    pub async fn save_to_dir(&self, path: &Path) -> Result<()> {
         // Ensure directory exists
         tokio::fs::create_dir_all(path).await?;
         
@@ -519,7 +570,7 @@ impl TokenFactory {
         }
         
         // Generate unique token denom (factory/{creator}/{symbol})
-        let denom = format!("factory/{}/{}", creator, symbol.to_lowercase());
+        let denom = Self::factory_denom(creator, &symbol);
         
         // Check if token already exists
         let tokens = self.tokens.read().await;
@@ -562,6 +613,11 @@ impl TokenFactory {
         
         info!("✅ Token created: {} ({}) - {} supply minted to {}", 
             metadata.name, denom, total_supply, creator);
+        self.events.emit(ModuleEvent::TokenCreated {
+            denom: denom.clone(),
+            creator: creator.to_string(),
+            initial_supply: total_supply.to_string(),
+        });
         
         Ok(denom)
     }
@@ -606,9 +662,20 @@ impl TokenFactory {
             .ok_or_else(|| anyhow::anyhow!("Recipient balance overflow"))?;
         
         info!("✅ Transferred {} {} from {} to {}", amount, denom, from, to);
+        self.events.emit(ModuleEvent::TokenTransferred {
+            denom: denom.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            amount: amount.to_string(),
+        });
         Ok(())
     }
     
+    /// Denom of the token `creator` creates with `symbol`
+    pub fn factory_denom(creator: &str, symbol: &str) -> String {
+        format!("factory/{}/{}", creator, symbol.to_lowercase())
+    }
+
     /// Get token metadata
     pub async fn get_metadata(&self, denom: &str) -> Option<TokenMetadata> {
         let tokens = self.tokens.read().await;
@@ -631,12 +698,26 @@ impl TokenFactory {
             .collect()
     }
     
+    /// All tokens with their denoms, optionally only those created by `creator`
+    pub async fn list_tokens(&self, creator: Option<&str>) -> Vec<(String, TokenMetadata)> {
+        let tokens = self.tokens.read().await;
+        tokens.iter()
+            .filter(|(_, metadata)| creator.is_none_or(|c| metadata.creator == c))
+            .map(|(denom, metadata)| (denom.clone(), metadata.clone()))
+            .collect()
+    }
+    
     /// Get total supply of a token (O(1) via metadata lookup)
     pub async fn get_total_supply(&self, denom: &str) -> u128 {
         let tokens = self.tokens.read().await;
         tokens.get(denom).map(|m| m.total_supply).unwrap_or(0)
     }
 
+    /// Verify the signature of a state-changing operation (always passes on unverified forks)
+    fn check_signature(&self, pubkey: &[u8; 32], message: &[u8], signature: &[u8]) -> bool {
+        !self.verify_signatures || Self::verify_ed25519_signature(pubkey, message, signature)
+    }
+
     /// Verify Ed25519 signature
     /// Used to authenticate state-changing operations
     pub fn verify_ed25519_signature(pubkey: &[u8; 32], message: &[u8], signature: &[u8]) -> bool {
@@ -673,11 +754,8 @@ impl TokenFactory {
         pubkey: &[u8; 32],
     ) -> Result<String> {
         // Verify signature over creation data
-        let sign_data = format!(
-            "create_token:{}:{}:{}:{}:{}:{:?}",
-            creator, name, symbol, decimals, total_supply, max_supply
-        );
-        if !Self::verify_ed25519_signature(pubkey, sign_data.as_bytes(), signature) {
+        let sign_data = api::create_token_message(creator, &name, &symbol, decimals, total_supply, max_supply);
+        if !self.check_signature(pubkey, sign_data.as_bytes(), signature) {
             bail!("Invalid Ed25519 signature on token creation");
         }
         debug!("✅ Token creation signature verified for {} by {}", symbol, creator);
@@ -702,7 +780,7 @@ impl TokenFactory {
         }
         
         // Generate unique token denom
-        let denom = format!("factory/{}/{}", creator, symbol.to_lowercase());
+        let denom = Self::factory_denom(creator, &symbol);
         
         // Check if token already exists
         let tokens = self.tokens.read().await;
@@ -742,6 +820,11 @@ impl TokenFactory {
         
         info!("✅ Token created: {} ({}) - {} supply minted to {}", 
             name, denom, total_supply, creator);
+        self.events.emit(ModuleEvent::TokenCreated {
+            denom: denom.clone(),
+            creator: creator.to_string(),
+            initial_supply: total_supply.to_string(),
+        });
         
         Ok(denom)
     }
@@ -757,8 +840,8 @@ impl TokenFactory {
         pubkey: &[u8; 32],
     ) -> Result<()> {
         // Verify signature over transfer data
-        let sign_data = format!("transfer:{}:{}:{}:{}", denom, from, to, amount);
-        if !Self::verify_ed25519_signature(pubkey, sign_data.as_bytes(), signature) {
+        let sign_data = api::token_transfer_message(denom, from, to, amount);
+        if !self.check_signature(pubkey, sign_data.as_bytes(), signature) {
             bail!("Invalid Ed25519 signature on transfer");
         }
         debug!("✅ Transfer signature verified for {} {} from {}", amount, denom, from);
@@ -794,6 +877,12 @@ impl TokenFactory {
             .ok_or_else(|| anyhow::anyhow!("Recipient balance overflow"))?;
         
         info!("✅ Transferred {} {} from {} to {}", amount, denom, from, to);
+        self.events.emit(ModuleEvent::TokenTransferred {
+            denom: denom.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            amount: amount.to_string(),
+        });
         Ok(())
     }
 
@@ -833,6 +922,11 @@ impl TokenFactory {
             .ok_or_else(|| anyhow::anyhow!("Recipient balance overflow"))?;
         
         info!("🪙 Minted {} {} to {} (internal)", amount, symbol, recipient);
+        self.events.emit(ModuleEvent::TokenMinted {
+            denom: denom.to_string(),
+            recipient: recipient.to_string(),
+            amount: amount.to_string(),
+        });
         Ok(())
     }
 
@@ -887,6 +981,11 @@ impl TokenFactory {
         balances.insert(key, total_supply);
         
         info!("🔷 LP token created: {} with {} supply", lp_denom, total_supply);
+        self.events.emit(ModuleEvent::TokenCreated {
+            denom: lp_denom.to_string(),
+            creator: pool_address.to_string(),
+            initial_supply: total_supply.to_string(),
+        });
         Ok(lp_denom.to_string())
     }
 
@@ -900,8 +999,8 @@ impl TokenFactory {
         pubkey: &[u8; 32],
     ) -> Result<()> {
         // Verify signature over mint data
-        let sign_data = format!("mint:{}:{}:{}", denom, recipient, amount);
-        if !Self::verify_ed25519_signature(pubkey, sign_data.as_bytes(), signature) {
+        let sign_data = api::mint_message(denom, recipient, amount);
+        if !self.check_signature(pubkey, sign_data.as_bytes(), signature) {
             bail!("Invalid Ed25519 signature on mint");
         }
         debug!("✅ Mint signature verified for {} {} to {}", amount, denom, recipient);
@@ -933,6 +1032,11 @@ impl TokenFactory {
                 .ok_or_else(|| anyhow::anyhow!("Recipient balance overflow"))?;
             
             info!("✅ Minted {} {} to {}", amount, symbol, recipient);
+            self.events.emit(ModuleEvent::TokenMinted {
+                denom: denom.to_string(),
+                recipient: recipient.to_string(),
+                amount: amount.to_string(),
+            });
             Ok(())
         } else {
             bail!("Minting not enabled for this token");
@@ -972,6 +1076,11 @@ impl TokenFactory {
         }
         
         info!("🔥 Burned {} {} from {} (internal)", amount, denom, from);
+        self.events.emit(ModuleEvent::TokenBurned {
+            denom: denom.to_string(),
+            from: from.to_string(),
+            amount: amount.to_string(),
+        });
         Ok(())
     }
 
@@ -985,8 +1094,8 @@ impl TokenFactory {
         pubkey: &[u8; 32],
     ) -> Result<()> {
         // Verify signature over burn data
-        let sign_data = format!("burn:{}:{}:{}", denom, from, amount);
-        if !Self::verify_ed25519_signature(pubkey, sign_data.as_bytes(), signature) {
+        let sign_data = api::burn_message(denom, from, amount);
+        if !self.check_signature(pubkey, sign_data.as_bytes(), signature) {
             bail!("Invalid Ed25519 signature on burn");
         }
         debug!("✅ Burn signature verified for {} {} from {}", amount, denom, from);
@@ -1015,6 +1124,11 @@ impl TokenFactory {
         }
         
         info!("✅ Burned {} {} from {}", amount, denom, from);
+        self.events.emit(ModuleEvent::TokenBurned {
+            denom: denom.to_string(),
+            from: from.to_string(),
+            amount: amount.to_string(),
+        });
         Ok(())
     }
 
@@ -13,7 +13,8 @@ use anyhow::Result;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use sha2::{Sha256, Digest};
-use ed25519_dalek::{Verifier, VerifyingKey, Signature};
+use ed25519_dalek::{Verifier, VerifyingKey, Signature, SignatureError};
+use curve25519_dalek::edwards::CompressedEdwardsY;
 use tracing::{debug, warn};
 
 /// Rate limit: max transactions per sender per block window
@@ -125,6 +126,85 @@ impl fmt::Display for ValidationError {
 
 impl std::error::Error for ValidationError {}
 
+/// Which Ed25519 rules decide a block's transaction signatures
+///
+/// Rejecting small-order points is a consensus change: a block accepted under
+/// the old rules may hold a signature the strict rules refuse. Every node on
+/// the network must switch at the same height, so blocks below it are still
+/// checked (and replayed) one signature at a time as before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureRules {
+    /// Each signature verified on its own
+    Legacy,
+    /// Small-order points rejected, then one batch check
+    Strict,
+}
+
+impl SignatureRules {
+    /// Rules for a block at `height`; unset activation keeps the legacy rules
+    pub fn at(activation: Option<u64>, height: u64) -> Self {
+        match activation {
+            Some(activation) if height >= activation => Self::Strict,
+            _ => Self::Legacy,
+        }
+    }
+}
+
+/// Verify Ed25519 signatures as one batch, falling back to one at a time
+///
+/// A batch check costs roughly half as much per signature, but only says whether
+/// every signature is valid. When it fails, each signature is checked on its own
+/// to find the bad ones. Returns one verdict per signature, in order.
+///
+/// A batch check weighs each signature by a random coefficient, so a signature
+/// whose R or public key has a small-order component passes a batch whenever
+/// the coefficient drawn cancels it, and fails on its own. Under
+/// `SignatureRules::Strict` those are rejected up front (see `is_unambiguous`),
+/// so a signature gets the same verdict alone as in any batch, and block
+/// producers and validators agree however their batches are grouped. Under
+/// `SignatureRules::Legacy` nothing is batched.
+pub fn verify_batch_or_each(
+    messages: &[&[u8]],
+    signatures: &[Signature],
+    keys: &[VerifyingKey],
+    rules: SignatureRules,
+) -> Vec<std::result::Result<(), SignatureError>> {
+    if rules == SignatureRules::Legacy {
+        return (0..signatures.len()).map(|i| keys[i].verify(messages[i], &signatures[i])).collect();
+    }
+    let mut verdicts: Vec<_> = signatures.iter().zip(keys)
+        .map(|(signature, key)| if is_unambiguous(signature, key) { Ok(()) } else { Err(SignatureError::new()) })
+        .collect();
+    let checked: Vec<usize> = (0..verdicts.len()).filter(|&i| verdicts[i].is_ok()).collect();
+    if checked.len() > 1 {
+        let batch_messages: Vec<&[u8]> = checked.iter().map(|&i| messages[i]).collect();
+        let batch_signatures: Vec<Signature> = checked.iter().map(|&i| signatures[i]).collect();
+        let batch_keys: Vec<VerifyingKey> = checked.iter().map(|&i| keys[i]).collect();
+        if ed25519_dalek::verify_batch(&batch_messages, &batch_signatures, &batch_keys).is_ok() {
+            return verdicts;
+        }
+    }
+    for i in checked {
+        verdicts[i] = keys[i].verify(messages[i], &signatures[i]);
+    }
+    verdicts
+}
+
+/// Whether batch and single verification give the same answer for this signature
+///
+/// True when the public key and R are canonical points with no small-order
+/// component. Anything else is rejected whatever the batch size.
+fn is_unambiguous(signature: &Signature, key: &VerifyingKey) -> bool {
+    if key.is_weak() || !key.to_edwards().is_torsion_free() {
+        return false;
+    }
+    let r_bytes = signature.r_bytes();
+    match CompressedEdwardsY(*r_bytes).decompress() {
+        Some(r) => r.compress().as_bytes() == r_bytes && r.is_torsion_free(),
+        None => false,
+    }
+}
+
 /// Validation mode for different contexts
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ValidationMode {
@@ -283,6 +363,50 @@ impl TransactionValidator {
 
     /// Verify Ed25519 signature
     fn verify_signature(&self, tx: &Transaction) -> Result<()> {
+        let (verifying_key, signature) = self.decode_signature(tx)?;
+        let message = self.build_signing_message(tx);
+
+        verifying_key.verify(message.as_bytes(), &signature)
+            .map_err(|e| ValidationError::SignatureVerificationFailed(e.to_string()))?;
+
+        debug!("Ed25519 signature verified for tx from {}", tx.from);
+        Ok(())
+    }
+
+    /// Verify the Ed25519 signatures of many transactions in one batch
+    ///
+    /// Returns one result per transaction, in order, with the same errors as
+    /// single verification.
+    pub fn verify_signatures(&self, txs: &[&Transaction]) -> Vec<Result<()>> {
+        let mut results: Vec<Result<()>> = Vec::with_capacity(txs.len());
+        let mut decoded = Vec::new();
+        for (i, tx) in txs.iter().enumerate() {
+            match self.decode_signature(tx) {
+                Ok((key, signature)) => {
+                    decoded.push((i, self.build_signing_message(tx), key, signature));
+                    results.push(Ok(()));
+                }
+                Err(e) => results.push(Err(e)),
+            }
+        }
+
+        let messages: Vec<&[u8]> = decoded.iter().map(|(_, m, _, _)| m.as_bytes()).collect();
+        let signatures: Vec<Signature> = decoded.iter().map(|(_, _, _, s)| *s).collect();
+        let keys: Vec<VerifyingKey> = decoded.iter().map(|(_, _, k, _)| *k).collect();
+        // Admission only: the mempool may refuse more than consensus does
+        let verdicts = verify_batch_or_each(&messages, &signatures, &keys, SignatureRules::Strict);
+        for ((i, ..), verdict) in decoded.iter().zip(verdicts) {
+            if let Err(e) = verdict {
+                results[*i] = Err(ValidationError::SignatureVerificationFailed(e.to_string()).into());
+            }
+        }
+
+        debug!("Ed25519 batch of {} signatures checked", txs.len());
+        results
+    }
+
+    /// Decode the public key and signature carried by a transaction
+    fn decode_signature(&self, tx: &Transaction) -> Result<(VerifyingKey, Signature)> {
         // Require signature
         let signature_hex = tx.signature.as_ref()
             .ok_or(ValidationError::MissingSignature)?;
@@ -311,10 +435,6 @@ impl TransactionValidator {
         let pubkey_bytes = hex::decode(pubkey_hex)
             .map_err(|e| ValidationError::InvalidPublicKeyHex(e.to_string()))?;
 
-        // Build message to verify (same as signing)
-        let message = self.build_signing_message(tx);
-
-        // Verify Ed25519 signature
         let pubkey_array: [u8; 32] = pubkey_bytes.try_into()
             .map_err(|_| ValidationError::InvalidPublicKeyLength { expected: 32, got: 0 })?;
         let sig_array: [u8; 64] = sig_bytes.try_into()
@@ -322,13 +442,7 @@ impl TransactionValidator {
 
         let verifying_key = VerifyingKey::from_bytes(&pubkey_array)
             .map_err(|e| ValidationError::InvalidPublicKey(e.to_string()))?;
-        let signature = Signature::from_bytes(&sig_array);
-
-        verifying_key.verify(message.as_bytes(), &signature)
-            .map_err(|e| ValidationError::SignatureVerificationFailed(e.to_string()))?;
-
-        debug!("Ed25519 signature verified for tx from {}", tx.from);
-        Ok(())
+        Ok((verifying_key, Signature::from_bytes(&sig_array)))
     }
 
     /// Build the message that should be signed
@@ -709,6 +823,116 @@ mod tests {
         assert!(result.is_ok(), "Valid signature should be accepted: {:?}", result);
     }
 
+    #[test]
+    fn test_batch_signature_verification() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let validator = TransactionValidator::new();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let mut txs: Vec<Transaction> = (0..6u8)
+            .map(|i| {
+                let signing_key = SigningKey::from_bytes(&[i + 1; 32]);
+                let mut tx = test_tx(&format!("sender_wallet_{}", i), "bob_wallet_addrs", 100, 1);
+                tx.timestamp = now;
+                tx.public_key = Some(hex::encode(signing_key.verifying_key().to_bytes()));
+                let signature = signing_key.sign(validator.build_signing_message(&tx).as_bytes());
+                tx.signature = Some(hex::encode(signature.to_bytes()));
+                tx
+            })
+            .collect();
+
+        let refs: Vec<&Transaction> = txs.iter().collect();
+        assert!(validator.verify_signatures(&refs).iter().all(|r| r.is_ok()));
+
+        // A forged transfer fails the batch; only that one is reported
+        txs[2].amount = 100_000;
+        txs[4].public_key = None;
+        let refs: Vec<&Transaction> = txs.iter().collect();
+        let results = validator.verify_signatures(&refs);
+        for (i, result) in results.iter().enumerate() {
+            assert_eq!(result.is_ok(), i != 2 && i != 4, "tx {}", i);
+        }
+        assert!(results[2].as_ref().unwrap_err().to_string().contains("Signature verification failed"));
+        assert!(results[4].as_ref().unwrap_err().to_string().contains("Missing public key"));
+    }
+
+    #[test]
+    fn test_small_order_signature_rejected_alone_and_in_batch() {
+        use curve25519_dalek::constants::{ED25519_BASEPOINT_POINT, EIGHT_TORSION};
+        use curve25519_dalek::scalar::Scalar;
+        use ed25519_dalek::{Signer, SigningKey};
+        use sha2::Sha512;
+
+        // s = r + k*a with R = r*B + T for the order-2 point T: a single check
+        // fails, a batch passes whenever its random coefficient for this
+        // signature is even
+        let a = Scalar::from_bytes_mod_order([7; 32]);
+        let key = VerifyingKey::from_bytes(&(ED25519_BASEPOINT_POINT * a).compress().to_bytes()).unwrap();
+        let message: &[u8] = b"small order R";
+        let r = Scalar::from_bytes_mod_order([9; 32]);
+        let big_r = (ED25519_BASEPOINT_POINT * r + EIGHT_TORSION[4]).compress();
+        let mut hasher = Sha512::new();
+        hasher.update(big_r.as_bytes());
+        hasher.update(key.as_bytes());
+        hasher.update(message);
+        let k = Scalar::from_bytes_mod_order_wide(&hasher.finalize().into());
+        let forged = Signature::from_components(big_r.to_bytes(), (r + k * a).to_bytes());
+
+        let signing_key = SigningKey::from_bytes(&[3; 32]);
+        let honest: &[u8] = b"honest";
+        let valid = signing_key.sign(honest);
+
+        for rules in [SignatureRules::Legacy, SignatureRules::Strict] {
+            let alone = verify_batch_or_each(&[message], &[forged], &[key], rules);
+            assert!(alone[0].is_err());
+            let batch = verify_batch_or_each(&[message, honest], &[forged, valid], &[key, signing_key.verifying_key()], rules);
+            assert!(batch[0].is_err());
+            assert!(batch[1].is_ok());
+        }
+        // Without the point checks some batches would have accepted it
+        let keys = [key, signing_key.verifying_key()];
+        assert!((0..64).any(|_| ed25519_dalek::verify_batch(&[message, honest], &[forged, valid], &keys).is_ok()));
+    }
+
+    #[test]
+    fn test_small_order_key_accepted_only_before_strict_rules() {
+        use curve25519_dalek::constants::{ED25519_BASEPOINT_POINT, EIGHT_TORSION};
+        use curve25519_dalek::scalar::Scalar;
+        use sha2::Sha512;
+
+        // A key with an order-2 component: R = sB - kA holds exactly when k is
+        // even, so the single check accepts it and the strict rules don't
+        let a = Scalar::from_bytes_mod_order([5; 32]);
+        let key_point = ED25519_BASEPOINT_POINT * a + EIGHT_TORSION[4];
+        let key = VerifyingKey::from_bytes(&key_point.compress().to_bytes()).unwrap();
+        let r = Scalar::from_bytes_mod_order([11; 32]);
+        let big_r = (ED25519_BASEPOINT_POINT * r).compress();
+        let (message, k) = (0u8..)
+            .map(|i| {
+                let message = vec![i];
+                let mut hasher = Sha512::new();
+                hasher.update(big_r.as_bytes());
+                hasher.update(key.as_bytes());
+                hasher.update(&message);
+                (message, Scalar::from_bytes_mod_order_wide(&hasher.finalize().into()))
+            })
+            .find(|(_, k)| k.as_bytes()[0] % 2 == 0)
+            .unwrap();
+        let signature = Signature::from_components(big_r.to_bytes(), (r + k * a).to_bytes());
+
+        let legacy = verify_batch_or_each(&[&message], &[signature], &[key], SignatureRules::Legacy);
+        assert!(legacy[0].is_ok());
+        let strict = verify_batch_or_each(&[&message], &[signature], &[key], SignatureRules::Strict);
+        assert!(strict[0].is_err());
+
+        assert_eq!(SignatureRules::at(None, 1_000_000), SignatureRules::Legacy);
+        assert_eq!(SignatureRules::at(Some(100), 99), SignatureRules::Legacy);
+        assert_eq!(SignatureRules::at(Some(100), 100), SignatureRules::Strict);
+    }
+
     #[test]
     fn test_invalid_signature_rejected() {
         let mut validator = TransactionValidator::new(); // Full verification enabled
@@ -0,0 +1,682 @@
+//! Pull-based transaction relay
+//!
+//! Transactions are gossiped as batches of content IDs (SHA-256 of the encoded
+//! transaction). A peer that sees an ID it doesn't have fetches the body from the
+//! announcing peer over `/sultan/tx-fetch/1.0.0`, so each node downloads every
+//! transaction once instead of once per mesh peer. Compact blocks use the same
+//! protocol to fetch the transactions they're missing by short ID, or the whole
+//! block's transactions when short IDs collide.
+//!
+//! Protocol 1.0 peers neither announce nor fetch. While one is connected the P2P
+//! loop also gossips new bodies in full on `TX_TOPIC`.
+
+use crate::handshake::{read_frame, write_frame};
+use anyhow::{bail, Context as _, Result};
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use futures::{AsyncWriteExt, FutureExt, StreamExt};
+use libp2p::core::upgrade::ReadyUpgrade;
+use libp2p::core::Endpoint;
+use libp2p::swarm::handler::{
+    ConnectionEvent, DialUpgradeError, FullyNegotiatedInbound, FullyNegotiatedOutbound,
+};
+use libp2p::swarm::{
+    ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
+    NetworkBehaviour, NotifyHandler, SubstreamProtocol, THandler, THandlerInEvent,
+    THandlerOutEvent, ToSwarm,
+};
+use libp2p::{Multiaddr, PeerId, Stream, StreamProtocol};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::debug;
+
+/// Stream protocol used to fetch transaction bodies
+pub const TX_FETCH_PROTOCOL: StreamProtocol = StreamProtocol::new("/sultan/tx-fetch/1.0.0");
+
+/// Maximum IDs in one fetch request
+pub const MAX_FETCH_BATCH: usize = 256;
+
+/// Maximum encoded fetch response (bodies past this are left out)
+const MAX_RESPONSE_SIZE: usize = 4 << 20;
+
+/// Maximum encoded fetch request
+const MAX_REQUEST_SIZE: usize = MAX_FETCH_BATCH * 40 + 64;
+
+/// Time allowed for one fetch round trip
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Concurrent fetch requests served per connection
+const MAX_INBOUND_PER_CONNECTION: usize = 8;
+
+/// How long transaction bodies stay available to peers
+const TX_CACHE_TTL: Duration = Duration::from_secs(120);
+
+/// Maximum transaction bodies kept for peers
+const TX_CACHE_CAPACITY: usize = 50_000;
+
+/// Content ID of a transaction: SHA-256 of its encoded bytes
+pub type TxId = [u8; 32];
+
+pub fn tx_id(tx_data: &[u8]) -> TxId {
+    Sha256::digest(tx_data).into()
+}
+
+/// Short transaction ID used in compact blocks
+pub type ShortTxId = [u8; 8];
+
+/// SipHash key for one compact block's short IDs (see `CompactBlock::short_id_key`)
+pub type ShortIdKey = [u8; 16];
+
+/// SipHash-2-4 of the content ID under the block's key, as in BIP152
+///
+/// The key changes with every block, so nobody can grind transactions that
+/// collide in advance.
+pub fn short_id(key: &ShortIdKey, id: &TxId) -> ShortTxId {
+    siphash24(key, id).to_le_bytes()
+}
+
+fn siphash24(key: &ShortIdKey, data: &[u8]) -> u64 {
+    fn round(v: &mut [u64; 4]) {
+        v[0] = v[0].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(13) ^ v[0];
+        v[0] = v[0].rotate_left(32);
+        v[2] = v[2].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(16) ^ v[2];
+        v[0] = v[0].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(21) ^ v[0];
+        v[2] = v[2].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(17) ^ v[2];
+        v[2] = v[2].rotate_left(32);
+    }
+    fn compress(v: &mut [u64; 4], m: u64) {
+        v[3] ^= m;
+        round(v);
+        round(v);
+        v[0] ^= m;
+    }
+
+    let k0 = u64::from_le_bytes(key[..8].try_into().expect("8-byte half"));
+    let k1 = u64::from_le_bytes(key[8..].try_into().expect("8-byte half"));
+    let mut v = [
+        k0 ^ 0x736f_6d65_7073_6575,
+        k1 ^ 0x646f_7261_6e64_6f6d,
+        k0 ^ 0x6c79_6765_6e65_7261,
+        k1 ^ 0x7465_6462_7974_6573,
+    ];
+    let chunks = data.chunks_exact(8);
+    let tail = chunks.remainder();
+    for chunk in chunks {
+        compress(&mut v, u64::from_le_bytes(chunk.try_into().expect("8-byte chunk")));
+    }
+    let mut last = [0u8; 8];
+    last[..tail.len()].copy_from_slice(tail);
+    last[7] = data.len() as u8;
+    compress(&mut v, u64::from_le_bytes(last));
+    v[2] ^= 0xff;
+    for _ in 0..4 {
+        round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+/// A transaction body as relayed between peers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchedTx {
+    pub tx_hash: String,
+    pub tx_data: Vec<u8>,
+}
+
+/// Transactions asked for in one fetch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FetchRequest {
+    /// By content ID, after a `TxAnnounce`
+    Ids(Vec<TxId>),
+    /// By short ID, to complete a compact block
+    ShortIds { key: ShortIdKey, ids: Vec<ShortTxId> },
+    /// Every transaction of a recently relayed block, in order, after its short
+    /// IDs failed to rebuild it
+    Block(String),
+}
+
+impl FetchRequest {
+    pub fn len(&self) -> usize {
+        match self {
+            FetchRequest::Ids(ids) => ids.len(),
+            FetchRequest::ShortIds { ids, .. } => ids.len(),
+            FetchRequest::Block(_) => 1,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FetchResponse {
+    txs: Vec<FetchedTx>,
+}
+
+/// Recently relayed blocks whose transaction order is kept for full fetches
+const MAX_CACHED_BLOCKS: usize = 32;
+
+/// Recently seen transaction bodies, served to peers that fetch them
+pub struct TxCache {
+    entries: HashMap<TxId, (FetchedTx, Instant)>,
+    order: VecDeque<TxId>,
+    /// Transaction IDs of recently relayed blocks, by block hash
+    blocks: HashMap<String, Vec<TxId>>,
+    block_order: VecDeque<String>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl Default for TxCache {
+    fn default() -> Self {
+        Self::new(TX_CACHE_CAPACITY, TX_CACHE_TTL)
+    }
+}
+
+impl TxCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            blocks: HashMap::new(),
+            block_order: VecDeque::new(),
+            capacity,
+            ttl,
+        }
+    }
+
+    /// Add a transaction; returns false if it was already cached
+    pub fn insert(&mut self, id: TxId, tx: FetchedTx) -> bool {
+        if self.entries.contains_key(&id) {
+            return false;
+        }
+        self.entries.insert(id, (tx, Instant::now()));
+        self.order.push_back(id);
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    fn remove(&mut self, id: &TxId) {
+        self.entries.remove(id);
+    }
+
+    pub fn contains(&self, id: &TxId) -> bool {
+        self.entries.contains_key(id)
+    }
+
+    /// Look up bodies by short ID under a block's `key`
+    ///
+    /// A short ID that matches no cached body, or more than one, resolves to `None`.
+    pub fn get_by_short(&self, key: &ShortIdKey, shorts: &[ShortTxId]) -> Vec<Option<&FetchedTx>> {
+        let wanted: HashSet<&ShortTxId> = shorts.iter().collect();
+        let mut matches: HashMap<ShortTxId, Option<&FetchedTx>> = HashMap::new();
+        for (id, (tx, _)) in &self.entries {
+            let short = short_id(key, id);
+            if wanted.contains(&short) {
+                matches.entry(short)
+                    .and_modify(|found| *found = None)
+                    .or_insert(Some(tx));
+            }
+        }
+        shorts.iter().map(|short| matches.get(short).copied().flatten()).collect()
+    }
+
+    /// The cached bodies among `ids`, up to `max_bytes` of transaction data
+    pub fn get_many(&self, ids: &[TxId], max_bytes: usize) -> Vec<FetchedTx> {
+        Self::take_bytes(ids.iter().filter_map(|id| self.entries.get(id)).map(|(tx, _)| tx), max_bytes)
+    }
+
+    /// The cached bodies among short `ids`, up to `max_bytes` of transaction data
+    pub fn get_many_short(&self, key: &ShortIdKey, ids: &[ShortTxId], max_bytes: usize) -> Vec<FetchedTx> {
+        Self::take_bytes(self.get_by_short(key, ids).into_iter().flatten(), max_bytes)
+    }
+
+    /// Remember the transaction order of a block we relayed, for full fetches
+    pub fn insert_block(&mut self, block_hash: String, ids: Vec<TxId>) {
+        if self.blocks.insert(block_hash.clone(), ids).is_none() {
+            self.block_order.push_back(block_hash);
+        }
+        while self.block_order.len() > MAX_CACHED_BLOCKS {
+            if let Some(oldest) = self.block_order.pop_front() {
+                self.blocks.remove(&oldest);
+            }
+        }
+    }
+
+    /// Whether we relayed (or rebuilt) a block recently
+    pub fn has_block(&self, block_hash: &str) -> bool {
+        self.blocks.contains_key(block_hash)
+    }
+
+    /// Every transaction of a relayed block in order, or nothing if any body is
+    /// gone or they don't fit in `max_bytes`
+    pub fn get_block(&self, block_hash: &str, max_bytes: usize) -> Vec<FetchedTx> {
+        let Some(ids) = self.blocks.get(block_hash) else {
+            return Vec::new();
+        };
+        let txs = Self::take_bytes(ids.iter().filter_map(|id| self.entries.get(id)).map(|(tx, _)| tx), max_bytes);
+        if txs.len() == ids.len() { txs } else { Vec::new() }
+    }
+
+    fn take_bytes<'a>(txs: impl Iterator<Item = &'a FetchedTx>, max_bytes: usize) -> Vec<FetchedTx> {
+        let mut size = 0;
+        txs.take_while(|tx| {
+            size += tx.tx_data.len() + tx.tx_hash.len();
+            size <= max_bytes
+        })
+        .cloned()
+        .collect()
+    }
+
+    /// Drop entries older than the TTL
+    pub fn prune(&mut self) {
+        let now = Instant::now();
+        while let Some(id) = self.order.front() {
+            match self.entries.get(id) {
+                Some((_, added)) if now.duration_since(*added) < self.ttl => break,
+                _ => {
+                    let id = *id;
+                    self.order.pop_front();
+                    self.remove(&id);
+                }
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Result of a fetch
+#[derive(Debug)]
+pub enum Event {
+    /// The peer answered; `txs` only holds bodies matching `requested`
+    Fetched { peer_id: PeerId, requested: FetchRequest, txs: Vec<FetchedTx> },
+    /// The request failed (unsupported protocol, bad response or timeout)
+    FetchFailed { peer_id: PeerId, requested: FetchRequest, error: String },
+}
+
+/// Fetch behaviour; serves requests from the shared cache
+pub struct Behaviour {
+    cache: Arc<RwLock<TxCache>>,
+    actions: VecDeque<ToSwarm<Event, FetchRequest>>,
+}
+
+impl Behaviour {
+    pub fn new(cache: Arc<RwLock<TxCache>>) -> Self {
+        Self { cache, actions: VecDeque::new() }
+    }
+
+    /// Ask `peer_id` for the bodies of `ids` (at most `MAX_FETCH_BATCH`)
+    pub fn fetch(&mut self, peer_id: PeerId, ids: Vec<TxId>) {
+        self.request(peer_id, FetchRequest::Ids(ids));
+    }
+
+    /// Ask `peer_id` for the bodies behind short `ids` (at most `MAX_FETCH_BATCH`)
+    pub fn fetch_short(&mut self, peer_id: PeerId, key: ShortIdKey, ids: Vec<ShortTxId>) {
+        self.request(peer_id, FetchRequest::ShortIds { key, ids });
+    }
+
+    /// Ask `peer_id` for every transaction of the block it relayed as `block_hash`
+    pub fn fetch_block(&mut self, peer_id: PeerId, block_hash: String) {
+        self.request(peer_id, FetchRequest::Block(block_hash));
+    }
+
+    fn request(&mut self, peer_id: PeerId, request: FetchRequest) {
+        self.actions.push_back(ToSwarm::NotifyHandler {
+            peer_id,
+            handler: NotifyHandler::Any,
+            event: request,
+        });
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = Handler;
+    type ToSwarm = Event;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        _peer: PeerId,
+        _local_addr: &Multiaddr,
+        _remote_addr: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(self.cache.clone()))
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        _peer: PeerId,
+        _addr: &Multiaddr,
+        _role_override: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::new(self.cache.clone()))
+    }
+
+    fn on_swarm_event(&mut self, _event: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer_id: PeerId,
+        _connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        let event = match event {
+            HandlerEvent::Fetched { requested, txs } => Event::Fetched { peer_id, requested, txs },
+            HandlerEvent::Failed { requested, error } => Event::FetchFailed { peer_id, requested, error },
+        };
+        self.actions.push_back(ToSwarm::GenerateEvent(event));
+    }
+
+    fn poll(&mut self, _cx: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        match self.actions.pop_front() {
+            Some(action) => Poll::Ready(action),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// What a connection handler reports to the behaviour
+#[derive(Debug)]
+pub enum HandlerEvent {
+    Fetched { requested: FetchRequest, txs: Vec<FetchedTx> },
+    Failed { requested: FetchRequest, error: String },
+}
+
+type OutboundFuture = BoxFuture<'static, (FetchRequest, Result<Vec<FetchedTx>>)>;
+
+/// Per-connection handler: one stream per request, in both directions
+pub struct Handler {
+    cache: Arc<RwLock<TxCache>>,
+    queued: VecDeque<FetchRequest>,
+    outbound: FuturesUnordered<OutboundFuture>,
+    inbound: FuturesUnordered<BoxFuture<'static, Result<()>>>,
+    events: VecDeque<HandlerEvent>,
+}
+
+impl Handler {
+    fn new(cache: Arc<RwLock<TxCache>>) -> Self {
+        Self {
+            cache,
+            queued: VecDeque::new(),
+            outbound: FuturesUnordered::new(),
+            inbound: FuturesUnordered::new(),
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl ConnectionHandler for Handler {
+    type FromBehaviour = FetchRequest;
+    type ToBehaviour = HandlerEvent;
+    type InboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type OutboundProtocol = ReadyUpgrade<StreamProtocol>;
+    type InboundOpenInfo = ();
+    type OutboundOpenInfo = FetchRequest;
+
+    fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, Self::InboundOpenInfo> {
+        SubstreamProtocol::new(ReadyUpgrade::new(TX_FETCH_PROTOCOL), ()).with_timeout(FETCH_TIMEOUT)
+    }
+
+    fn connection_keep_alive(&self) -> bool {
+        !self.queued.is_empty() || !self.outbound.is_empty() || !self.inbound.is_empty()
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<ConnectionHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::ToBehaviour>> {
+        if let Some(event) = self.events.pop_front() {
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(event));
+        }
+
+        if let Some(request) = self.queued.pop_front() {
+            return Poll::Ready(ConnectionHandlerEvent::OutboundSubstreamRequest {
+                protocol: SubstreamProtocol::new(ReadyUpgrade::new(TX_FETCH_PROTOCOL), request)
+                    .with_timeout(FETCH_TIMEOUT),
+            });
+        }
+
+        if let Poll::Ready(Some((requested, result))) = self.outbound.poll_next_unpin(cx) {
+            let event = match result {
+                Ok(txs) => HandlerEvent::Fetched { requested, txs },
+                Err(e) => HandlerEvent::Failed { requested, error: format!("{:#}", e) },
+            };
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(event));
+        }
+
+        while let Poll::Ready(Some(result)) = self.inbound.poll_next_unpin(cx) {
+            if let Err(e) = result {
+                debug!("Failed to serve transaction fetch: {:#}", e);
+            }
+        }
+
+        Poll::Pending
+    }
+
+    fn on_behaviour_event(&mut self, request: Self::FromBehaviour) {
+        self.queued.push_back(request);
+    }
+
+    fn on_connection_event(
+        &mut self,
+        event: ConnectionEvent<
+            Self::InboundProtocol,
+            Self::OutboundProtocol,
+            Self::InboundOpenInfo,
+            Self::OutboundOpenInfo,
+        >,
+    ) {
+        match event {
+            ConnectionEvent::FullyNegotiatedInbound(FullyNegotiatedInbound { protocol: stream, .. }) => {
+                if self.inbound.len() >= MAX_INBOUND_PER_CONNECTION {
+                    debug!("Dropping transaction fetch: too many in flight");
+                    return;
+                }
+                self.inbound.push(serve(stream, self.cache.clone()).boxed());
+            }
+            ConnectionEvent::FullyNegotiatedOutbound(FullyNegotiatedOutbound { protocol: stream, info: request }) => {
+                self.outbound.push(
+                    async move {
+                        let result = fetch(stream, &request).await;
+                        (request, result)
+                    }
+                    .boxed(),
+                );
+            }
+            ConnectionEvent::DialUpgradeError(DialUpgradeError { info: request, error }) => {
+                self.events.push_back(HandlerEvent::Failed {
+                    requested: request,
+                    error: format!("fetch stream failed: {}", error),
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Send `request` and keep only the bodies that hash to one of the requested IDs
+async fn fetch(mut stream: Stream, request: &FetchRequest) -> Result<Vec<FetchedTx>> {
+    let response: FetchResponse = tokio::time::timeout(FETCH_TIMEOUT, async {
+        write_frame(&mut stream, request).await?;
+        stream.close().await?;
+        read_frame(&mut stream, MAX_RESPONSE_SIZE).await
+    })
+    .await
+    .context("timed out fetching transactions")??;
+
+    let txs = response.txs.into_iter();
+    Ok(match request {
+        FetchRequest::Ids(ids) => {
+            let mut wanted: HashSet<&TxId> = ids.iter().collect();
+            txs.filter(|tx| wanted.remove(&tx_id(&tx.tx_data))).collect()
+        }
+        FetchRequest::ShortIds { key, ids } => {
+            let mut wanted: HashSet<&ShortTxId> = ids.iter().collect();
+            txs.filter(|tx| wanted.remove(&short_id(key, &tx_id(&tx.tx_data)))).collect()
+        }
+        // Checked against the signed block hash once rebuilt
+        FetchRequest::Block(_) => txs.collect(),
+    })
+}
+
+/// Answer a fetch request from the cache
+async fn serve(mut stream: Stream, cache: Arc<RwLock<TxCache>>) -> Result<()> {
+    tokio::time::timeout(FETCH_TIMEOUT, async {
+        let request: FetchRequest = read_frame(&mut stream, MAX_REQUEST_SIZE).await?;
+        if request.len() > MAX_FETCH_BATCH {
+            bail!("fetch of {} transactions exceeds maximum {}", request.len(), MAX_FETCH_BATCH);
+        }
+        let cache = cache.read().await;
+        let txs = match &request {
+            FetchRequest::Ids(ids) => cache.get_many(ids, MAX_RESPONSE_SIZE - 1024),
+            FetchRequest::ShortIds { key, ids } => cache.get_many_short(key, ids, MAX_RESPONSE_SIZE - 1024),
+            FetchRequest::Block(block_hash) => cache.get_block(block_hash, MAX_RESPONSE_SIZE - 1024),
+        };
+        drop(cache);
+        write_frame(&mut stream, &FetchResponse { txs }).await?;
+        stream.close().await?;
+        Ok(())
+    })
+    .await
+    .context("timed out serving transaction fetch")?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::swarm::SwarmEvent;
+    use libp2p::{noise, tcp, yamux, Swarm};
+
+    fn tx(n: u8) -> (TxId, FetchedTx) {
+        let tx = FetchedTx { tx_hash: format!("alice:bob:{}", n), tx_data: vec![n; 64] };
+        (tx_id(&tx.tx_data), tx)
+    }
+
+    #[test]
+    fn test_tx_cache() {
+        let mut cache = TxCache::new(2, Duration::from_secs(60));
+        let (id1, tx1) = tx(1);
+        let (id2, tx2) = tx(2);
+        let (id3, tx3) = tx(3);
+
+        assert!(cache.insert(id1, tx1.clone()));
+        assert!(!cache.insert(id1, tx1.clone()));
+        cache.insert(id2, tx2.clone());
+        cache.insert(id3, tx3.clone());
+
+        // Oldest entry evicted at capacity
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.contains(&id1));
+        assert_eq!(cache.get_many(&[id1, id2, id3], usize::MAX), vec![tx2.clone(), tx3.clone()]);
+        // Byte budget stops the response early
+        assert_eq!(cache.get_many(&[id2, id3], 100), vec![tx2.clone()]);
+
+        // Short IDs resolve until the entry is evicted
+        let key = [9u8; 16];
+        let shorts = [short_id(&key, &id2), short_id(&key, &id1)];
+        assert_eq!(cache.get_by_short(&key, &shorts), vec![Some(&tx2), None]);
+        assert_eq!(cache.get_many_short(&key, &[short_id(&key, &id3)], usize::MAX).len(), 1);
+
+        // Full blocks come back whole and in order, or not at all
+        cache.insert_block("block".to_string(), vec![id3, id2]);
+        assert_eq!(cache.get_block("block", usize::MAX), vec![tx3.clone(), tx2.clone()]);
+        cache.insert_block("partly evicted".to_string(), vec![id1, id2]);
+        assert!(cache.get_block("partly evicted", usize::MAX).is_empty());
+        assert!(cache.get_block("unknown", usize::MAX).is_empty());
+
+        let mut expiring = TxCache::new(10, Duration::ZERO);
+        expiring.insert(id1, tx1);
+        expiring.prune();
+        assert!(expiring.is_empty());
+    }
+
+    #[test]
+    fn test_short_ids_depend_on_block_key() {
+        // Reference vector from the SipHash paper (key 00..0f, message 00..0e)
+        let key: ShortIdKey = core::array::from_fn(|i| i as u8);
+        let message: Vec<u8> = (0..15).collect();
+        assert_eq!(siphash24(&key, &message), 0xa129_ca61_49be_45e5);
+
+        let (id, _) = tx(1);
+        assert_ne!(short_id(&[1u8; 16], &id), short_id(&[2u8; 16], &id));
+        assert_ne!(short_id(&[1u8; 16], &id), id[..8]);
+    }
+
+    fn swarm(cache: Arc<RwLock<TxCache>>) -> Swarm<Behaviour> {
+        libp2p::SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(tcp::Config::default(), noise::Config::new, yamux::Config::default)
+            .unwrap()
+            .with_behaviour(|_| Ok(Behaviour::new(cache)))
+            .unwrap()
+            .with_swarm_config(|cfg| cfg.with_idle_connection_timeout(Duration::from_secs(60)))
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_fetch_from_peer() {
+        let (id1, tx1) = tx(1);
+        let (id2, _) = tx(2);
+        let server_cache = Arc::new(RwLock::new(TxCache::default()));
+        server_cache.write().await.insert(id1, tx1.clone());
+
+        let mut server = swarm(server_cache);
+        let mut client = swarm(Arc::new(RwLock::new(TxCache::default())));
+        server.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap()).unwrap();
+        let addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = server.select_next_some().await {
+                break address;
+            }
+        };
+        let server_id = *server.local_peer_id();
+        client.dial(addr).unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                tokio::select! {
+                    _ = server.select_next_some() => {}
+                    event = client.select_next_some() => match event {
+                        SwarmEvent::ConnectionEstablished { .. } => {
+                            client.behaviour_mut().fetch(server_id, vec![id1, id2]);
+                        }
+                        SwarmEvent::Behaviour(event) => break event,
+                        _ => {}
+                    },
+                }
+            }
+        })
+        .await
+        .expect("fetch did not complete");
+
+        // Only the transaction the server has comes back
+        match event {
+            Event::Fetched { peer_id, requested, txs } => {
+                assert_eq!(peer_id, server_id);
+                assert_eq!(requested, FetchRequest::Ids(vec![id1, id2]));
+                assert_eq!(txs, vec![tx1]);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+}
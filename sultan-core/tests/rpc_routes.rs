@@ -0,0 +1,144 @@
+//! The node's real RPC filters
+//!
+//! Every documented operation must be served at its path and method, and every
+//! operation outside `REST_ONLY` must be a JSON-RPC method. Simulations weigh
+//! more than other requests in the rate limiter.
+
+use clap::Parser;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use sultan_core::jsonrpc::METHOD_NOT_FOUND;
+use sultan_core::node::{rpc, Args, NodeState};
+use sultan_core::openapi::{self, REST_ONLY};
+use tempfile::TempDir;
+use warp::http::StatusCode;
+
+/// A fresh client address per request, so the rate limiter never kicks in
+fn next_addr() -> SocketAddr {
+    static NEXT: AtomicU32 = AtomicU32::new(1);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    SocketAddr::from(([10, (n >> 16) as u8, (n >> 8) as u8, n as u8], 4000))
+}
+
+async fn node() -> (Arc<NodeState>, TempDir) {
+    let data_dir = TempDir::new().unwrap();
+    let args = Args::parse_from(["sultan-node", "--data-dir", data_dir.path().to_str().unwrap()]);
+    (Arc::new(NodeState::new(&args).await.unwrap()), data_dir)
+}
+
+/// `(method, path, operationId)` of every documented operation, with a sample
+/// value in each path parameter
+fn operations(document: &Value) -> Vec<(String, String, String)> {
+    let mut out = Vec::new();
+    for (template, item) in document["paths"].as_object().unwrap() {
+        for (method, operation) in item.as_object().unwrap() {
+            let mut path = template.clone();
+            for parameter in operation["parameters"].as_array().into_iter().flatten() {
+                if parameter["in"] == "path" {
+                    let sample = if parameter["schema"]["type"] == "integer" { "1" } else { "sample" };
+                    path = path.replace(&format!("{{{}}}", parameter["name"].as_str().unwrap()), sample);
+                }
+            }
+            out.push((method.to_uppercase(), path, operation["operationId"].as_str().unwrap().to_string()));
+        }
+    }
+    out
+}
+
+#[tokio::test]
+async fn test_documented_routes_are_served() {
+    let (state, _data_dir) = node().await;
+    let routes = rpc::routes(state);
+    let document = openapi::document();
+
+    for (method, path, operation_id) in operations(&document) {
+        // Any route on the path answers another method with 405; unknown paths get 404
+        let reply = warp::test::request()
+            .method("DELETE")
+            .path(&path)
+            .remote_addr(next_addr())
+            .reply(&routes)
+            .await;
+        assert_eq!(reply.status(), StatusCode::METHOD_NOT_ALLOWED, "{} {} ({}) is not routed", method, path, operation_id);
+
+        let reply = warp::test::request()
+            .method(&method)
+            .path(&path)
+            .remote_addr(next_addr())
+            .reply(&routes)
+            .await;
+        assert_ne!(reply.status(), StatusCode::METHOD_NOT_ALLOWED, "{} {} ({}) is not routed", method, path, operation_id);
+    }
+}
+
+#[tokio::test]
+async fn test_documented_operations_are_jsonrpc_methods() {
+    let (state, _data_dir) = node().await;
+    let routes = rpc::routes(state);
+    let document = openapi::document();
+
+    for (_, _, operation_id) in operations(&document) {
+        let reply = warp::test::request()
+            .method("POST")
+            .path("/rpc")
+            .remote_addr(next_addr())
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": operation_id, "params": {} }))
+            .reply(&routes)
+            .await;
+        let response: Value = serde_json::from_slice(reply.body()).unwrap();
+        let method_found = response["error"]["code"] != METHOD_NOT_FOUND;
+        assert_eq!(method_found, !REST_ONLY.contains(&operation_id.as_str()), "JSON-RPC method {}", operation_id);
+    }
+}
+
+#[tokio::test]
+async fn test_simulations_count_against_the_rate_limit() {
+    let (state, _data_dir) = node().await;
+    let routes = rpc::routes(state);
+    let simulate = json!({ "type": "transfer", "from": "sultan1a", "to": "sultan1b", "amount": 1, "nonce": 0 });
+
+    // The limit is 100 requests per 10 seconds and a simulation counts as 10
+    let addr = next_addr();
+    for _ in 0..10 {
+        let reply = warp::test::request()
+            .method("POST")
+            .path("/simulate")
+            .remote_addr(addr)
+            .json(&simulate)
+            .reply(&routes)
+            .await;
+        assert_eq!(reply.status(), StatusCode::OK);
+    }
+    let reply = warp::test::request()
+        .method("POST")
+        .path("/simulate")
+        .remote_addr(addr)
+        .json(&simulate)
+        .reply(&routes)
+        .await;
+    assert_eq!(reply.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Same weight over JSON-RPC
+    let addr = next_addr();
+    let call = json!({ "jsonrpc": "2.0", "id": 1, "method": "chain_simulate", "params": simulate });
+    for _ in 0..10 {
+        let reply = warp::test::request()
+            .method("POST")
+            .path("/rpc")
+            .remote_addr(addr)
+            .json(&call)
+            .reply(&routes)
+            .await;
+        assert_eq!(reply.status(), StatusCode::OK);
+    }
+    let reply = warp::test::request()
+        .method("POST")
+        .path("/rpc")
+        .remote_addr(addr)
+        .json(&call)
+        .reply(&routes)
+        .await;
+    assert_eq!(reply.status(), StatusCode::TOO_MANY_REQUESTS);
+}
@@ -61,6 +61,30 @@ async function getRpcUrl() {
   return SULTAN_RPC_URL_DEV;
 }
 
+// Largest page the node serves on list endpoints
+const MAX_PAGE_LIMIT = 500;
+
+/**
+ * Fetch every page of a cursor-paginated list endpoint
+ * The node returns one page as `{ [key]: [...], next_cursor }`; follow the
+ * cursor until there are no more pages.
+ */
+async function fetchAllPages(rpcUrl, path, key) {
+  const items = [];
+  let cursor = null;
+  do {
+    const query = cursor
+      ? `?limit=${MAX_PAGE_LIMIT}&cursor=${encodeURIComponent(cursor)}`
+      : `?limit=${MAX_PAGE_LIMIT}`;
+    const response = await fetch(`${rpcUrl}${path}${query}`);
+    const page = await response.json();
+    if (!Array.isArray(page[key])) break;
+    items.push(...page[key]);
+    cursor = page.next_cursor;
+  } while (cursor);
+  return items;
+}
+
 // =====================================================
 // Security: Rate Limiting
 // =====================================================
@@ -757,10 +781,9 @@ async function handleRpcRequest(method, params, origin) {
     case 'getValidators': {
       try {
         const rpcUrl = await getRpcUrl();
-        const response = await fetch(`${rpcUrl}/staking/validators`);
-        const validators = await response.json();
+        const validators = await fetchAllPages(rpcUrl, '/staking/validators', 'validators');
         
-        if (!Array.isArray(validators) || validators.length === 0) {
+        if (validators.length === 0) {
           return { result: [] };
         }
 
@@ -166,18 +166,22 @@ describe('Sultan API', () => {
     it('should validate validators response', async () => {
       mockFetch.mockResolvedValue({
         ok: true,
-        json: () => Promise.resolve([
-          {
-            validator_address: 'sultanval1london',
-            self_stake: 100000,
-            delegated_stake: 500000,
-            total_stake: 600000,
-            commission_rate: 0.1,
-            jailed: false,
-            blocks_signed: 1000,
-            blocks_missed: 10,
-          },
-        ]),
+        json: () => Promise.resolve({
+          validators: [
+            {
+              validator_address: 'sultanval1london',
+              self_stake: 100000,
+              delegated_stake: 500000,
+              total_stake: 600000,
+              commission_rate: 0.1,
+              jailed: false,
+              blocks_signed: 1000,
+              blocks_missed: 10,
+            },
+          ],
+          count: 1,
+          next_cursor: null,
+        }),
       });
 
       const result = await getValidators();
@@ -188,6 +192,34 @@ describe('Sultan API', () => {
       expect(result[0].commission).toBe(0.1);
       expect(result[0].status).toBe('active');
     });
+
+    it('should follow next_cursor through every validators page', async () => {
+      const validator = (address: string) => ({
+        validator_address: address,
+        self_stake: 100000,
+        delegated_stake: 0,
+        total_stake: 100000,
+        commission_rate: 0.05,
+        jailed: false,
+        blocks_signed: 10,
+        blocks_missed: 0,
+      });
+      mockFetch
+        .mockResolvedValueOnce({
+          ok: true,
+          json: () => Promise.resolve({ validators: [validator('sultanval1')], count: 2, next_cursor: 'sultanval1' }),
+        })
+        .mockResolvedValueOnce({
+          ok: true,
+          json: () => Promise.resolve({ validators: [validator('sultanval2')], count: 2, next_cursor: null }),
+        });
+
+      const result = await getValidators();
+
+      expect(result.map(v => v.address)).toEqual(['sultanval1', 'sultanval2']);
+      expect(mockFetch).toHaveBeenCalledTimes(2);
+      expect(mockFetch.mock.calls[1][0]).toContain('/staking/validators?limit=500&cursor=sultanval1');
+    });
   });
 
   describe('User-Agent header', () => {
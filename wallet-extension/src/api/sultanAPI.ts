@@ -113,6 +113,9 @@ const MAX_RETRIES = 3;
 const INITIAL_RETRY_DELAY_MS = 1000;
 const RETRYABLE_STATUS_CODES = [408, 429, 500, 502, 503, 504];
 
+// Largest page the node serves on list endpoints
+const MAX_PAGE_LIMIT = 500;
+
 // ============================================================================
 // Zod Response Schemas (Runtime Type Validation)
 // ============================================================================
@@ -272,6 +275,29 @@ async function restApi<T>(
   throw lastError || new Error('Max retries exceeded');
 }
 
+/**
+ * Fetch every page of a cursor-paginated list endpoint
+ * List endpoints return one page plus `next_cursor`; keep following it until
+ * the node reports no more pages.
+ */
+async function restApiAllPages<P extends { next_cursor?: string | null }, T>(
+  endpoint: string,
+  items: (page: P) => T[],
+  schema?: z.ZodType<P>
+): Promise<T[]> {
+  const all: T[] = [];
+  let cursor: string | null | undefined;
+  do {
+    const query = cursor
+      ? `?limit=${MAX_PAGE_LIMIT}&cursor=${encodeURIComponent(cursor)}`
+      : `?limit=${MAX_PAGE_LIMIT}`;
+    const page = await restApi<P>(`${endpoint}${query}`, 'GET', undefined, API_TIMEOUT_MS, schema);
+    all.push(...items(page));
+    cursor = page.next_cursor;
+  } while (cursor);
+  return all;
+}
+
 /**
  * Get account balance
  */
@@ -339,17 +365,15 @@ export async function getStakingInfo(address: string): Promise<StakingInfo> {
  */
 export async function getValidators(): Promise<Validator[]> {
   try {
-    // Use REST API: GET /staking/validators with Zod validation
-    const result = await restApi(
+    // Use REST API: GET /staking/validators (every page) with Zod validation
+    const validators = await restApiAllPages(
       '/staking/validators',
-      'GET',
-      undefined,
-      API_TIMEOUT_MS,
-      z.array(ValidatorSchema)
+      page => page.validators,
+      z.object({ validators: z.array(ValidatorSchema), next_cursor: z.string().nullish() })
     );
     
     // If empty array, return empty (no fallback to mocks)
-    if (!result || result.length === 0) {
+    if (validators.length === 0) {
       return [];
     }
     
@@ -362,7 +386,7 @@ export async function getValidators(): Promise<Validator[]> {
       'sultanval6newyork': 'New York Validator',
     };
     
-    return result.map(v => {
+    return validators.map(v => {
       const name = validatorNames[v.validator_address] || v.validator_address;
       // Calculate uptime from blocks signed/missed
       const totalBlocks = v.blocks_signed + v.blocks_missed;
@@ -811,7 +835,7 @@ export const sultanAPI = {
   getProposals: async (): Promise<Proposal[]> => {
     try {
       // The blockchain returns snake_case, we need to map to camelCase
-      const result = await restApi<Array<{
+      const proposals = await restApiAllPages('/governance/proposals', (page: { next_cursor?: string | null; proposals: Array<{
         id: number;
         proposer: string;
         title: string;
@@ -832,9 +856,9 @@ export const sultanAPI = {
           passed: boolean;
           vetoed: boolean;
         };
-      }>>('/governance/proposals');
+      }> }) => page.proposals);
       
-      return result.map(p => ({
+      return proposals.map(p => ({
         id: p.id,
         proposer: p.proposer,
         title: p.title,